@@ -0,0 +1,942 @@
+use std::collections::HashMap;
+
+use roxmltree::{Document, Node as XmlNode};
+
+use crate::{resolver::ResourceResolver, Importer, ImportError, ImportErrorType, Mat4, Quat, Vec2, Vec3, Vec4, Vertex, BoundingBox};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpAxis {
+    X,
+    Y,
+    Z
+}
+
+#[derive(Debug)]
+pub struct Image {
+    pub id:   String,
+    pub name: Option<String>,
+    pub path: String
+}
+
+/// The shading model of an effect's common profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shading {
+    Constant,
+    Lambert,
+    Phong,
+    Blinn
+}
+
+/// An effect from the common profile. Textures refer to images by index.
+#[derive(Debug)]
+pub struct Effect {
+    pub id:               String,
+    pub shading:          Shading,
+
+    pub diffuse:          Vec4,
+    pub diffuse_texture:  Option<usize>,
+    pub emission:         Vec4,
+    pub emission_texture: Option<usize>,
+    pub specular:         Vec4,
+    pub shininess:        f32,
+
+    /// The opacity, worked out from the `transparent` and `transparency` elements.
+    pub opacity:          f32,
+    pub opacity_texture:  Option<usize>,
+
+    /// Bump maps aren't part of the common profile, but most exporters write them in an `<extra>`.
+    pub bump_texture:     Option<usize>
+}
+
+#[derive(Debug)]
+pub struct Material {
+    pub id:     String,
+    pub name:   Option<String>,
+    pub effect: Option<usize>
+}
+
+/// A set of triangles from a `<triangles>`, `<polylist>` or `<polygons>` element.
+#[derive(Debug)]
+pub struct Primitive {
+    /// The material symbol, which is bound to a material when the geometry is instanced.
+    pub material: Option<String>,
+
+    pub vertices:    Vec<Vertex>,
+    pub indices:     Vec<u32>,
+
+    /// Whether the primitive has a `NORMAL` input.
+    pub has_normals: bool
+}
+
+#[derive(Debug)]
+pub struct Geometry {
+    pub id:         String,
+    pub name:       Option<String>,
+    pub primitives: Vec<Primitive>
+}
+
+#[derive(Debug)]
+pub struct InstanceGeometry {
+    pub geometry:  usize,
+
+    /// Maps material symbols to material indices.
+    pub materials: HashMap<String, usize>
+}
+
+#[derive(Debug)]
+pub struct Node {
+    pub id:         Option<String>,
+    pub name:       Option<String>,
+    pub transform:  Mat4,
+    pub geometries: Vec<InstanceGeometry>,
+    pub children:   Vec<usize>
+}
+
+/// A COLLADA 1.4 or 1.5 document.
+///
+/// Only the geometry, materials and visual scene are read. Animations, controllers, cameras and
+/// lights are ignored, although skinned geometry is still instanced in its bind pose.
+#[derive(Debug)]
+pub struct Collada {
+    pub up_axis:    UpAxis,
+
+    /// The size of a unit in meters.
+    pub unit:       f32,
+
+    pub images:     Vec<Image>,
+    pub effects:    Vec<Effect>,
+    pub materials:  Vec<Material>,
+    pub geometries: Vec<Geometry>,
+
+    /// The nodes of the visual scene, which is empty if the document has none.
+    pub nodes:      Vec<Node>,
+    pub root_nodes: Vec<usize>
+}
+
+impl Collada {
+    /// Parses the text of a COLLADA document.
+    pub fn parse(text: &str) -> Result<Self, ImportError> {
+        let document = Document::parse(text).map_err(|err| parse_error(format!("Invalid XML: {err}")))?;
+        let root = document.root_element();
+
+        if root.tag_name().name() != "COLLADA" {
+            return Err(parse_error("The document is not a COLLADA document."));
+        }
+
+        let mut parser = Parser {
+            ids: root.descendants().filter_map(|node| node.attribute("id").map(|id| (id, node))).collect(),
+            images: Vec::new(),
+            effects: Vec::new(),
+            materials: Vec::new(),
+            geometries: Vec::new(),
+            nodes: Vec::new(),
+            image_indices: HashMap::new(),
+            effect_indices: HashMap::new(),
+            material_indices: HashMap::new(),
+            geometry_indices: HashMap::new()
+        };
+
+        let asset = child(root, "asset");
+
+        let up_axis = match asset.and_then(|asset| child(asset, "up_axis")).and_then(|axis| axis.text()).map(str::trim) {
+            Some("X_UP") => UpAxis::X,
+            Some("Z_UP") => UpAxis::Z,
+            _ => UpAxis::Y
+        };
+
+        let unit = asset
+            .and_then(|asset| child(asset, "unit"))
+            .and_then(|unit| unit.attribute("meter"))
+            .and_then(|meter| meter.parse().ok())
+            .unwrap_or(1.0);
+
+        for library in children(root, "library_images") {
+            for image in children(library, "image") {
+                parser.image(image);
+            }
+        }
+
+        // Some exporters put images inside effects.
+        for image in root.descendants().filter(|node| node.has_tag_name("image") && node.parent().is_some_and(|parent| !parent.has_tag_name("library_images"))) {
+            parser.image(image);
+        }
+
+        for library in children(root, "library_effects") {
+            for effect in children(library, "effect") {
+                parser.effect(effect)?;
+            }
+        }
+
+        for library in children(root, "library_materials") {
+            for material in children(library, "material") {
+                parser.material(material);
+            }
+        }
+
+        for library in children(root, "library_geometries") {
+            for geometry in children(library, "geometry") {
+                parser.geometry(geometry)?;
+            }
+        }
+
+        // Use the scene's visual scene, or the first one if the document has no scene.
+        let visual_scene = child(root, "scene")
+            .and_then(|scene| child(scene, "instance_visual_scene"))
+            .and_then(|instance| parser.lookup(instance.attribute("url")))
+            .or_else(|| {
+                children(root, "library_visual_scenes")
+                    .flat_map(|library| children(library, "visual_scene"))
+                    .next()
+            });
+
+        let mut root_nodes = Vec::new();
+
+        if let Some(visual_scene) = visual_scene {
+            for node in children(visual_scene, "node") {
+                root_nodes.push(parser.node(node, 0)?);
+            }
+        }
+
+        Ok(Self {
+            up_axis,
+            unit,
+            images: parser.images,
+            effects: parser.effects,
+            materials: parser.materials,
+            geometries: parser.geometries,
+            nodes: parser.nodes,
+            root_nodes
+        })
+    }
+
+    /// The transform that converts from the document's units and up axis to meters and +Y up.
+    pub fn axis_transform(&self) -> Mat4 {
+        let s = self.unit;
+
+        match self.up_axis {
+            UpAxis::X => Mat4::new(
+                Vec4::new(0.0, -s, 0.0, 0.0),
+                Vec4::new(s, 0.0, 0.0, 0.0),
+                Vec4::new(0.0, 0.0, s, 0.0),
+                Vec4::new(0.0, 0.0, 0.0, 1.0)
+            ),
+            UpAxis::Y => Mat4::new(
+                Vec4::new(s, 0.0, 0.0, 0.0),
+                Vec4::new(0.0, s, 0.0, 0.0),
+                Vec4::new(0.0, 0.0, s, 0.0),
+                Vec4::new(0.0, 0.0, 0.0, 1.0)
+            ),
+            UpAxis::Z => Mat4::new(
+                Vec4::new(s, 0.0, 0.0, 0.0),
+                Vec4::new(0.0, 0.0, s, 0.0),
+                Vec4::new(0.0, -s, 0.0, 0.0),
+                Vec4::new(0.0, 0.0, 0.0, 1.0)
+            )
+        }
+    }
+}
+
+impl Importer for Collada {
+    fn import_bytes(data: &[u8], _resolver: &dyn ResourceResolver) -> Result<Self, ImportError> where Self : Sized {
+        let text = std::str::from_utf8(data).map_err(|_| parse_error("The file contains invalid UTF-8."))?;
+
+        Self::parse(text)
+    }
+
+    fn to_scene(&self, _resolver: &dyn ResourceResolver) -> Result<crate::Scene, ImportError> {
+        let images = self.images.iter().map(|image| crate::Image {
+            path: Some(image.path.clone()),
+            data_type: None,
+            data: None,
+            name: image.name.clone().or_else(|| Some(image.id.clone())),
+            extras: None
+        }).collect::<Vec<_>>();
+
+        let materials = self.materials.iter().map(|material| {
+            let effect = material.effect.map(|effect| &self.effects[effect]);
+            let mut scene_material = effect.map_or_else(default_material, effect_to_material);
+
+            scene_material.name = material.name.clone().or_else(|| Some(material.id.clone()));
+            scene_material
+        }).collect::<Vec<_>>();
+
+        let mut meshes = Vec::new();
+
+        // The same primitive can be bound to different materials in different instances, so each
+        // combination of primitive and material becomes its own mesh.
+        let mut mesh_cache = HashMap::new();
+
+        let mut add_mesh = |geometry: usize, primitive: usize, material: Option<usize>| {
+            *mesh_cache.entry((geometry, primitive, material)).or_insert_with(|| {
+                let geometry = &self.geometries[geometry];
+                let primitive = &geometry.primitives[primitive];
+
+                meshes.push(crate::Mesh {
+                    vertices: primitive.vertices.clone(),
+                    indices: Some(primitive.indices.clone()),
+                    material,
+                    has_normals: primitive.has_normals,
+                    bounds: BoundingBox::from_vertices(&primitive.vertices),
+                    name: geometry.name.clone().or_else(|| Some(geometry.id.clone())),
+                    extras: None
+                });
+
+                meshes.len() - 1
+            })
+        };
+
+        let mut nodes = Vec::with_capacity(self.nodes.len());
+
+        for node in &self.nodes {
+            let mut node_meshes = Vec::new();
+
+            for instance in &node.geometries {
+                for (i, primitive) in self.geometries[instance.geometry].primitives.iter().enumerate() {
+                    let material = primitive.material.as_ref().and_then(|symbol| instance.materials.get(symbol)).copied();
+                    node_meshes.push(add_mesh(instance.geometry, i, material));
+                }
+            }
+
+            nodes.push(crate::Node {
+                transform: node.transform,
+                meshes: node_meshes,
+                children: node.children.clone(),
+                name: node.name.clone().or_else(|| node.id.clone()),
+                extras: None
+            });
+        }
+
+        // Without a visual scene, fall back to just the geometry.
+        if nodes.is_empty() {
+            for (geometry_index, geometry) in self.geometries.iter().enumerate() {
+                for i in 0..geometry.primitives.len() {
+                    add_mesh(geometry_index, i, None);
+                }
+            }
+        }
+
+        // Convert the roots to meters and +Y up, so the whole scene is converted.
+        let axis_transform = self.axis_transform();
+
+        for &root in &self.root_nodes {
+            nodes[root].transform = axis_transform * nodes[root].transform;
+        }
+
+        Ok(crate::Scene {
+            meshes,
+            materials: if materials.is_empty() { None } else { Some(materials) },
+            images: if images.is_empty() { None } else { Some(images) },
+            nodes,
+            root_nodes: self.root_nodes.clone()
+        })
+    }
+}
+
+fn default_material() -> crate::Material {
+    crate::Material {
+        albedo_color: Vec4::new(1.0, 1.0, 1.0, 1.0),
+        albedo_texture: None,
+        normal_texture: None,
+        metallic: 0.0,
+        metallic_texture: None,
+        roughness: 1.0,
+        roughness_texture: None,
+        occlusion_texture: None,
+        emissive_texture: None,
+        alpha_mode: crate::AlphaMode::Opaque,
+        alpha_cutoff: 0.5,
+        double_sided: false,
+        name: None,
+        extras: None
+    }
+}
+
+/// Approximates a PBR material from a Phong-style effect.
+fn effect_to_material(effect: &Effect) -> crate::Material {
+    // Lambert and constant shading have no highlights, so are fully rough.
+    let roughness = match effect.shading {
+        Shading::Phong | Shading::Blinn => (2.0 / (effect.shininess + 2.0)).sqrt(),
+        Shading::Lambert | Shading::Constant => 1.0
+    };
+
+    let alpha_mode = if effect.opacity_texture.is_some() || effect.opacity < 1.0 {
+        crate::AlphaMode::Blend
+    } else {
+        crate::AlphaMode::Opaque
+    };
+
+    let diffuse = effect.diffuse;
+
+    crate::Material {
+        albedo_color: Vec4::new(diffuse.x, diffuse.y, diffuse.z, diffuse.w * effect.opacity),
+        albedo_texture: effect.diffuse_texture,
+        normal_texture: effect.bump_texture,
+        roughness,
+        emissive_texture: effect.emission_texture,
+        alpha_mode,
+        ..default_material()
+    }
+}
+
+struct Parser<'a, 'input> {
+    ids:              HashMap<&'a str, XmlNode<'a, 'input>>,
+
+    images:           Vec<Image>,
+    effects:          Vec<Effect>,
+    materials:        Vec<Material>,
+    geometries:       Vec<Geometry>,
+    nodes:            Vec<Node>,
+
+    image_indices:    HashMap<String, usize>,
+    effect_indices:   HashMap<String, usize>,
+    material_indices: HashMap<String, usize>,
+    geometry_indices: HashMap<String, usize>
+}
+
+impl<'a, 'input> Parser<'a, 'input> {
+    /// Finds the element a URL such as `#id` refers to. URLs to other documents aren't supported.
+    fn lookup(&self, url: Option<&str>) -> Option<XmlNode<'a, 'input>> {
+        url.and_then(|url| url.strip_prefix('#')).and_then(|id| self.ids.get(id)).copied()
+    }
+
+    fn image(&mut self, image: XmlNode) {
+        let Some(id) = image.attribute("id") else {
+            return;
+        };
+
+        if self.image_indices.contains_key(id) {
+            return;
+        }
+
+        // COLLADA 1.5 wraps the path in a <ref> element.
+        let path = child(image, "init_from")
+            .map(|init_from| child(init_from, "ref").unwrap_or(init_from))
+            .and_then(|path| path.text());
+
+        let Some(path) = path else {
+            return;
+        };
+
+        self.image_indices.insert(id.to_string(), self.images.len());
+        self.images.push(Image {
+            id: id.to_string(),
+            name: image.attribute("name").map(String::from),
+            path: decode_path(path.trim())
+        });
+    }
+
+    fn effect(&mut self, effect: XmlNode) -> Result<(), ImportError> {
+        let Some(id) = effect.attribute("id") else {
+            return Ok(());
+        };
+
+        let Some(profile) = child(effect, "profile_COMMON") else {
+            return Ok(());
+        };
+
+        let Some(technique) = child(profile, "technique") else {
+            return Ok(());
+        };
+
+        let (shading, model) = match [("phong", Shading::Phong), ("blinn", Shading::Blinn), ("lambert", Shading::Lambert), ("constant", Shading::Constant)]
+            .into_iter()
+            .find_map(|(name, shading)| child(technique, name).map(|model| (shading, model)))
+        {
+            Some(model) => model,
+            None => return Ok(())
+        };
+
+        // Textures refer to a sampler, which refers to a surface, which refers to an image. Some
+        // exporters refer to the image directly.
+        let new_params = profile.descendants().filter(|node| node.has_tag_name("newparam")).collect::<Vec<_>>();
+
+        let param = |sid: &str| new_params.iter().find(|param| param.attribute("sid") == Some(sid)).copied();
+
+        let texture_image = |texture: XmlNode| -> Option<usize> {
+            let name = texture.attribute("texture")?;
+
+            let image_id = match param(name).and_then(|param| child(param, "sampler2D")) {
+                Some(sampler) => {
+                    if let Some(instance) = child(sampler, "instance_image") {
+                        instance.attribute("url")?.strip_prefix('#')?.to_string()
+                    } else {
+                        let surface = child(sampler, "source")?.text()?.trim();
+
+                        param(surface)
+                            .and_then(|param| child(param, "surface"))
+                            .and_then(|surface| child(surface, "init_from"))
+                            .and_then(|init_from| init_from.text())?
+                            .trim()
+                            .to_string()
+                    }
+                },
+                None => name.to_string()
+            };
+
+            self.image_indices.get(&image_id).copied()
+        };
+
+        let color_or_texture = |name: &str, default: Vec4| -> Result<(Vec4, Option<usize>), ImportError> {
+            let Some(element) = child(model, name) else {
+                return Ok((default, None));
+            };
+
+            if let Some(texture) = child(element, "texture") {
+                return Ok((Vec4::new(1.0, 1.0, 1.0, 1.0), texture_image(texture)));
+            }
+
+            match child(element, "color").and_then(|color| color.text()) {
+                Some(text) => {
+                    let values = parse_floats(text)?;
+
+                    match values[..] {
+                        [r, g, b] => Ok((Vec4::new(r, g, b, 1.0), None)),
+                        [r, g, b, a] => Ok((Vec4::new(r, g, b, a), None)),
+                        _ => Err(parse_error(format!("Effect \"{id}\" has a color with the wrong number of components.")))
+                    }
+                },
+                None => Ok((default, None))
+            }
+        };
+
+        let float = |name: &str, default: f32| {
+            child(model, name)
+                .and_then(|element| child(element, "float"))
+                .and_then(|float| float.text())
+                .and_then(|text| text.trim().parse().ok())
+                .unwrap_or(default)
+        };
+
+        let (diffuse, diffuse_texture) = color_or_texture("diffuse", Vec4::new(1.0, 1.0, 1.0, 1.0))?;
+        let (emission, emission_texture) = color_or_texture("emission", Vec4::new(0.0, 0.0, 0.0, 1.0))?;
+        let (specular, _) = color_or_texture("specular", Vec4::new(0.0, 0.0, 0.0, 1.0))?;
+        let (transparent, opacity_texture) = color_or_texture("transparent", Vec4::new(0.0, 0.0, 0.0, 1.0))?;
+
+        let transparency = float("transparency", 1.0);
+
+        // A_ONE, the default, takes the opacity from the alpha channel. RGB_ZERO takes it from
+        // the inverse of the color.
+        let rgb_zero = child(model, "transparent").and_then(|element| element.attribute("opaque")) == Some("RGB_ZERO");
+
+        let opacity = if rgb_zero {
+            1.0 - (transparent.x + transparent.y + transparent.z) / 3.0 * transparency
+        } else {
+            transparent.w * transparency
+        };
+
+        let bump_texture = effect
+            .descendants()
+            .find(|node| node.has_tag_name("bump"))
+            .and_then(|bump| child(bump, "texture"))
+            .and_then(texture_image);
+
+        self.effect_indices.insert(id.to_string(), self.effects.len());
+        self.effects.push(Effect {
+            id: id.to_string(),
+            shading,
+            diffuse,
+            diffuse_texture,
+            emission,
+            emission_texture,
+            specular,
+            shininess: float("shininess", 0.0),
+            opacity: opacity.clamp(0.0, 1.0),
+            opacity_texture,
+            bump_texture
+        });
+
+        Ok(())
+    }
+
+    fn material(&mut self, material: XmlNode) {
+        let Some(id) = material.attribute("id") else {
+            return;
+        };
+
+        let effect = child(material, "instance_effect")
+            .and_then(|instance| instance.attribute("url"))
+            .and_then(|url| url.strip_prefix('#'))
+            .and_then(|effect| self.effect_indices.get(effect))
+            .copied();
+
+        self.material_indices.insert(id.to_string(), self.materials.len());
+        self.materials.push(Material {
+            id: id.to_string(),
+            name: material.attribute("name").map(String::from),
+            effect
+        });
+    }
+
+    fn geometry(&mut self, geometry: XmlNode) -> Result<(), ImportError> {
+        let Some(id) = geometry.attribute("id") else {
+            return Ok(());
+        };
+
+        // Splines and other geometry types aren't supported.
+        let Some(mesh) = child(geometry, "mesh") else {
+            return Ok(());
+        };
+
+        let mut primitives = Vec::new();
+
+        for element in mesh.children().filter(|node| node.is_element()) {
+            let kind = element.tag_name().name();
+
+            if !matches!(kind, "triangles" | "polylist" | "polygons") {
+                continue;
+            }
+
+            let error = |message: &str| parse_error(format!("Geometry \"{id}\": {message}"));
+
+            let inputs = self.primitive_inputs(element)?;
+            let stride = inputs.iter().map(|input| input.offset + 1).max().unwrap_or(1);
+
+            // Each polygon is a list of indices into the <p> element, with `stride` indices per corner.
+            let mut polygons: Vec<Vec<u32>> = Vec::new();
+
+            match kind {
+                "triangles" => {
+                    let p = child(element, "p").and_then(|p| p.text()).map(parse_indices).transpose()?.unwrap_or_default();
+                    polygons.extend(p.chunks_exact(stride * 3).map(|triangle| triangle.to_vec()));
+                },
+                "polylist" => {
+                    let p = child(element, "p").and_then(|p| p.text()).map(parse_indices).transpose()?.unwrap_or_default();
+                    let counts = child(element, "vcount").and_then(|vcount| vcount.text()).map(parse_indices).transpose()?.unwrap_or_default();
+
+                    let mut start = 0;
+
+                    for count in counts {
+                        let end = start + count as usize * stride;
+
+                        let Some(polygon) = p.get(start..end) else {
+                            return Err(error("The polylist has fewer indices than its vcount."));
+                        };
+
+                        polygons.push(polygon.to_vec());
+                        start = end;
+                    }
+                },
+                _ => {
+                    // <ph> elements are polygons with holes, which aren't supported.
+                    for p in children(element, "p") {
+                        polygons.push(p.text().map(parse_indices).transpose()?.unwrap_or_default());
+                    }
+                }
+            }
+
+            let mut vertices = Vec::new();
+            let mut indices = Vec::new();
+            let mut vertex_cache = HashMap::new();
+
+            for polygon in polygons {
+                let corners = polygon.chunks_exact(stride).collect::<Vec<_>>();
+
+                let mut corner_indices = Vec::with_capacity(corners.len());
+
+                for corner in corners {
+                    let index = match vertex_cache.get(corner) {
+                        Some(&index) => index,
+                        None => {
+                            vertices.push(corner_vertex(&inputs, corner).ok_or_else(|| error("An index is out of range."))?);
+
+                            let index = (vertices.len() - 1) as u32;
+                            vertex_cache.insert(corner.to_vec(), index);
+                            index
+                        }
+                    };
+
+                    corner_indices.push(index);
+                }
+
+                // Triangulate polygons as a fan.
+                for i in 2..corner_indices.len() {
+                    indices.extend_from_slice(&[corner_indices[0], corner_indices[i - 1], corner_indices[i]]);
+                }
+            }
+
+            primitives.push(Primitive {
+                material: element.attribute("material").map(String::from),
+                vertices,
+                indices,
+                has_normals: inputs.iter().any(|input| input.semantic == "NORMAL")
+            });
+        }
+
+        self.geometry_indices.insert(id.to_string(), self.geometries.len());
+        self.geometries.push(Geometry {
+            id: id.to_string(),
+            name: geometry.attribute("name").map(String::from),
+            primitives
+        });
+
+        Ok(())
+    }
+
+    /// Reads the inputs of a primitive, expanding the VERTEX input into the inputs of its <vertices>.
+    fn primitive_inputs(&self, element: XmlNode) -> Result<Vec<Input>, ImportError> {
+        let mut inputs = Vec::new();
+
+        for input in children(element, "input") {
+            let semantic = input.attribute("semantic").unwrap_or("");
+            let offset = input.attribute("offset").and_then(|offset| offset.parse().ok()).unwrap_or(0);
+            let set = input.attribute("set").and_then(|set| set.parse().ok()).unwrap_or(0);
+
+            let Some(source) = self.lookup(input.attribute("source")) else {
+                return Err(parse_error(format!("Input \"{semantic}\" has an unknown source.")));
+            };
+
+            if semantic == "VERTEX" {
+                for vertex_input in children(source, "input") {
+                    let Some(vertex_source) = self.lookup(vertex_input.attribute("source")) else {
+                        return Err(parse_error("A vertices input has an unknown source."));
+                    };
+
+                    let semantic = vertex_input.attribute("semantic").unwrap_or("");
+                    inputs.push(Input { semantic: semantic.to_string(), offset, set: 0, source: read_source(vertex_source)? });
+                }
+            } else {
+                inputs.push(Input { semantic: semantic.to_string(), offset, set, source: read_source(source)? });
+            }
+        }
+
+        Ok(inputs)
+    }
+
+    fn node(&mut self, node: XmlNode, depth: usize) -> Result<usize, ImportError> {
+        // Instanced nodes can refer back to themselves, so stop eventually.
+        if depth > 256 {
+            return Err(parse_error("The node hierarchy is too deep."));
+        }
+
+        let mut transform = Mat4::identity();
+
+        for element in node.children().filter(|node| node.is_element()) {
+            let values = || element.text().map(parse_floats).transpose().map(Option::unwrap_or_default);
+
+            let element_transform = match element.tag_name().name() {
+                "matrix" => match values()?[..] {
+                    [a, b, c, d, e, f, g, h, i, j, k, l, m, n, o, p] => Mat4::new(
+                        Vec4::new(a, b, c, d),
+                        Vec4::new(e, f, g, h),
+                        Vec4::new(i, j, k, l),
+                        Vec4::new(m, n, o, p)
+                    ),
+                    _ => return Err(parse_error("Matrices must have 16 values."))
+                },
+                "translate" => match values()?[..] {
+                    [x, y, z] => Mat4::from_trs(Vec3::new(x, y, z), Quat::new(0.0, 0.0, 0.0, 1.0), Vec3::new(1.0, 1.0, 1.0)),
+                    _ => return Err(parse_error("Translations must have 3 values."))
+                },
+                "rotate" => match values()?[..] {
+                    [x, y, z, angle] => {
+                        let mut axis = Vec3::new(x, y, z);
+
+                        if axis.magnitude_squared() == 0.0 {
+                            continue;
+                        }
+
+                        axis.normalize();
+
+                        let (sin, cos) = (angle.to_radians() / 2.0).sin_cos();
+                        Mat4::from_trs(Vec3::new(0.0, 0.0, 0.0), Quat::new(axis.x * sin, axis.y * sin, axis.z * sin, cos), Vec3::new(1.0, 1.0, 1.0))
+                    },
+                    _ => return Err(parse_error("Rotations must have 4 values."))
+                },
+                "scale" => match values()?[..] {
+                    [x, y, z] => Mat4::from_trs(Vec3::new(0.0, 0.0, 0.0), Quat::new(0.0, 0.0, 0.0, 1.0), Vec3::new(x, y, z)),
+                    _ => return Err(parse_error("Scales must have 3 values."))
+                },
+
+                // lookat and skew are rarely used, and are ignored.
+                _ => continue
+            };
+
+            transform = transform * element_transform;
+        }
+
+        let mut geometries = Vec::new();
+
+        for instance in node.children().filter(|node| node.has_tag_name("instance_geometry") || node.has_tag_name("instance_controller")) {
+            let mut target = self.lookup(instance.attribute("url"));
+
+            // Skinned meshes are instanced through a controller, which refers to the geometry.
+            if let Some(controller) = target.filter(|target| target.has_tag_name("controller")) {
+                target = child(controller, "skin").or_else(|| child(controller, "morph")).and_then(|skin| self.lookup(skin.attribute("source")));
+            }
+
+            let Some(geometry) = target.and_then(|target| target.attribute("id")).and_then(|id| self.geometry_indices.get(id)).copied() else {
+                continue;
+            };
+
+            let materials = instance
+                .descendants()
+                .filter(|node| node.has_tag_name("instance_material"))
+                .filter_map(|material| {
+                    let symbol = material.attribute("symbol")?;
+                    let target = material.attribute("target")?.strip_prefix('#')?;
+
+                    Some((symbol.to_string(), *self.material_indices.get(target)?))
+                })
+                .collect();
+
+            geometries.push(InstanceGeometry { geometry, materials });
+        }
+
+        let index = self.nodes.len();
+
+        self.nodes.push(Node {
+            id: node.attribute("id").map(String::from),
+            name: node.attribute("name").map(String::from),
+            transform,
+            geometries,
+            children: Vec::new()
+        });
+
+        let mut node_children = Vec::new();
+
+        for element in node.children().filter(|node| node.is_element()) {
+            let child_node = match element.tag_name().name() {
+                "node" => element,
+                "instance_node" => match self.lookup(element.attribute("url")) {
+                    Some(node) => node,
+                    None => continue
+                },
+                _ => continue
+            };
+
+            node_children.push(self.node(child_node, depth + 1)?);
+        }
+
+        self.nodes[index].children = node_children;
+
+        Ok(index)
+    }
+}
+
+struct Input {
+    semantic: String,
+    offset:   usize,
+    set:      usize,
+    source:   Source
+}
+
+/// The data of a <source>, with `stride` values for each element.
+struct Source {
+    data:   Vec<f32>,
+    offset: usize,
+    stride: usize
+}
+
+impl Source {
+    fn get(&self, index: usize, component: usize) -> Option<f32> {
+        self.data.get(self.offset + index * self.stride + component).copied()
+    }
+}
+
+fn read_source(source: XmlNode) -> Result<Source, ImportError> {
+    let data = match child(source, "float_array").and_then(|array| array.text()) {
+        Some(text) => parse_floats(text)?,
+        None => Vec::new()
+    };
+
+    let accessor = child(source, "technique_common").and_then(|technique| child(technique, "accessor"));
+    let attribute = |name: &str, default: usize| accessor.and_then(|accessor| accessor.attribute(name)).and_then(|value| value.parse().ok()).unwrap_or(default);
+
+    Ok(Source {
+        data,
+        offset: attribute("offset", 0),
+        stride: attribute("stride", 1)
+    })
+}
+
+/// Builds the vertex for a corner of a polygon, returning `None` if any index is out of range.
+fn corner_vertex(inputs: &[Input], corner: &[u32]) -> Option<Vertex> {
+    let mut vertex = Vertex {
+        position: Vec3::new(0.0, 0.0, 0.0),
+        tex_coord: Vec2::new(0.0, 0.0),
+        color: Vec4::new(1.0, 1.0, 1.0, 1.0),
+        normal: Vec3::new(0.0, 0.0, 0.0),
+        tangent: Vec4::new(0.0, 0.0, 0.0, 0.0)
+    };
+
+    // Only the first texture coordinate set is used.
+    let tex_coord_set = inputs.iter().filter(|input| input.semantic == "TEXCOORD").map(|input| input.set).min();
+
+    for input in inputs {
+        let index = *corner.get(input.offset)? as usize;
+        let source = &input.source;
+
+        let vec3 = || Some(Vec3::new(source.get(index, 0)?, source.get(index, 1)?, source.get(index, 2)?));
+
+        match input.semantic.as_str() {
+            "POSITION" => vertex.position = vec3()?,
+            "NORMAL" => vertex.normal = vec3()?,
+            "TEXCOORD" if Some(input.set) == tex_coord_set => {
+                // COLLADA texture coordinates start at the bottom left, however modelo's start at the top left.
+                vertex.tex_coord = Vec2::new(source.get(index, 0)?, 1.0 - source.get(index, 1)?);
+            },
+            "COLOR" => {
+                let alpha = if source.stride >= 4 { source.get(index, 3)? } else { 1.0 };
+                let color = vec3()?;
+                vertex.color = Vec4::new(color.x, color.y, color.z, alpha);
+            },
+            _ => {}
+        }
+    }
+
+    Some(vertex)
+}
+
+fn child<'a, 'input>(node: XmlNode<'a, 'input>, name: &str) -> Option<XmlNode<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(name))
+}
+
+fn children<'a, 'input: 'a>(node: XmlNode<'a, 'input>, name: &'a str) -> impl Iterator<Item = XmlNode<'a, 'input>> + 'a {
+    node.children().filter(move |child| child.has_tag_name(name))
+}
+
+fn parse_error<T: ToString>(message: T) -> ImportError {
+    ImportError::new(ImportErrorType::StringParseError, message)
+}
+
+fn parse_floats(text: &str) -> Result<Vec<f32>, ImportError> {
+    text.split_whitespace()
+        .map(|value| value.parse::<f32>().map_err(|_| parse_error(format!("Invalid number \"{value}\"."))))
+        .collect()
+}
+
+fn parse_indices(text: &str) -> Result<Vec<u32>, ImportError> {
+    text.split_whitespace()
+        .map(|value| value.parse::<u32>().map_err(|_| parse_error(format!("Invalid index \"{value}\"."))))
+        .collect()
+}
+
+/// Converts an image URI into a path, removing any `file://` scheme and decoding escaped characters.
+fn decode_path(uri: &str) -> String {
+    let path = uri.strip_prefix("file://").unwrap_or(uri);
+
+    // Absolute Windows paths are written as file:///C:/...
+    let path = match path.strip_prefix('/') {
+        Some(rest) if rest.as_bytes().get(1) == Some(&b':') => rest,
+        _ => path
+    };
+
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(value) = path.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                decoded.push(value);
+                i += 3;
+                continue;
+            }
+        }
+
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
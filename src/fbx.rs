@@ -0,0 +1,1189 @@
+use std::{collections::{BTreeMap, HashMap}, io::Read};
+
+use crate::{resolver::ResourceResolver, Importer, ImportError, ImportErrorType, Mat4, Vec2, Vec3, Vec4, Vertex, BoundingBox};
+
+const BINARY_MAGIC: &[u8] = b"Kaydara FBX Binary  \0";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Binary,
+    Ascii
+}
+
+/// A value of a record. ASCII files only have 64-bit numbers, strings and arrays.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Property {
+    Bool(bool),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    String(String),
+    Raw(Vec<u8>),
+    BoolArray(Vec<bool>),
+    I32Array(Vec<i32>),
+    I64Array(Vec<i64>),
+    F32Array(Vec<f32>),
+    F64Array(Vec<f64>)
+}
+
+impl Property {
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Self::Bool(value) => Some(value as i64),
+            Self::I16(value) => Some(value as i64),
+            Self::I32(value) => Some(value as i64),
+            Self::I64(value) => Some(value),
+            _ => None
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Self::F32(value) => Some(value as f64),
+            Self::F64(value) => Some(value),
+            _ => self.as_i64().map(|value| value as f64)
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None
+        }
+    }
+
+    /// Converts any array into 64-bit floats.
+    pub fn to_f64_vec(&self) -> Option<Vec<f64>> {
+        match self {
+            Self::F64Array(values) => Some(values.clone()),
+            Self::F32Array(values) => Some(values.iter().map(|&value| value as f64).collect()),
+            _ => self.to_i64_vec().map(|values| values.into_iter().map(|value| value as f64).collect())
+        }
+    }
+
+    /// Converts any integer array into 64-bit integers.
+    pub fn to_i64_vec(&self) -> Option<Vec<i64>> {
+        match self {
+            Self::I64Array(values) => Some(values.clone()),
+            Self::I32Array(values) => Some(values.iter().map(|&value| value as i64).collect()),
+            Self::BoolArray(values) => Some(values.iter().map(|&value| value as i64).collect()),
+            _ => None
+        }
+    }
+}
+
+/// A node record, the basic building block of both binary and ASCII FBX files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub name:       String,
+    pub properties: Vec<Property>,
+    pub children:   Vec<Record>
+}
+
+impl Record {
+    pub fn child(&self, name: &str) -> Option<&Record> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Record> + 'a {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    fn property(&self, index: usize) -> Option<&Property> {
+        self.properties.get(index)
+    }
+}
+
+#[derive(Debug)]
+pub struct Texture {
+    pub id:   i64,
+    pub name: String,
+    pub path: String
+}
+
+/// A material, using the properties of FBX's Phong and Lambert surfaces. Textures refer to
+/// [`Fbx::textures`] by index.
+#[derive(Debug)]
+pub struct Material {
+    pub id:               i64,
+    pub name:             String,
+
+    pub diffuse:          Vec3,
+    pub diffuse_texture:  Option<usize>,
+    pub emissive:         Vec3,
+    pub emissive_texture: Option<usize>,
+    pub specular:         Vec3,
+    pub shininess:        f32,
+    pub opacity:          f32,
+    pub opacity_texture:  Option<usize>,
+    pub normal_texture:   Option<usize>
+}
+
+/// The triangles of a geometry that use the same material slot.
+#[derive(Debug)]
+pub struct Primitive {
+    /// The index into the materials of the model the geometry is attached to.
+    pub material: usize,
+
+    pub vertices:    Vec<Vertex>,
+    pub indices:     Vec<u32>,
+
+    /// Whether the geometry has a `LayerElementNormal`.
+    pub has_normals: bool
+}
+
+#[derive(Debug)]
+pub struct Geometry {
+    pub id:         i64,
+    pub name:       String,
+    pub primitives: Vec<Primitive>
+}
+
+/// The order Euler rotations are applied in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RotationOrder {
+    #[default]
+    Xyz,
+    Xzy,
+    Yzx,
+    Yxz,
+    Zxy,
+    Zyx
+}
+
+/// The transform properties of a model. Rotations are Euler angles in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelTransform {
+    pub translation:     Vec3,
+    pub rotation:        Vec3,
+    pub scaling:         Vec3,
+    pub pre_rotation:    Vec3,
+    pub post_rotation:   Vec3,
+    pub rotation_offset: Vec3,
+    pub rotation_pivot:  Vec3,
+    pub scaling_offset:  Vec3,
+    pub scaling_pivot:   Vec3,
+    pub rotation_order:  RotationOrder
+}
+
+impl Default for ModelTransform {
+    fn default() -> Self {
+        let zero = Vec3::new(0.0, 0.0, 0.0);
+
+        Self {
+            translation: zero,
+            rotation: zero,
+            scaling: Vec3::new(1.0, 1.0, 1.0),
+            pre_rotation: zero,
+            post_rotation: zero,
+            rotation_offset: zero,
+            rotation_pivot: zero,
+            scaling_offset: zero,
+            scaling_pivot: zero,
+            rotation_order: RotationOrder::Xyz
+        }
+    }
+}
+
+impl ModelTransform {
+    /// Works out the local transform, which is
+    /// `T * Roff * Rp * Rpre * R * Rpost⁻¹ * Rp⁻¹ * Soff * Sp * S * Sp⁻¹`.
+    ///
+    /// The pre and post rotations always use XYZ order, whatever the rotation order is.
+    pub fn matrix(&self) -> Mat4 {
+        let negate = |v: Vec3| Vec3::new(-v.x, -v.y, -v.z);
+
+        let post_rotation = euler_matrix(self.post_rotation, RotationOrder::Xyz);
+
+        // The inverse of a rotation is its transpose.
+        let post_rotation_inverse = Mat4::new(post_rotation.column0(), post_rotation.column1(), post_rotation.column2(), post_rotation.column3());
+
+        translation_matrix(self.translation)
+            * translation_matrix(self.rotation_offset)
+            * translation_matrix(self.rotation_pivot)
+            * euler_matrix(self.pre_rotation, RotationOrder::Xyz)
+            * euler_matrix(self.rotation, self.rotation_order)
+            * post_rotation_inverse
+            * translation_matrix(negate(self.rotation_pivot))
+            * translation_matrix(self.scaling_offset)
+            * translation_matrix(self.scaling_pivot)
+            * scale_matrix(self.scaling)
+            * translation_matrix(negate(self.scaling_pivot))
+    }
+}
+
+#[derive(Debug)]
+pub struct Model {
+    pub id:         i64,
+    pub name:       String,
+
+    /// The type of the model, such as "Mesh", "Null" or "LimbNode".
+    pub kind:       String,
+    pub transform:  ModelTransform,
+
+    pub geometries: Vec<usize>,
+
+    /// The materials of the model, in the order of the geometry's material slots.
+    pub materials:  Vec<usize>,
+    pub children:   Vec<usize>
+}
+
+/// An FBX file, either binary or ASCII 7.x.
+///
+/// The record tree is kept as it was read, and the geometry, materials, textures and model
+/// hierarchy are extracted from it. Animation, deformers, cameras and lights are ignored.
+#[derive(Debug)]
+pub struct Fbx {
+    pub format:         Format,
+    pub version:        u32,
+    pub records:        Vec<Record>,
+
+    pub textures:       Vec<Texture>,
+    pub materials:      Vec<Material>,
+    pub geometries:     Vec<Geometry>,
+    pub models:         Vec<Model>,
+    pub root_models:    Vec<usize>,
+
+    /// Converts from the file's axes and units to +Y up and meters.
+    pub axis_transform: Mat4
+}
+
+impl Fbx {
+    /// Parses a binary or ASCII FBX file.
+    pub fn parse(data: &[u8]) -> Result<Self, ImportError> {
+        let (format, version, records) = if data.starts_with(BINARY_MAGIC) {
+            let (version, records) = parse_binary(data)?;
+            (Format::Binary, version, records)
+        } else {
+            let text = std::str::from_utf8(data).map_err(|_| parse_error("The file is neither binary FBX nor valid UTF-8."))?;
+            let records = parse_ascii(text)?;
+
+            let version = records.iter()
+                .find(|record| record.name == "FBXHeaderExtension")
+                .and_then(|header| header.child("FBXVersion"))
+                .and_then(|version| version.property(0))
+                .and_then(Property::as_i64)
+                .unwrap_or(0) as u32;
+
+            (Format::Ascii, version, records)
+        };
+
+        if version != 0 && version < 7000 {
+            return Err(ImportError::new(ImportErrorType::UnsupportedFormat, format!("FBX version {version} is not supported.")));
+        }
+
+        let mut fbx = Self {
+            format,
+            version,
+            records: Vec::new(),
+            textures: Vec::new(),
+            materials: Vec::new(),
+            geometries: Vec::new(),
+            models: Vec::new(),
+            root_models: Vec::new(),
+            axis_transform: Mat4::identity()
+        };
+
+        fbx.read_objects(&records)?;
+        fbx.records = records;
+
+        Ok(fbx)
+    }
+
+    fn read_objects(&mut self, records: &[Record]) -> Result<(), ImportError> {
+        // Files without global settings use the defaults, which are +Y up and centimeters.
+        let settings = records.iter().find(|record| record.name == "GlobalSettings");
+        self.axis_transform = axis_transform(&settings.map(properties70).unwrap_or_default());
+
+        let mut ids = HashMap::new();
+
+        for objects in records.iter().filter(|record| record.name == "Objects") {
+            for object in &objects.children {
+                let id = object.property(0).and_then(Property::as_i64).unwrap_or(0);
+                let name = object.property(1).and_then(Property::as_str).map(object_name).unwrap_or_default();
+                let class = object.property(2).and_then(Property::as_str).unwrap_or("");
+
+                match object.name.as_str() {
+                    "Geometry" if class == "Mesh" => {
+                        ids.insert(id, Object::Geometry(self.geometries.len()));
+                        self.geometries.push(read_geometry(object, id, name)?);
+                    },
+                    "Material" => {
+                        ids.insert(id, Object::Material(self.materials.len()));
+                        self.materials.push(read_material(object, id, name));
+                    },
+                    "Texture" => {
+                        let path = ["RelativeFilename", "FileName", "Filename"]
+                            .into_iter()
+                            .filter_map(|key| object.child(key).and_then(|path| path.property(0)).and_then(Property::as_str))
+                            .find(|path| !path.is_empty())
+                            .unwrap_or("")
+                            .replace('\\', "/");
+
+                        ids.insert(id, Object::Texture(self.textures.len()));
+                        self.textures.push(Texture { id, name, path });
+                    },
+                    "Model" => {
+                        ids.insert(id, Object::Model(self.models.len()));
+                        self.models.push(Model {
+                            id,
+                            name,
+                            kind: class.to_string(),
+                            transform: read_transform(&properties70(object)),
+                            geometries: Vec::new(),
+                            materials: Vec::new(),
+                            children: Vec::new()
+                        });
+                    },
+                    _ => {}
+                }
+            }
+        }
+
+        let mut has_parent = vec![false; self.models.len()];
+
+        for connections in records.iter().filter(|record| record.name == "Connections") {
+            for connection in connections.children_named("C") {
+                let child = connection.property(1).and_then(Property::as_i64).and_then(|id| ids.get(&id));
+                let parent = connection.property(2).and_then(Property::as_i64).and_then(|id| ids.get(&id));
+                let property = connection.property(3).and_then(Property::as_str);
+
+                match (child, parent) {
+                    (Some(&Object::Geometry(geometry)), Some(&Object::Model(model))) => self.models[model].geometries.push(geometry),
+                    (Some(&Object::Material(material)), Some(&Object::Model(model))) => self.models[model].materials.push(material),
+                    (Some(&Object::Model(child)), Some(&Object::Model(model))) => {
+                        self.models[model].children.push(child);
+                        has_parent[child] = true;
+                    },
+                    (Some(&Object::Texture(texture)), Some(&Object::Material(material))) => {
+                        let material = &mut self.materials[material];
+
+                        match property.unwrap_or("") {
+                            "DiffuseColor" | "Diffuse" => material.diffuse_texture = Some(texture),
+                            "EmissiveColor" | "EmissiveFactor" => material.emissive_texture = Some(texture),
+                            "TransparentColor" | "TransparencyFactor" => material.opacity_texture = Some(texture),
+                            "NormalMap" => material.normal_texture = Some(texture),
+
+                            // Prefer a normal map to a bump map.
+                            "Bump" => material.normal_texture = material.normal_texture.or(Some(texture)),
+                            _ => {}
+                        }
+                    },
+                    _ => {}
+                }
+            }
+        }
+
+        self.root_models = (0..self.models.len()).filter(|&model| !has_parent[model]).collect();
+
+        Ok(())
+    }
+}
+
+impl Importer for Fbx {
+    fn import_bytes(data: &[u8], _resolver: &dyn ResourceResolver) -> Result<Self, ImportError> where Self : Sized {
+        Self::parse(data)
+    }
+
+    fn to_scene(&self, _resolver: &dyn ResourceResolver) -> Result<crate::Scene, ImportError> {
+        let images = self.textures.iter().map(|texture| crate::Image {
+            path: Some(texture.path.clone()),
+            data_type: None,
+            data: None,
+            name: Some(texture.name.clone()),
+            extras: None
+        }).collect::<Vec<_>>();
+
+        let materials = self.materials.iter().map(|material| {
+            let alpha_mode = if material.opacity_texture.is_some() || material.opacity < 1.0 {
+                crate::AlphaMode::Blend
+            } else {
+                crate::AlphaMode::Opaque
+            };
+
+            crate::Material {
+                albedo_color: Vec4::new(material.diffuse.x, material.diffuse.y, material.diffuse.z, material.opacity),
+                albedo_texture: material.diffuse_texture,
+                normal_texture: material.normal_texture,
+                metallic: 0.0,
+                metallic_texture: None,
+                roughness: (2.0 / (material.shininess + 2.0)).sqrt(),
+                roughness_texture: None,
+                occlusion_texture: None,
+                emissive_texture: material.emissive_texture,
+                alpha_mode,
+                alpha_cutoff: 0.5,
+                double_sided: false,
+                name: Some(material.name.clone()),
+                extras: None
+            }
+        }).collect::<Vec<_>>();
+
+        let mut meshes = Vec::new();
+
+        // Geometry can be shared by models with different materials, so each combination of
+        // primitive and material becomes its own mesh.
+        let mut mesh_cache = HashMap::new();
+
+        let mut add_mesh = |geometry: usize, primitive: usize, material: Option<usize>| {
+            *mesh_cache.entry((geometry, primitive, material)).or_insert_with(|| {
+                let geometry = &self.geometries[geometry];
+                let primitive = &geometry.primitives[primitive];
+
+                meshes.push(crate::Mesh {
+                    vertices: primitive.vertices.clone(),
+                    indices: Some(primitive.indices.clone()),
+                    material,
+                    has_normals: primitive.has_normals,
+                    bounds: BoundingBox::from_vertices(&primitive.vertices),
+                    name: Some(geometry.name.clone()),
+                    extras: None
+                });
+
+                meshes.len() - 1
+            })
+        };
+
+        let mut nodes = Vec::with_capacity(self.models.len());
+
+        for model in &self.models {
+            let mut node_meshes = Vec::new();
+
+            for &geometry in &model.geometries {
+                for (i, primitive) in self.geometries[geometry].primitives.iter().enumerate() {
+                    node_meshes.push(add_mesh(geometry, i, model.materials.get(primitive.material).copied()));
+                }
+            }
+
+            nodes.push(crate::Node {
+                transform: model.transform.matrix(),
+                meshes: node_meshes,
+                children: model.children.clone(),
+                name: Some(model.name.clone()),
+                extras: None
+            });
+        }
+
+        // Without any models, fall back to just the geometry.
+        if nodes.is_empty() {
+            for (geometry_index, geometry) in self.geometries.iter().enumerate() {
+                for i in 0..geometry.primitives.len() {
+                    add_mesh(geometry_index, i, None);
+                }
+            }
+        }
+
+        for &root in &self.root_models {
+            nodes[root].transform = self.axis_transform * nodes[root].transform;
+        }
+
+        Ok(crate::Scene {
+            meshes,
+            materials: if materials.is_empty() { None } else { Some(materials) },
+            images: if images.is_empty() { None } else { Some(images) },
+            nodes,
+            root_nodes: self.root_models.clone()
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Object {
+    Geometry(usize),
+    Material(usize),
+    Texture(usize),
+    Model(usize)
+}
+
+/// Gets the values of each property in a `Properties70` block, by name.
+fn properties70(record: &Record) -> HashMap<&str, &[Property]> {
+    record.child("Properties70")
+        .into_iter()
+        .flat_map(|properties| properties.children_named("P"))
+        .filter_map(|property| Some((property.property(0)?.as_str()?, property.properties.get(4..)?)))
+        .collect()
+}
+
+fn property_f32(properties: &HashMap<&str, &[Property]>, name: &str) -> Option<f32> {
+    properties.get(name)?.first()?.as_f64().map(|value| value as f32)
+}
+
+fn property_vec3(properties: &HashMap<&str, &[Property]>, name: &str) -> Option<Vec3> {
+    match properties.get(name)? {
+        [x, y, z, ..] => Some(Vec3::new(x.as_f64()? as f32, y.as_f64()? as f32, z.as_f64()? as f32)),
+        _ => None
+    }
+}
+
+fn read_transform(properties: &HashMap<&str, &[Property]>) -> ModelTransform {
+    let default = ModelTransform::default();
+    let vec3 = |name: &str, default: Vec3| property_vec3(properties, name).unwrap_or(default);
+
+    let rotation_order = match property_f32(properties, "RotationOrder").map(|order| order as i32) {
+        Some(1) => RotationOrder::Xzy,
+        Some(2) => RotationOrder::Yzx,
+        Some(3) => RotationOrder::Yxz,
+        Some(4) => RotationOrder::Zxy,
+        Some(5) => RotationOrder::Zyx,
+        _ => RotationOrder::Xyz
+    };
+
+    ModelTransform {
+        translation: vec3("Lcl Translation", default.translation),
+        rotation: vec3("Lcl Rotation", default.rotation),
+        scaling: vec3("Lcl Scaling", default.scaling),
+        pre_rotation: vec3("PreRotation", default.pre_rotation),
+        post_rotation: vec3("PostRotation", default.post_rotation),
+        rotation_offset: vec3("RotationOffset", default.rotation_offset),
+        rotation_pivot: vec3("RotationPivot", default.rotation_pivot),
+        scaling_offset: vec3("ScalingOffset", default.scaling_offset),
+        scaling_pivot: vec3("ScalingPivot", default.scaling_pivot),
+        rotation_order
+    }
+}
+
+fn read_material(record: &Record, id: i64, name: String) -> Material {
+    let properties = properties70(record);
+    let factor = |name: &str| property_f32(&properties, name).unwrap_or(1.0);
+    let color = |name: &str, default: Vec3| property_vec3(&properties, name).unwrap_or(default);
+    let scale = |v: Vec3, s: f32| Vec3::new(v.x * s, v.y * s, v.z * s);
+
+    // Opacity is written by some exporters, otherwise it comes from the transparency.
+    let opacity = property_f32(&properties, "Opacity").unwrap_or_else(|| {
+        let transparent = color("TransparentColor", Vec3::new(1.0, 1.0, 1.0));
+        let transparency = property_f32(&properties, "TransparencyFactor").unwrap_or(0.0);
+
+        1.0 - transparency * (transparent.x + transparent.y + transparent.z) / 3.0
+    });
+
+    Material {
+        id,
+        name,
+        diffuse: scale(color("DiffuseColor", Vec3::new(0.8, 0.8, 0.8)), factor("DiffuseFactor")),
+        diffuse_texture: None,
+        emissive: scale(color("EmissiveColor", Vec3::new(0.0, 0.0, 0.0)), factor("EmissiveFactor")),
+        emissive_texture: None,
+        specular: scale(color("SpecularColor", Vec3::new(0.2, 0.2, 0.2)), factor("SpecularFactor")),
+        shininess: property_f32(&properties, "ShininessExponent").or_else(|| property_f32(&properties, "Shininess")).unwrap_or(20.0),
+        opacity: opacity.clamp(0.0, 1.0),
+        opacity_texture: None,
+        normal_texture: None
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mapping {
+    ByPolygonVertex,
+    ByControlPoint,
+    ByPolygon,
+    AllSame
+}
+
+/// A layer element, such as the normals or texture coordinates of a geometry.
+struct Layer {
+    mapping: Mapping,
+    data:    Vec<f64>,
+    indices: Option<Vec<i64>>
+}
+
+impl Layer {
+    fn read(geometry: &Record, element: &str, data_name: &str, index_names: &[&str]) -> Option<Self> {
+        // Only the first layer is used.
+        let element = geometry.child(element)?;
+        let string = |name: &str| element.child(name).and_then(|child| child.property(0)).and_then(Property::as_str);
+
+        let mapping = match string("MappingInformationType")? {
+            "ByPolygonVertex" => Mapping::ByPolygonVertex,
+            "ByVertex" | "ByVertice" | "ByControlPoint" => Mapping::ByControlPoint,
+            "ByPolygon" => Mapping::ByPolygon,
+            "AllSame" => Mapping::AllSame,
+            _ => return None
+        };
+
+        let data = element.child(data_name)?.property(0)?.to_f64_vec()?;
+
+        let indices = match string("ReferenceInformationType") {
+            Some("IndexToDirect" | "Index") => index_names
+                .iter()
+                .find_map(|name| element.child(name))
+                .and_then(|indices| indices.property(0))
+                .and_then(Property::to_i64_vec),
+            _ => None
+        };
+
+        Some(Self {
+            mapping,
+            data,
+            indices
+        })
+    }
+
+    /// Gets the index of the value for a polygon vertex.
+    fn index(&self, polygon_vertex: usize, control_point: usize, polygon: usize) -> Option<usize> {
+        let index = match self.mapping {
+            Mapping::ByPolygonVertex => polygon_vertex,
+            Mapping::ByControlPoint => control_point,
+            Mapping::ByPolygon => polygon,
+            Mapping::AllSame => 0
+        };
+
+        match &self.indices {
+            Some(indices) => indices.get(index).and_then(|&index| usize::try_from(index).ok()),
+            None => Some(index)
+        }
+    }
+
+    fn value<const N: usize>(&self, index: usize) -> Option<[f32; N]> {
+        let values = self.data.get(index * N..index * N + N)?;
+        Some(std::array::from_fn(|i| values[i] as f32))
+    }
+}
+
+fn read_geometry(record: &Record, id: i64, name: String) -> Result<Geometry, ImportError> {
+    let error = |message: &str| parse_error(format!("Geometry \"{name}\": {message}"));
+
+    let positions = record.child("Vertices").and_then(|vertices| vertices.property(0)).and_then(Property::to_f64_vec).unwrap_or_default();
+    let polygon_vertices = record.child("PolygonVertexIndex").and_then(|indices| indices.property(0)).and_then(Property::to_i64_vec).unwrap_or_default();
+
+    let normals = Layer::read(record, "LayerElementNormal", "Normals", &["NormalsIndex", "NormalIndex"]);
+    let tex_coords = Layer::read(record, "LayerElementUV", "UV", &["UVIndex"]);
+    let colors = Layer::read(record, "LayerElementColor", "Colors", &["ColorIndex"]);
+
+    // The material layer's data is already the material slot, so it never has separate indices.
+    let materials = Layer::read(record, "LayerElementMaterial", "Materials", &[]);
+
+    struct Builder {
+        vertices: Vec<Vertex>,
+        indices:  Vec<u32>,
+        cache:    HashMap<[Option<usize>; 4], u32>
+    }
+
+    let mut primitives: BTreeMap<usize, Builder> = BTreeMap::new();
+
+    let mut polygon = 0;
+    let mut start = 0;
+
+    for (end, &index) in polygon_vertices.iter().enumerate() {
+        // The last index of each polygon is stored as its bitwise complement, so is negative.
+        if index >= 0 {
+            continue;
+        }
+
+        let slot = materials.as_ref()
+            .and_then(|materials| materials.index(start, 0, polygon))
+            .and_then(|index| materials.as_ref()?.data.get(index))
+            .map_or(0, |&slot| slot as usize);
+
+        let builder = primitives.entry(slot).or_insert_with(|| Builder { vertices: Vec::new(), indices: Vec::new(), cache: HashMap::new() });
+
+        let mut corners = Vec::with_capacity(end + 1 - start);
+
+        for (polygon_vertex, &index) in polygon_vertices.iter().enumerate().take(end + 1).skip(start) {
+            let control_point = if index < 0 { !index } else { index } as usize;
+
+            let layer_index = |layer: &Option<Layer>| layer.as_ref().and_then(|layer| layer.index(polygon_vertex, control_point, polygon));
+            let key = [Some(control_point), layer_index(&normals), layer_index(&tex_coords), layer_index(&colors)];
+
+            if let Some(&vertex) = builder.cache.get(&key) {
+                corners.push(vertex);
+                continue;
+            }
+
+            let position = match positions.get(control_point * 3..control_point * 3 + 3) {
+                Some(&[x, y, z]) => Vec3::new(x as f32, y as f32, z as f32),
+                _ => return Err(error("A polygon vertex index is out of range."))
+            };
+
+            let normal = match (&normals, key[1]) {
+                (Some(layer), Some(index)) => {
+                    let [x, y, z] = layer.value::<3>(index).ok_or_else(|| error("A normal index is out of range."))?;
+                    Vec3::new(x, y, z)
+                },
+                _ => Vec3::new(0.0, 0.0, 0.0)
+            };
+
+            let tex_coord = match (&tex_coords, key[2]) {
+                (Some(layer), Some(index)) => {
+                    let [u, v] = layer.value::<2>(index).ok_or_else(|| error("A texture coordinate index is out of range."))?;
+
+                    // FBX texture coordinates start at the bottom left, however modelo's start at the top left.
+                    Vec2::new(u, 1.0 - v)
+                },
+                _ => Vec2::new(0.0, 0.0)
+            };
+
+            let color = match (&colors, key[3]) {
+                (Some(layer), Some(index)) => {
+                    let [r, g, b, a] = layer.value::<4>(index).ok_or_else(|| error("A color index is out of range."))?;
+                    Vec4::new(r, g, b, a)
+                },
+                _ => Vec4::new(1.0, 1.0, 1.0, 1.0)
+            };
+
+            builder.vertices.push(Vertex {
+                position,
+                tex_coord,
+                color,
+                normal,
+                tangent: Vec4::new(0.0, 0.0, 0.0, 0.0)
+            });
+
+            let vertex = (builder.vertices.len() - 1) as u32;
+            builder.cache.insert(key, vertex);
+            corners.push(vertex);
+        }
+
+        // Triangulate polygons as a fan.
+        for i in 2..corners.len() {
+            builder.indices.extend_from_slice(&[corners[0], corners[i - 1], corners[i]]);
+        }
+
+        polygon += 1;
+        start = end + 1;
+    }
+
+    Ok(Geometry {
+        id,
+        name,
+        primitives: primitives.into_iter().map(|(material, builder)| Primitive {
+            material,
+            vertices: builder.vertices,
+            indices: builder.indices,
+            has_normals: normals.is_some()
+        }).collect()
+    })
+}
+
+/// Object names are stored as `Class::Name` in ASCII files, and `Name\0\x01Class` in binary files.
+fn object_name(name: &str) -> String {
+    if let Some((name, _)) = name.split_once("\0\u{1}") {
+        name.to_string()
+    } else if let Some((_, name)) = name.split_once("::") {
+        name.to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+fn axis_transform(properties: &HashMap<&str, &[Property]>) -> Mat4 {
+    let integer = |name: &str, default: i32| property_f32(properties, name).map_or(default, |value| value as i32);
+
+    // FBX units are centimeters.
+    let scale = property_f32(properties, "UnitScaleFactor").unwrap_or(1.0) / 100.0;
+
+    let axis = |axis: &str, sign: &str, default: i32| {
+        let sign = if integer(sign, 1) < 0 { -scale } else { scale };
+
+        match integer(axis, default) {
+            0 => Vec4::new(sign, 0.0, 0.0, 0.0),
+            1 => Vec4::new(0.0, sign, 0.0, 0.0),
+            _ => Vec4::new(0.0, 0.0, sign, 0.0)
+        }
+    };
+
+    Mat4::new(
+        axis("CoordAxis", "CoordAxisSign", 0),
+        axis("UpAxis", "UpAxisSign", 1),
+        axis("FrontAxis", "FrontAxisSign", 2),
+        Vec4::new(0.0, 0.0, 0.0, 1.0)
+    )
+}
+
+fn translation_matrix(translation: Vec3) -> Mat4 {
+    Mat4::new(
+        Vec4::new(1.0, 0.0, 0.0, translation.x),
+        Vec4::new(0.0, 1.0, 0.0, translation.y),
+        Vec4::new(0.0, 0.0, 1.0, translation.z),
+        Vec4::new(0.0, 0.0, 0.0, 1.0)
+    )
+}
+
+fn scale_matrix(scale: Vec3) -> Mat4 {
+    Mat4::new(
+        Vec4::new(scale.x, 0.0, 0.0, 0.0),
+        Vec4::new(0.0, scale.y, 0.0, 0.0),
+        Vec4::new(0.0, 0.0, scale.z, 0.0),
+        Vec4::new(0.0, 0.0, 0.0, 1.0)
+    )
+}
+
+/// Creates a rotation from Euler angles in degrees, applied in the given order.
+fn euler_matrix(angles: Vec3, order: RotationOrder) -> Mat4 {
+    let (sx, cx) = angles.x.to_radians().sin_cos();
+    let (sy, cy) = angles.y.to_radians().sin_cos();
+    let (sz, cz) = angles.z.to_radians().sin_cos();
+
+    let x = Mat4::new(
+        Vec4::new(1.0, 0.0, 0.0, 0.0),
+        Vec4::new(0.0, cx, -sx, 0.0),
+        Vec4::new(0.0, sx, cx, 0.0),
+        Vec4::new(0.0, 0.0, 0.0, 1.0)
+    );
+
+    let y = Mat4::new(
+        Vec4::new(cy, 0.0, sy, 0.0),
+        Vec4::new(0.0, 1.0, 0.0, 0.0),
+        Vec4::new(-sy, 0.0, cy, 0.0),
+        Vec4::new(0.0, 0.0, 0.0, 1.0)
+    );
+
+    let z = Mat4::new(
+        Vec4::new(cz, -sz, 0.0, 0.0),
+        Vec4::new(sz, cz, 0.0, 0.0),
+        Vec4::new(0.0, 0.0, 1.0, 0.0),
+        Vec4::new(0.0, 0.0, 0.0, 1.0)
+    );
+
+    // The first rotation is applied first, so is on the right.
+    match order {
+        RotationOrder::Xyz => z * y * x,
+        RotationOrder::Xzy => y * z * x,
+        RotationOrder::Yzx => x * z * y,
+        RotationOrder::Yxz => z * x * y,
+        RotationOrder::Zxy => y * x * z,
+        RotationOrder::Zyx => x * y * z
+    }
+}
+
+fn parse_binary(data: &[u8]) -> Result<(u32, Vec<Record>), ImportError> {
+    let mut reader = BinaryReader {
+        data,
+        position: BINARY_MAGIC.len() + 2,
+        wide: false
+    };
+
+    let version = reader.u32()?;
+
+    // Version 7.5 made the record offsets 64-bit.
+    reader.wide = version >= 7500;
+
+    let mut records = Vec::new();
+
+    // The top-level records end with a null record, followed by a footer.
+    while let Some(record) = reader.record()? {
+        records.push(record);
+    }
+
+    Ok((version, records))
+}
+
+struct BinaryReader<'a> {
+    data:     &'a [u8],
+    position: usize,
+    wide:     bool
+}
+
+impl<'a> BinaryReader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], ImportError> {
+        let bytes = self.position.checked_add(length)
+            .and_then(|end| self.data.get(self.position..end))
+            .ok_or_else(|| parse_error(format!("Unexpected end of file at offset {}.", self.position)))?;
+
+        self.position += length;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ImportError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn u32(&mut self) -> Result<u32, ImportError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn offset(&mut self) -> Result<usize, ImportError> {
+        if self.wide {
+            Ok(u64::from_le_bytes(self.array()?) as usize)
+        } else {
+            Ok(self.u32()? as usize)
+        }
+    }
+
+    /// Reads a record, returning `None` for the null record that ends a list of records.
+    fn record(&mut self) -> Result<Option<Record>, ImportError> {
+        let end = self.offset()?;
+        let property_count = self.offset()?;
+        let property_length = self.offset()?;
+        let name_length = self.array::<1>()?[0] as usize;
+        let name = String::from_utf8_lossy(self.take(name_length)?).into_owned();
+
+        if end == 0 {
+            return Ok(None);
+        }
+
+        if end > self.data.len() || end < self.position {
+            return Err(parse_error(format!("Record \"{name}\" has an invalid end offset.")));
+        }
+
+        let properties_start = self.position;
+        let mut properties = Vec::with_capacity(property_count.min(1024));
+
+        for _ in 0..property_count {
+            properties.push(self.property()?);
+        }
+
+        self.position = properties_start + property_length;
+
+        let mut children = Vec::new();
+
+        while self.position < end {
+            match self.record()? {
+                Some(child) => children.push(child),
+                None => break
+            }
+        }
+
+        self.position = end;
+
+        Ok(Some(Record {
+            name,
+            properties,
+            children
+        }))
+    }
+
+    fn property(&mut self) -> Result<Property, ImportError> {
+        let type_code = self.array::<1>()?[0];
+
+        Ok(match type_code {
+            b'C' => Property::Bool(self.array::<1>()?[0] != 0),
+            b'Y' => Property::I16(i16::from_le_bytes(self.array()?)),
+            b'I' => Property::I32(i32::from_le_bytes(self.array()?)),
+            b'L' => Property::I64(i64::from_le_bytes(self.array()?)),
+            b'F' => Property::F32(f32::from_le_bytes(self.array()?)),
+            b'D' => Property::F64(f64::from_le_bytes(self.array()?)),
+            b'S' => {
+                let length = self.u32()? as usize;
+                Property::String(String::from_utf8_lossy(self.take(length)?).into_owned())
+            },
+            b'R' => {
+                let length = self.u32()? as usize;
+                Property::Raw(self.take(length)?.to_vec())
+            },
+            b'b' => Property::BoolArray(self.array_property(1)?.iter().map(|&value| value != 0).collect()),
+            b'i' => Property::I32Array(self.array_property(4)?.chunks_exact(4).map(|bytes| i32::from_le_bytes(bytes.try_into().unwrap())).collect()),
+            b'l' => Property::I64Array(self.array_property(8)?.chunks_exact(8).map(|bytes| i64::from_le_bytes(bytes.try_into().unwrap())).collect()),
+            b'f' => Property::F32Array(self.array_property(4)?.chunks_exact(4).map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap())).collect()),
+            b'd' => Property::F64Array(self.array_property(8)?.chunks_exact(8).map(|bytes| f64::from_le_bytes(bytes.try_into().unwrap())).collect()),
+            _ => return Err(parse_error(format!("Unknown property type '{}' at offset {}.", type_code as char, self.position - 1)))
+        })
+    }
+
+    /// Reads the contents of an array property, decompressing it if needed.
+    fn array_property(&mut self, element_size: usize) -> Result<Vec<u8>, ImportError> {
+        let length = self.u32()? as usize;
+        let encoding = self.u32()?;
+        let compressed_length = self.u32()? as usize;
+        let bytes = self.take(compressed_length)?;
+
+        // Deflate can't shrink data by more than about 1032 times, so longer arrays can only come from
+        // a corrupt file. They're rejected before anything is allocated for them.
+        let expected_length = length.checked_mul(element_size)
+            .filter(|&expected_length| match encoding {
+                0 => expected_length == compressed_length,
+                _ => expected_length <= compressed_length.saturating_mul(1032)
+            })
+            .ok_or_else(|| parse_error(format!("An array's length of {length} doesn't match its data.")))?;
+
+        let data = match encoding {
+            0 => bytes.to_vec(),
+            1 => {
+                let mut data = Vec::new();
+                flate2::read::ZlibDecoder::new(bytes)
+                    .take(expected_length as u64)
+                    .read_to_end(&mut data)
+                    .map_err(|err| parse_error(format!("Could not decompress an array: {err}")))?;
+                data
+            },
+            _ => return Err(parse_error(format!("Unknown array encoding {encoding}.")))
+        };
+
+        if data.len() != expected_length {
+            return Err(parse_error("An array is shorter than its length."));
+        }
+
+        Ok(data)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Key(&'a str),
+    Word(&'a str),
+    String(&'a str),
+    Number(&'a str),
+    Count,
+    Comma,
+    Open,
+    Close,
+    End
+}
+
+fn tokenize(text: &str) -> Result<Vec<(Token<'_>, usize)>, ImportError> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut position = 0;
+    let mut line = 1;
+
+    while position < bytes.len() {
+        let start = position;
+
+        let token = match bytes[position] {
+            b'\n' => {
+                line += 1;
+                position += 1;
+                continue;
+            },
+            byte if byte.is_ascii_whitespace() => {
+                position += 1;
+                continue;
+            },
+            b';' => {
+                while position < bytes.len() && bytes[position] != b'\n' {
+                    position += 1;
+                }
+
+                continue;
+            },
+            b'{' => Token::Open,
+            b'}' => Token::Close,
+            b',' => Token::Comma,
+            b'*' => Token::Count,
+            b'"' => {
+                let Some(length) = text[position + 1..].find('"') else {
+                    return Err(parse_error(format!("Line {line}: Unterminated string.")));
+                };
+
+                line += text[position + 1..position + 1 + length].matches('\n').count();
+                position += length + 2;
+                tokens.push((Token::String(&text[start + 1..position - 1]), line));
+                continue;
+            },
+            byte if byte.is_ascii_digit() || matches!(byte, b'-' | b'+' | b'.') => {
+                while position < bytes.len() && (bytes[position].is_ascii_alphanumeric() || matches!(bytes[position], b'-' | b'+' | b'.' | b'#')) {
+                    position += 1;
+                }
+
+                tokens.push((Token::Number(&text[start..position]), line));
+                continue;
+            },
+            byte if byte.is_ascii_alphabetic() || byte == b'_' => {
+                while position < bytes.len() && (bytes[position].is_ascii_alphanumeric() || matches!(bytes[position], b'_' | b'|')) {
+                    position += 1;
+                }
+
+                let word = &text[start..position];
+
+                if bytes.get(position) == Some(&b':') {
+                    position += 1;
+                    tokens.push((Token::Key(word), line));
+                } else {
+                    tokens.push((Token::Word(word), line));
+                }
+
+                continue;
+            },
+            byte => return Err(parse_error(format!("Line {line}: Unexpected character '{}'.", byte as char)))
+        };
+
+        tokens.push((token, line));
+        position += 1;
+    }
+
+    tokens.push((Token::End, line));
+
+    Ok(tokens)
+}
+
+fn parse_ascii(text: &str) -> Result<Vec<Record>, ImportError> {
+    let tokens = tokenize(text)?;
+    let mut position = 0;
+
+    parse_ascii_records(&tokens, &mut position, false)
+}
+
+fn parse_ascii_records(tokens: &[(Token, usize)], position: &mut usize, nested: bool) -> Result<Vec<Record>, ImportError> {
+    let mut records = Vec::new();
+
+    loop {
+        let (token, line) = &tokens[*position];
+
+        let name = match token {
+            Token::Key(name) => name,
+            Token::Close if nested => {
+                *position += 1;
+                return Ok(records);
+            },
+            Token::End if !nested => return Ok(records),
+            Token::End => return Err(parse_error(format!("Line {line}: Unexpected end of file."))),
+            _ => return Err(parse_error(format!("Line {line}: Expected a property name.")))
+        };
+
+        *position += 1;
+
+        let mut properties = Vec::new();
+        let mut is_array = false;
+
+        loop {
+            let (token, line) = &tokens[*position];
+
+            match token {
+                Token::Comma => {},
+                Token::Count => is_array = true,
+                Token::String(value) | Token::Word(value) => properties.push(Property::String(value.to_string())),
+                Token::Number(value) => properties.push(parse_ascii_number(value, *line)?),
+                _ => break
+            }
+
+            *position += 1;
+        }
+
+        let mut children = Vec::new();
+
+        if tokens[*position].0 == Token::Open {
+            *position += 1;
+            children = parse_ascii_records(tokens, position, true)?;
+        }
+
+        // Arrays are written as `Name: *count { a: values }`.
+        if is_array {
+            let values = children.iter().find(|child| child.name == "a").map_or(&[][..], |values| &values.properties[..]);
+
+            let property = if values.iter().all(|value| matches!(value, Property::I64(_))) {
+                Property::I64Array(values.iter().filter_map(Property::as_i64).collect())
+            } else {
+                Property::F64Array(values.iter().filter_map(Property::as_f64).collect())
+            };
+
+            properties = vec![property];
+            children.clear();
+        }
+
+        records.push(Record {
+            name: name.to_string(),
+            properties,
+            children
+        });
+    }
+}
+
+fn parse_ascii_number(value: &str, line: usize) -> Result<Property, ImportError> {
+    let error = || parse_error(format!("Line {line}: Invalid number \"{value}\"."));
+
+    if value.contains(['.', 'e', 'E', '#']) {
+        // Some exporters write infinities and NaNs as "1.#INF" and "-1.#IND".
+        if value.contains('#') {
+            return Ok(Property::F64(0.0));
+        }
+
+        value.parse().map(Property::F64).map_err(|_| error())
+    } else {
+        value.parse().map(Property::I64).map_err(|_| error())
+    }
+}
+
+fn parse_error<T: ToString>(message: T) -> ImportError {
+    ImportError::new(ImportErrorType::StringParseError, message)
+}
@@ -0,0 +1,229 @@
+use std::{path::Path, sync::{OnceLock, RwLock}};
+
+use crate::{resolver::ResourceResolver, Importer, Exporter, ImportError, ImportErrorType, ExportError, ExportErrorType, Scene, gltf::Gltf, obj::Obj, ply::Ply, stl::Stl, collada::Collada, fbx::Fbx, usd::Usd, md2::Md2, md3::Md3, iqm::Iqm, off::Off, x3d::X3d, max3ds::Max3ds};
+
+/// Saves a scene to the given path.
+pub type SaveFn = fn(&Scene, &str) -> Result<(), ExportError>;
+
+/// A file format that scenes can be loaded from and saved to.
+///
+/// The built-in formats are always registered, and other crates can add their own with [`register`].
+#[derive(Debug, Clone, Copy)]
+pub struct Format {
+    pub name:       &'static str,
+
+    /// File extensions, without the leading dot. These are matched case-insensitively.
+    pub extensions: &'static [&'static str],
+    pub mime_types: &'static [&'static str],
+
+    /// Checks whether the contents of a file are in this format, for files without a known extension.
+    pub sniff:      Option<fn(&[u8]) -> bool>,
+
+    /// Loads a scene from the contents of a file.
+    pub load:       fn(&[u8], &dyn ResourceResolver) -> Result<Scene, ImportError>,
+
+    /// Saves a scene to the given path. This is `None` for formats that can only be loaded.
+    pub save:       Option<SaveFn>
+}
+
+impl Format {
+    /// Creates a format that loads and saves through the given type.
+    pub fn new<T: Importer + Exporter>(name: &'static str, extensions: &'static [&'static str], mime_types: &'static [&'static str], sniff: Option<fn(&[u8]) -> bool>) -> Self {
+        Self {
+            save: Some(save::<T>),
+            ..Self::import_only::<T>(name, extensions, mime_types, sniff)
+        }
+    }
+
+    /// Creates a format that loads through the given importer, and can't be saved to.
+    pub fn import_only<T: Importer>(name: &'static str, extensions: &'static [&'static str], mime_types: &'static [&'static str], sniff: Option<fn(&[u8]) -> bool>) -> Self {
+        Self {
+            name,
+            extensions,
+            mime_types,
+            sniff,
+            load: load::<T>,
+            save: None
+        }
+    }
+
+    pub fn has_extension(&self, extension: &str) -> bool {
+        self.extensions.iter().any(|e| e.eq_ignore_ascii_case(extension))
+    }
+}
+
+fn load<T: Importer>(data: &[u8], resolver: &dyn ResourceResolver) -> Result<Scene, ImportError> {
+    T::import_bytes(data, resolver)?.to_scene(resolver)
+}
+
+fn save<T: Exporter>(scene: &Scene, path: &str) -> Result<(), ExportError> {
+    T::from_scene(scene).export(path)
+}
+
+fn registry() -> &'static RwLock<Vec<Format>> {
+    static REGISTRY: OnceLock<RwLock<Vec<Format>>> = OnceLock::new();
+
+    // Formats registered later are checked first, so the formats with the weakest sniffers are
+    // registered first.
+    REGISTRY.get_or_init(|| {
+        #[allow(unused_mut)]
+        let mut formats = vec![
+            Format::new::<Obj>("Wavefront OBJ", &["obj"], &["model/obj"], Some(sniff_obj)),
+            Format::new::<Off>("Object File Format", &["off"], &[], Some(sniff_off)),
+            Format::import_only::<Collada>("COLLADA", &["dae"], &["model/vnd.collada+xml"], Some(sniff_collada)),
+            Format::new::<Usd>("USD", &["usda"], &["model/vnd.usda"], Some(sniff_usd)),
+            Format::import_only::<X3d>("X3D", &["x3d"], &["model/x3d+xml"], Some(sniff_x3d)),
+            Format::import_only::<X3d>("VRML97", &["wrl"], &["model/vrml"], Some(sniff_vrml)),
+            Format::import_only::<Max3ds>("3D Studio", &["3ds"], &["application/x-3ds", "image/x-3ds"], Some(sniff_3ds)),
+            Format::import_only::<Md2>("Quake II MD2", &["md2"], &[], Some(sniff_md2)),
+            Format::import_only::<Md3>("Quake III MD3", &["md3"], &[], Some(sniff_md3)),
+            Format::import_only::<Iqm>("Inter-Quake Model", &["iqm"], &[], Some(sniff_iqm)),
+            Format::import_only::<Fbx>("FBX", &["fbx"], &["application/vnd.autodesk.fbx"], Some(sniff_fbx)),
+            Format::new::<Stl>("STL", &["stl"], &["model/stl", "model/x.stl-ascii", "model/x.stl-binary"], Some(sniff_stl)),
+            Format::new::<Ply>("Stanford PLY", &["ply"], &["model/x-ply"], Some(sniff_ply)),
+            Format::new::<Gltf>("glTF 2.0", &["gltf", "glb"], &["model/gltf+json", "model/gltf-binary"], Some(sniff_gltf))
+        ];
+
+        #[cfg(feature = "zip")]
+        formats.push(Format::new::<crate::threemf::ThreeMf>("3MF", &["3mf"], &["model/3mf"], Some(sniff_3mf)));
+
+        RwLock::new(formats)
+    })
+}
+
+/// Registers a format, so it can be used by [`Scene::load`] and [`Scene::save`].
+///
+/// Formats are checked in the reverse order they were registered, so a format can replace a
+/// built-in format by using the same extensions.
+pub fn register(format: Format) {
+    registry().write().unwrap().push(format);
+}
+
+/// Gets every registered format, in the order they are checked.
+pub fn formats() -> Vec<Format> {
+    registry().read().unwrap().iter().rev().copied().collect()
+}
+
+pub fn find_by_name(name: &str) -> Option<Format> {
+    formats().into_iter().find(|format| format.name == name)
+}
+
+pub fn find_by_extension(extension: &str) -> Option<Format> {
+    formats().into_iter().find(|format| format.has_extension(extension))
+}
+
+pub fn find_by_path(path: &Path) -> Option<Format> {
+    path.extension().and_then(|extension| find_by_extension(&extension.to_string_lossy()))
+}
+
+pub fn find_by_mime_type(mime_type: &str) -> Option<Format> {
+    formats().into_iter().find(|format| format.mime_types.iter().any(|m| m.eq_ignore_ascii_case(mime_type)))
+}
+
+/// Guesses the format from the contents of a file.
+pub fn detect(data: &[u8]) -> Option<Format> {
+    formats().into_iter().find(|format| format.sniff.is_some_and(|sniff| sniff(data)))
+}
+
+pub(crate) fn unknown_format(name: &str) -> ImportError {
+    ImportError::new(ImportErrorType::UnsupportedFormat, format!("Could not work out the format of {name}."))
+}
+
+pub(crate) fn unsupported_extension(path: &str) -> ExportError {
+    ExportError::new(ExportErrorType::UnsupportedFormat, format!("\"{path}\" does not have the extension of a supported format."))
+}
+
+pub(crate) fn unsupported_export(format: &Format) -> ExportError {
+    ExportError::new(ExportErrorType::UnsupportedFormat, format!("Saving {} files isn't supported.", format.name))
+}
+
+fn sniff_gltf(data: &[u8]) -> bool {
+    data.starts_with(crate::gltf::GLB_MAGIC) || data.trim_ascii_start().starts_with(b"{")
+}
+
+fn sniff_ply(data: &[u8]) -> bool {
+    data.starts_with(b"ply\n") || data.starts_with(b"ply\r\n")
+}
+
+fn sniff_stl(data: &[u8]) -> bool {
+    // Binary STL files have no magic, but their size always matches the facet count.
+    if let Some(count) = data.get(80..84) {
+        let count = u32::from_le_bytes(count.try_into().unwrap()) as usize;
+
+        if data.len() == 84 + count * 50 {
+            return true;
+        }
+    }
+
+    data.trim_ascii_start().starts_with(b"solid")
+}
+
+fn sniff_collada(data: &[u8]) -> bool {
+    // The root element comes after the XML declaration and any comments.
+    let start = &data[..data.len().min(1024)];
+    start.windows(8).any(|window| window == b"<COLLADA")
+}
+
+fn sniff_fbx(data: &[u8]) -> bool {
+    // ASCII files start with comments, then the header.
+    let start = &data[..data.len().min(1024)];
+    data.starts_with(b"Kaydara FBX Binary") || start.windows(19).any(|window| window == b"FBXHeaderExtension:")
+}
+
+fn sniff_off(data: &[u8]) -> bool {
+    // The header keyword comes first after any comments, such as "OFF" or "STCNOFF".
+    data.split(|&byte| byte == b'\n')
+        .take(64)
+        .map(|line| line.trim_ascii())
+        .find(|line| !line.is_empty() && !line.starts_with(b"#"))
+        .and_then(|line| line.split(|byte| byte.is_ascii_whitespace()).next())
+        .is_some_and(|keyword| keyword.ends_with(b"OFF") && keyword[..keyword.len() - 3].iter().all(|byte| b"STCN".contains(byte)))
+}
+
+fn sniff_x3d(data: &[u8]) -> bool {
+    let start = &data[..data.len().min(1024)];
+    start.windows(4).any(|window| window == b"<X3D")
+}
+
+fn sniff_vrml(data: &[u8]) -> bool {
+    data.starts_with(b"#VRML V2.0")
+}
+
+fn sniff_3ds(data: &[u8]) -> bool {
+    // The main chunk covers the whole file, and starts with the version or the editor chunk.
+    data.len() >= 8
+        && data.starts_with(&[0x4D, 0x4D])
+        && u32::from_le_bytes(data[2..6].try_into().unwrap()) as usize <= data.len()
+        && matches!(&data[6..8], [0x02, 0x00] | [0x3D, 0x3D])
+}
+
+fn sniff_md2(data: &[u8]) -> bool {
+    data.starts_with(b"IDP2")
+}
+
+fn sniff_md3(data: &[u8]) -> bool {
+    data.starts_with(b"IDP3")
+}
+
+fn sniff_iqm(data: &[u8]) -> bool {
+    data.starts_with(b"INTERQUAKEMODEL\0")
+}
+
+fn sniff_usd(data: &[u8]) -> bool {
+    data.starts_with(b"#usda ")
+}
+
+#[cfg(feature = "zip")]
+fn sniff_3mf(data: &[u8]) -> bool {
+    // 3MF files are zip archives, and the file names are in the central directory at the end.
+    let end = &data[data.len().saturating_sub(64 * 1024)..];
+    data.starts_with(b"PK\x03\x04") && end.windows(6).any(|window| window == b".model")
+}
+
+fn sniff_obj(data: &[u8]) -> bool {
+    // OBJ files have no header, so look for a statement only OBJ files have near the start.
+    data.split(|&byte| byte == b'\n')
+        .take(64)
+        .filter_map(|line| line.trim_ascii().split(|&byte| byte == b' ' || byte == b'\t').next())
+        .any(|keyword| matches!(keyword, b"v" | b"vt" | b"vn" | b"f" | b"o" | b"g" | b"mtllib" | b"usemtl"))
+}
@@ -1,8 +1,8 @@
 use std::path::Path;
 
-use serde_json::Value;
+use serde_json::{Map, Value};
 
-use crate::{Importer, Vec4, Vec3, Mat4, Quat, Vec2, Vertex, utils};
+use crate::{resolver::ResourceResolver, Importer, Exporter, Vec4, Vec3, Mat4, Quat, Vec2, Vertex, BoundingBox, ExportError, ExportErrorType};
 
 #[derive(Debug)]
 pub struct Asset {
@@ -35,9 +35,31 @@ impl EnumConvert for ComponentType {
             ct => panic!("Unrecognized component type {ct}")
         }
     }
+
+    fn to_u64(&self) -> u64 {
+        match self {
+            Self::Byte => 5120,
+            Self::UnsignedByte => 5121,
+            Self::Short => 5122,
+            Self::UnsignedShort => 5123,
+            Self::UnsignedInt => 5125,
+            Self::Float => 5126
+        }
+    }
 }
 
-#[derive(Debug)]
+impl ComponentType {
+    /// The size of the component, in bytes.
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Byte | Self::UnsignedByte => 1,
+            Self::Short | Self::UnsignedShort => 2,
+            Self::UnsignedInt | Self::Float => 4
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
 pub enum AccessorType {
     Scalar,
     Vec2,
@@ -48,6 +70,31 @@ pub enum AccessorType {
     Mat4
 }
 
+impl AccessorType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Scalar => "SCALAR",
+            Self::Vec2 => "VEC2",
+            Self::Vec3 => "VEC3",
+            Self::Vec4 => "VEC4",
+            Self::Mat2 => "MAT2",
+            Self::Mat3 => "MAT3",
+            Self::Mat4 => "MAT4"
+        }
+    }
+
+    pub fn num_components(&self) -> usize {
+        match self {
+            Self::Scalar => 1,
+            Self::Vec2 => 2,
+            Self::Vec3 => 3,
+            Self::Vec4 | Self::Mat2 => 4,
+            Self::Mat3 => 9,
+            Self::Mat4 => 16
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Accessor {
     pub buffer_view:    Option<u64>,
@@ -58,8 +105,28 @@ pub struct Accessor {
     pub a_type:         AccessorType,
     pub max:            Option<Vec<f32>>,
     pub min:            Option<Vec<f32>>,
-    // TODO: Sparse
-    // pub sparse:      Option<AccessorSparse>
+    pub sparse:         Option<AccessorSparse>
+}
+
+#[derive(Debug)]
+pub struct AccessorSparseIndices {
+    pub buffer_view:    u64,
+    pub byte_offset:    u64,
+    pub component_type: ComponentType
+}
+
+#[derive(Debug)]
+pub struct AccessorSparseValues {
+    pub buffer_view: u64,
+    pub byte_offset: u64
+}
+
+/// Sparse accessors replace the values of some elements, which are typically used for morph targets.
+#[derive(Debug)]
+pub struct AccessorSparse {
+    pub count:   u64,
+    pub indices: AccessorSparseIndices,
+    pub values:  AccessorSparseValues
 }
 
 #[derive(Debug)]
@@ -83,6 +150,13 @@ impl EnumConvert for BufferTarget {
             bt => panic!("Unrecognized buffer target {bt}.")
         }
     }
+
+    fn to_u64(&self) -> u64 {
+        match self {
+            Self::ArrayBuffer => 34962,
+            Self::ElementArrayBuffer => 34963
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -105,6 +179,9 @@ pub struct Image {
     /// will not export using this mime type as it is not in the spec.
     pub mime_type:   Option<String>,
     pub buffer_view: Option<u64>,
+
+    pub name:        Option<String>,
+    pub extras:      Option<Value>
 }
 
 #[derive(Debug)]
@@ -139,7 +216,10 @@ pub struct Material {
     pub emissive_factor:        Vec3,
     pub alpha_mode:             AlphaMode,
     pub alpha_cutoff:           f32,
-    pub double_sided:           bool
+    pub double_sided:           bool,
+
+    pub name:                   Option<String>,
+    pub extras:                 Option<Value>
 }
 
 #[derive(Debug)]
@@ -167,6 +247,18 @@ impl EnumConvert for PrimitiveTopology {
             pt => panic!("Unrecognized primitive topology {pt}")
         }
     }
+
+    fn to_u64(&self) -> u64 {
+        match self {
+            Self::Points => 0,
+            Self::Lines => 1,
+            Self::LineLoop => 2,
+            Self::LineStrip => 3,
+            Self::Triangles => 4,
+            Self::TriangleStrip => 5,
+            Self::TriangleFan => 6
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -181,7 +273,10 @@ pub struct MeshPrimitive {
 #[derive(Debug)]
 pub struct Mesh {
     pub primitives: Vec<MeshPrimitive>,
-    pub weights:    Option<Vec<f32>>
+    pub weights:    Option<Vec<f32>>,
+
+    pub name:       Option<String>,
+    pub extras:     Option<Value>
 }
 
 #[derive(Debug)]
@@ -194,7 +289,10 @@ pub struct Node {
     pub rotation:    Quat,
     pub scale:       Vec3,
     pub translation: Vec3,
-    pub weights:     Option<Vec<f32>>
+    pub weights:     Option<Vec<f32>>,
+
+    pub name:        Option<String>,
+    pub extras:      Option<Value>
 }
 
 #[derive(Debug)]
@@ -220,6 +318,17 @@ impl EnumConvert for TextureFilter {
             tf => panic!("Unrecognized texture filter {tf}.")
         }
     }
+
+    fn to_u64(&self) -> u64 {
+        match self {
+            Self::Nearest => 9728,
+            Self::Linear => 9729,
+            Self::NearestMipmapNearest => 9984,
+            Self::LinearMipmapNearest => 9985,
+            Self::NearestMipmapLinear => 9986,
+            Self::LinearMipmapLinear => 9987
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -239,6 +348,14 @@ impl EnumConvert for TextureWrapMode {
             tm => panic!("Unrecognized texture wrap mode {tm}")
         }
     }
+
+    fn to_u64(&self) -> u64 {
+        match self {
+            Self::ClampToEdge => 33071,
+            Self::MirroredRepeat => 33648,
+            Self::Repeat => 10497
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -275,7 +392,10 @@ pub struct Gltf {
     pub scenes:       Option<Vec<Scene>>,
     pub textures:     Option<Vec<Texture>>,
 
-    /// If the file is a GLB, this field should be filled.
+    /// If the file is a GLB, this field should be filled. It is also filled when creating a glTF with
+    /// [`Gltf::from_scene`], as all data is packed into a single buffer.
+    ///
+    /// The first buffer will refer to this data if it has no URI.
     pub glb_data:     Option<Vec<u8>>
 
     // TODO: These
@@ -285,23 +405,18 @@ pub struct Gltf {
 }
 
 impl Importer for Gltf {
-    fn import(path: &str) -> Result<Self, crate::ImportError> where Self : Sized {
-        // TODO: GLB support.
-
-        let json = match std::fs::read_to_string(path) {
-            Ok(json) => json,
-            Err(err) => {
-                if err.kind() == std::io::ErrorKind::NotFound {
-                    return Err(crate::ImportError::new(crate::ImportErrorType::FileNotFound, "The given file was not found."));
-                } else {
-                    return Err(crate::ImportError::new(crate::ImportErrorType::Other, err.to_string()));
-                }
-            },
+    fn import_bytes(data: &[u8], _resolver: &dyn ResourceResolver) -> Result<Self, crate::ImportError> where Self : Sized {
+        // GLB files contain the JSON in their first chunk, and the binary buffer in the second.
+        let (json, glb_data) = if data.starts_with(GLB_MAGIC) {
+            let (json, bin) = read_glb(data)?;
+            (json, bin.map(|bin| bin.to_vec()))
+        } else {
+            (data, None)
         };
 
-        let json = match serde_json::from_str::<Value>(&json) {
+        let json = match serde_json::from_slice::<Value>(json) {
             Ok(gltf) => gltf,
-            Err(err) => {
+            Err(_) => {
                 return Err(crate::ImportError::new(crate::ImportErrorType::Other, "Parsing error occurred."));
             }
         };
@@ -380,6 +495,24 @@ impl Importer for Gltf {
                     None
                 };
 
+                let sparse = accessor.get("sparse").map(|sparse| {
+                    let indices = sparse.get("indices").unwrap();
+                    let values = sparse.get("values").unwrap();
+
+                    AccessorSparse {
+                        count: sparse.get("count").unwrap().as_u64().unwrap(),
+                        indices: AccessorSparseIndices {
+                            buffer_view: indices.get("bufferView").unwrap().as_u64().unwrap(),
+                            byte_offset: to_u64_or_default(indices.get("byteOffset"), 0),
+                            component_type: ComponentType::from_u64(indices.get("componentType").unwrap().as_u64().unwrap())
+                        },
+                        values: AccessorSparseValues {
+                            buffer_view: values.get("bufferView").unwrap().as_u64().unwrap(),
+                            byte_offset: to_u64_or_default(values.get("byteOffset"), 0)
+                        }
+                    }
+                });
+
                 acc_vec.push(Accessor {
                     buffer_view,
                     byte_offset,
@@ -389,6 +522,7 @@ impl Importer for Gltf {
                     a_type,
                     max,
                     min,
+                    sparse
                 });
             }
 
@@ -457,10 +591,16 @@ impl Importer for Gltf {
 
                 let buffer_view = to_u64_or_none(image.get("bufferView"));
 
+                let name = to_string_or_none(image.get("name"));
+
+                let extras = image.get("extras").cloned();
+
                 img_vec.push(Image {
                     uri,
                     mime_type,
                     buffer_view,
+                    name,
+                    extras
                 });
             }
 
@@ -545,6 +685,10 @@ impl Importer for Gltf {
 
                 let double_sided = to_bool_or_default(material.get("doubleSided"), false);
 
+                let name = to_string_or_none(material.get("name"));
+
+                let extras = material.get("extras").cloned();
+
                 mat_vec.push(Material {
                     pbr_metallic_roughness,
                     normal_texture,
@@ -554,6 +698,8 @@ impl Importer for Gltf {
                     alpha_mode,
                     alpha_cutoff,
                     double_sided,
+                    name,
+                    extras
                 });
             }
 
@@ -593,19 +739,23 @@ impl Importer for Gltf {
                     });
                 }
 
-                let weights = if let Some(weights) = mesh.get("weights") {
-                    Some(weights
+                let weights = mesh.get("weights").map(|weights| {
+                    weights
                         .as_array().unwrap()
                         .iter()
                         .map(|value| value.as_f64().unwrap() as f32)
-                        .collect())
-                } else {
-                    None
-                };
+                        .collect()
+                });
+
+                let name = to_string_or_none(mesh.get("name"));
+
+                let extras = mesh.get("extras").cloned();
 
                 mesh_vec.push(Mesh {
                     primitives: prim_vec,
                     weights,
+                    name,
+                    extras
                 });
             }
 
@@ -621,15 +771,13 @@ impl Importer for Gltf {
             for node in nodes {
                 let camera = to_u64_or_none(node.get("camera"));
 
-                let children = if let Some(children) = node.get("children") {
-                    Some(children
+                let children = node.get("children").map(|children| {
+                    children
                         .as_array().unwrap()
                         .iter()
                         .map(|value| value.as_u64().unwrap())
-                        .collect())
-                } else {
-                    None
-                };
+                        .collect()
+                });
 
                 let skin = to_u64_or_none(node.get("skin"));
 
@@ -701,15 +849,17 @@ impl Importer for Gltf {
                     Vec3::new(0.0, 0.0, 0.0)
                 };
 
-                let weights = if let Some(weights) = node.get("weights") {
-                    Some(weights
+                let weights = node.get("weights").map(|weights| {
+                    weights
                         .as_array().unwrap()
                         .iter()
                         .map(|value| value.as_f64().unwrap() as f32)
-                        .collect())
-                } else {
-                    None
-                };
+                        .collect()
+                });
+
+                let name = to_string_or_none(node.get("name"));
+
+                let extras = node.get("extras").cloned();
 
                 node_vec.push(Node {
                     camera,
@@ -721,6 +871,8 @@ impl Importer for Gltf {
                     scale,
                     translation,
                     weights,
+                    name,
+                    extras
                 });
             }
 
@@ -762,15 +914,13 @@ impl Importer for Gltf {
             let mut scene_vec = Vec::with_capacity(scenes.len());
 
             for scene in scenes {
-                let nodes = if let Some(nodes) = scene.get("nodes") {
-                    Some(nodes
+                let nodes = scene.get("nodes").map(|nodes| {
+                    nodes
                         .as_array().unwrap()
                         .iter()
                         .map(|value| value.as_u64().unwrap())
-                        .collect())
-                } else {
-                    None
-                };
+                        .collect()
+                });
 
                 scene_vec.push(Scene {
                     nodes,
@@ -816,24 +966,15 @@ impl Importer for Gltf {
             scenes,
             textures,
 
-
-            glb_data: None
+            glb_data
         })
     }
 
-    fn export(path: &str) {
-        todo!()
-    }
-
-    fn from_scene(scene: &crate::Scene) -> Self {
-        todo!()
-    }
-
-    fn to_scene(&self, directory: &Path) -> crate::Scene {
-        let gltf_buffers = self.buffers.as_ref().unwrap();
-        let gltf_meshes = self.meshes.as_ref().unwrap();
-        let gltf_accessors = self.accessors.as_ref().unwrap();
-        let gltf_views = self.buffer_views.as_ref().unwrap();
+    fn to_scene(&self, resolver: &dyn ResourceResolver) -> Result<crate::Scene, crate::ImportError> {
+        // Files without any meshes, such as those exported from an empty scene, can leave out the
+        // buffers and meshes entirely. Missing accessors and buffer views are found when they're used.
+        let gltf_buffers = self.buffers.as_deref().unwrap_or_default();
+        let gltf_meshes = self.meshes.as_deref().unwrap_or_default();
 
         let gltf_materials = &self.materials;
         let gltf_images = &self.images;
@@ -843,7 +984,11 @@ impl Importer for Gltf {
 
             for buffer in gltf_buffers {
                 if let Some(uri) = &buffer.uri {
-                    bufs.push(std::fs::read(directory.join(uri)).unwrap());
+                    bufs.push(resolver.resolve(&decode_uri(uri))?);
+                } else if let Some(data) = &self.glb_data {
+                    bufs.push(data.clone());
+                } else {
+                    return Err(crate::ImportError::new(crate::ImportErrorType::Other, "A buffer has no URI, and there is no GLB data."));
                 }
             }
 
@@ -853,60 +998,75 @@ impl Importer for Gltf {
         let mut positions = Vec::new();
         let mut tex_coords = Vec::new();
         let mut normals = Vec::new();
+        let mut colors = Vec::new();
+        let mut tangents = Vec::new();
 
         let mut meshes = Vec::new();
 
+        // Each glTF primitive becomes its own modelo mesh, so keep track of which modelo meshes
+        // belong to each glTF mesh, so nodes can refer to them.
+        let mut mesh_ranges = Vec::with_capacity(gltf_meshes.len());
+
         for mesh in gltf_meshes {
+            let first_mesh = meshes.len();
+
             for primitive in &mesh.primitives {
                 positions.clear();
                 tex_coords.clear();
                 normals.clear();
+                colors.clear();
+                tangents.clear();
+
+                let mut bounds = None;
 
                 for (name, index) in &primitive.attributes {
-                    let accessor = &gltf_accessors[*index as usize];
+                    let accessor = self.accessor(*index)?;
 
                     let name = name.to_lowercase();
                     let name = name.as_str();
 
-                    let view = &gltf_views[accessor.buffer_view.unwrap() as usize];
-
-                    let buffer = &buffers[view.buffer as usize];
-
-                    let start = view.byte_offset as usize + accessor.byte_offset as usize;
-                    let end = start + view.byte_length as usize;
-
-                    let buffer = &buffer[start..end];
-
-                    let stride = match view.byte_stride {
-                        Some(stride) => stride as usize,
-                        None => 0
-                    };
-
                     match name {
                         "position" => {
-                            if stride != 0 && stride != std::mem::size_of::<Vec3>() {
-                                todo!();
-                            } else {
-                                positions = unsafe { std::slice::from_raw_parts::<Vec3>(buffer.as_ptr() as *const _, buffer.len() / std::mem::size_of::<Vec3>()).to_vec() };
-                            }
+                            positions = self.read_accessor(accessor, &buffers)?
+                                .chunks_exact(3)
+                                .map(|value| Vec3::new(value[0], value[1], value[2]))
+                                .collect();
+
+                            bounds = accessor_bounds(accessor);
                         },
 
                         // TODO: Handle multiple texture coordinates.
                         "texcoord_0" => {
-                            if stride != 0 && stride != std::mem::size_of::<Vec2>() {
-                                todo!();
-                            } else {
-                                tex_coords = unsafe { std::slice::from_raw_parts::<Vec2>(buffer.as_ptr() as *const _, buffer.len() / std::mem::size_of::<Vec2>()).to_vec() };
-                            }
+                            tex_coords = self.read_accessor(accessor, &buffers)?
+                                .chunks_exact(2)
+                                .map(|value| Vec2::new(value[0], value[1]))
+                                .collect();
                         },
 
                         "normal" => {
-                            if stride != 0 && stride != std::mem::size_of::<Vec3>() {
-                                todo!();
-                            } else {
-                                normals = unsafe { std::slice::from_raw_parts::<Vec3>(buffer.as_ptr() as *const _, buffer.len() / std::mem::size_of::<Vec3>()).to_vec() };
-                            }
-                        }
+                            normals = self.read_accessor(accessor, &buffers)?
+                                .chunks_exact(3)
+                                .map(|value| Vec3::new(value[0], value[1], value[2]))
+                                .collect();
+                        },
+
+                        // Colors can either be RGB or RGBA.
+                        "color_0" => {
+                            let components = accessor.a_type.num_components();
+
+                            colors = self.read_accessor(accessor, &buffers)?
+                                .chunks_exact(components)
+                                .map(|value| Vec4::new(value[0], value[1], value[2], if components == 4 { value[3] } else { 1.0 }))
+                                .collect();
+                        },
+
+                        // glTF tangents have a W component for the bitangent sign.
+                        "tangent" => {
+                            tangents = self.read_accessor(accessor, &buffers)?
+                                .chunks_exact(4)
+                                .map(|value| Vec4::new(value[0], value[1], value[2], value[3]))
+                                .collect();
+                        },
 
                         _ => {}
                     }
@@ -916,76 +1076,123 @@ impl Importer for Gltf {
 
                 // According to the glTF spec, we can rely on the fact that every Vec will be the same length:
                 // "All attribute accessors for a given primitive MUST have the same count."
-                for i in 0..positions.len() {
+                for (i, position) in positions.iter().enumerate() {
                     let normal = match normals.get(i) {
                         Some(normal) => *normal,
                         None => Vec3 { x: 0.0, y: 0.0, z: 0.0 }
                     };
 
+                    let tex_coord = match tex_coords.get(i) {
+                        Some(tex_coord) => *tex_coord,
+                        None => Vec2 { x: 0.0, y: 0.0 }
+                    };
+
+                    let color = match colors.get(i) {
+                        Some(color) => *color,
+                        None => Vec4 { x: 1.0, y: 1.0, z: 1.0, w: 1.0 }
+                    };
+
+                    let tangent = match tangents.get(i) {
+                        Some(tangent) => *tangent,
+                        None => Vec4 { x: 0.0, y: 0.0, z: 0.0, w: 0.0 }
+                    };
+
                     let vertex = Vertex {
-                        position: positions[i],
-                        color: Vec4 { x: 1.0, y: 1.0, z: 1.0, w: 1.0 },
-                        tex_coord: tex_coords[i],
+                        position: *position,
+                        color,
+                        tex_coord,
                         normal,
-                        tangent: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+                        tangent,
                     };
 
                     vertices.push(vertex);
                 }
 
-                let indices = if let Some(indices) = primitive.indices {
-                    let accessor = &gltf_accessors[indices as usize];
-
-                    let view = &gltf_views[accessor.buffer_view.unwrap() as usize];
-
-                    let buffer = &buffers[view.buffer as usize];
-
-                    let start = view.byte_offset as usize + accessor.byte_offset as usize;
-                    let end = start + view.byte_length as usize;
-
-                    let buffer = &buffer[start..end];
-
-                    Some(unsafe {
-                        match accessor.component_type {
-                            ComponentType::Byte => {
-                                let buffer = utils::reinterpret_cast_slice::<i8>(buffer);
-                                utils::cast_slice_to_type::<i8, u32>(buffer)
-                            },
-                            ComponentType::UnsignedByte => {
-                                utils::cast_slice_to_type::<u8, u32>(buffer)
-                            },
-                            ComponentType::Short => {
-                                let buffer = utils::reinterpret_cast_slice::<i16>(buffer);
-                                utils::cast_slice_to_type::<i16, u32>(buffer)
-                            },
-                            ComponentType::UnsignedShort => {
-                                let buffer = utils::reinterpret_cast_slice::<u16>(buffer);
-                                utils::cast_slice_to_type::<u16, u32>(buffer)
-                            },
-                            // If the component type is UnsignedInt, this is the fastest to load, as no conversion
-                            // needs to be applied.
-                            ComponentType::UnsignedInt => utils::reinterpret_cast_slice::<u32>(buffer).to_vec(),
-                            ComponentType::Float => {
-                                unimplemented!()
-                            },
+                let indices = match primitive.indices {
+                    Some(indices) => {
+                        let indices = self.read_accessor_raw(self.accessor(indices)?, &buffers)?;
+
+                        // The post-processors index the vertices with these, so they must all be in range.
+                        if let Some(index) = indices.iter().find(|&&index| index as usize >= vertices.len()) {
+                            return Err(invalid_file(format!("Index {index} is out of range, as the mesh has {} vertices.", vertices.len())));
                         }
-                    })
-                } else {
-                    None
-                };
 
-                let material = if let Some(material) = primitive.material {
-                    Some(material as usize)
-                } else {
-                    None
+                        Some(indices.iter().map(|index| *index as u32).collect())
+                    },
+
+                    None => None
                 };
 
+                let material = primitive.material.map(|material| material as usize);
+
+                // Position accessors are required to have min and max values, however not every
+                // exporter follows this, so calculate the bounds ourselves if they are missing.
+                let bounds = bounds.unwrap_or_else(|| BoundingBox::from_vertices(&vertices));
+
                 meshes.push(crate::Mesh {
                     vertices,
                     indices,
-                    material
+                    material,
+                    has_normals: !normals.is_empty(),
+                    bounds,
+
+                    name: mesh.name.clone(),
+                    extras: mesh.extras.clone()
                 });
             }
+
+            mesh_ranges.push(first_mesh..meshes.len());
+        }
+
+        let nodes: Vec<crate::Node> = if let Some(gltf_nodes) = &self.nodes {
+            gltf_nodes
+                .iter()
+                .map(|node| {
+                    // The spec says that a node will only ever have a matrix *or* TRS properties,
+                    // and the other will be the identity, so we can just multiply them together.
+                    let transform = node.matrix * Mat4::from_trs(node.translation, node.rotation, node.scale);
+
+                    let meshes = match node.mesh {
+                        Some(mesh) => mesh_ranges
+                            .get(mesh as usize)
+                            .ok_or_else(|| invalid_file(format!("Mesh {mesh} doesn't exist.")))?
+                            .clone()
+                            .collect(),
+                        None => Vec::new()
+                    };
+
+                    let children = match &node.children {
+                        Some(children) => children.iter().map(|child| *child as usize).collect(),
+                        None => Vec::new()
+                    };
+
+                    Ok(crate::Node {
+                        transform,
+                        meshes,
+                        children,
+
+                        name: node.name.clone(),
+                        extras: node.extras.clone()
+                    })
+                })
+                .collect::<Result<_, crate::ImportError>>()?
+        } else {
+            Vec::new()
+        };
+
+        validate_hierarchy(&nodes)?;
+
+        let root_nodes: Vec<usize> = match self.scenes.as_ref().and_then(|scenes| scenes.get(self.scene.unwrap_or(0) as usize)) {
+            Some(Scene { nodes: Some(scene_nodes) }) => scene_nodes.iter().map(|node| *node as usize).collect(),
+
+            // If no scene is present, every node that isn't a child of another node is a root node.
+            _ => (0..nodes.len())
+                .filter(|index| !nodes.iter().any(|node| node.children.contains(index)))
+                .collect()
+        };
+
+        if let Some(root) = root_nodes.iter().find(|&&root| root >= nodes.len()) {
+            return Err(invalid_file(format!("The scene's node {root} doesn't exist.")));
         }
 
         let materials = if let Some(gltf_materials) = gltf_materials {
@@ -1003,35 +1210,15 @@ impl Importer for Gltf {
                     }
                 };
 
-                let albedo_texture = if let Some(texture) = &pbr.base_color_texture {
-                    Some(texture.index as usize)
-                } else {
-                    None
-                };
+                let albedo_texture = pbr.base_color_texture.as_ref().and_then(|texture| self.texture_image(texture));
 
-                let metallic_roughness_texture = if let Some(texture) = &pbr.metallic_roughness_texture {
-                    Some(texture.index as usize)
-                } else {
-                    None
-                };
+                let metallic_roughness_texture = pbr.metallic_roughness_texture.as_ref().and_then(|texture| self.texture_image(texture));
 
-                let normal_texture = if let Some(texture) = &material.normal_texture {
-                    Some(texture.index as usize)
-                } else {
-                    None
-                };
+                let normal_texture = material.normal_texture.as_ref().and_then(|texture| self.texture_image(texture));
 
-                let occlusion_texture = if let Some(texture) = &material.occlusion_texture {
-                    Some(texture.index as usize)
-                } else {
-                    None
-                };
+                let occlusion_texture = material.occlusion_texture.as_ref().and_then(|texture| self.texture_image(texture));
 
-                let emissive_texture = if let Some(texture) = &material.emissive_texture {
-                    Some(texture.index as usize)
-                } else {
-                    None
-                };
+                let emissive_texture = material.emissive_texture.as_ref().and_then(|texture| self.texture_image(texture));
 
                 let alpha_mode = match material.alpha_mode {
                     AlphaMode::Opaque => crate::AlphaMode::Opaque,
@@ -1052,6 +1239,9 @@ impl Importer for Gltf {
                     alpha_mode,
                     alpha_cutoff: material.alpha_cutoff,
                     double_sided: material.double_sided,
+
+                    name: material.name.clone(),
+                    extras: material.extras.clone()
                 });
             }
 
@@ -1064,21 +1254,34 @@ impl Importer for Gltf {
             let mut images = Vec::with_capacity(gltf_images.len());
 
             for image in gltf_images {
-                let path = if let Some(uri) = &image.uri {
+                let (path, data_type, data) = if let Some(uri) = &image.uri {
                     // TODO: Handle cases where texture data is directly stored in the URI.
                     // These are base64 strings and start with `data:`
+                    (Some(decode_uri(uri)), None, None)
+                } else if let Some(view) = image.buffer_view {
+                    // Images stored in a buffer view, which is typically the case for GLB files.
+                    let (_, data) = self.view_data(view, &buffers)?;
+
+                    let data_type = match image.mime_type.as_deref() {
+                        Some("image/png") => crate::ImageDataType::Png,
+                        Some("image/jpeg") => crate::ImageDataType::Jpg,
+                        Some("image/bmp") => crate::ImageDataType::Bmp,
+                        Some("image/vnd-ms.dds") => crate::ImageDataType::Dds,
+                        _ => crate::ImageDataType::Unknown
+                    };
 
-                    // ugghhh I hate that glTF can include %xy unicode strings
-                    // TODO: Find a better way to handle these.
-                    Some(uri.clone().replace("%20", " "))
+                    (None, Some(data_type), Some(data.to_vec()))
                 } else {
-                    todo!()
+                    (None, None, None)
                 };
 
                 images.push(crate::Image {
                     path,
-                    data_type: None,
-                    data: None
+                    data_type,
+                    data,
+
+                    name: image.name.clone(),
+                    extras: image.extras.clone()
                 });
             }
 
@@ -1087,24 +1290,1032 @@ impl Importer for Gltf {
             None
         };
 
-        crate::Scene {
+        Ok(crate::Scene {
             meshes,
             materials,
+            images,
+            nodes,
+            root_nodes
+        })
+    }
+}
+
+impl Exporter for Gltf {
+    fn export(&self, path: &str) -> Result<(), ExportError> {
+        let path = Path::new(path);
+
+        let mut json = self.to_json();
+
+        let is_glb = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("glb"));
+
+        if is_glb {
+            let mut bin = self.glb_data.clone().unwrap_or_default();
+
+            let directory = path.parent().unwrap_or(Path::new("")).to_path_buf();
+            embed_images(&mut json, &mut bin, self.glb_data.is_some(), &directory)?;
+
+            let json = serde_json::to_vec(&json)
+                .map_err(|err| ExportError::new(ExportErrorType::Other, err))?;
+
+            std::fs::write(path, write_glb(&json, (!bin.is_empty()).then_some(&bin[..])))?;
+
+            return Ok(());
+        }
+
+        // The GLB data is stored in the first buffer, but .gltf files can't contain binary data, so
+        // write it out to a .bin file next to the .gltf, and point the buffer at it.
+        if let Some(data) = &self.glb_data {
+            let bin_name = match path.file_stem() {
+                Some(stem) => format!("{}.bin", stem.to_string_lossy()),
+                None => return Err(ExportError::new(ExportErrorType::Other, "The given path has no file name."))
+            };
+
+            std::fs::write(path.with_file_name(&bin_name), data)?;
+
+            if let Some(buffer) = json["buffers"].get_mut(0) {
+                buffer["uri"] = Value::from(encode_uri(&bin_name));
+            }
+        }
+
+        let json = serde_json::to_string_pretty(&json)
+            .map_err(|err| ExportError::new(ExportErrorType::Other, err))?;
+
+        std::fs::write(path, json)?;
+
+        Ok(())
+    }
+
+    fn from_scene(scene: &crate::Scene) -> Self {
+        let mut builder = BufferBuilder::default();
+
+        // glTF nodes can only contain a single mesh, however modelo nodes can contain multiple, so
+        // each unique set of meshes used by a node becomes a single glTF mesh, with one primitive
+        // for each modelo mesh.
+        let mut mesh_groups: Vec<Vec<usize>> = Vec::new();
+
+        for node in &scene.nodes {
+            if !node.meshes.is_empty() && !mesh_groups.contains(&node.meshes) {
+                mesh_groups.push(node.meshes.clone());
+            }
+        }
+
+        // Any meshes that aren't used by a node still need to be exported.
+        for mesh in 0..scene.meshes.len() {
+            if !mesh_groups.iter().any(|group| group.contains(&mesh)) {
+                mesh_groups.push(vec![mesh]);
+            }
+        }
+
+        // Primitives that use the same modelo mesh can share the same accessors.
+        let mut primitives = Vec::with_capacity(scene.meshes.len());
+
+        for mesh in &scene.meshes {
+            primitives.push(builder.push_mesh(mesh));
+        }
+
+        let meshes = mesh_groups
+            .iter()
+            .map(|group| {
+                let first = &scene.meshes[group[0]];
+
+                Mesh {
+                    primitives: group
+                        .iter()
+                        .map(|mesh| {
+                            let (attributes, indices) = &primitives[*mesh];
+
+                            MeshPrimitive {
+                                attributes: attributes.clone(),
+                                indices: *indices,
+                                material: scene.meshes[*mesh].material.map(|material| material as u64),
+                                mode: PrimitiveTopology::Triangles,
+                            }
+                        })
+                        .collect(),
+                    weights: None,
+                    name: first.name.clone(),
+                    extras: first.extras.clone()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let (nodes, root_nodes) = if scene.nodes.is_empty() {
+            // Formats without a hierarchy need a node for each mesh, otherwise nothing will be visible.
+            let nodes = (0..meshes.len())
+                .map(|mesh| Node::from_mesh(Some(mesh as u64), Mat4::identity(), None, None, None))
+                .collect::<Vec<_>>();
+
+            let root_nodes = (0..nodes.len() as u64).collect();
+
+            (nodes, root_nodes)
+        } else {
+            let nodes = scene.nodes
+                .iter()
+                .map(|node| {
+                    let mesh = mesh_groups
+                        .iter()
+                        .position(|group| *group == node.meshes)
+                        .map(|mesh| mesh as u64);
+
+                    let children = if node.children.is_empty() {
+                        None
+                    } else {
+                        Some(node.children.iter().map(|child| *child as u64).collect())
+                    };
+
+                    Node::from_mesh(mesh, node.transform, children, node.name.clone(), node.extras.clone())
+                })
+                .collect();
+
+            let root_nodes = scene.root_nodes.iter().map(|node| *node as u64).collect();
+
+            (nodes, root_nodes)
+        };
+
+        let images = scene.images.as_ref().map(|images| {
             images
+                .iter()
+                .map(|image| {
+                    // Images with data are embedded in the buffer, otherwise they are referenced by their path.
+                    let (uri, mime_type, buffer_view) = if let Some(data) = &image.data {
+                        let view = builder.push_view(data, None, None);
+                        (None, Some(image_mime_type(image.data_type, data).to_string()), Some(view))
+                    } else {
+                        (image.path.as_ref().map(|path| encode_uri(path)), None, None)
+                    };
+
+                    Image {
+                        uri,
+                        mime_type,
+                        buffer_view,
+                        name: image.name.clone(),
+                        extras: image.extras.clone()
+                    }
+                })
+                .collect::<Vec<_>>()
+        });
+
+        // modelo materials refer to images directly, so create a texture for each image.
+        let textures = images.as_ref().map(|images| {
+            (0..images.len() as u64)
+                .map(|image| Texture {
+                    sampler: None,
+                    source: Some(image)
+                })
+                .collect()
+        });
+
+        let materials = scene.materials.as_ref().map(|materials| {
+            materials
+                .iter()
+                .map(Material::from_scene_material)
+                .collect()
+        });
+
+        let BufferBuilder { data, views, accessors } = builder;
+
+        let buffers = if data.is_empty() {
+            None
+        } else {
+            Some(vec![Buffer {
+                uri: None,
+                byte_length: data.len() as u64
+            }])
+        };
+
+        Gltf {
+            accessors: if accessors.is_empty() { None } else { Some(accessors) },
+            asset: Asset {
+                version: "2.0".to_string(),
+                copyright: None,
+                generator: Some("modelo".to_string()),
+                min_version: None
+            },
+            buffers,
+            buffer_views: if views.is_empty() { None } else { Some(views) },
+            images,
+            materials,
+            meshes: Some(meshes),
+            nodes: Some(nodes),
+            samplers: None,
+            scene: Some(0),
+            scenes: Some(vec![Scene {
+                nodes: Some(root_nodes)
+            }]),
+            textures,
+
+            glb_data: if data.is_empty() { None } else { Some(data) }
+        }
+    }
+}
+
+impl Gltf {
+    /// Gets the index of the image used by the given texture.
+    fn texture_image(&self, info: &TextureInfo) -> Option<usize> {
+        self.textures
+            .as_ref()
+            .and_then(|textures| textures.get(info.index as usize))
+            .and_then(|texture| texture.source)
+            .map(|source| source as usize)
+    }
+
+    /// Reads every component of the accessor as a float, applying normalization if required.
+    ///
+    /// The returned values are flattened, so a `VEC3` accessor will return `count * 3` values.
+    fn read_accessor(&self, accessor: &Accessor, buffers: &[Vec<u8>]) -> Result<Vec<f32>, crate::ImportError> {
+        let values = self.read_accessor_raw(accessor, buffers)?;
+
+        if !accessor.normalized {
+            return Ok(values.iter().map(|value| *value as f32).collect());
+        }
+
+        // Normalized integers are mapped to the 0-1 range, or -1-1 for signed types.
+        let max = match accessor.component_type {
+            ComponentType::Byte => i8::MAX as f64,
+            ComponentType::UnsignedByte => u8::MAX as f64,
+            ComponentType::Short => i16::MAX as f64,
+            ComponentType::UnsignedShort => u16::MAX as f64,
+            ComponentType::UnsignedInt => u32::MAX as f64,
+            ComponentType::Float => 1.0
+        };
+
+        Ok(values
+            .iter()
+            .map(|value| (value / max).max(-1.0) as f32)
+            .collect())
+    }
+
+    /// Reads every component of the accessor without normalization, taking into account the buffer
+    /// view's stride and any sparse values.
+    ///
+    /// Values are returned as `f64`, as it can exactly represent every glTF component type.
+    fn read_accessor_raw(&self, accessor: &Accessor, buffers: &[Vec<u8>]) -> Result<Vec<f64>, crate::ImportError> {
+        let components = accessor.a_type.num_components();
+        let component_size = accessor.component_type.size();
+        let element_size = components * component_size;
+
+        let view = accessor.buffer_view.map(|view| self.view_data(view, buffers)).transpose()?;
+
+        let stride = match view {
+            Some((BufferView { byte_stride: Some(stride), .. }, _)) => *stride as usize,
+            _ => element_size
+        };
+
+        // The count comes from the file, so check it against the data before allocating for it. Accessors
+        // without a buffer view have nothing to check against, but a real file has other data for each of
+        // their elements, so they can't have more elements than there are bytes in the buffers.
+        let fits = match view {
+            Some((_, data)) => elements_fit(accessor.byte_offset, accessor.count, stride, element_size, data.len()),
+            None => accessor.count <= buffers.iter().map(Vec::len).sum::<usize>() as u64
+        };
+
+        if !fits {
+            return Err(invalid_file(format!("An accessor's {} elements don't fit in its buffer view.", accessor.count)));
+        }
+
+        // Accessors without a buffer view are initialized to zeros.
+        let mut values = vec![0.0; accessor.count as usize * components];
+
+        if let Some((_, data)) = view {
+            for (i, element) in values.chunks_exact_mut(components).enumerate() {
+                let offset = accessor.byte_offset as usize + i * stride;
+
+                for (c, value) in element.iter_mut().enumerate() {
+                    *value = read_component(data, offset + c * component_size, &accessor.component_type);
+                }
+            }
+        }
+
+        if let Some(sparse) = &accessor.sparse {
+            let index_size = sparse.indices.component_type.size();
+
+            let (_, indices_data) = self.view_data(sparse.indices.buffer_view, buffers)?;
+            let (_, values_data) = self.view_data(sparse.values.buffer_view, buffers)?;
+
+            if sparse.count > accessor.count
+                || !elements_fit(sparse.indices.byte_offset, sparse.count, index_size, index_size, indices_data.len())
+                || !elements_fit(sparse.values.byte_offset, sparse.count, element_size, element_size, values_data.len()) {
+                return Err(invalid_file(format!("An accessor's {} sparse values don't fit in their buffer views.", sparse.count)));
+            }
+
+            for i in 0..sparse.count as usize {
+                let index_offset = sparse.indices.byte_offset as usize + i * index_size;
+                let index = read_component(indices_data, index_offset, &sparse.indices.component_type) as usize;
+
+                if index >= accessor.count as usize {
+                    return Err(invalid_file(format!("Sparse index {index} is out of range.")));
+                }
+
+                for c in 0..components {
+                    let value_offset = sparse.values.byte_offset as usize + (i * components + c) * component_size;
+                    values[index * components + c] = read_component(values_data, value_offset, &accessor.component_type);
+                }
+            }
+        }
+
+        Ok(values)
+    }
+
+    fn accessor(&self, index: u64) -> Result<&Accessor, crate::ImportError> {
+        self.accessors
+            .as_ref()
+            .and_then(|accessors| accessors.get(index as usize))
+            .ok_or_else(|| invalid_file(format!("Accessor {index} doesn't exist.")))
+    }
+
+    /// Gets the buffer view and its data, checking that it fits inside its buffer.
+    fn view_data<'a>(&'a self, index: u64, buffers: &'a [Vec<u8>]) -> Result<(&'a BufferView, &'a [u8]), crate::ImportError> {
+        let view = self.buffer_views
+            .as_ref()
+            .and_then(|views| views.get(index as usize))
+            .ok_or_else(|| invalid_file(format!("Buffer view {index} doesn't exist.")))?;
+
+        let buffer = buffers
+            .get(view.buffer as usize)
+            .ok_or_else(|| invalid_file(format!("Buffer {} doesn't exist.", view.buffer)))?;
+
+        let data = view.byte_offset
+            .checked_add(view.byte_length)
+            .and_then(|end| buffer.get(view.byte_offset as usize..end as usize))
+            .ok_or_else(|| invalid_file(format!("Buffer view {index} is larger than its buffer.")))?;
+
+        Ok((view, data))
+    }
+
+    /// Converts the glTF into its JSON representation.
+    pub fn to_json(&self) -> Value {
+        let mut json = Map::new();
+
+        let mut asset = Map::new();
+        asset.insert("version".into(), Value::from(self.asset.version.clone()));
+        insert_some(&mut asset, "copyright", self.asset.copyright.clone());
+        insert_some(&mut asset, "generator", self.asset.generator.clone());
+        insert_some(&mut asset, "minVersion", self.asset.min_version.clone());
+        json.insert("asset".into(), Value::Object(asset));
+
+        insert_array(&mut json, "accessors", &self.accessors, |accessor| {
+            let mut map = Map::new();
+            insert_some(&mut map, "bufferView", accessor.buffer_view);
+            if accessor.byte_offset != 0 {
+                map.insert("byteOffset".into(), Value::from(accessor.byte_offset));
+            }
+            map.insert("componentType".into(), Value::from(accessor.component_type.to_u64()));
+            if accessor.normalized {
+                map.insert("normalized".into(), Value::from(true));
+            }
+            map.insert("count".into(), Value::from(accessor.count));
+            map.insert("type".into(), Value::from(accessor.a_type.as_str()));
+            insert_some(&mut map, "max", accessor.max.clone());
+            insert_some(&mut map, "min", accessor.min.clone());
+
+            if let Some(sparse) = &accessor.sparse {
+                let mut indices = Map::new();
+                indices.insert("bufferView".into(), Value::from(sparse.indices.buffer_view));
+                indices.insert("byteOffset".into(), Value::from(sparse.indices.byte_offset));
+                indices.insert("componentType".into(), Value::from(sparse.indices.component_type.to_u64()));
+
+                let mut values = Map::new();
+                values.insert("bufferView".into(), Value::from(sparse.values.buffer_view));
+                values.insert("byteOffset".into(), Value::from(sparse.values.byte_offset));
+
+                let mut sparse_map = Map::new();
+                sparse_map.insert("count".into(), Value::from(sparse.count));
+                sparse_map.insert("indices".into(), Value::Object(indices));
+                sparse_map.insert("values".into(), Value::Object(values));
+
+                map.insert("sparse".into(), Value::Object(sparse_map));
+            }
+
+            map
+        });
+
+        insert_array(&mut json, "buffers", &self.buffers, |buffer| {
+            let mut map = Map::new();
+            insert_some(&mut map, "uri", buffer.uri.clone());
+            map.insert("byteLength".into(), Value::from(buffer.byte_length));
+            map
+        });
+
+        insert_array(&mut json, "bufferViews", &self.buffer_views, |view| {
+            let mut map = Map::new();
+            map.insert("buffer".into(), Value::from(view.buffer));
+            if view.byte_offset != 0 {
+                map.insert("byteOffset".into(), Value::from(view.byte_offset));
+            }
+            map.insert("byteLength".into(), Value::from(view.byte_length));
+            insert_some(&mut map, "byteStride", view.byte_stride);
+            insert_some(&mut map, "target", view.target.as_ref().map(EnumConvert::to_u64));
+            map
+        });
+
+        insert_array(&mut json, "images", &self.images, |image| {
+            let mut map = Map::new();
+            insert_some(&mut map, "uri", image.uri.clone());
+            insert_some(&mut map, "mimeType", image.mime_type.clone());
+            insert_some(&mut map, "bufferView", image.buffer_view);
+            insert_some(&mut map, "name", image.name.clone());
+            insert_some(&mut map, "extras", image.extras.clone());
+            map
+        });
+
+        insert_array(&mut json, "materials", &self.materials, |material| {
+            let mut map = Map::new();
+
+            if let Some(pbr) = &material.pbr_metallic_roughness {
+                let mut pbr_map = Map::new();
+                pbr_map.insert("baseColorFactor".into(), Value::from(vec![pbr.base_color_factor.x, pbr.base_color_factor.y, pbr.base_color_factor.z, pbr.base_color_factor.w]));
+                insert_some(&mut pbr_map, "baseColorTexture", pbr.base_color_texture.as_ref().map(|info| texture_info_to_value(info, "scale")));
+                pbr_map.insert("metallicFactor".into(), Value::from(pbr.metallic_factor));
+                pbr_map.insert("roughnessFactor".into(), Value::from(pbr.roughness_factor));
+                insert_some(&mut pbr_map, "metallicRoughnessTexture", pbr.metallic_roughness_texture.as_ref().map(|info| texture_info_to_value(info, "scale")));
+                map.insert("pbrMetallicRoughness".into(), Value::Object(pbr_map));
+            }
+
+            insert_some(&mut map, "normalTexture", material.normal_texture.as_ref().map(|info| texture_info_to_value(info, "scale")));
+            insert_some(&mut map, "occlusionTexture", material.occlusion_texture.as_ref().map(|info| texture_info_to_value(info, "strength")));
+            insert_some(&mut map, "emissiveTexture", material.emissive_texture.as_ref().map(|info| texture_info_to_value(info, "scale")));
+            map.insert("emissiveFactor".into(), Value::from(vec![material.emissive_factor.x, material.emissive_factor.y, material.emissive_factor.z]));
+
+            let alpha_mode = match material.alpha_mode {
+                AlphaMode::Opaque => "OPAQUE",
+                AlphaMode::Mask => "MASK",
+                AlphaMode::Blend => "BLEND"
+            };
+
+            map.insert("alphaMode".into(), Value::from(alpha_mode));
+            map.insert("alphaCutoff".into(), Value::from(material.alpha_cutoff));
+            map.insert("doubleSided".into(), Value::from(material.double_sided));
+            insert_some(&mut map, "name", material.name.clone());
+            insert_some(&mut map, "extras", material.extras.clone());
+            map
+        });
+
+        insert_array(&mut json, "meshes", &self.meshes, |mesh| {
+            let mut map = Map::new();
+
+            let primitives = mesh.primitives
+                .iter()
+                .map(|primitive| {
+                    let mut prim_map = Map::new();
+
+                    let attributes = primitive.attributes
+                        .iter()
+                        .map(|(name, accessor)| (name.clone(), Value::from(*accessor)))
+                        .collect::<Map<_, _>>();
+
+                    prim_map.insert("attributes".into(), Value::Object(attributes));
+                    insert_some(&mut prim_map, "indices", primitive.indices);
+                    insert_some(&mut prim_map, "material", primitive.material);
+                    prim_map.insert("mode".into(), Value::from(primitive.mode.to_u64()));
+
+                    Value::Object(prim_map)
+                })
+                .collect::<Vec<_>>();
+
+            map.insert("primitives".into(), Value::from(primitives));
+            insert_some(&mut map, "weights", mesh.weights.clone());
+            insert_some(&mut map, "name", mesh.name.clone());
+            insert_some(&mut map, "extras", mesh.extras.clone());
+            map
+        });
+
+        insert_array(&mut json, "nodes", &self.nodes, |node| {
+            let mut map = Map::new();
+            insert_some(&mut map, "camera", node.camera);
+            insert_some(&mut map, "children", node.children.clone());
+            insert_some(&mut map, "skin", node.skin);
+
+            // glTF matrices are column-major, so we write each column in turn.
+            if node.matrix != Mat4::identity() {
+                let matrix = [node.matrix.column0(), node.matrix.column1(), node.matrix.column2(), node.matrix.column3()]
+                    .iter()
+                    .flat_map(|column| [column.x, column.y, column.z, column.w])
+                    .collect::<Vec<_>>();
+
+                map.insert("matrix".into(), Value::from(matrix));
+            }
+
+            insert_some(&mut map, "mesh", node.mesh);
+
+            if node.rotation != Quat::new(0.0, 0.0, 0.0, 1.0) {
+                map.insert("rotation".into(), Value::from(vec![node.rotation.x, node.rotation.y, node.rotation.z, node.rotation.w]));
+            }
+
+            if node.scale != Vec3::new(1.0, 1.0, 1.0) {
+                map.insert("scale".into(), Value::from(vec![node.scale.x, node.scale.y, node.scale.z]));
+            }
+
+            if node.translation != Vec3::new(0.0, 0.0, 0.0) {
+                map.insert("translation".into(), Value::from(vec![node.translation.x, node.translation.y, node.translation.z]));
+            }
+
+            insert_some(&mut map, "weights", node.weights.clone());
+            insert_some(&mut map, "name", node.name.clone());
+            insert_some(&mut map, "extras", node.extras.clone());
+            map
+        });
+
+        insert_array(&mut json, "samplers", &self.samplers, |sampler| {
+            let mut map = Map::new();
+            insert_some(&mut map, "magFilter", sampler.mag_filter.as_ref().map(EnumConvert::to_u64));
+            insert_some(&mut map, "minFilter", sampler.min_filter.as_ref().map(EnumConvert::to_u64));
+            map.insert("wrapS".into(), Value::from(sampler.wrap_s.to_u64()));
+            map.insert("wrapT".into(), Value::from(sampler.wrap_t.to_u64()));
+            map
+        });
+
+        insert_some(&mut json, "scene", self.scene);
+
+        insert_array(&mut json, "scenes", &self.scenes, |scene| {
+            let mut map = Map::new();
+            insert_some(&mut map, "nodes", scene.nodes.clone());
+            map
+        });
+
+        insert_array(&mut json, "textures", &self.textures, |texture| {
+            let mut map = Map::new();
+            insert_some(&mut map, "sampler", texture.sampler);
+            insert_some(&mut map, "source", texture.source);
+            map
+        });
+
+        Value::Object(json)
+    }
+}
+
+impl Node {
+    fn from_mesh(mesh: Option<u64>, matrix: Mat4, children: Option<Vec<u64>>, name: Option<String>, extras: Option<Value>) -> Self {
+        Self {
+            camera: None,
+            children,
+            skin: None,
+            matrix,
+            mesh,
+            rotation: Quat::new(0.0, 0.0, 0.0, 1.0),
+            scale: Vec3::new(1.0, 1.0, 1.0),
+            translation: Vec3::new(0.0, 0.0, 0.0),
+            weights: None,
+            name,
+            extras
+        }
+    }
+}
+
+impl Material {
+    fn from_scene_material(material: &crate::Material) -> Self {
+        // modelo materials refer to images directly, and we create one texture per image, so the
+        // texture index is always the same as the image index.
+        let texture_info = |texture: Option<usize>| {
+            texture.map(|index| TextureInfo {
+                index: index as u64,
+                tex_coord: 0,
+                scale: None
+            })
+        };
+
+        let alpha_mode = match material.alpha_mode {
+            crate::AlphaMode::Opaque => AlphaMode::Opaque,
+            crate::AlphaMode::Cutoff => AlphaMode::Mask,
+            crate::AlphaMode::Blend => AlphaMode::Blend
+        };
+
+        // modelo doesn't store an emissive factor, so the emissive texture must be multiplied by 1,
+        // otherwise it would not show up at all.
+        let emissive_factor = if material.emissive_texture.is_some() {
+            Vec3::new(1.0, 1.0, 1.0)
+        } else {
+            Vec3::new(0.0, 0.0, 0.0)
+        };
+
+        Self {
+            pbr_metallic_roughness: Some(PbrMetallicRoughness {
+                base_color_factor: material.albedo_color,
+                base_color_texture: texture_info(material.albedo_texture),
+                metallic_factor: material.metallic,
+                roughness_factor: material.roughness,
+                // glTF stores metallic and roughness in the same texture.
+                metallic_roughness_texture: texture_info(material.metallic_texture.or(material.roughness_texture))
+            }),
+            normal_texture: texture_info(material.normal_texture),
+            occlusion_texture: texture_info(material.occlusion_texture),
+            emissive_texture: texture_info(material.emissive_texture),
+            emissive_factor,
+            alpha_mode,
+            alpha_cutoff: material.alpha_cutoff,
+            double_sided: material.double_sided,
+            name: material.name.clone(),
+            extras: material.extras.clone()
+        }
+    }
+}
+
+/// Packs data into a single buffer, creating buffer views and accessors as it goes.
+#[derive(Default)]
+struct BufferBuilder {
+    data:      Vec<u8>,
+    views:     Vec<BufferView>,
+    accessors: Vec<Accessor>
+}
+
+impl BufferBuilder {
+    fn push_view(&mut self, bytes: &[u8], byte_stride: Option<u64>, target: Option<BufferTarget>) -> u64 {
+        // Accessors must be aligned to the size of their component type, so pad each view to 4 bytes
+        // which covers all of them.
+        while !self.data.len().is_multiple_of(4) {
+            self.data.push(0);
+        }
+
+        self.views.push(BufferView {
+            buffer: 0,
+            byte_offset: self.data.len() as u64,
+            byte_length: bytes.len() as u64,
+            byte_stride,
+            target
+        });
+
+        self.data.extend_from_slice(bytes);
+
+        (self.views.len() - 1) as u64
+    }
+
+    /// Pushes a float accessor, where each element has `components` floats.
+    fn push_f32_accessor(&mut self, values: &[f32], components: usize, a_type: AccessorType) -> u64 {
+        let mut min = vec![f32::INFINITY; components];
+        let mut max = vec![f32::NEG_INFINITY; components];
+
+        for element in values.chunks_exact(components) {
+            for (i, value) in element.iter().enumerate() {
+                min[i] = min[i].min(*value);
+                max[i] = max[i].max(*value);
+            }
+        }
+
+        let bytes = values.iter().flat_map(|value| value.to_le_bytes()).collect::<Vec<_>>();
+        let view = self.push_view(&bytes, None, Some(BufferTarget::ArrayBuffer));
+
+        self.push_accessor(view, ComponentType::Float, (values.len() / components) as u64, a_type, min, max)
+    }
+
+    fn push_indices(&mut self, indices: &[u32]) -> u64 {
+        let max_index = indices.iter().copied().max().unwrap_or(0);
+
+        // Use the smallest index type we can, to save space.
+        let (bytes, component_type) = if max_index <= u16::MAX as u32 {
+            (indices.iter().flat_map(|index| (*index as u16).to_le_bytes()).collect::<Vec<_>>(), ComponentType::UnsignedShort)
+        } else {
+            (indices.iter().flat_map(|index| index.to_le_bytes()).collect::<Vec<_>>(), ComponentType::UnsignedInt)
+        };
+
+        let view = self.push_view(&bytes, None, Some(BufferTarget::ElementArrayBuffer));
+
+        let min_index = indices.iter().copied().min().unwrap_or(0);
+
+        self.push_accessor(view, component_type, indices.len() as u64, AccessorType::Scalar, vec![min_index as f32], vec![max_index as f32])
+    }
+
+    fn push_accessor(&mut self, view: u64, component_type: ComponentType, count: u64, a_type: AccessorType, min: Vec<f32>, max: Vec<f32>) -> u64 {
+        let has_bounds = count > 0;
+
+        self.accessors.push(Accessor {
+            buffer_view: Some(view),
+            byte_offset: 0,
+            component_type,
+            normalized: false,
+            count,
+            a_type,
+            max: if has_bounds { Some(max) } else { None },
+            min: if has_bounds { Some(min) } else { None },
+            sparse: None
+        });
+
+        (self.accessors.len() - 1) as u64
+    }
+
+    /// Writes the mesh's vertices and indices, returning the primitive attributes and indices accessor.
+    fn push_mesh(&mut self, mesh: &crate::Mesh) -> (Vec<(String, u64)>, Option<u64>) {
+        let vertices = &mesh.vertices;
+
+        let mut attributes = Vec::new();
+
+        let positions = vertices.iter().flat_map(|v| [v.position.x, v.position.y, v.position.z]).collect::<Vec<_>>();
+        attributes.push(("POSITION".to_string(), self.push_f32_accessor(&positions, 3, AccessorType::Vec3)));
+
+        // Only write the other attributes if they contain something useful.
+        if mesh.has_normals {
+            let normals = vertices.iter().flat_map(|v| [v.normal.x, v.normal.y, v.normal.z]).collect::<Vec<_>>();
+            attributes.push(("NORMAL".to_string(), self.push_f32_accessor(&normals, 3, AccessorType::Vec3)));
+        }
+
+        // A tangent's W is the bitangent sign, so is only zero if there isn't a tangent.
+        if vertices.iter().any(|v| v.tangent.w != 0.0) {
+            let tangents = vertices.iter().flat_map(|v| [v.tangent.x, v.tangent.y, v.tangent.z, v.tangent.w]).collect::<Vec<_>>();
+            attributes.push(("TANGENT".to_string(), self.push_f32_accessor(&tangents, 4, AccessorType::Vec4)));
+        }
+
+        if vertices.iter().any(|v| v.tex_coord.x != 0.0 || v.tex_coord.y != 0.0) {
+            let tex_coords = vertices.iter().flat_map(|v| [v.tex_coord.x, v.tex_coord.y]).collect::<Vec<_>>();
+            attributes.push(("TEXCOORD_0".to_string(), self.push_f32_accessor(&tex_coords, 2, AccessorType::Vec2)));
+        }
+
+        if vertices.iter().any(|v| v.color != Vec4::new(1.0, 1.0, 1.0, 1.0)) {
+            let colors = vertices.iter().flat_map(|v| [v.color.x, v.color.y, v.color.z, v.color.w]).collect::<Vec<_>>();
+            attributes.push(("COLOR_0".to_string(), self.push_f32_accessor(&colors, 4, AccessorType::Vec4)));
+        }
+
+        let indices = mesh.indices.as_ref().map(|indices| self.push_indices(indices));
+
+        (attributes, indices)
+    }
+}
+
+/// Checks that `count` elements of `element_size` bytes, each `stride` bytes after the last and starting
+/// at `offset`, fit in `length` bytes.
+fn elements_fit(offset: u64, count: u64, stride: usize, element_size: usize, length: usize) -> bool {
+    count == 0 || (count - 1).checked_mul(stride as u64)
+        .and_then(|last| last.checked_add(offset))
+        .and_then(|last| last.checked_add(element_size as u64))
+        .is_some_and(|end| end <= length as u64)
+}
+
+/// Checks that the nodes form trees, as glTF requires, so walking the hierarchy always ends.
+fn validate_hierarchy(nodes: &[crate::Node]) -> Result<(), crate::ImportError> {
+    let mut parents = vec![None; nodes.len()];
+
+    for (i, node) in nodes.iter().enumerate() {
+        for &child in &node.children {
+            match parents.get_mut(child) {
+                None => return Err(invalid_file(format!("Node {i} has a child {child}, which doesn't exist."))),
+                Some(Some(_)) => return Err(invalid_file(format!("Node {child} has more than one parent."))),
+                Some(parent) => *parent = Some(i)
+            }
+        }
+    }
+
+    // As every node has at most one parent, any node that can't be reached from a node without a
+    // parent is part of a cycle, or below one.
+    let mut reached = vec![false; nodes.len()];
+    let mut stack = (0..nodes.len()).filter(|&i| parents[i].is_none()).collect::<Vec<_>>();
+
+    while let Some(node) = stack.pop() {
+        reached[node] = true;
+        stack.extend_from_slice(&nodes[node].children);
+    }
+
+    match reached.iter().position(|reached| !reached) {
+        Some(node) => Err(invalid_file(format!("Node {node} is in or below a cycle of nodes."))),
+        None => Ok(())
+    }
+}
+
+fn invalid_file<T: ToString>(message: T) -> crate::ImportError {
+    crate::ImportError::new(crate::ImportErrorType::Other, message)
+}
+
+/// Reads a single little-endian component from the buffer at the given offset, which must already have
+/// been checked to be in range.
+fn read_component(buffer: &[u8], offset: usize, component_type: &ComponentType) -> f64 {
+    let bytes = &buffer[offset..offset + component_type.size()];
+
+    match component_type {
+        ComponentType::Byte => bytes[0] as i8 as f64,
+        ComponentType::UnsignedByte => bytes[0] as f64,
+        ComponentType::Short => i16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+        ComponentType::UnsignedShort => u16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+        ComponentType::UnsignedInt => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+        ComponentType::Float => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64
+    }
+}
+
+pub(crate) const GLB_MAGIC: &[u8] = b"glTF";
+
+const GLB_CHUNK_JSON: u32 = 0x4E4F534A;
+const GLB_CHUNK_BIN: u32 = 0x004E4942;
+
+/// Splits a GLB file into its JSON chunk, and its binary chunk if it has one.
+fn read_glb(data: &[u8]) -> Result<(&[u8], Option<&[u8]>), crate::ImportError> {
+    let read_u32 = |offset: usize| {
+        data.get(offset..offset + 4)
+            .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .ok_or_else(|| crate::ImportError::new(crate::ImportErrorType::Other, "Unexpected end of GLB file."))
+    };
+
+    let version = read_u32(4)?;
+    if version != 2 {
+        return Err(crate::ImportError::new(crate::ImportErrorType::Other, format!("Unsupported GLB version {version}.")));
+    }
+
+    let length = (read_u32(8)? as usize).min(data.len());
+
+    let mut json = None;
+    let mut bin = None;
+
+    // The header is 12 bytes, and each chunk has an 8 byte header of its length and type.
+    let mut offset = 12;
+
+    while offset + 8 <= length {
+        let chunk_length = read_u32(offset)? as usize;
+        let chunk_type = read_u32(offset + 4)?;
+
+        let start = offset + 8;
+        let chunk = data.get(start..start + chunk_length)
+            .ok_or_else(|| crate::ImportError::new(crate::ImportErrorType::Other, "GLB chunk is out of bounds."))?;
+
+        match chunk_type {
+            GLB_CHUNK_JSON if json.is_none() => json = Some(chunk),
+            GLB_CHUNK_BIN if bin.is_none() => bin = Some(chunk),
+
+            // Unknown chunks must be ignored.
+            _ => {}
+        }
+
+        offset = start + chunk_length;
+    }
+
+    match json {
+        Some(json) => Ok((json, bin)),
+        None => Err(crate::ImportError::new(crate::ImportErrorType::Other, "GLB file has no JSON chunk."))
+    }
+}
+
+/// Creates a GLB file from the given JSON and binary data, padding each chunk to 4 bytes.
+fn write_glb(json: &[u8], bin: Option<&[u8]>) -> Vec<u8> {
+    let padded = |length: usize| length.next_multiple_of(4);
+
+    let json_length = padded(json.len());
+    let bin_length = bin.map(|bin| padded(bin.len()));
+
+    let total_length = 12 + 8 + json_length + bin_length.map_or(0, |length| 8 + length);
+
+    let mut glb = Vec::with_capacity(total_length);
+
+    glb.extend_from_slice(GLB_MAGIC);
+    glb.extend_from_slice(&2u32.to_le_bytes());
+    glb.extend_from_slice(&(total_length as u32).to_le_bytes());
+
+    // The JSON chunk must be padded with spaces, and the binary chunk with zeros.
+    glb.extend_from_slice(&(json_length as u32).to_le_bytes());
+    glb.extend_from_slice(&GLB_CHUNK_JSON.to_le_bytes());
+    glb.extend_from_slice(json);
+    glb.resize(20 + json_length, b' ');
+
+    if let (Some(bin), Some(bin_length)) = (bin, bin_length) {
+        glb.extend_from_slice(&(bin_length as u32).to_le_bytes());
+        glb.extend_from_slice(&GLB_CHUNK_BIN.to_le_bytes());
+        glb.extend_from_slice(bin);
+        glb.resize(total_length, 0);
+    }
+
+    glb
+}
+
+/// Embeds the images that are only referred to by a URI into the binary chunk of a GLB, so the file
+/// can be loaded on its own. URIs are relative to the GLB, so the files are resolved from its directory.
+///
+/// The binary chunk is always the first buffer, so if there wasn't one, it's inserted before the others.
+fn embed_images(json: &mut Value, bin: &mut Vec<u8>, has_bin: bool, resolver: &dyn ResourceResolver) -> Result<(), ExportError> {
+    let uris = json["images"].as_array().into_iter().flatten()
+        .enumerate()
+        .filter_map(|(i, image)| Some((i, image.get("uri")?.as_str()?.to_string())))
+        .filter(|(_, uri)| !uri.starts_with("data:"))
+        .collect::<Vec<_>>();
+
+    if uris.is_empty() {
+        return Ok(());
+    }
+
+    if !has_bin {
+        for view in json["bufferViews"].as_array_mut().into_iter().flatten() {
+            view["buffer"] = Value::from(view["buffer"].as_u64().unwrap_or(0) + 1);
+        }
+
+        match json["buffers"].as_array_mut() {
+            Some(buffers) => buffers.insert(0, Value::Object(Map::new())),
+            None => json["buffers"] = Value::Array(vec![Value::Object(Map::new())])
         }
     }
+
+    if json["bufferViews"].as_array().is_none() {
+        json["bufferViews"] = Value::Array(Vec::new());
+    }
+
+    for (i, uri) in uris {
+        let data = resolver.resolve(&decode_uri(&uri))
+            .map_err(|err| ExportError::new(ExportErrorType::IoError, format!("Couldn't embed image \"{uri}\": {}", err.message)))?;
+
+        while !bin.len().is_multiple_of(4) {
+            bin.push(0);
+        }
+
+        let views = json["bufferViews"].as_array_mut().unwrap();
+        views.push(serde_json::json!({ "buffer": 0, "byteOffset": bin.len(), "byteLength": data.len() }));
+        let view = views.len() - 1;
+
+        let image = json["images"][i].as_object_mut().unwrap();
+        image.remove("uri");
+        image.insert("bufferView".into(), Value::from(view));
+        image.insert("mimeType".into(), Value::from(image_mime_type(None, &data)));
+
+        bin.extend_from_slice(&data);
+    }
+
+    json["buffers"][0]["byteLength"] = Value::from(bin.len());
+
+    Ok(())
+}
+
+/// Gets the mime type to use for the image, guessing it from the data if the type is unknown.
+fn image_mime_type(data_type: Option<crate::ImageDataType>, data: &[u8]) -> &'static str {
+    match data_type {
+        Some(crate::ImageDataType::Png) => "image/png",
+        Some(crate::ImageDataType::Jpg) => "image/jpeg",
+        Some(crate::ImageDataType::Bmp) => "image/bmp",
+        Some(crate::ImageDataType::Dds) => "image/vnd-ms.dds",
+
+        _ => {
+            if data.starts_with(&[0x89, b'P', b'N', b'G']) {
+                "image/png"
+            } else if data.starts_with(&[0xFF, 0xD8]) {
+                "image/jpeg"
+            } else if data.starts_with(b"BM") {
+                "image/bmp"
+            } else if data.starts_with(b"DDS ") {
+                "image/vnd-ms.dds"
+            } else {
+                "application/octet-stream"
+            }
+        }
+    }
+}
+
+/// Percent-encodes the characters in a path that aren't valid in a URI, with backslashes becoming
+/// forward slashes. [`decode_uri`] gives the path back.
+fn encode_uri(path: &str) -> String {
+    let mut uri = String::with_capacity(path.len());
+
+    for byte in path.replace('\\', "/").bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{byte:02X}"));
+        }
+    }
+
+    uri
+}
+
+/// Decodes the percent-encoded characters in a URI. Anything that isn't a valid escape is kept as it is.
+fn decode_uri(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escaped = match bytes.get(i + 1..i + 3) {
+            Some(hex) if bytes[i] == b'%' && hex.iter().all(u8::is_ascii_hexdigit) => {
+                u8::from_str_radix(std::str::from_utf8(hex).unwrap(), 16).ok()
+            },
+            _ => None
+        };
+
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            },
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Gets the bounds from a position accessor's min and max values, if they are present.
+fn accessor_bounds(accessor: &Accessor) -> Option<BoundingBox> {
+    // Normalized accessors store their min and max before normalization, so we can't use them directly.
+    if accessor.normalized {
+        return None;
+    }
+
+    match (&accessor.min, &accessor.max) {
+        (Some(min), Some(max)) if min.len() == 3 && max.len() == 3 => {
+            Some(BoundingBox::new(Vec3::new(min[0], min[1], min[2]), Vec3::new(max[0], max[1], max[2])))
+        },
+
+        _ => None
+    }
 }
 
 pub trait EnumConvert {
     fn from_u64(value: u64) -> Self;
+
+    fn to_u64(&self) -> u64;
 }
 
 fn to_enum_or_none<T: EnumConvert>(option: Option<&Value>) -> Option<T> {
-    if let Some(value) = option {
-        Some(T::from_u64(value.as_u64().unwrap()))
-    } else {
-        None
-    }
+    option.map(|value| T::from_u64(value.as_u64().unwrap()))
 }
 
 fn to_enum_or_default<T: EnumConvert>(option: Option<&Value>, default: T) -> T {
@@ -1122,20 +2333,12 @@ fn value_to_string(value: &Value) -> String {
 
 #[inline(always)]
 fn to_string_or_none(option: Option<&Value>) -> Option<String> {
-    if let Some(value) = option {
-        Some(value_to_string(value))
-    } else {
-        None
-    }
+    option.map(value_to_string)
 }
 
 #[inline(always)]
 fn to_u64_or_none(option: Option<&Value>) -> Option<u64> {
-    if let Some(value) = option {
-        Some(value.as_u64().unwrap())
-    } else {
-        None
-    }
+    option.map(|value| value.as_u64().unwrap())
 }
 
 #[inline(always)]
@@ -1165,18 +2368,41 @@ fn to_bool_or_default(option: Option<&Value>, default: bool) -> bool {
     }
 }
 
+fn texture_info_to_value(info: &TextureInfo, scale_name: &str) -> Value {
+    let mut map = Map::new();
+
+    map.insert("index".into(), Value::from(info.index));
+
+    if info.tex_coord != 0 {
+        map.insert("texCoord".into(), Value::from(info.tex_coord));
+    }
+
+    insert_some(&mut map, scale_name, info.scale);
+
+    Value::Object(map)
+}
+
+#[inline(always)]
+fn insert_some<T: Into<Value>>(map: &mut Map<String, Value>, key: &str, value: Option<T>) {
+    if let Some(value) = value {
+        map.insert(key.to_string(), value.into());
+    }
+}
+
+fn insert_array<T, F: Fn(&T) -> Map<String, Value>>(map: &mut Map<String, Value>, key: &str, values: &Option<Vec<T>>, f: F) {
+    if let Some(values) = values {
+        map.insert(key.to_string(), Value::Array(values.iter().map(|value| Value::Object(f(value))).collect()));
+    }
+}
+
 fn value_to_texture_info(value: &Value) -> TextureInfo {
     let index = value.get("index").unwrap().as_u64().unwrap();
     
     let tex_coord = to_u64_or_default(value.get("texCoord"), 0);
 
-    let scale = if let Some(scale) = value.get("scale") {
-        Some(scale.as_f64().unwrap() as f32)
-    } else if let Some(strength) = value.get("strength") {
-        Some(strength.as_f64().unwrap() as f32)
-    } else {
-        None
-    };
+    let scale = value.get("scale")
+        .or_else(|| value.get("strength"))
+        .map(|scale| scale.as_f64().unwrap() as f32);
 
     TextureInfo {
         index,
@@ -1187,9 +2413,5 @@ fn value_to_texture_info(value: &Value) -> TextureInfo {
 
 #[inline(always)]
 fn to_texture_info_or_none(option: Option<&Value>) -> Option<TextureInfo> {
-    if let Some(value) = option {
-        Some(value_to_texture_info(value))
-    } else {
-        None
-    }
+    option.map(value_to_texture_info)
 }
\ No newline at end of file
@@ -0,0 +1,388 @@
+use std::collections::HashMap;
+
+use serde_json::json;
+
+use crate::{resolver::ResourceResolver, utils::{z_up_axis_transform, texture_material, LeReader}, Importer, ImportError, ImportErrorType, Mat4, Quat, Vec2, Vec3, Vec4, Vertex, BoundingBox};
+
+const MAGIC: &[u8] = b"INTERQUAKEMODEL\0";
+
+/// The number of animated channels of a pose: translation, rotation and scale.
+const CHANNELS: usize = 10;
+
+/// A range of vertices and triangles that use one material.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mesh {
+    pub name:           String,
+    pub material:       String,
+    pub first_vertex:   usize,
+    pub vertex_count:   usize,
+    pub first_triangle: usize,
+    pub triangle_count: usize
+}
+
+/// A bone of the skeleton in its bind pose, relative to its parent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Joint {
+    pub name:        String,
+    pub parent:      Option<usize>,
+    pub translation: Vec3,
+    pub rotation:    Quat,
+    pub scale:       Vec3
+}
+
+/// How a joint is animated. Each of the ten channels is the offset, plus the frame's value times
+/// the scale if the channel is animated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pose {
+    pub parent:          Option<usize>,
+    pub channel_mask:    u32,
+    pub channel_offsets: [f32; CHANNELS],
+    pub channel_scales:  [f32; CHANNELS]
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Animation {
+    pub name:        String,
+    pub first_frame: usize,
+    pub frame_count: usize,
+    pub framerate:   f32,
+    pub looping:     bool
+}
+
+/// An Inter-Quake Model, which is skinned to a skeleton of joints.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Iqm {
+    pub meshes:         Vec<Mesh>,
+    pub positions:      Vec<Vec3>,
+    pub tex_coords:     Vec<Vec2>,
+    pub normals:        Vec<Vec3>,
+
+    /// Tangents, with the sign of the bitangent in w.
+    pub tangents:       Vec<Vec4>,
+    pub colors:         Vec<Vec4>,
+
+    /// The joints that move each vertex, and how much they move it.
+    pub blend_indices:  Vec<[u8; 4]>,
+    pub blend_weights:  Vec<[f32; 4]>,
+    pub triangles:      Vec<[u32; 3]>,
+    pub joints:         Vec<Joint>,
+    pub poses:          Vec<Pose>,
+    pub animations:     Vec<Animation>,
+
+    /// The animated channel values of every frame.
+    pub frames:         Vec<u16>,
+    pub frame_channels: usize
+}
+
+impl Iqm {
+    pub fn parse(data: &[u8]) -> Result<Self, ImportError> {
+        let mut header = LeReader::new(data, 0);
+
+        if header.bytes(MAGIC.len())? != MAGIC {
+            return Err(parse_error("The file is not an IQM model."));
+        }
+
+        let version = header.u32()?;
+
+        if version != 2 {
+            return Err(ImportError::new(ImportErrorType::UnsupportedFormat, format!("IQM version {version} isn't supported.")));
+        }
+
+        let [_file_size, _flags, text_size, text_offset, mesh_count, meshes_offset, vertex_array_count, vertex_count, vertex_arrays_offset, triangle_count, triangles_offset, _adjacency_offset,
+            joint_count, joints_offset, pose_count, poses_offset, animation_count, animations_offset, frame_count, frame_channels, frames_offset, ..] = header.counts::<26>()?;
+
+        let text = LeReader::new(data, text_offset).bytes(text_size)?;
+
+        // Names are offsets into the text, which is a list of null terminated strings.
+        let string = |offset: u32| -> String {
+            let bytes = text.get(offset as usize..).unwrap_or_default();
+            let end = bytes.iter().position(|&byte| byte == 0).unwrap_or(bytes.len());
+
+            String::from_utf8_lossy(&bytes[..end]).into_owned()
+        };
+
+        let mut reader = LeReader::new(data, meshes_offset);
+        let meshes = (0..mesh_count).map(|_| {
+            let name = string(reader.u32()?);
+            let material = string(reader.u32()?);
+            let [first_vertex, vertex_count, first_triangle, triangle_count] = reader.counts()?;
+
+            Ok(Mesh { name, material, first_vertex, vertex_count, first_triangle, triangle_count })
+        }).collect::<Result<Vec<_>, ImportError>>()?;
+
+        let mut iqm = Self {
+            meshes,
+            positions: Vec::new(),
+            tex_coords: Vec::new(),
+            normals: Vec::new(),
+            tangents: Vec::new(),
+            colors: Vec::new(),
+            blend_indices: Vec::new(),
+            blend_weights: Vec::new(),
+            triangles: Vec::new(),
+            joints: Vec::new(),
+            poses: Vec::new(),
+            animations: Vec::new(),
+            frames: Vec::new(),
+            frame_channels
+        };
+
+        let mut reader = LeReader::new(data, vertex_arrays_offset);
+
+        for _ in 0..vertex_array_count {
+            let [array_type, _flags, format, size, offset] = reader.counts()?;
+            // Integer weights and colors are fractions of their range.
+            let normalized = matches!(array_type, 5 | 6);
+            let values = read_vertex_array(data, offset, format, size, vertex_count, normalized)?;
+
+            // Vertex arrays can have fewer components than they're used as, or extra ones.
+            let vectors = || values.chunks_exact(size).map(|value| {
+                let mut vector = [0.0; 4];
+                let count = size.min(4);
+                vector[..count].copy_from_slice(&value[..count]);
+                vector
+            });
+
+            match array_type {
+                0 => iqm.positions = vectors().map(|[x, y, z, _]| Vec3::new(x, y, z)).collect(),
+                1 => iqm.tex_coords = vectors().map(|[u, v, ..]| Vec2::new(u, v)).collect(),
+                2 => iqm.normals = vectors().map(|[x, y, z, _]| Vec3::new(x, y, z)).collect(),
+                3 => iqm.tangents = vectors().map(|[x, y, z, w]| Vec4::new(x, y, z, w)).collect(),
+                4 => iqm.blend_indices = vectors().map(|indices| indices.map(|index| index as u8)).collect(),
+                5 => iqm.blend_weights = vectors().collect(),
+                6 => iqm.colors = vectors().map(|[r, g, b, a]| Vec4::new(r, g, b, if size < 4 { 1.0 } else { a })).collect(),
+                _ => {}
+            }
+        }
+
+        let mut reader = LeReader::new(data, triangles_offset);
+        iqm.triangles = (0..triangle_count).map(|_| Ok([reader.u32()?, reader.u32()?, reader.u32()?])).collect::<Result<_, ImportError>>()?;
+
+        if iqm.triangles.iter().flatten().any(|&vertex| vertex as usize >= vertex_count) {
+            return Err(parse_error("A triangle has a vertex that doesn't exist."));
+        }
+
+        if let Some(mesh) = iqm.meshes.iter().find(|mesh| mesh.first_vertex.saturating_add(mesh.vertex_count) > vertex_count || mesh.first_triangle.saturating_add(mesh.triangle_count) > triangle_count) {
+            return Err(parse_error(format!("Mesh \"{}\" has vertices or triangles that don't exist.", mesh.name)));
+        }
+
+        let parent = |parent: i32, count: usize| usize::try_from(parent).ok().filter(|&parent| parent < count);
+
+        let mut reader = LeReader::new(data, joints_offset);
+        iqm.joints = (0..joint_count).map(|_| Ok(Joint {
+            name: string(reader.u32()?),
+            parent: parent(reader.i32()?, joint_count),
+            translation: reader.vec3()?,
+            rotation: Quat::new(reader.f32()?, reader.f32()?, reader.f32()?, reader.f32()?),
+            scale: reader.vec3()?
+        })).collect::<Result<_, ImportError>>()?;
+
+        let mut reader = LeReader::new(data, poses_offset);
+        iqm.poses = (0..pose_count).map(|_| {
+            let parent = parent(reader.i32()?, pose_count);
+            let channel_mask = reader.u32()?;
+
+            let mut channel_offsets = [0.0; CHANNELS];
+            let mut channel_scales = [0.0; CHANNELS];
+
+            for offset in &mut channel_offsets {
+                *offset = reader.f32()?;
+            }
+
+            for scale in &mut channel_scales {
+                *scale = reader.f32()?;
+            }
+
+            Ok(Pose { parent, channel_mask, channel_offsets, channel_scales })
+        }).collect::<Result<_, ImportError>>()?;
+
+        let mut reader = LeReader::new(data, animations_offset);
+        iqm.animations = (0..animation_count).map(|_| {
+            let name = string(reader.u32()?);
+            let [first_frame, frame_count] = reader.counts()?;
+
+            Ok(Animation {
+                name,
+                first_frame,
+                frame_count,
+                framerate: reader.f32()?,
+                looping: reader.u32()? & 1 != 0
+            })
+        }).collect::<Result<_, ImportError>>()?;
+
+        let mut reader = LeReader::new(data, frames_offset);
+        iqm.frames = (0..frame_count.saturating_mul(frame_channels)).map(|_| reader.u16()).collect::<Result<_, _>>()?;
+
+        Ok(iqm)
+    }
+
+    /// Gets the transform of each pose in a frame, relative to its parent.
+    pub fn frame_transforms(&self, frame: usize) -> Option<Vec<Mat4>> {
+        let start = frame.checked_mul(self.frame_channels)?;
+        let mut values = self.frames.get(start..start.checked_add(self.frame_channels)?)?.iter();
+
+        self.poses.iter().map(|pose| {
+            let mut channels = pose.channel_offsets;
+
+            for (i, channel) in channels.iter_mut().enumerate() {
+                if pose.channel_mask & (1 << i) != 0 {
+                    *channel += *values.next()? as f32 * pose.channel_scales[i];
+                }
+            }
+
+            let [tx, ty, tz, rx, ry, rz, rw, sx, sy, sz] = channels;
+
+            let mut rotation = Quat::new(rx, ry, rz, rw);
+            let length = rotation.dot(&rotation).sqrt();
+
+            if length > 0.0 {
+                rotation = Quat::new(rx / length, ry / length, rz / length, rw / length);
+            }
+
+            Some(Mat4::from_trs(Vec3::new(tx, ty, tz), rotation, Vec3::new(sx, sy, sz)))
+        }).collect()
+    }
+}
+
+/// Reads a vertex array as floats, optionally normalizing unsigned bytes to between zero and one.
+fn read_vertex_array(data: &[u8], offset: usize, format: usize, size: usize, vertex_count: usize, normalized: bool) -> Result<Vec<f32>, ImportError> {
+    if size == 0 {
+        return Err(parse_error("A vertex array has no components."));
+    }
+
+    let mut reader = LeReader::new(data, offset);
+    let count = vertex_count.saturating_mul(size);
+
+    (0..count).map(|_| Ok(match format {
+        0 => reader.u8()? as i8 as f32,
+        1 => reader.u8()? as f32,
+        2 => reader.i16()? as f32,
+        3 => reader.u16()? as f32,
+        4 => reader.i32()? as f32,
+        5 => reader.u32()? as f32,
+        6 => half_to_f32(reader.u16()?),
+        7 => reader.f32()?,
+        8 => f64::from_le_bytes(reader.bytes(8)?.try_into().unwrap()) as f32,
+        _ => return Err(parse_error(format!("Vertex array format {format} is invalid.")))
+    })).collect::<Result<Vec<_>, _>>().map(|values| match format {
+        1 if normalized => values.into_iter().map(|value| value / 255.0).collect(),
+        _ => values
+    })
+}
+
+fn half_to_f32(half: u16) -> f32 {
+    let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = (half >> 10) & 0x1f;
+    let mantissa = (half & 0x3ff) as f32;
+
+    sign * match exponent {
+        0 => mantissa * 2f32.powi(-24),
+        0x1f if mantissa == 0.0 => f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => (1.0 + mantissa / 1024.0) * 2f32.powi(exponent as i32 - 15)
+    }
+}
+
+impl Importer for Iqm {
+    fn import_bytes(data: &[u8], _resolver: &dyn ResourceResolver) -> Result<Self, ImportError> where Self : Sized {
+        Self::parse(data)
+    }
+
+    /// Creates a scene with the meshes and joints in their bind pose, under a node that converts
+    /// from +Z up. Joints are nodes without meshes, and the animations are in the root node's
+    /// extras. Skinning weights aren't part of the scene, so they're only in [`Iqm`].
+    fn to_scene(&self, _resolver: &dyn ResourceResolver) -> Result<crate::Scene, ImportError> {
+        let mut materials = Vec::new();
+        let mut images = Vec::new();
+        let mut material_indices = HashMap::new();
+
+        let meshes = self.meshes.iter().map(|mesh| {
+            let vertices = (mesh.first_vertex..mesh.first_vertex + mesh.vertex_count).map(|i| {
+                Vertex {
+                    position: self.positions.get(i).copied().unwrap_or(Vec3::new(0.0, 0.0, 0.0)),
+                    tex_coord: self.tex_coords.get(i).copied().unwrap_or(Vec2::new(0.0, 0.0)),
+                    color: self.colors.get(i).copied().unwrap_or(Vec4::new(1.0, 1.0, 1.0, 1.0)),
+                    normal: self.normals.get(i).copied().unwrap_or(Vec3::new(0.0, 0.0, 0.0)),
+                    tangent: self.tangents.get(i).copied().unwrap_or(Vec4::new(0.0, 0.0, 0.0, 0.0))
+                }
+            }).collect::<Vec<_>>();
+
+            let first = mesh.first_vertex as u32;
+
+            // Triangles are wound clockwise, and use indices into all the vertices.
+            let indices = self.triangles[mesh.first_triangle..mesh.first_triangle + mesh.triangle_count].iter()
+                .map(|&[a, b, c]| [a, c, b].map(|index| index.checked_sub(first).filter(|&index| (index as usize) < vertices.len())))
+                .map(|triangle| match triangle {
+                    [Some(a), Some(b), Some(c)] => Ok([a, b, c]),
+                    _ => Err(parse_error(format!("Mesh \"{}\" has a triangle with a vertex from another mesh.", mesh.name)))
+                })
+                .collect::<Result<Vec<_>, _>>()?
+                .concat();
+
+            let material = if mesh.material.is_empty() {
+                None
+            } else {
+                Some(*material_indices.entry(mesh.material.clone()).or_insert_with(|| {
+                    let (material, image) = texture_material(&mesh.material, images.len());
+
+                    materials.push(material);
+                    images.push(image);
+
+                    materials.len() - 1
+                }))
+            };
+
+            Ok(crate::Mesh {
+                bounds: BoundingBox::from_vertices(&vertices),
+                vertices,
+                indices: Some(indices),
+                material,
+                has_normals: !self.normals.is_empty(),
+                name: if mesh.name.is_empty() { None } else { Some(mesh.name.clone()) },
+                extras: None
+            })
+        }).collect::<Result<Vec<_>, ImportError>>()?;
+
+        let animations = self.animations.iter().map(|animation| json!({
+            "name": animation.name,
+            "first_frame": animation.first_frame,
+            "frames": animation.frame_count,
+            "framerate": animation.framerate,
+            "loop": animation.looping
+        })).collect::<Vec<_>>();
+
+        let mut nodes = vec![crate::Node {
+            transform: z_up_axis_transform(),
+            meshes: (0..meshes.len()).collect(),
+            children: Vec::new(),
+            name: None,
+            extras: Some(json!({ "animations": animations }))
+        }];
+
+        // Joints come after their parents, and are offset by the root node.
+        for (i, joint) in self.joints.iter().enumerate() {
+            let parent = joint.parent.filter(|&parent| parent < i).map_or(0, |parent| parent + 1);
+            nodes[parent].children.push(i + 1);
+
+            nodes.push(crate::Node {
+                transform: Mat4::from_trs(joint.translation, joint.rotation, joint.scale),
+                meshes: Vec::new(),
+                children: Vec::new(),
+                name: Some(joint.name.clone()),
+                extras: None
+            });
+        }
+
+        Ok(crate::Scene {
+            meshes,
+            materials: if materials.is_empty() { None } else { Some(materials) },
+            images: if images.is_empty() { None } else { Some(images) },
+            nodes,
+            root_nodes: vec![0]
+        })
+    }
+}
+
+fn parse_error<T: ToString>(message: T) -> ImportError {
+    ImportError::new(ImportErrorType::StringParseError, message)
+}
@@ -1,19 +1,41 @@
-use std::{path::Path, collections::HashMap};
+use std::{path::Path, collections::HashMap, io::{Read, Seek, SeekFrom}};
 
 use bitflags::bitflags;
-use gltf::Gltf;
+use format::Format;
+use resolver::ResourceResolver;
+use serde_json::Value;
 
 pub mod utils;
+pub mod format;
+pub mod resolver;
 pub mod gltf;
 pub mod obj;
+pub mod ply;
+pub mod stl;
+pub mod collada;
+pub mod fbx;
+pub mod md2;
+pub mod md3;
+pub mod iqm;
+pub mod max3ds;
+pub mod off;
+pub mod usd;
+pub mod x3d;
+#[cfg(feature = "zip")]
+pub mod threemf;
 
 pub mod native;
 
+mod post_process;
+
 #[derive(Debug)]
 pub enum ImportErrorType {
     FileNotFound,
     StringParseError,
 
+    /// The format of the file could not be worked out, or isn't supported.
+    UnsupportedFormat,
+
     Other
 }
 
@@ -32,32 +54,236 @@ impl ImportError {
     }
 }
 
+#[derive(Debug)]
+pub enum ExportErrorType {
+    IoError,
+
+    /// The format to save to could not be worked out, or isn't supported.
+    UnsupportedFormat,
+
+    Other
+}
+
+#[derive(Debug)]
+pub struct ExportError {
+    pub e_type: ExportErrorType,
+    pub message: String
+}
+
+impl ExportError {
+    pub fn new<T: ToString>(e_type: ExportErrorType, message: T) -> Self {
+        Self {
+            e_type,
+            message: message.to_string()
+        }
+    }
+}
+
+impl From<std::io::Error> for ImportError {
+    fn from(value: std::io::Error) -> Self {
+        if value.kind() == std::io::ErrorKind::NotFound {
+            Self::new(ImportErrorType::FileNotFound, "The given file was not found.")
+        } else {
+            Self::new(ImportErrorType::Other, value)
+        }
+    }
+}
+
+impl From<std::io::Error> for ExportError {
+    fn from(value: std::io::Error) -> Self {
+        Self::new(ExportErrorType::IoError, value)
+    }
+}
+
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq)]
     pub struct PostProcessFlags: u32 {
         const GENERATE_INDICES = 1 << 0;
+
+        /// Generates smooth, area-weighted normals for meshes without them. Other ways of generating
+        /// normals are available with [`Scene::generate_normals`].
         const GENERATE_NORMALS = 1 << 1;
+
+        /// Generates MikkTSpace-compatible tangents for meshes with normals and texture coordinates,
+        /// splitting vertices shared by faces with mirrored texture coordinates.
+        const GENERATE_TANGENTS = 1 << 2;
+
+        /// Generates flat normals for meshes without them, splitting vertices shared between faces.
+        /// Takes precedence over `GENERATE_NORMALS`.
+        const GENERATE_FLAT_NORMALS = 1 << 3;
+
+        /// Merges vertices with the same position, using the defaults of [`WeldOptions`]. Use
+        /// [`Scene::weld_vertices`] to choose the tolerances, or to find how many were merged.
+        const WELD_VERTICES = 1 << 4;
+    }
+}
+
+/// How [`Scene::generate_normals`] works out the normal of each vertex.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NormalMode {
+    /// Each face's vertices use the face's normal, so vertices shared between faces are split.
+    Flat,
+
+    /// The normals of the faces around a position are averaged, weighted by their area.
+    AreaWeighted,
+
+    /// The normals of the faces around a position are averaged, weighted by the angle of their
+    /// corner there, so the result doesn't depend on how polygons were triangulated.
+    AngleWeighted,
+
+    /// Like `AngleWeighted`, but faces only smooth each other if the angle between them, in radians,
+    /// is at most this. Vertices are split along sharper edges.
+    CreaseAngle(f32)
+}
+
+/// How close vertices need to be for [`Scene::weld_vertices`] to merge them.
+///
+/// Attributes without a tolerance are ignored, and merged vertices keep the attributes of the
+/// first vertex at that position. If that loses normals, because vertices with different normals
+/// were merged, the mesh's `has_normals` is cleared so they can be generated again. Merged vertices
+/// with different tangents are left without one, so tangents should be generated after welding.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeldOptions {
+    /// The furthest apart two positions can be.
+    pub position_epsilon:    f32,
+
+    /// The most each component of the attributes can differ by.
+    pub tex_coord_tolerance: Option<f32>,
+    pub color_tolerance:     Option<f32>,
+    pub normal_tolerance:    Option<f32>,
+    pub tangent_tolerance:   Option<f32>
+}
+
+impl Default for WeldOptions {
+    fn default() -> Self {
+        Self {
+            position_epsilon: 1e-5,
+            tex_coord_tolerance: None,
+            color_tolerance: None,
+            normal_tolerance: None,
+            tangent_tolerance: None
+        }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct Vertex {
     pub position:  Vec3,
     pub tex_coord: Vec2,
     pub color:     Vec4,
     pub normal:    Vec3,
-    pub tangent:   Vec3
+
+    /// The tangent, with the sign of the bitangent in w, so that `bitangent = cross(normal, tangent) * w`.
+    /// All zeros if the vertex has no tangent.
+    pub tangent:   Vec4
 }
 
 #[derive(Debug)]
 pub struct Mesh {
-    pub vertices: Vec<Vertex>,
-    pub indices:  Option<Vec<u32>>,
-    pub material: Option<usize>
+    pub vertices:    Vec<Vertex>,
+    pub indices:     Option<Vec<u32>>,
+    pub material:    Option<usize>,
+
+    /// Whether the vertices have normals, either from the file or from generating them.
+    pub has_normals: bool,
+
+    /// The axis-aligned bounds of the mesh, in mesh space.
+    pub bounds:      BoundingBox,
+
+    pub name:        Option<String>,
+
+    /// Any application-specific data attached to the mesh, stored as raw JSON.
+    pub extras:      Option<Value>
 }
 
-#[derive(Debug)]
+/// An axis-aligned bounding box.
+///
+/// An empty box has its `min` set to positive infinity and its `max` set to negative infinity,
+/// so that expanding it by any point results in a box containing just that point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct BoundingBox {
+    pub min: Vec3,
+    pub max: Vec3
+}
+
+impl BoundingBox {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self {
+            min,
+            max
+        }
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            min: Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            max: Vec3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY)
+        }
+    }
+
+    pub fn from_vertices(vertices: &[Vertex]) -> Self {
+        let mut bounds = Self::empty();
+
+        for vertex in vertices {
+            bounds.expand(vertex.position);
+        }
+
+        bounds
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    pub fn expand(&mut self, point: Vec3) {
+        self.min = self.min.min(&point);
+        self.max = self.max.max(&point);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            min: self.min.min(&other.min),
+            max: self.max.max(&other.max)
+        }
+    }
+
+    pub fn center(&self) -> Vec3 {
+        Vec3::new(
+            (self.min.x + self.max.x) * 0.5,
+            (self.min.y + self.max.y) * 0.5,
+            (self.min.z + self.max.z) * 0.5
+        )
+    }
+
+    pub fn size(&self) -> Vec3 {
+        self.max - self.min
+    }
+
+    /// Transforms all 8 corners of the box by the given matrix, and returns a new box that contains them.
+    pub fn transform(&self, matrix: &Mat4) -> Self {
+        if self.is_empty() {
+            return *self;
+        }
+
+        let mut bounds = Self::empty();
+
+        for i in 0..8 {
+            let corner = Vec3::new(
+                if i & 1 == 0 { self.min.x } else { self.max.x },
+                if i & 2 == 0 { self.min.y } else { self.max.y },
+                if i & 4 == 0 { self.min.z } else { self.max.z }
+            );
+
+            bounds.expand(matrix.transform_point(corner));
+        }
+
+        bounds
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 #[repr(C)]
 pub enum ImageDataType {
     Unknown,
@@ -67,7 +293,7 @@ pub enum ImageDataType {
     Dds
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 #[repr(C)]
 pub enum AlphaMode {
     Opaque,
@@ -95,7 +321,10 @@ pub struct Material {
     pub alpha_mode:        AlphaMode,
     pub alpha_cutoff:      f32,
 
-    pub double_sided:      bool
+    pub double_sided:      bool,
+
+    pub name:              Option<String>,
+    pub extras:            Option<Value>
 }
 
 #[derive(Debug)]
@@ -103,26 +332,193 @@ pub struct Image {
     pub path:      Option<String>,
 
     pub data_type: Option<ImageDataType>,
-    pub data:      Option<Vec<u8>>
+    pub data:      Option<Vec<u8>>,
+
+    pub name:      Option<String>,
+    pub extras:    Option<Value>
+}
+
+#[derive(Debug)]
+pub struct Node {
+    /// The transform of the node, relative to its parent.
+    pub transform: Mat4,
+    pub meshes:    Vec<usize>,
+    pub children:  Vec<usize>,
+
+    pub name:      Option<String>,
+    pub extras:    Option<Value>
 }
 
 #[derive(Debug)]
 pub struct Scene {
-    pub meshes:    Vec<Mesh>,
-    pub materials: Option<Vec<Material>>,
-    pub images:    Option<Vec<Image>>
+    pub meshes:     Vec<Mesh>,
+    pub materials:  Option<Vec<Material>>,
+    pub images:     Option<Vec<Image>>,
+
+    /// The node hierarchy of the scene. Formats that don't support hierarchies will leave this empty.
+    pub nodes:      Vec<Node>,
+    pub root_nodes: Vec<usize>
 }
 
 impl Scene {
-    pub fn load(path: &str, flags: PostProcessFlags) -> Self {
-        let directory = Path::new(path).parent().unwrap();
+    /// Loads the scene at the given path, working out its format from the extension, or from the
+    /// contents of the file if the extension isn't recognized. Any format added to the
+    /// [`format`] registry can be loaded.
+    pub fn load(path: &str, flags: PostProcessFlags) -> Result<Self, ImportError> {
+        let data = std::fs::read(path)?;
+
+        let format = match format::find_by_path(Path::new(path)) {
+            Some(format) => format,
+            None => format::detect(&data).ok_or_else(|| format::unknown_format(&format!("\"{path}\"")))?
+        };
+
+        let directory = Path::new(path).parent().unwrap_or(Path::new("")).to_path_buf();
+
+        let mut scene = (format.load)(&data, &directory)?;
+        scene.post_process(flags);
+
+        Ok(scene)
+    }
+
+    /// Loads a scene from the contents of a file, working out its format from the contents.
+    ///
+    /// Any external files the scene refers to are loaded through the resolver.
+    pub fn load_bytes(data: &[u8], resolver: &dyn ResourceResolver, flags: PostProcessFlags) -> Result<Self, ImportError> {
+        let format = format::detect(data).ok_or_else(|| format::unknown_format("the data"))?;
+
+        let mut scene = (format.load)(data, resolver)?;
+        scene.post_process(flags);
+
+        Ok(scene)
+    }
+
+    /// Loads the scene at the given path inside the resolver, such as a model inside a zip archive.
+    ///
+    /// The format is worked out from the extension, or from the contents if the extension isn't
+    /// recognized. Files the scene refers to are resolved relative to it.
+    pub fn load_from(resolver: &dyn ResourceResolver, path: &str, flags: PostProcessFlags) -> Result<Self, ImportError> {
+        let data = resolver.resolve(path)?;
+
+        let format = match format::find_by_path(Path::new(path)) {
+            Some(format) => format,
+            None => format::detect(&data).ok_or_else(|| format::unknown_format(&format!("\"{path}\"")))?
+        };
+
+        let path = resolver::normalize_path(path)?;
 
-        let gltf = Gltf::import(path).unwrap();
+        let relative = resolver::RelativeResolver {
+            resolver,
+            directory: path.rsplit_once('/').map_or(String::new(), |(directory, _)| directory.to_string())
+        };
 
-        let mut scene = gltf.to_scene(directory);
+        let mut scene = (format.load)(&data, &relative)?;
         scene.post_process(flags);
 
-        scene
+        Ok(scene)
+    }
+
+    /// Loads a scene from the rest of the reader, working out its format from the contents.
+    pub fn load_reader<R: Read + Seek>(mut reader: R, resolver: &dyn ResourceResolver, flags: PostProcessFlags) -> Result<Self, ImportError> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+
+        Self::load_bytes(&data, resolver, flags)
+    }
+
+    /// Saves the scene to the given path, in the format given by its extension.
+    pub fn save(&self, path: &str) -> Result<(), ExportError> {
+        match format::find_by_path(Path::new(path)) {
+            Some(Format { save: Some(save), .. }) => save(self, path),
+            Some(format) => Err(format::unsupported_export(&format)),
+            None => Err(format::unsupported_extension(path))
+        }
+    }
+
+    /// Gets the world space bounds of the entire scene.
+    ///
+    /// If the scene has no nodes, this is the union of the bounds of every mesh.
+    pub fn bounds(&self) -> BoundingBox {
+        if self.root_nodes.is_empty() {
+            return self.meshes
+                .iter()
+                .fold(BoundingBox::empty(), |bounds, mesh| bounds.union(&mesh.bounds));
+        }
+
+        let mut bounds = BoundingBox::empty();
+
+        for root in &self.root_nodes {
+            self.accumulate_bounds(*root, &Mat4::identity(), &mut bounds);
+        }
+
+        bounds
+    }
+
+    /// Gets the world space bounds of the given node, including all of its children.
+    pub fn node_bounds(&self, node: usize) -> BoundingBox {
+        let parent_transform = match self.parent_of(node) {
+            Some(parent) => self.world_transform(parent),
+            None => Mat4::identity()
+        };
+
+        let mut bounds = BoundingBox::empty();
+        self.accumulate_bounds(node, &parent_transform, &mut bounds);
+
+        bounds
+    }
+
+    /// Gets the world transform of the given node, by walking up its parents.
+    pub fn world_transform(&self, node: usize) -> Mat4 {
+        let mut transform = self.nodes[node].transform;
+        let mut current = node;
+
+        while let Some(parent) = self.parent_of(current) {
+            transform = self.nodes[parent].transform * transform;
+            current = parent;
+        }
+
+        transform
+    }
+
+    fn parent_of(&self, node: usize) -> Option<usize> {
+        self.nodes
+            .iter()
+            .position(|parent| parent.children.contains(&node))
+    }
+
+    /// Finds the first node with the given name.
+    pub fn find_node(&self, name: &str) -> Option<usize> {
+        self.nodes
+            .iter()
+            .position(|node| node.name.as_deref() == Some(name))
+    }
+
+    fn accumulate_bounds(&self, node: usize, parent_transform: &Mat4, bounds: &mut BoundingBox) {
+        let node = &self.nodes[node];
+        let transform = *parent_transform * node.transform;
+
+        for mesh in &node.meshes {
+            *bounds = bounds.union(&self.meshes[*mesh].bounds.transform(&transform));
+        }
+
+        for child in &node.children {
+            self.accumulate_bounds(*child, &transform, bounds);
+        }
+    }
+
+    /// Generates normals for every mesh that doesn't have them. To replace a mesh's existing normals,
+    /// set its `has_normals` to false first.
+    pub fn generate_normals(&mut self, mode: NormalMode) {
+        for mesh in &mut self.meshes {
+            if !mesh.has_normals {
+                post_process::generate_normals(mesh, mode);
+            }
+        }
+    }
+
+    /// Merges vertices that are within the tolerances of each other, indexing any meshes without
+    /// indices. Returns how many vertices were merged away.
+    pub fn weld_vertices(&mut self, options: WeldOptions) -> usize {
+        self.meshes.iter_mut().map(|mesh| post_process::weld_vertices(mesh, &options)).sum()
     }
 
     pub fn post_process(&mut self, flags: PostProcessFlags) {
@@ -131,7 +527,7 @@ impl Scene {
             // Stores a list of all vertices, of type HashableVertex as floats can't be easily hashed.
             let mut vertex_cache = HashMap::new();
 
-            for mut mesh in &mut self.meshes {
+            for mesh in &mut self.meshes {
                 if mesh.indices.is_some() {
                     continue;
                 }
@@ -164,85 +560,60 @@ impl Scene {
             }
         }
 
-        // TODO: I'm not 100% sure this is entirely working correctly. Some of the normals look a bit off.
-        if flags.contains(PostProcessFlags::GENERATE_NORMALS) {
-            for mesh in &mut self.meshes {
-                let indices = mesh.indices.as_ref();
-                let vertices = &mut mesh.vertices;
-
-                // As normals *should* always have a magnitude of precisely 1, we just check
-                // to see if the magnitude is greater than 0.5.
-                // If no normals have been generated, the magnitude will be 0/NaN, so we generate them.
-                // Otherwise, don't bother.
-                // TODO: This does need testing to make sure it works properly.
-                if vertices[0].normal.magnitude() > 0.5 {
-                    continue;
-                }
+        if flags.contains(PostProcessFlags::WELD_VERTICES) {
+            self.weld_vertices(WeldOptions::default());
+        }
 
-                println!("Generating normals for mesh!");
+        if flags.contains(PostProcessFlags::GENERATE_FLAT_NORMALS) {
+            self.generate_normals(NormalMode::Flat);
+        } else if flags.contains(PostProcessFlags::GENERATE_NORMALS) {
+            self.generate_normals(NormalMode::AreaWeighted);
+        }
 
-                if let Some(indices) = indices {
-                    for i in (0..indices.len()).step_by(3) {
-                        let i1 = indices[i + 0];
-                        let i2 = indices[i + 1];
-                        let i3 = indices[i + 2];
+        // Runs after normals are generated, as tangents need them.
+        if flags.contains(PostProcessFlags::GENERATE_TANGENTS) {
+            for mesh in &mut self.meshes {
+                post_process::generate_tangents(mesh);
+            }
+        }
+    }
+}
 
-                        let v1 = &vertices[i1 as usize];
-                        let v2 = &vertices[i2 as usize];
-                        let v3 = &vertices[i3 as usize];
+pub trait Importer {
+    /// Imports from the contents of a file, using the resolver to load any external files it refers to.
+    fn import_bytes(data: &[u8], resolver: &dyn ResourceResolver) -> Result<Self, ImportError> where Self : Sized;
 
-                        let e1 = v1.position - v2.position;
-                        let e2 = v3.position - v2.position;
-                        let mut no = Vec3::cross(&e1, &e2);
+    /// Imports from the rest of the reader.
+    fn import_reader<R: Read + Seek>(mut reader: R, resolver: &dyn ResourceResolver) -> Result<Self, ImportError> where Self : Sized {
+        let position = reader.stream_position()?;
+        let length = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(position))?;
 
-                        no.x = -no.x;
-                        no.y = -no.y;
-                        no.z = -no.z;
+        let mut data = Vec::with_capacity((length - position) as usize);
+        reader.read_to_end(&mut data)?;
 
-                        vertices[i1 as usize].normal += no;
-                        vertices[i2 as usize].normal += no;
-                        vertices[i3 as usize].normal += no;
-                    }
-                } else {
-                    // TODO: For some reason this only generates flat-shaded normals, instead of smooth-shaded.
-                    for i in (0..vertices.len()).step_by(3) {
-                        let v1 = &vertices[i + 0];
-                        let v2 = &vertices[i + 1];
-                        let v3 = &vertices[i + 2];
-
-                        let e1 = v1.position - v2.position;
-                        let e2 = v3.position - v2.position;
-                        let mut no = Vec3::cross(&e1, &e2);
-
-                        no.x = -no.x;
-                        no.y = -no.y;
-                        no.z = -no.z;
-
-                        vertices[i + 0].normal += no;
-                        vertices[i + 1].normal += no;
-                        vertices[i + 2].normal += no;
-                    }
-                }
+        Self::import_bytes(&data, resolver)
+    }
 
-                for vertex in vertices {
-                    vertex.normal.normalize();
-                }
-            }
-        }
+    /// Imports the file at the given path, resolving external files relative to it.
+    fn import(path: &str) -> Result<Self, ImportError> where Self : Sized {
+        let data = std::fs::read(path)?;
+        let directory = Path::new(path).parent().unwrap_or(Path::new("")).to_path_buf();
+
+        Self::import_bytes(&data, &directory)
     }
-}
 
-pub trait Importer {
-    fn import(path: &str) -> Result<Self, ImportError> where Self : Sized;
+    fn to_scene(&self, resolver: &dyn ResourceResolver) -> Result<Scene, ImportError>;
+}
 
-    fn export(path: &str);
+/// A format that scenes can be saved to. Formats that can only be loaded don't implement this.
+pub trait Exporter {
+    fn export(&self, path: &str) -> Result<(), ExportError>;
 
     fn from_scene(scene: &Scene) -> Self;
-
-    fn to_scene(&self, directory: &Path) -> Scene;
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct Vec2 {
     pub x: f32,
@@ -258,7 +629,7 @@ impl Vec2 {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct Vec3 {
     pub x: f32,
@@ -301,6 +672,24 @@ impl Vec3 {
             z: self.x * vec.y - self.y * vec.x
         }
     }
+
+    /// Returns the component-wise minimum of the two vectors.
+    pub fn min(&self, vec: &Self) -> Self {
+        Self {
+            x: self.x.min(vec.x),
+            y: self.y.min(vec.y),
+            z: self.z.min(vec.z)
+        }
+    }
+
+    /// Returns the component-wise maximum of the two vectors.
+    pub fn max(&self, vec: &Self) -> Self {
+        Self {
+            x: self.x.max(vec.x),
+            y: self.y.max(vec.y),
+            z: self.z.max(vec.z)
+        }
+    }
 }
 
 impl std::ops::AddAssign<Vec3> for Vec3 {
@@ -323,7 +712,19 @@ impl std::ops::Sub<Vec3> for Vec3 {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+impl std::ops::Mul<f32> for Vec3 {
+    type Output = Self;
+
+    fn mul(self, rhs: f32) -> Self::Output {
+        Self::Output {
+            x: self.x * rhs,
+            y: self.y * rhs,
+            z: self.z * rhs
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct Vec4 {
     pub x: f32,
@@ -341,11 +742,15 @@ impl Vec4 {
             w
         }
     }
+
+    pub fn dot(&self, vec: &Self) -> f32 {
+        self.x * vec.x + self.y * vec.y + self.z * vec.z + self.w * vec.w
+    }
 }
 
 pub type Quat = Vec4;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct Mat4 {
     pub row0: Vec4,
@@ -372,6 +777,77 @@ impl Mat4 {
             row3: Vec4::new(0.0, 0.0, 0.0, 1.0),
         }
     }
+
+    /// Creates a matrix that scales, then rotates, then translates.
+    pub fn from_trs(translation: Vec3, rotation: Quat, scale: Vec3) -> Self {
+        let Quat { x, y, z, w } = rotation;
+
+        Self {
+            row0: Vec4::new(
+                (1.0 - 2.0 * (y * y + z * z)) * scale.x,
+                (2.0 * (x * y - z * w)) * scale.y,
+                (2.0 * (x * z + y * w)) * scale.z,
+                translation.x
+            ),
+            row1: Vec4::new(
+                (2.0 * (x * y + z * w)) * scale.x,
+                (1.0 - 2.0 * (x * x + z * z)) * scale.y,
+                (2.0 * (y * z - x * w)) * scale.z,
+                translation.y
+            ),
+            row2: Vec4::new(
+                (2.0 * (x * z - y * w)) * scale.x,
+                (2.0 * (y * z + x * w)) * scale.y,
+                (1.0 - 2.0 * (x * x + y * y)) * scale.z,
+                translation.z
+            ),
+            row3: Vec4::new(0.0, 0.0, 0.0, 1.0)
+        }
+    }
+
+    pub fn column0(&self) -> Vec4 {
+        Vec4::new(self.row0.x, self.row1.x, self.row2.x, self.row3.x)
+    }
+
+    pub fn column1(&self) -> Vec4 {
+        Vec4::new(self.row0.y, self.row1.y, self.row2.y, self.row3.y)
+    }
+
+    pub fn column2(&self) -> Vec4 {
+        Vec4::new(self.row0.z, self.row1.z, self.row2.z, self.row3.z)
+    }
+
+    pub fn column3(&self) -> Vec4 {
+        Vec4::new(self.row0.w, self.row1.w, self.row2.w, self.row3.w)
+    }
+
+    /// Transforms the given point by this matrix, assuming a `w` of 1.
+    pub fn transform_point(&self, point: Vec3) -> Vec3 {
+        let point = Vec4::new(point.x, point.y, point.z, 1.0);
+
+        Vec3 {
+            x: self.row0.dot(&point),
+            y: self.row1.dot(&point),
+            z: self.row2.dot(&point)
+        }
+    }
+}
+
+impl std::ops::Mul<Mat4> for Mat4 {
+    type Output = Self;
+
+    fn mul(self, rhs: Mat4) -> Self::Output {
+        let (c0, c1, c2, c3) = (rhs.column0(), rhs.column1(), rhs.column2(), rhs.column3());
+
+        let row = |row: &Vec4| Vec4::new(row.dot(&c0), row.dot(&c1), row.dot(&c2), row.dot(&c3));
+
+        Self::Output {
+            row0: row(&self.row0),
+            row1: row(&self.row1),
+            row2: row(&self.row2),
+            row3: row(&self.row3)
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
@@ -405,5 +881,5 @@ struct HashableVertex {
     pub color:     HashableVec4,
     pub tex_coord: HashableVec2,
     pub normal:    HashableVec3,
-    pub tangent:   HashableVec3
+    pub tangent:   HashableVec4
 }
\ No newline at end of file
@@ -0,0 +1,710 @@
+use std::collections::HashMap;
+
+use crate::{resolver::ResourceResolver, utils::{affine_inverse, z_up_axis_transform, LeReader}, Importer, ImportError, ImportErrorType, Mat4, NormalMode, Quat, Vec2, Vec3, Vec4, Vertex, BoundingBox};
+
+const MAIN: u16 = 0x4D4D;
+const VERSION: u16 = 0x0002;
+const EDITOR: u16 = 0x3D3D;
+const MASTER_SCALE: u16 = 0x0100;
+const OBJECT: u16 = 0x4000;
+const TRIANGLE_MESH: u16 = 0x4100;
+const POINTS: u16 = 0x4110;
+const FACES: u16 = 0x4120;
+const FACE_MATERIAL: u16 = 0x4130;
+const TEX_COORDS: u16 = 0x4140;
+const SMOOTHING_GROUPS: u16 = 0x4150;
+const MESH_MATRIX: u16 = 0x4160;
+
+const MATERIAL: u16 = 0xAFFF;
+const MATERIAL_NAME: u16 = 0xA000;
+const AMBIENT: u16 = 0xA010;
+const DIFFUSE: u16 = 0xA020;
+const SPECULAR: u16 = 0xA030;
+const SHININESS: u16 = 0xA040;
+const TRANSPARENCY: u16 = 0xA050;
+const TWO_SIDED: u16 = 0xA081;
+const TEXTURE_MAP: u16 = 0xA200;
+const SPECULAR_MAP: u16 = 0xA204;
+const OPACITY_MAP: u16 = 0xA210;
+const BUMP_MAP: u16 = 0xA230;
+const SELF_ILLUMINATION_MAP: u16 = 0xA33D;
+const MAP_FILE: u16 = 0xA300;
+const MAP_U_SCALE: u16 = 0xA354;
+const MAP_V_SCALE: u16 = 0xA356;
+const MAP_U_OFFSET: u16 = 0xA358;
+const MAP_V_OFFSET: u16 = 0xA35A;
+
+const COLOR_FLOAT: u16 = 0x0010;
+const COLOR_BYTE: u16 = 0x0011;
+const LINEAR_COLOR_BYTE: u16 = 0x0012;
+const LINEAR_COLOR_FLOAT: u16 = 0x0013;
+const PERCENT_INT: u16 = 0x0030;
+const PERCENT_FLOAT: u16 = 0x0031;
+
+const KEYFRAMER: u16 = 0xB000;
+const OBJECT_NODE: u16 = 0xB002;
+const NODE_HEADER: u16 = 0xB010;
+const INSTANCE_NAME: u16 = 0xB011;
+const PIVOT: u16 = 0xB013;
+const POSITION_TRACK: u16 = 0xB020;
+const ROTATION_TRACK: u16 = 0xB021;
+const SCALE_TRACK: u16 = 0xB022;
+const NODE_ID: u16 = 0xB030;
+
+/// The name of keyframer nodes that only group other nodes.
+const DUMMY_NAME: &str = "$$$DUMMY";
+
+/// A texture map of a material. The strength is how much the map replaces the color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextureMap {
+    pub path:     String,
+    pub strength: f32,
+    pub scale:    Vec2,
+    pub offset:   Vec2
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Material {
+    pub name:                  String,
+    pub ambient:               Vec3,
+    pub diffuse:               Vec3,
+    pub specular:              Vec3,
+
+    /// The shininess, from 0 to 1.
+    pub shininess:             f32,
+    pub transparency:          f32,
+    pub two_sided:             bool,
+
+    pub texture_map:           Option<TextureMap>,
+    pub specular_map:          Option<TextureMap>,
+    pub opacity_map:           Option<TextureMap>,
+    pub bump_map:              Option<TextureMap>,
+    pub self_illumination_map: Option<TextureMap>
+}
+
+/// The faces that use a material, by index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaceMaterial {
+    pub material: String,
+    pub faces:    Vec<u16>
+}
+
+/// A triangle mesh object. Points are stored in world space, as they were when the mesh was
+/// created, and the matrix is the object's transform at that time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriangleMesh {
+    pub name:             String,
+    pub points:           Vec<Vec3>,
+    pub tex_coords:       Vec<Vec2>,
+    pub faces:            Vec<[u16; 3]>,
+
+    /// A bit mask of the smoothing groups each face is in. Normals are only smoothed between faces
+    /// in the same group, and faces in no group are flat.
+    pub smoothing_groups: Vec<u32>,
+    pub materials:        Vec<FaceMaterial>,
+    pub matrix:           Mat4
+}
+
+/// An object node from the keyframer, which places an object in the hierarchy. Only the first key
+/// of each track is kept.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyframerNode {
+    pub id:            u16,
+
+    /// The name of the object, or "$$$DUMMY" for nodes that only group other nodes.
+    pub name:          String,
+    pub instance_name: Option<String>,
+
+    /// The parent's ID.
+    pub parent:        Option<u16>,
+    pub pivot:         Vec3,
+    pub position:      Vec3,
+    pub rotation:      Quat,
+    pub scale:         Vec3
+}
+
+impl KeyframerNode {
+    pub fn transform(&self) -> Mat4 {
+        Mat4::from_trs(self.position, self.rotation, self.scale)
+    }
+}
+
+/// A 3D Studio mesh file, which is a tree of chunks. Only triangle meshes, materials and the object
+/// hierarchy are read, and lights and cameras are skipped.
+///
+/// Models are +Z up, so are converted to +Y up. There are no units.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Max3ds {
+    pub version:      u32,
+    pub master_scale: f32,
+    pub materials:    Vec<Material>,
+    pub meshes:       Vec<TriangleMesh>,
+    pub nodes:        Vec<KeyframerNode>
+}
+
+impl Max3ds {
+    pub fn parse(data: &[u8]) -> Result<Self, ImportError> {
+        if !data.starts_with(&MAIN.to_le_bytes()) {
+            return Err(parse_error("The file is not a 3DS file."));
+        }
+
+        let Some(&(_, main)) = chunks(data)?.first() else {
+            return Err(parse_error("The file is too short to be a 3DS file."));
+        };
+
+        let mut max3ds = Self {
+            version: 3,
+            master_scale: 1.0,
+            materials: Vec::new(),
+            meshes: Vec::new(),
+            nodes: Vec::new()
+        };
+
+        for (id, contents) in chunks(main)? {
+            match id {
+                VERSION => max3ds.version = LeReader::new(contents, 0).u32()?,
+                EDITOR => max3ds.editor(contents)?,
+                KEYFRAMER => {
+                    for (id, contents) in chunks(contents)? {
+                        if id == OBJECT_NODE {
+                            let index = max3ds.nodes.len() as u16;
+                            max3ds.nodes.push(keyframer_node(contents, index)?);
+                        }
+                    }
+                },
+                _ => {}
+            }
+        }
+
+        Ok(max3ds)
+    }
+
+    fn editor(&mut self, data: &[u8]) -> Result<(), ImportError> {
+        for (id, contents) in chunks(data)? {
+            match id {
+                MASTER_SCALE => self.master_scale = LeReader::new(contents, 0).f32()?,
+                MATERIAL => self.materials.push(material(contents)?),
+                OBJECT => {
+                    let mut reader = LeReader::new(contents, 0);
+                    let name = reader.null_terminated_string()?;
+
+                    for (id, contents) in chunks(&contents[reader.position()..])? {
+                        if id == TRIANGLE_MESH {
+                            self.meshes.push(triangle_mesh(&name, contents)?);
+                        }
+                    }
+                },
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Importer for Max3ds {
+    fn import_bytes(data: &[u8], _resolver: &dyn ResourceResolver) -> Result<Self, ImportError> where Self : Sized {
+        Self::parse(data)
+    }
+
+    /// Creates a scene with a node for each keyframer node, or a node for each object if there's
+    /// no keyframer. Objects are split into a mesh for each material.
+    fn to_scene(&self, _resolver: &dyn ResourceResolver) -> Result<crate::Scene, ImportError> {
+        let mut images = Vec::new();
+        let mut image_indices = HashMap::new();
+
+        let mut image = |map: &Option<TextureMap>| map.as_ref().map(|map| {
+            *image_indices.entry(map.path.clone()).or_insert_with(|| {
+                images.push(crate::Image {
+                    path: Some(map.path.clone()),
+                    data_type: None,
+                    data: None,
+                    name: None,
+                    extras: None
+                });
+
+                images.len() - 1
+            })
+        });
+
+        let materials = self.materials.iter().map(|material| {
+            let roughness = (2.0 / (material.shininess * 128.0 + 2.0)).sqrt();
+
+            // A full strength texture replaces the diffuse color.
+            let diffuse = match &material.texture_map {
+                Some(map) if map.strength >= 1.0 => Vec3::new(1.0, 1.0, 1.0),
+                _ => material.diffuse
+            };
+
+            let alpha_mode = if material.transparency > 0.0 || material.opacity_map.is_some() {
+                crate::AlphaMode::Blend
+            } else {
+                crate::AlphaMode::Opaque
+            };
+
+            crate::Material {
+                albedo_color: Vec4::new(diffuse.x, diffuse.y, diffuse.z, 1.0 - material.transparency),
+                albedo_texture: image(&material.texture_map),
+                normal_texture: image(&material.bump_map),
+                metallic: 0.0,
+                metallic_texture: None,
+                roughness,
+                roughness_texture: None,
+                occlusion_texture: None,
+                emissive_texture: image(&material.self_illumination_map),
+                alpha_mode,
+                alpha_cutoff: 0.5,
+                double_sided: material.two_sided,
+                name: Some(material.name.clone()),
+                extras: None
+            }
+        }).collect::<Vec<_>>();
+
+        let material_indices = self.materials.iter().enumerate().map(|(i, material)| (material.name.as_str(), i)).collect::<HashMap<_, _>>();
+
+        let mut meshes = Vec::new();
+        let mut nodes = Vec::new();
+        let mut root_nodes = Vec::new();
+
+        // Objects can be used by several nodes with different pivots, so are converted for each.
+        let mut mesh_cache = HashMap::<(usize, Option<[u32; 3]>), Vec<usize>>::new();
+
+        let mut add_meshes = |object: usize, pivot: Option<Vec3>| -> Result<Vec<usize>, ImportError> {
+            let key = (object, pivot.map(|pivot| [pivot.x, pivot.y, pivot.z].map(f32::to_bits)));
+
+            if let Some(indices) = mesh_cache.get(&key) {
+                return Ok(indices.clone());
+            }
+
+            let mesh = &self.meshes[object];
+
+            // Keyframer nodes place the object, so its points are moved back from where it was
+            // created to be relative to its pivot.
+            let transform = match pivot {
+                Some(pivot) => Mat4::from_trs(Vec3::new(-pivot.x, -pivot.y, -pivot.z), Quat::new(0.0, 0.0, 0.0, 1.0), Vec3::new(1.0, 1.0, 1.0)) * affine_inverse(&mesh.matrix),
+                None => Mat4::identity()
+            };
+
+            let start = meshes.len();
+            meshes.extend(triangle_mesh_to_meshes(mesh, &transform, &material_indices)?);
+
+            let indices = (start..meshes.len()).collect::<Vec<_>>();
+            mesh_cache.insert(key, indices.clone());
+
+            Ok(indices)
+        };
+
+        let object_indices = self.meshes.iter().enumerate().map(|(i, mesh)| (mesh.name.as_str(), i)).collect::<HashMap<_, _>>();
+        let mut used_objects = vec![false; self.meshes.len()];
+
+        let node_indices = self.nodes.iter().enumerate().map(|(i, node)| (node.id, i)).collect::<HashMap<_, _>>();
+
+        for node in &self.nodes {
+            let object = object_indices.get(node.name.as_str()).copied().filter(|_| node.name != DUMMY_NAME);
+
+            if let Some(object) = object {
+                used_objects[object] = true;
+            }
+
+            nodes.push(crate::Node {
+                transform: node.transform(),
+                meshes: object.map(|object| add_meshes(object, Some(node.pivot))).transpose()?.unwrap_or_default(),
+                children: Vec::new(),
+                name: Some(node.instance_name.clone().unwrap_or_else(|| node.name.clone())),
+                extras: None
+            });
+        }
+
+        for (i, node) in self.nodes.iter().enumerate() {
+            match node.parent.and_then(|parent| node_indices.get(&parent)) {
+                Some(&parent) if parent != i => nodes[parent].children.push(i),
+                _ => root_nodes.push(i)
+            }
+        }
+
+        // Objects that aren't in the keyframer are left where they were created.
+        for (object, mesh) in self.meshes.iter().enumerate() {
+            if !used_objects[object] {
+                root_nodes.push(nodes.len());
+
+                nodes.push(crate::Node {
+                    transform: Mat4::identity(),
+                    meshes: add_meshes(object, None)?,
+                    children: Vec::new(),
+                    name: Some(mesh.name.clone()),
+                    extras: None
+                });
+            }
+        }
+
+        let axis_transform = z_up_axis_transform();
+
+        for &root in &root_nodes {
+            nodes[root].transform = axis_transform * nodes[root].transform;
+        }
+
+        Ok(crate::Scene {
+            meshes,
+            materials: if materials.is_empty() { None } else { Some(materials) },
+            images: if images.is_empty() { None } else { Some(images) },
+            nodes,
+            root_nodes
+        })
+    }
+}
+
+/// Converts an object to a mesh for each material, with normals smoothed within smoothing groups.
+fn triangle_mesh_to_meshes(mesh: &TriangleMesh, transform: &Mat4, material_indices: &HashMap<&str, usize>) -> Result<Vec<crate::Mesh>, ImportError> {
+    let points = mesh.points.iter().map(|&point| transform.transform_point(point)).collect::<Vec<_>>();
+
+    for face in &mesh.faces {
+        if let Some(&index) = face.iter().find(|&&index| index as usize >= points.len()) {
+            return Err(parse_error(format!("A face of \"{}\" uses the point {index}, which doesn't exist.", mesh.name)));
+        }
+    }
+
+    // Normals are generated for the whole object, so they're smoothed across materials, and it's
+    // split by material afterwards.
+    let vertices = points.iter().enumerate().map(|(i, &position)| Vertex {
+        position,
+        tex_coord: mesh.tex_coords.get(i).map_or(Vec2::new(0.0, 0.0), |tex_coord| Vec2::new(tex_coord.x, 1.0 - tex_coord.y)),
+        color: Vec4::new(1.0, 1.0, 1.0, 1.0),
+        normal: Vec3::new(0.0, 0.0, 0.0),
+        tangent: Vec4::new(0.0, 0.0, 0.0, 0.0)
+    }).collect::<Vec<_>>();
+
+    let mut object = crate::Mesh {
+        bounds: BoundingBox::from_vertices(&vertices),
+        vertices,
+        indices: Some(mesh.faces.iter().flat_map(|face| face.map(u32::from)).collect()),
+        material: None,
+        has_normals: false,
+        name: None,
+        extras: None
+    };
+
+    let smoothing_group = |face: usize| mesh.smoothing_groups.get(face).copied().unwrap_or(0);
+    crate::post_process::generate_normals_where(&mut object, NormalMode::AreaWeighted, |a, b| smoothing_group(a) & smoothing_group(b) != 0);
+
+    let object_indices = object.indices.unwrap_or_default();
+
+    // Faces that aren't given a material use the default material.
+    let mut face_groups = vec![None; mesh.faces.len()];
+
+    for (i, group) in mesh.materials.iter().enumerate() {
+        for &face in &group.faces {
+            if let Some(face_group) = face_groups.get_mut(face as usize) {
+                *face_group = Some(i);
+            }
+        }
+    }
+
+    let mut groups = mesh.materials.iter().enumerate().map(|(i, group)| (Some(i), material_indices.get(group.material.as_str()).copied())).collect::<Vec<_>>();
+
+    if face_groups.contains(&None) {
+        groups.push((None, None));
+    }
+
+    let mut meshes = Vec::new();
+
+    for (group, material) in groups {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        let mut cache = HashMap::new();
+
+        for (_, triangle) in object_indices.chunks_exact(3).enumerate().filter(|&(i, _)| face_groups[i] == group) {
+            for &index in triangle {
+                indices.push(*cache.entry(index).or_insert_with(|| {
+                    vertices.push(object.vertices[index as usize]);
+                    vertices.len() as u32 - 1
+                }));
+            }
+        }
+
+        if indices.is_empty() {
+            continue;
+        }
+
+        meshes.push(crate::Mesh {
+            bounds: BoundingBox::from_vertices(&vertices),
+            vertices,
+            indices: Some(indices),
+            material,
+            has_normals: true,
+            name: Some(mesh.name.clone()),
+            extras: None
+        });
+    }
+
+    Ok(meshes)
+}
+
+/// Splits a chunk's contents into its child chunks, which each start with an ID and their length,
+/// including the six byte header.
+fn chunks(data: &[u8]) -> Result<Vec<(u16, &[u8])>, ImportError> {
+    let mut chunks = Vec::new();
+    let mut reader = LeReader::new(data, 0);
+
+    while data.len() - reader.position() >= 6 {
+        let start = reader.position();
+        let id = reader.u16()?;
+        let length = reader.u32()? as usize;
+
+        if length < 6 {
+            return Err(parse_error(format!("The chunk 0x{id:04X} at offset {start} has an invalid length.")));
+        }
+
+        let contents = reader.bytes(length - 6).map_err(|_| parse_error(format!("The chunk 0x{id:04X} at offset {start} is longer than its parent.")))?;
+
+        chunks.push((id, contents));
+    }
+
+    Ok(chunks)
+}
+
+fn triangle_mesh(name: &str, data: &[u8]) -> Result<TriangleMesh, ImportError> {
+    let mut mesh = TriangleMesh {
+        name: name.to_string(),
+        points: Vec::new(),
+        tex_coords: Vec::new(),
+        faces: Vec::new(),
+        smoothing_groups: Vec::new(),
+        materials: Vec::new(),
+        matrix: Mat4::identity()
+    };
+
+    for (id, contents) in chunks(data)? {
+        let mut reader = LeReader::new(contents, 0);
+
+        match id {
+            POINTS => {
+                let count = reader.u16()?;
+                mesh.points = (0..count).map(|_| reader.vec3()).collect::<Result<_, _>>()?;
+            },
+            TEX_COORDS => {
+                let count = reader.u16()?;
+                mesh.tex_coords = (0..count).map(|_| Ok(Vec2::new(reader.f32()?, reader.f32()?))).collect::<Result<_, ImportError>>()?;
+            },
+            FACES => {
+                let count = reader.u16()?;
+
+                // Each face has edge visibility flags, which aren't needed.
+                for _ in 0..count {
+                    mesh.faces.push([reader.u16()?, reader.u16()?, reader.u16()?]);
+                    reader.u16()?;
+                }
+
+                for (id, contents) in chunks(&contents[reader.position()..])? {
+                    let mut reader = LeReader::new(contents, 0);
+
+                    match id {
+                        FACE_MATERIAL => {
+                            let material = reader.null_terminated_string()?;
+                            let count = reader.u16()?;
+                            let faces = (0..count).map(|_| reader.u16()).collect::<Result<_, _>>()?;
+
+                            mesh.materials.push(FaceMaterial { material, faces });
+                        },
+                        SMOOTHING_GROUPS => {
+                            mesh.smoothing_groups = (0..mesh.faces.len()).map(|_| reader.u32()).collect::<Result<_, _>>()?;
+                        },
+                        _ => {}
+                    }
+                }
+            },
+
+            // The rows are the X, Y and Z axes, then the origin.
+            MESH_MATRIX => {
+                let [x, y, z, origin] = [reader.vec3()?, reader.vec3()?, reader.vec3()?, reader.vec3()?];
+
+                mesh.matrix = Mat4::new(
+                    Vec4::new(x.x, y.x, z.x, origin.x),
+                    Vec4::new(x.y, y.y, z.y, origin.y),
+                    Vec4::new(x.z, y.z, z.z, origin.z),
+                    Vec4::new(0.0, 0.0, 0.0, 1.0)
+                );
+            },
+            _ => {}
+        }
+    }
+
+    Ok(mesh)
+}
+
+fn material(data: &[u8]) -> Result<Material, ImportError> {
+    let mut material = Material {
+        name: String::new(),
+        ambient: Vec3::new(0.0, 0.0, 0.0),
+        diffuse: Vec3::new(0.8, 0.8, 0.8),
+        specular: Vec3::new(0.0, 0.0, 0.0),
+        shininess: 0.0,
+        transparency: 0.0,
+        two_sided: false,
+        texture_map: None,
+        specular_map: None,
+        opacity_map: None,
+        bump_map: None,
+        self_illumination_map: None
+    };
+
+    for (id, contents) in chunks(data)? {
+        match id {
+            MATERIAL_NAME => material.name = LeReader::new(contents, 0).null_terminated_string()?,
+            AMBIENT => material.ambient = color(contents)?.unwrap_or(material.ambient),
+            DIFFUSE => material.diffuse = color(contents)?.unwrap_or(material.diffuse),
+            SPECULAR => material.specular = color(contents)?.unwrap_or(material.specular),
+            SHININESS => material.shininess = percent(contents)?.unwrap_or(material.shininess),
+            TRANSPARENCY => material.transparency = percent(contents)?.unwrap_or(material.transparency),
+            TWO_SIDED => material.two_sided = true,
+            TEXTURE_MAP => material.texture_map = texture_map(contents)?,
+            SPECULAR_MAP => material.specular_map = texture_map(contents)?,
+            OPACITY_MAP => material.opacity_map = texture_map(contents)?,
+            BUMP_MAP => material.bump_map = texture_map(contents)?,
+            SELF_ILLUMINATION_MAP => material.self_illumination_map = texture_map(contents)?,
+            _ => {}
+        }
+    }
+
+    Ok(material)
+}
+
+/// Reads a color from its sub-chunks. Gamma corrected colors are preferred, if there are any.
+fn color(data: &[u8]) -> Result<Option<Vec3>, ImportError> {
+    let mut color = None;
+
+    for (id, contents) in chunks(data)? {
+        let mut reader = LeReader::new(contents, 0);
+
+        let value = match id {
+            COLOR_FLOAT | LINEAR_COLOR_FLOAT => reader.vec3()?,
+            COLOR_BYTE | LINEAR_COLOR_BYTE => Vec3::new(reader.u8()? as f32 / 255.0, reader.u8()? as f32 / 255.0, reader.u8()? as f32 / 255.0),
+            _ => continue
+        };
+
+        if color.is_none() || matches!(id, LINEAR_COLOR_BYTE | LINEAR_COLOR_FLOAT) {
+            color = Some(value);
+        }
+    }
+
+    Ok(color)
+}
+
+/// Reads a percentage from its sub-chunk, as a fraction.
+fn percent(data: &[u8]) -> Result<Option<f32>, ImportError> {
+    for (id, contents) in chunks(data)? {
+        let mut reader = LeReader::new(contents, 0);
+
+        match id {
+            PERCENT_INT => return Ok(Some(reader.i16()? as f32 / 100.0)),
+            PERCENT_FLOAT => return Ok(Some(reader.f32()?)),
+            _ => {}
+        }
+    }
+
+    Ok(None)
+}
+
+/// Reads a texture map. Maps without a file are skipped.
+fn texture_map(data: &[u8]) -> Result<Option<TextureMap>, ImportError> {
+    let mut map = TextureMap {
+        path: String::new(),
+        strength: 1.0,
+        scale: Vec2::new(1.0, 1.0),
+        offset: Vec2::new(0.0, 0.0)
+    };
+
+    for (id, contents) in chunks(data)? {
+        let mut reader = LeReader::new(contents, 0);
+
+        match id {
+            PERCENT_INT => map.strength = reader.i16()? as f32 / 100.0,
+            PERCENT_FLOAT => map.strength = reader.f32()?,
+            MAP_FILE => map.path = reader.null_terminated_string()?,
+            MAP_U_SCALE => map.scale.x = reader.f32()?,
+            MAP_V_SCALE => map.scale.y = reader.f32()?,
+            MAP_U_OFFSET => map.offset.x = reader.f32()?,
+            MAP_V_OFFSET => map.offset.y = reader.f32()?,
+            _ => {}
+        }
+    }
+
+    Ok(if map.path.is_empty() { None } else { Some(map) })
+}
+
+fn keyframer_node(data: &[u8], index: u16) -> Result<KeyframerNode, ImportError> {
+    let mut node = KeyframerNode {
+        id: index,
+        name: String::new(),
+        instance_name: None,
+        parent: None,
+        pivot: Vec3::new(0.0, 0.0, 0.0),
+        position: Vec3::new(0.0, 0.0, 0.0),
+        rotation: Quat::new(0.0, 0.0, 0.0, 1.0),
+        scale: Vec3::new(1.0, 1.0, 1.0)
+    };
+
+    for (id, contents) in chunks(data)? {
+        let mut reader = LeReader::new(contents, 0);
+
+        // Tracks start with a header, which leaves the reader at the first key's value.
+        let has_key = matches!(id, POSITION_TRACK | ROTATION_TRACK | SCALE_TRACK) && first_key(&mut reader)?;
+
+        match id {
+            NODE_ID => node.id = reader.u16()?,
+            NODE_HEADER => {
+                node.name = reader.null_terminated_string()?;
+
+                // Two flag words, then the parent, which is -1 for root nodes.
+                reader.u32()?;
+                let parent = reader.u16()?;
+                node.parent = if parent == u16::MAX { None } else { Some(parent) };
+            },
+            INSTANCE_NAME => node.instance_name = Some(reader.null_terminated_string()?),
+            PIVOT => node.pivot = reader.vec3()?,
+            POSITION_TRACK if has_key => node.position = reader.vec3()?,
+            SCALE_TRACK if has_key => node.scale = reader.vec3()?,
+
+            // Rotations are an angle in radians, then an axis.
+            ROTATION_TRACK if has_key => {
+                let angle = reader.f32()?;
+                let mut axis = reader.vec3()?;
+
+                if axis.magnitude_squared() > 0.0 {
+                    axis.normalize();
+                    let (sin, cos) = (angle / 2.0).sin_cos();
+                    node.rotation = Quat::new(axis.x * sin, axis.y * sin, axis.z * sin, cos);
+                }
+            },
+            _ => {}
+        }
+    }
+
+    Ok(node)
+}
+
+/// Skips a track's header and the first key's spline properties, leaving the reader at the first
+/// key's value. Returns false if the track has no keys.
+fn first_key(reader: &mut LeReader) -> Result<bool, ImportError> {
+    reader.u16()?;
+    reader.bytes(8)?;
+
+    if reader.u32()? == 0 {
+        return Ok(false);
+    }
+
+    // The frame, then flags saying which of the tension, continuity, bias and eases are written.
+    reader.u32()?;
+    let flags = reader.u16()?;
+
+    for bit in 0..5 {
+        if flags & (1 << bit) != 0 {
+            reader.f32()?;
+        }
+    }
+
+    Ok(true)
+}
+
+fn parse_error<T: ToString>(message: T) -> ImportError {
+    ImportError::new(ImportErrorType::StringParseError, message)
+}
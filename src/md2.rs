@@ -0,0 +1,220 @@
+use std::{collections::HashMap, ops::Range};
+
+use serde_json::json;
+
+use crate::{resolver::ResourceResolver, utils::{z_up_axis_transform, texture_material, LeReader}, Importer, ImportError, ImportErrorType, Vec2, Vec3, Vec4, Vertex, BoundingBox};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Triangle {
+    pub vertices:   [u16; 3],
+    pub tex_coords: [u16; 3]
+}
+
+/// A keyframe of the vertex animation, with a position for every vertex.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub name:           String,
+    pub positions:      Vec<Vec3>,
+
+    /// Indices into Quake II's table of precomputed normals.
+    pub normal_indices: Vec<u8>
+}
+
+/// A Quake II model, which is animated by storing every vertex position for each frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Md2 {
+    pub skin_width:  u32,
+    pub skin_height: u32,
+    pub skins:       Vec<String>,
+
+    /// Texture coordinates in pixels of the skin.
+    pub tex_coords:  Vec<[i16; 2]>,
+    pub triangles:   Vec<Triangle>,
+    pub frames:      Vec<Frame>
+}
+
+impl Md2 {
+    pub fn parse(data: &[u8]) -> Result<Self, ImportError> {
+        let mut header = LeReader::new(data, 0);
+
+        if header.bytes(4)? != b"IDP2" {
+            return Err(parse_error("The file is not an MD2 model."));
+        }
+
+        let version = header.i32()?;
+
+        if version != 8 {
+            return Err(ImportError::new(ImportErrorType::UnsupportedFormat, format!("MD2 version {version} isn't supported.")));
+        }
+
+        let [skin_width, skin_height, frame_size, skin_count, vertex_count, tex_coord_count, triangle_count, _, frame_count, skins_offset, tex_coords_offset, triangles_offset, frames_offset, ..] = header.counts::<15>()?;
+
+        let mut reader = LeReader::new(data, skins_offset);
+        let skins = (0..skin_count).map(|_| reader.string(64)).collect::<Result<_, _>>()?;
+
+        let mut reader = LeReader::new(data, tex_coords_offset);
+        let tex_coords = (0..tex_coord_count).map(|_| Ok([reader.i16()?, reader.i16()?])).collect::<Result<_, ImportError>>()?;
+
+        let mut reader = LeReader::new(data, triangles_offset);
+        let triangles = (0..triangle_count).map(|_| Ok(Triangle {
+            vertices: [reader.u16()?, reader.u16()?, reader.u16()?],
+            tex_coords: [reader.u16()?, reader.u16()?, reader.u16()?]
+        })).collect::<Result<Vec<_>, ImportError>>()?;
+
+        if let Some(triangle) = triangles.iter().find(|triangle| triangle.vertices.iter().any(|&vertex| vertex as usize >= vertex_count) || triangle.tex_coords.iter().any(|&tex_coord| tex_coord as usize >= tex_coord_count)) {
+            return Err(parse_error(format!("Triangle {:?} refers to a vertex or texture coordinate that doesn't exist.", triangle.vertices)));
+        }
+
+        // Positions are compressed to a byte per component, with a scale and translation for each frame.
+        let frames = (0..frame_count).map(|i| {
+            let mut reader = LeReader::new(data, frames_offset.saturating_add(i.saturating_mul(frame_size)));
+
+            let scale = reader.vec3()?;
+            let translate = reader.vec3()?;
+            let name = reader.string(16)?;
+
+            let mut positions = Vec::new();
+            let mut normal_indices = Vec::new();
+
+            for _ in 0..vertex_count {
+                let [x, y, z, normal] = [reader.u8()?, reader.u8()?, reader.u8()?, reader.u8()?];
+
+                positions.push(Vec3::new(x as f32 * scale.x + translate.x, y as f32 * scale.y + translate.y, z as f32 * scale.z + translate.z));
+                normal_indices.push(normal);
+            }
+
+            Ok(Frame { name, positions, normal_indices })
+        }).collect::<Result<_, ImportError>>()?;
+
+        Ok(Self {
+            skin_width: skin_width as u32,
+            skin_height: skin_height as u32,
+            skins,
+            tex_coords,
+            triangles,
+            frames
+        })
+    }
+
+    /// Groups the frames into animations by their names without the frame number, so `run1` to
+    /// `run6` are the `run` animation.
+    pub fn animations(&self) -> Vec<(String, Range<usize>)> {
+        let mut animations: Vec<(String, Range<usize>)> = Vec::new();
+
+        for (i, frame) in self.frames.iter().enumerate() {
+            let name = frame.name.trim_end_matches(|c: char| c.is_ascii_digit());
+
+            match animations.last_mut() {
+                Some((last, range)) if last == name => range.end = i + 1,
+                _ => animations.push((name.to_string(), i..i + 1))
+            }
+        }
+
+        animations
+    }
+
+    /// Creates a mesh with the positions of a frame, so each frame can be used as a morph target.
+    ///
+    /// Vertices are only split where texture coordinates differ, so meshes for different frames
+    /// have the same vertices and indices. Normals are computed from the frame's triangles, as
+    /// the normal table isn't included.
+    pub fn mesh(&self, frame: usize) -> Option<crate::Mesh> {
+        let frame = self.frames.get(frame)?;
+
+        // Vertices are shared by triangles around them, so their normals are smoothed.
+        let mut normals = vec![Vec3::new(0.0, 0.0, 0.0); frame.positions.len()];
+
+        for triangle in &self.triangles {
+            let [a, b, c] = triangle.vertices.map(|vertex| frame.positions[vertex as usize]);
+            let normal = (c - a).cross(&(b - a));
+
+            for vertex in triangle.vertices {
+                normals[vertex as usize] += normal;
+            }
+        }
+
+        for normal in &mut normals {
+            if normal.magnitude_squared() > 0.0 {
+                normal.normalize();
+            }
+        }
+
+        let width = self.skin_width.max(1) as f32;
+        let height = self.skin_height.max(1) as f32;
+
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        let mut cache = HashMap::new();
+
+        for triangle in &self.triangles {
+            // Triangles are wound clockwise.
+            for corner in [0, 2, 1] {
+                let key = (triangle.vertices[corner], triangle.tex_coords[corner]);
+
+                let index = *cache.entry(key).or_insert_with(|| {
+                    let [s, t] = self.tex_coords[key.1 as usize];
+
+                    vertices.push(Vertex {
+                        position: frame.positions[key.0 as usize],
+                        tex_coord: Vec2::new(s as f32 / width, t as f32 / height),
+                        color: Vec4::new(1.0, 1.0, 1.0, 1.0),
+                        normal: normals[key.0 as usize],
+                        tangent: Vec4::new(0.0, 0.0, 0.0, 0.0)
+                    });
+
+                    vertices.len() as u32 - 1
+                });
+
+                indices.push(index);
+            }
+        }
+
+        Some(crate::Mesh {
+            bounds: BoundingBox::from_vertices(&vertices),
+            vertices,
+            indices: Some(indices),
+            material: if self.skins.is_empty() { None } else { Some(0) },
+            has_normals: true,
+            name: Some(frame.name.clone()),
+            extras: None
+        })
+    }
+}
+
+impl Importer for Md2 {
+    fn import_bytes(data: &[u8], _resolver: &dyn ResourceResolver) -> Result<Self, ImportError> where Self : Sized {
+        Self::parse(data)
+    }
+
+    /// Creates a scene with the first frame as the mesh, under a node that converts from +Z up.
+    /// The names and frame ranges of the animations are in the node's extras.
+    fn to_scene(&self, _resolver: &dyn ResourceResolver) -> Result<crate::Scene, ImportError> {
+        let meshes = self.mesh(0).into_iter().collect::<Vec<_>>();
+
+        let (materials, images) = self.skins.first().map(|skin| texture_material(skin, 0)).unzip();
+
+        let animations = self.animations().into_iter().map(|(name, frames)| json!({
+            "name": name,
+            "first_frame": frames.start,
+            "frames": frames.len()
+        })).collect::<Vec<_>>();
+
+        Ok(crate::Scene {
+            nodes: vec![crate::Node {
+                transform: z_up_axis_transform(),
+                meshes: (0..meshes.len()).collect(),
+                children: Vec::new(),
+                name: None,
+                extras: Some(json!({ "animations": animations }))
+            }],
+            meshes,
+            materials: materials.map(|material| vec![material]),
+            images: images.map(|image| vec![image]),
+            root_nodes: vec![0]
+        })
+    }
+}
+
+fn parse_error<T: ToString>(message: T) -> ImportError {
+    ImportError::new(ImportErrorType::StringParseError, message)
+}
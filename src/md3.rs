@@ -0,0 +1,272 @@
+use std::{collections::HashMap, f32::consts::TAU};
+
+use serde_json::json;
+
+use crate::{resolver::ResourceResolver, utils::{z_up_axis_transform, texture_material, LeReader}, Importer, ImportError, ImportErrorType, Mat4, Vec2, Vec3, Vec4, Vertex, BoundingBox};
+
+/// The scale of the fixed point vertex positions.
+const POSITION_SCALE: f32 = 1.0 / 64.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub name:   String,
+    pub bounds: BoundingBox,
+    pub origin: Vec3,
+    pub radius: f32
+}
+
+/// A named attachment point, such as where a weapon is held.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tag {
+    pub name:   String,
+    pub origin: Vec3,
+
+    /// The forward, left and up axes of the tag.
+    pub axes:   [Vec3; 3]
+}
+
+impl Tag {
+    pub fn transform(&self) -> Mat4 {
+        let [x, y, z] = self.axes;
+
+        Mat4::new(
+            Vec4::new(x.x, y.x, z.x, self.origin.x),
+            Vec4::new(x.y, y.y, z.y, self.origin.y),
+            Vec4::new(x.z, y.z, z.z, self.origin.z),
+            Vec4::new(0.0, 0.0, 0.0, 1.0)
+        )
+    }
+}
+
+/// The vertices of a surface in one frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SurfaceFrame {
+    pub positions: Vec<Vec3>,
+    pub normals:   Vec<Vec3>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Surface {
+    pub name:       String,
+    pub shaders:    Vec<String>,
+    pub triangles:  Vec<[u32; 3]>,
+    pub tex_coords: Vec<Vec2>,
+    pub frames:     Vec<SurfaceFrame>
+}
+
+/// A Quake III model, which has a surface for each shader, and tags to attach other models to.
+/// Like MD2, it's animated by storing the vertices for every frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Md3 {
+    pub name:     String,
+    pub frames:   Vec<Frame>,
+
+    /// The tags in each frame.
+    pub tags:     Vec<Vec<Tag>>,
+    pub surfaces: Vec<Surface>
+}
+
+impl Md3 {
+    pub fn parse(data: &[u8]) -> Result<Self, ImportError> {
+        let mut header = LeReader::new(data, 0);
+
+        if header.bytes(4)? != b"IDP3" {
+            return Err(parse_error("The file is not an MD3 model."));
+        }
+
+        let version = header.i32()?;
+
+        if version != 15 {
+            return Err(ImportError::new(ImportErrorType::UnsupportedFormat, format!("MD3 version {version} isn't supported.")));
+        }
+
+        let name = header.string(64)?;
+        let _flags = header.u32()?;
+
+        let [frame_count, tag_count, surface_count, _, frames_offset, tags_offset, surfaces_offset, _] = header.counts()?;
+
+        let mut reader = LeReader::new(data, frames_offset);
+        let frames = (0..frame_count).map(|_| Ok(Frame {
+            bounds: BoundingBox::new(reader.vec3()?, reader.vec3()?),
+            origin: reader.vec3()?,
+            radius: reader.f32()?,
+            name: reader.string(16)?
+        })).collect::<Result<_, ImportError>>()?;
+
+        let mut reader = LeReader::new(data, tags_offset);
+        let tags = (0..frame_count).map(|_| (0..tag_count).map(|_| Ok(Tag {
+            name: reader.string(64)?,
+            origin: reader.vec3()?,
+            axes: [reader.vec3()?, reader.vec3()?, reader.vec3()?]
+        })).collect()).collect::<Result<_, ImportError>>()?;
+
+        let mut surfaces = Vec::new();
+        let mut offset = surfaces_offset;
+
+        for _ in 0..surface_count {
+            let (surface, length) = read_surface(data, offset)?;
+
+            surfaces.push(surface);
+            offset = offset.saturating_add(length);
+        }
+
+        Ok(Self {
+            name,
+            frames,
+            tags,
+            surfaces
+        })
+    }
+
+    /// Creates a mesh for a surface with the vertices of a frame, so each frame can be used as a
+    /// morph target.
+    pub fn mesh(&self, surface: usize, frame: usize) -> Option<crate::Mesh> {
+        let surface = self.surfaces.get(surface)?;
+        let frame = surface.frames.get(frame)?;
+
+        let vertices = frame.positions.iter().zip(&frame.normals).zip(&surface.tex_coords).map(|((&position, &normal), &tex_coord)| Vertex {
+            position,
+            tex_coord,
+            color: Vec4::new(1.0, 1.0, 1.0, 1.0),
+            normal,
+            tangent: Vec4::new(0.0, 0.0, 0.0, 0.0)
+        }).collect::<Vec<_>>();
+
+        // Triangles are wound clockwise.
+        let indices = surface.triangles.iter().flat_map(|&[a, b, c]| [a, c, b]).collect();
+
+        Some(crate::Mesh {
+            bounds: BoundingBox::from_vertices(&vertices),
+            vertices,
+            indices: Some(indices),
+            material: None,
+            has_normals: true,
+            name: Some(surface.name.clone()),
+            extras: None
+        })
+    }
+}
+
+/// Reads the surface at an offset, and gets its size. The offsets in a surface are relative to
+/// its start.
+fn read_surface(data: &[u8], start: usize) -> Result<(Surface, usize), ImportError> {
+    let mut header = LeReader::new(data, start);
+
+    if header.bytes(4)? != b"IDP3" {
+        return Err(parse_error(format!("The surface at offset {start} is invalid.")));
+    }
+
+    let name = header.string(64)?;
+    let _flags = header.u32()?;
+
+    let [frame_count, shader_count, vertex_count, triangle_count, triangles_offset, shaders_offset, tex_coords_offset, vertices_offset, length] = header.counts()?;
+
+    let reader = |offset: usize| LeReader::new(data, start.saturating_add(offset));
+
+    let mut shaders_reader = reader(shaders_offset);
+    let shaders = (0..shader_count).map(|_| {
+        let shader = shaders_reader.string(64)?;
+        shaders_reader.i32()?;
+        Ok(shader)
+    }).collect::<Result<_, ImportError>>()?;
+
+    let mut triangles_reader = reader(triangles_offset);
+    let triangles = (0..triangle_count).map(|_| Ok([triangles_reader.u32()?, triangles_reader.u32()?, triangles_reader.u32()?])).collect::<Result<Vec<_>, ImportError>>()?;
+
+    if triangles.iter().flatten().any(|&vertex| vertex as usize >= vertex_count) {
+        return Err(parse_error(format!("Surface \"{name}\" has a triangle with a vertex that doesn't exist.")));
+    }
+
+    let mut tex_coords_reader = reader(tex_coords_offset);
+    let tex_coords = (0..vertex_count).map(|_| Ok(Vec2::new(tex_coords_reader.f32()?, tex_coords_reader.f32()?))).collect::<Result<_, ImportError>>()?;
+
+    let mut vertices_reader = reader(vertices_offset);
+    let frames = (0..frame_count).map(|_| {
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+
+        for _ in 0..vertex_count {
+            let [x, y, z] = [vertices_reader.i16()?, vertices_reader.i16()?, vertices_reader.i16()?];
+            positions.push(Vec3::new(x as f32 * POSITION_SCALE, y as f32 * POSITION_SCALE, z as f32 * POSITION_SCALE));
+
+            // Normals are stored as spherical coordinates, with a byte for each angle.
+            let longitude = vertices_reader.u8()? as f32 * TAU / 255.0;
+            let latitude = vertices_reader.u8()? as f32 * TAU / 255.0;
+
+            normals.push(Vec3::new(latitude.cos() * longitude.sin(), latitude.sin() * longitude.sin(), longitude.cos()));
+        }
+
+        Ok(SurfaceFrame { positions, normals })
+    }).collect::<Result<_, ImportError>>()?;
+
+    Ok((Surface { name, shaders, triangles, tex_coords, frames }, length))
+}
+
+impl Importer for Md3 {
+    fn import_bytes(data: &[u8], _resolver: &dyn ResourceResolver) -> Result<Self, ImportError> where Self : Sized {
+        Self::parse(data)
+    }
+
+    /// Creates a scene with the first frame of each surface as a mesh, under a node that converts
+    /// from +Z up. The tags of the first frame are child nodes, and the number of frames is in the
+    /// node's extras.
+    fn to_scene(&self, _resolver: &dyn ResourceResolver) -> Result<crate::Scene, ImportError> {
+        let mut materials = Vec::new();
+        let mut images = Vec::new();
+        let mut shader_materials = HashMap::new();
+
+        let mut meshes = Vec::new();
+
+        for i in 0..self.surfaces.len() {
+            let Some(mut mesh) = self.mesh(i, 0) else {
+                continue;
+            };
+
+            if let Some(shader) = self.surfaces[i].shaders.first().filter(|shader| !shader.is_empty()) {
+                mesh.material = Some(*shader_materials.entry(shader.clone()).or_insert_with(|| {
+                    let (material, image) = texture_material(shader, images.len());
+
+                    materials.push(material);
+                    images.push(image);
+
+                    materials.len() - 1
+                }));
+            }
+
+            meshes.push(mesh);
+        }
+
+        let mut nodes = vec![crate::Node {
+            transform: z_up_axis_transform(),
+            meshes: (0..meshes.len()).collect(),
+            children: Vec::new(),
+            name: if self.name.is_empty() { None } else { Some(self.name.clone()) },
+            extras: Some(json!({ "frames": self.frames.len() }))
+        }];
+
+        for tag in self.tags.first().into_iter().flatten() {
+            let index = nodes.len();
+            nodes[0].children.push(index);
+
+            nodes.push(crate::Node {
+                transform: tag.transform(),
+                meshes: Vec::new(),
+                children: Vec::new(),
+                name: Some(tag.name.clone()),
+                extras: None
+            });
+        }
+
+        Ok(crate::Scene {
+            meshes,
+            materials: if materials.is_empty() { None } else { Some(materials) },
+            images: if images.is_empty() { None } else { Some(images) },
+            nodes,
+            root_nodes: vec![0]
+        })
+    }
+}
+
+fn parse_error<T: ToString>(message: T) -> ImportError {
+    ImportError::new(ImportErrorType::StringParseError, message)
+}
@@ -1,6 +1,6 @@
 use std::ffi::{c_char, CStr, CString};
 
-use crate::{Vertex, Scene, Vec4, AlphaMode};
+use crate::{resolver::NoResolver, Vertex, Scene, Vec4, AlphaMode, BoundingBox};
 
 #[repr(C)]
 pub struct MdMesh {
@@ -10,7 +10,9 @@ pub struct MdMesh {
     pub indices:      *mut u32,
     pub num_indices:  usize,
 
-    pub material:     usize
+    pub material:     usize,
+
+    pub bounds:       BoundingBox
 }
 
 impl Drop for MdMesh {
@@ -58,11 +60,11 @@ pub struct MdImage {
 impl Drop for MdImage {
     fn drop(&mut self) {
         unsafe {
-            if self.path != std::ptr::null_mut() {
+            if !self.path.is_null() {
                 drop(CString::from_raw(self.path));
             }
 
-            if self.data != std::ptr::null_mut() {
+            if !self.data.is_null() {
                 Vec::from_raw_parts(self.data, self.data_length, self.data_length);
             }
         }
@@ -98,10 +100,50 @@ impl Drop for MdScene {
     }
 }
 
+/// Loads the scene at the given path, and writes a pointer to it into `scene`. If the scene could
+/// not be loaded, a null pointer is written instead.
+///
+/// Like [`Scene::load`], this can load any format in the [`crate::format`] registry.
+///
+/// # Safety
+///
+/// `path` must be a valid, null-terminated string, and `scene` must be a valid pointer.
+/// The returned scene must be freed with [`mdFree`].
 #[no_mangle]
-pub unsafe extern fn mdLoad(path: *const c_char, flags: u32, scene: *mut *mut MdScene) {
+pub unsafe extern "C" fn mdLoad(path: *const c_char, flags: u32, scene: *mut *mut MdScene) {
     let path = CStr::from_ptr(path).to_str().unwrap();
-    let scene_safe = Scene::load(path, crate::PostProcessFlags::from_bits_truncate(flags));
+    let result = Scene::load(path, crate::PostProcessFlags::from_bits_truncate(flags));
+
+    store_scene(result, scene);
+}
+
+/// Loads a scene from the contents of a file in memory, and writes a pointer to it into `scene`.
+/// If the scene could not be loaded, a null pointer is written instead.
+///
+/// The format is worked out from the data. Scenes that refer to external files, such as a
+/// `.gltf` with a separate `.bin`, can't be loaded this way.
+///
+/// # Safety
+///
+/// `data` must point to `length` readable bytes, and `scene` must be a valid pointer.
+/// The returned scene must be freed with [`mdFree`].
+#[no_mangle]
+pub unsafe extern "C" fn mdLoadMemory(data: *const u8, length: usize, flags: u32, scene: *mut *mut MdScene) {
+    let data = std::slice::from_raw_parts(data, length);
+    let result = Scene::load_bytes(data, &NoResolver, crate::PostProcessFlags::from_bits_truncate(flags));
+
+    store_scene(result, scene);
+}
+
+/// Converts the scene into its C representation, and writes a pointer to it into `scene`.
+unsafe fn store_scene(result: Result<Scene, crate::ImportError>, scene: *mut *mut MdScene) {
+    let scene_safe = match result {
+        Ok(scene) => scene,
+        Err(_) => {
+            *scene = std::ptr::null_mut();
+            return;
+        }
+    };
 
     let mut meshes = Vec::with_capacity(scene_safe.meshes.len());
     
@@ -129,6 +171,7 @@ pub unsafe extern fn mdLoad(path: *const c_char, flags: u32, scene: *mut *mut Md
             indices,
             num_indices,
             material,
+            bounds: mesh.bounds
         });
     }
 
@@ -210,8 +253,13 @@ pub unsafe extern fn mdLoad(path: *const c_char, flags: u32, scene: *mut *mut Md
     *scene = Box::into_raw(Box::new(scene_unsafe))
 }
 
+/// Frees a scene loaded with [`mdLoad`].
+///
+/// # Safety
+///
+/// `scene` must have been returned from [`mdLoad`], and must not be used after this call.
 #[no_mangle]
-pub unsafe extern fn mdFree(scene: *mut MdScene) {
+pub unsafe extern "C" fn mdFree(scene: *mut MdScene) {
     println!("start drop");
     drop(Box::from_raw(scene));
     println!("finish drop");
@@ -1,55 +1,989 @@
-use std::path::Path;
+use std::{collections::HashMap, fmt::Write, path::Path};
 
-use crate::{Importer, Vec3, Vec2};
+use serde_json::json;
 
-#[derive(Debug)]
+use crate::{resolver::ResourceResolver, Importer, Exporter, ImportError, ImportErrorType, ExportError, Vec3, Vec2, Vec4, Vertex, BoundingBox};
+
+/// A single corner of a face. Indices are zero-based, and have already been resolved if they were
+/// relative in the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct FaceElement {
     pub vertex:    usize,
-    pub tex_coord: usize,
-    pub normal:    usize
+    pub tex_coord: Option<usize>,
+    pub normal:    Option<usize>
 }
 
 #[derive(Debug)]
 pub struct Mesh {
-    pub vertices:      Vec<Vec3>,
-    pub normals:       Option<Vec<Vec3>>,
-    pub tex_coords:    Option<Vec<Vec2>>,
+    /// The name given by the `o` or `g` statement this mesh was declared in.
+    pub name:          Option<String>,
+
+    /// Faces are triangulated on import, so every 3 face elements form a triangle.
     pub face_elements: Vec<FaceElement>,
 
     pub material:      Option<usize>
 }
 
+/// A material from an MTL file.
+///
+/// Materials referenced by `usemtl` that aren't defined in any material library keep their default values.
 #[derive(Debug)]
-pub struct Obj {
-    pub meshes: Vec<Mesh>,
-    //pub materials: Option<Vec<Material>>
+pub struct Material {
+    pub name:           String,
+
+    /// `Ka`
+    pub ambient:        Vec3,
+    /// `Kd`
+    pub diffuse:        Vec3,
+    /// `Ks`
+    pub specular:       Vec3,
+    /// `Ke`
+    pub emissive:       Vec3,
+    /// `Ns`
+    pub shininess:      f32,
+    /// `d`, or `1 - Tr`.
+    pub dissolve:       f32,
+    /// `illum`
+    pub illumination:   Option<u32>,
+
+    /// `Pr`, from the PBR extension.
+    pub roughness:      Option<f32>,
+    /// `Pm`, from the PBR extension.
+    pub metallic:       Option<f32>,
+
+    // Texture maps. Paths are relative to the OBJ file.
+
+    /// `map_Kd`
+    pub diffuse_map:    Option<String>,
+    /// `map_Bump` or `bump`
+    pub bump_map:       Option<String>,
+    /// `norm`
+    pub normal_map:     Option<String>,
+    /// `map_d`
+    pub dissolve_map:   Option<String>,
+    /// `map_Ke`
+    pub emissive_map:   Option<String>,
+    /// `map_Pr`
+    pub roughness_map:  Option<String>,
+    /// `map_Pm`
+    pub metallic_map:   Option<String>
 }
 
-impl Importer for Obj {
-    fn import(path: &str) -> Result<Self, crate::ImportError> where Self : Sized {
-        let text = std::fs::read_to_string(path).unwrap();
+impl Material {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            ambient: Vec3::new(1.0, 1.0, 1.0),
+            diffuse: Vec3::new(1.0, 1.0, 1.0),
+            specular: Vec3::new(0.0, 0.0, 0.0),
+            emissive: Vec3::new(0.0, 0.0, 0.0),
+            shininess: 0.0,
+            dissolve: 1.0,
+            illumination: None,
+            roughness: None,
+            metallic: None,
+            diffuse_map: None,
+            bump_map: None,
+            normal_map: None,
+            dissolve_map: None,
+            emissive_map: None,
+            roughness_map: None,
+            metallic_map: None
+        }
+    }
+
+    /// Converts the material into a PBR material, adding any textures it uses to `images`.
+    fn to_scene_material(&self, images: &mut Vec<crate::Image>) -> crate::Material {
+        let mut image = |path: &Option<String>| {
+            path.as_ref().map(|path| {
+                match images.iter().position(|image| image.path.as_ref() == Some(path)) {
+                    Some(index) => index,
+                    None => {
+                        images.push(crate::Image {
+                            path: Some(path.clone()),
+                            data_type: None,
+                            data: None,
+                            name: None,
+                            extras: None
+                        });
+
+                        images.len() - 1
+                    }
+                }
+            })
+        };
+
+        // Without the PBR extension, approximate the roughness from the Blinn-Phong specular exponent.
+        let roughness = match self.roughness {
+            Some(roughness) => roughness,
+            None => (2.0 / (self.shininess + 2.0)).sqrt()
+        };
+
+        // Textured transparency is usually used for cutouts, such as leaves, so use alpha testing.
+        let alpha_mode = if self.dissolve_map.is_some() {
+            crate::AlphaMode::Cutoff
+        } else if self.dissolve < 1.0 {
+            crate::AlphaMode::Blend
+        } else {
+            crate::AlphaMode::Opaque
+        };
+
+        // Keep the values that can't be represented in a PBR material, so they aren't lost.
+        let extras = json!({
+            "ambient": [self.ambient.x, self.ambient.y, self.ambient.z],
+            "specular": [self.specular.x, self.specular.y, self.specular.z],
+            "emissive": [self.emissive.x, self.emissive.y, self.emissive.z],
+            "shininess": self.shininess,
+            "illum": self.illumination
+        });
+
+        crate::Material {
+            albedo_color: Vec4::new(self.diffuse.x, self.diffuse.y, self.diffuse.z, self.dissolve),
+            albedo_texture: image(&self.diffuse_map),
+            normal_texture: image(self.normal_map.as_ref().map_or(&self.bump_map, |_| &self.normal_map)),
+            metallic: self.metallic.unwrap_or(0.0),
+            metallic_texture: image(&self.metallic_map),
+            roughness,
+            roughness_texture: image(&self.roughness_map),
+            occlusion_texture: None,
+            emissive_texture: image(&self.emissive_map),
+            alpha_mode,
+            alpha_cutoff: 0.5,
+            double_sided: false,
+            name: Some(self.name.clone()),
+            extras: Some(extras)
+        }
+    }
+
+    fn from_scene_material(material: &crate::Material, index: usize, images: &Option<Vec<crate::Image>>) -> Self {
+        // Only images stored as files can be referenced from an MTL file.
+        let map = |texture: Option<usize>| {
+            texture
+                .and_then(|texture| images.as_ref()?.get(texture))
+                .and_then(|image| image.path.clone())
+        };
+
+        let extras = material.extras.as_ref();
+
+        let vec3_extra = |key: &str, default: Vec3| {
+            extras
+                .and_then(|extras| extras.get(key)?.as_array())
+                .filter(|values| values.len() == 3)
+                .map(|values| {
+                    let value = |i: usize| values[i].as_f64().unwrap_or(0.0) as f32;
+                    Vec3::new(value(0), value(1), value(2))
+                })
+                .unwrap_or(default)
+        };
+
+        // Materials that came from an MTL file keep their original Phong values in their extras,
+        // otherwise the specular exponent is derived from the roughness, inverting the import.
+        let shininess = extras
+            .and_then(|extras| extras.get("shininess")?.as_f64())
+            .map(|shininess| shininess as f32)
+            .unwrap_or_else(|| 2.0 / material.roughness.max(0.01).powi(2) - 2.0);
+
+        let illumination = match extras.and_then(|extras| extras.get("illum")) {
+            Some(illum) => illum.as_u64().map(|illum| illum as u32),
+            None => Some(2)
+        };
+
+        let color = material.albedo_color;
+
+        Self {
+            name: material.name.clone().unwrap_or_else(|| format!("Material{index}")),
+            ambient: vec3_extra("ambient", Vec3::new(1.0, 1.0, 1.0)),
+            diffuse: Vec3::new(color.x, color.y, color.z),
+            specular: vec3_extra("specular", Vec3::new(0.5, 0.5, 0.5)),
+            emissive: vec3_extra("emissive", Vec3::new(0.0, 0.0, 0.0)),
+            shininess,
+            dissolve: color.w,
+            illumination,
+            roughness: Some(material.roughness),
+            metallic: Some(material.metallic),
+            diffuse_map: map(material.albedo_texture),
+            bump_map: None,
+            normal_map: map(material.normal_texture),
+            dissolve_map: if material.alpha_mode == crate::AlphaMode::Cutoff { map(material.albedo_texture) } else { None },
+            emissive_map: map(material.emissive_texture),
+            roughness_map: map(material.roughness_texture),
+            metallic_map: map(material.metallic_texture)
+        }
+    }
+}
 
-        /*let mut vertices = Vec::new();
+/// A Wavefront OBJ file.
+///
+/// Vertex data is shared between every mesh in the file, with meshes referring to it by index.
+#[derive(Debug)]
+pub struct Obj {
+    pub vertices:   Vec<Vec3>,
+    pub normals:    Option<Vec<Vec3>>,
+    pub tex_coords: Option<Vec<Vec2>>,
+
+    /// Vertex colors, with one color for each vertex. These either come from `vc` statements, or
+    /// from the non-standard `v x y z r g b` extension.
+    pub colors:     Option<Vec<Vec4>>,
+
+    pub meshes:     Vec<Mesh>,
+    pub materials:  Option<Vec<Material>>,
+
+    /// The paths given by `mtllib` statements, relative to the OBJ file.
+    pub material_libraries: Vec<String>
+}
+
+impl Obj {
+    /// Parses the text of an OBJ file.
+    pub fn parse(text: &str) -> Result<Self, ImportError> {
+        let mut vertices = Vec::new();
         let mut normals = Vec::new();
         let mut tex_coords = Vec::new();
-        let mut face_elements = Vec::new();
+        let mut colors = Vec::new();
+        let mut meshes = Vec::new();
+        let mut materials: Vec<Material> = Vec::new();
+        let mut material_libraries = Vec::new();
+
+        let mut current = Mesh {
+            name: None,
+            face_elements: Vec::new(),
+            material: None
+        };
+
+        let mut polygon = Vec::new();
+
+        for (line_number, line) in logical_lines(text) {
+            let mut words = line.split_whitespace();
+
+            let Some(keyword) = words.next() else {
+                continue;
+            };
+
+            let error = |message: &str| ImportError::new(ImportErrorType::StringParseError, format!("Line {line_number}: {message}"));
+
+            match keyword {
+                "v" => {
+                    let values = parse_floats(words).map_err(|_| error("Invalid vertex."))?;
+
+                    match values.len() {
+                        // The optional 4th component is the W coordinate, which we ignore.
+                        3 | 4 => vertices.push(Vec3::new(values[0], values[1], values[2])),
+
+                        // Some programs write vertex colors after the position.
+                        6 | 7 => {
+                            vertices.push(Vec3::new(values[0], values[1], values[2]));
+
+                            // Vertices before this one without a color from a `vc` statement get the default.
+                            let index = vertices.len() - 1;
+                            let color = Vec4::new(values[3], values[4], values[5], if values.len() == 7 { values[6] } else { 1.0 });
+
+                            if colors.len() > index {
+                                colors[index] = color;
+                            } else {
+                                colors.resize(index, Vec4::new(1.0, 1.0, 1.0, 1.0));
+                                colors.push(color);
+                            }
+                        },
+
+                        _ => return Err(error("Vertices must have 3 components."))
+                    }
+                },
+
+                "vt" => {
+                    let values = parse_floats(words).map_err(|_| error("Invalid texture coordinate."))?;
+
+                    if values.is_empty() {
+                        return Err(error("Texture coordinates must have at least 1 component."));
+                    }
+
+                    tex_coords.push(Vec2::new(values[0], values.get(1).copied().unwrap_or(0.0)));
+                },
+
+                "vn" => {
+                    let values = parse_floats(words).map_err(|_| error("Invalid normal."))?;
+
+                    if values.len() != 3 {
+                        return Err(error("Normals must have 3 components."));
+                    }
+
+                    normals.push(Vec3::new(values[0], values[1], values[2]));
+                },
+
+                "vc" => {
+                    let values = parse_floats(words).map_err(|_| error("Invalid vertex color."))?;
 
-        let mut meshes = Vec::new();*/
+                    if values.len() != 3 && values.len() != 4 {
+                        return Err(error("Vertex colors must have 3 or 4 components."));
+                    }
 
-        
+                    colors.push(Vec4::new(values[0], values[1], values[2], values.get(3).copied().unwrap_or(1.0)));
+                },
 
-        todo!()
+                "f" => {
+                    polygon.clear();
+
+                    for word in words {
+                        polygon.push(parse_face_element(word, vertices.len(), tex_coords.len(), normals.len()).map_err(|message| error(&message))?);
+                    }
+
+                    if polygon.len() < 3 {
+                        return Err(error("Faces must have at least 3 vertices."));
+                    }
+
+                    // Polygons are triangulated as a fan around the first vertex, which is correct
+                    // for the convex polygons that OBJ files almost always contain.
+                    for i in 1..polygon.len() - 1 {
+                        current.face_elements.push(polygon[0]);
+                        current.face_elements.push(polygon[i]);
+                        current.face_elements.push(polygon[i + 1]);
+                    }
+                },
+
+                "o" | "g" => {
+                    let name = words.collect::<Vec<_>>().join(" ");
+                    let material = current.material;
+
+                    push_mesh(&mut meshes, &mut current);
+
+                    current.name = if name.is_empty() { None } else { Some(name) };
+
+                    // The current material carries over into new groups.
+                    current.material = material;
+                },
+
+                "usemtl" => {
+                    let name = words.collect::<Vec<_>>().join(" ");
+
+                    let material = match materials.iter().position(|material| material.name == name) {
+                        Some(material) => material,
+                        None => {
+                            materials.push(Material::new(name));
+                            materials.len() - 1
+                        }
+                    };
+
+                    if current.material != Some(material) {
+                        let name = current.name.clone();
+
+                        push_mesh(&mut meshes, &mut current);
+
+                        current.name = name;
+                        current.material = Some(material);
+                    }
+                },
+
+                "mtllib" => {
+                    // Library names can't contain spaces, as multiple libraries can be given at once.
+                    material_libraries.extend(words.map(String::from));
+                },
+
+                // Statements we don't support, such as lines, points, smoothing groups and free-form
+                // geometry, are ignored.
+                _ => {}
+            }
+        }
+
+        push_mesh(&mut meshes, &mut current);
+
+        // Colors are per-vertex, so make sure every vertex has one if any of them do.
+        let colors = if colors.is_empty() {
+            None
+        } else {
+            colors.resize(vertices.len(), Vec4::new(1.0, 1.0, 1.0, 1.0));
+            Some(colors)
+        };
+
+        Ok(Self {
+            vertices,
+            normals: if normals.is_empty() { None } else { Some(normals) },
+            tex_coords: if tex_coords.is_empty() { None } else { Some(tex_coords) },
+            colors,
+            meshes,
+            materials: if materials.is_empty() { None } else { Some(materials) },
+            material_libraries
+        })
     }
 
-    fn export(path: &str) {
-        todo!()
+    /// Parses the text of an MTL file, filling in any materials with matching names, and adding
+    /// any materials that haven't been used yet.
+    ///
+    /// Texture paths are prefixed with `directory`, which should be the directory of the material
+    /// library relative to the OBJ file.
+    pub fn load_material_library(&mut self, text: &str, directory: &Path) -> Result<(), ImportError> {
+        let materials = self.materials.get_or_insert_with(Vec::new);
+        let mut current = None;
+
+        for (line_number, line) in logical_lines(text) {
+            let mut words = line.split_whitespace();
+
+            let Some(keyword) = words.next() else {
+                continue;
+            };
+
+            let error = |message: &str| ImportError::new(ImportErrorType::StringParseError, format!("Line {line_number}: {message}"));
+
+            if keyword == "newmtl" {
+                let name = words.collect::<Vec<_>>().join(" ");
+
+                current = Some(match materials.iter().position(|material| material.name == name) {
+                    Some(material) => material,
+                    None => {
+                        materials.push(Material::new(name));
+                        materials.len() - 1
+                    }
+                });
+
+                continue;
+            }
+
+            let Some(material) = current.map(|current| &mut materials[current]) else {
+                return Err(error("Material properties must come after a newmtl statement."));
+            };
+
+            let color = |words: std::str::SplitWhitespace| {
+                let values = parse_floats(words).map_err(|_| error("Invalid color."))?;
+
+                // A single value is used for all 3 channels. Colors given as `spectral` or `xyz`
+                // aren't supported, and fail to parse here.
+                match values.len() {
+                    1 => Ok(Vec3::new(values[0], values[0], values[0])),
+                    3 => Ok(Vec3::new(values[0], values[1], values[2])),
+                    _ => Err(error("Colors must have 1 or 3 components."))
+                }
+            };
+
+            let float = |mut words: std::str::SplitWhitespace| {
+                words.next()
+                    .and_then(|word| word.parse::<f32>().ok())
+                    .ok_or_else(|| error("Invalid value."))
+            };
+
+            let map = |words: std::str::SplitWhitespace| {
+                match texture_path(words) {
+                    Some(path) => Ok(Some(directory.join(path).to_string_lossy().replace('\\', "/"))),
+                    None => Err(error("Texture map has no file name."))
+                }
+            };
+
+            match keyword {
+                "Ka" => material.ambient = color(words)?,
+                "Kd" => material.diffuse = color(words)?,
+                "Ks" => material.specular = color(words)?,
+                "Ke" => material.emissive = color(words)?,
+                "Ns" => material.shininess = float(words)?,
+                "d" => material.dissolve = float(words)?,
+                "Tr" => material.dissolve = 1.0 - float(words)?,
+                "illum" => material.illumination = Some(float(words)? as u32),
+                "Pr" => material.roughness = Some(float(words)?),
+                "Pm" => material.metallic = Some(float(words)?),
+
+                "map_Kd" => material.diffuse_map = map(words)?,
+                "map_Bump" | "map_bump" | "bump" => material.bump_map = map(words)?,
+                "norm" => material.normal_map = map(words)?,
+                "map_d" => material.dissolve_map = map(words)?,
+                "map_Ke" => material.emissive_map = map(words)?,
+                "map_Pr" => material.roughness_map = map(words)?,
+                "map_Pm" => material.metallic_map = map(words)?,
+
+                // Other properties, such as ambient and specular maps, are ignored.
+                _ => {}
+            }
+        }
+
+        Ok(())
     }
+}
 
+impl Exporter for Obj {
+    fn export(&self, path: &str) -> Result<(), ExportError> {
+        let path = Path::new(path);
+
+        // Materials are written to an MTL file next to the OBJ, with the same name. `mtllib` splits
+        // library names on whitespace, so it's replaced with underscores.
+        let material_library = match &self.materials {
+            Some(_) => {
+                let name = match path.file_stem() {
+                    Some(stem) => format!("{}.mtl", stem.to_string_lossy().replace(char::is_whitespace, "_")),
+                    None => String::from("materials.mtl")
+                };
+
+                std::fs::write(path.with_file_name(&name), self.write_mtl())?;
+
+                Some(name)
+            },
+            None => None
+        };
+
+        std::fs::write(path, self.write_obj(material_library.as_deref()))?;
+
+        Ok(())
+    }
+
+    /// Creates an OBJ from the scene, with one object for each mesh.
+    ///
+    /// OBJ has no node hierarchy, so meshes are written in their own space, without any node
+    /// transforms applied. As materials carry over between objects, meshes without a material are
+    /// given a default one, rather than using the material of the mesh before them.
     fn from_scene(scene: &crate::Scene) -> Self {
-        todo!()
+        let has_colors = scene.meshes
+            .iter()
+            .any(|mesh| mesh.vertices.iter().any(|v| v.color != Vec4::new(1.0, 1.0, 1.0, 1.0)));
+
+        let mut vertices = Vec::new();
+        let mut colors = Vec::new();
+        let mut tex_coords = Vec::new();
+        let mut normals = Vec::new();
+
+        // Vertex data is shared between every mesh, so identical values are only written once.
+        // Colors are stored with positions, so they are part of the position's key.
+        let mut vertex_cache = HashMap::new();
+        let mut tex_coord_cache = HashMap::new();
+        let mut normal_cache = HashMap::new();
+
+        let mut meshes = Vec::with_capacity(scene.meshes.len());
+
+        for (i, mesh) in scene.meshes.iter().enumerate() {
+            let has_tex_coords = mesh.vertices.iter().any(|v| v.tex_coord.x != 0.0 || v.tex_coord.y != 0.0);
+            let has_normals = mesh.has_normals;
+
+            let elements = mesh.vertices
+                .iter()
+                .map(|v| {
+                    let color = if has_colors { v.color } else { Vec4::new(1.0, 1.0, 1.0, 1.0) };
+                    let key = [v.position.x, v.position.y, v.position.z, color.x, color.y, color.z, color.w].map(f32::to_bits);
+
+                    let vertex = *vertex_cache.entry(key).or_insert_with(|| {
+                        vertices.push(v.position);
+                        colors.push(color);
+                        vertices.len() - 1
+                    });
+
+                    let tex_coord = has_tex_coords.then(|| {
+                        // Flip back to OBJ's bottom left origin.
+                        let tex_coord = Vec2::new(v.tex_coord.x, 1.0 - v.tex_coord.y);
+
+                        *tex_coord_cache.entry([tex_coord.x, tex_coord.y].map(f32::to_bits)).or_insert_with(|| {
+                            tex_coords.push(tex_coord);
+                            tex_coords.len() - 1
+                        })
+                    });
+
+                    let normal = has_normals.then(|| {
+                        *normal_cache.entry([v.normal.x, v.normal.y, v.normal.z].map(f32::to_bits)).or_insert_with(|| {
+                            normals.push(v.normal);
+                            normals.len() - 1
+                        })
+                    });
+
+                    FaceElement { vertex, tex_coord, normal }
+                })
+                .collect::<Vec<_>>();
+
+            let face_elements = match &mesh.indices {
+                Some(indices) => indices.iter().map(|&index| elements[index as usize]).collect(),
+                None => elements
+            };
+
+            meshes.push(Mesh {
+                name: Some(mesh.name.clone().unwrap_or_else(|| format!("Mesh{i}"))),
+                face_elements,
+                material: mesh.material
+            });
+        }
+
+        let mut materials = scene.materials.as_ref().map(|materials| {
+            materials
+                .iter()
+                .enumerate()
+                .map(|(i, material)| Material::from_scene_material(material, i, &scene.images))
+                .collect::<Vec<_>>()
+        });
+
+        if let Some(materials) = materials.as_mut().filter(|_| meshes.iter().any(|mesh| mesh.material.is_none())) {
+            let mut name = String::from("default");
+
+            for i in 1.. {
+                if !materials.iter().any(|material| material.name == name) {
+                    break;
+                }
+
+                name = format!("default{i}");
+            }
+
+            materials.push(Material::new(name));
+
+            for mesh in &mut meshes {
+                mesh.material.get_or_insert(materials.len() - 1);
+            }
+        }
+
+        Self {
+            vertices,
+            normals: if normals.is_empty() { None } else { Some(normals) },
+            tex_coords: if tex_coords.is_empty() { None } else { Some(tex_coords) },
+            colors: if has_colors { Some(colors) } else { None },
+            meshes,
+            materials,
+            material_libraries: Vec::new()
+        }
+    }
+}
+
+impl Importer for Obj {
+    fn import_bytes(data: &[u8], resolver: &dyn ResourceResolver) -> Result<Self, ImportError> where Self : Sized {
+        let text = std::str::from_utf8(data).map_err(|_| ImportError::new(ImportErrorType::StringParseError, "The file contains invalid UTF-8."))?;
+
+        let mut obj = Self::parse(text)?;
+
+        for library in obj.material_libraries.clone() {
+            // Missing material libraries are common, especially when files are moved around, so
+            // the materials are left with their default values.
+            let data = match resolver.resolve(&library) {
+                Ok(data) => data,
+                Err(err) if matches!(err.e_type, ImportErrorType::FileNotFound) => continue,
+                Err(err) => return Err(err)
+            };
+
+            let text = String::from_utf8_lossy(&data);
+
+            let library_directory = Path::new(&library).parent().unwrap_or(Path::new(""));
+
+            obj.load_material_library(&text, library_directory)?;
+        }
+
+        Ok(obj)
+    }
+
+    fn to_scene(&self, _resolver: &dyn ResourceResolver) -> Result<crate::Scene, ImportError> {
+        let mut meshes = Vec::with_capacity(self.meshes.len());
+
+        // OBJ face elements index positions, texture coordinates and normals separately, however
+        // modelo vertices contain all of them, so each unique combination becomes a vertex.
+        let mut vertex_cache = HashMap::new();
+
+        for mesh in &self.meshes {
+            vertex_cache.clear();
+
+            let mut vertices = Vec::new();
+            let mut indices = Vec::with_capacity(mesh.face_elements.len());
+
+            for element in &mesh.face_elements {
+                let index = *vertex_cache.entry(*element).or_insert_with(|| {
+                    vertices.push(self.vertex(element));
+                    (vertices.len() - 1) as u32
+                });
+
+                indices.push(index);
+            }
+
+            let bounds = BoundingBox::from_vertices(&vertices);
+
+            meshes.push(crate::Mesh {
+                vertices,
+                indices: Some(indices),
+                material: mesh.material,
+                has_normals: mesh.face_elements.iter().any(|element| element.normal.is_some()),
+                bounds,
+                name: mesh.name.clone(),
+                extras: None
+            });
+        }
+
+        let mut images = Vec::new();
+
+        let materials = self.materials.as_ref().map(|materials| {
+            materials
+                .iter()
+                .map(|material| material.to_scene_material(&mut images))
+                .collect()
+        });
+
+        Ok(crate::Scene {
+            meshes,
+            materials,
+            images: if images.is_empty() { None } else { Some(images) },
+            nodes: Vec::new(),
+            root_nodes: Vec::new()
+        })
+    }
+}
+
+impl Obj {
+    /// Writes the OBJ file as text, referring to the given material library if there is one.
+    pub fn write_obj(&self, material_library: Option<&str>) -> String {
+        let mut text = String::new();
+
+        if let Some(library) = material_library {
+            writeln!(text, "mtllib {library}").unwrap();
+        }
+
+        for (i, vertex) in self.vertices.iter().enumerate() {
+            write!(text, "v {} {} {}", vertex.x, vertex.y, vertex.z).unwrap();
+
+            if let Some(color) = self.colors.as_ref().and_then(|colors| colors.get(i)) {
+                write!(text, " {} {} {}", color.x, color.y, color.z).unwrap();
+
+                if color.w != 1.0 {
+                    write!(text, " {}", color.w).unwrap();
+                }
+            }
+
+            text.push('\n');
+        }
+
+        for tex_coord in self.tex_coords.iter().flatten() {
+            writeln!(text, "vt {} {}", tex_coord.x, tex_coord.y).unwrap();
+        }
+
+        for normal in self.normals.iter().flatten() {
+            writeln!(text, "vn {} {} {}", normal.x, normal.y, normal.z).unwrap();
+        }
+
+        for mesh in &self.meshes {
+            text.push('\n');
+
+            if let Some(name) = &mesh.name {
+                writeln!(text, "o {name}").unwrap();
+            }
+
+            if let Some(material) = mesh.material.and_then(|material| self.materials.as_ref()?.get(material)) {
+                writeln!(text, "usemtl {}", material.name).unwrap();
+            }
+
+            for triangle in mesh.face_elements.chunks_exact(3) {
+                text.push('f');
+
+                for element in triangle {
+                    write!(text, " {}", element.vertex + 1).unwrap();
+
+                    match (element.tex_coord, element.normal) {
+                        (Some(tex_coord), Some(normal)) => write!(text, "/{}/{}", tex_coord + 1, normal + 1).unwrap(),
+                        (Some(tex_coord), None) => write!(text, "/{}", tex_coord + 1).unwrap(),
+                        (None, Some(normal)) => write!(text, "//{}", normal + 1).unwrap(),
+                        (None, None) => {}
+                    }
+                }
+
+                text.push('\n');
+            }
+        }
+
+        text
+    }
+
+    /// Writes the materials as the text of an MTL file.
+    pub fn write_mtl(&self) -> String {
+        let mut text = String::new();
+
+        for material in self.materials.iter().flatten() {
+            if !text.is_empty() {
+                text.push('\n');
+            }
+
+            let color = |text: &mut String, keyword: &str, color: Vec3| {
+                writeln!(text, "{keyword} {} {} {}", color.x, color.y, color.z).unwrap();
+            };
+
+            writeln!(text, "newmtl {}", material.name).unwrap();
+            color(&mut text, "Ka", material.ambient);
+            color(&mut text, "Kd", material.diffuse);
+            color(&mut text, "Ks", material.specular);
+            color(&mut text, "Ke", material.emissive);
+            writeln!(text, "Ns {}", material.shininess).unwrap();
+            writeln!(text, "d {}", material.dissolve).unwrap();
+
+            if let Some(illumination) = material.illumination {
+                writeln!(text, "illum {illumination}").unwrap();
+            }
+
+            if let Some(roughness) = material.roughness {
+                writeln!(text, "Pr {roughness}").unwrap();
+            }
+
+            if let Some(metallic) = material.metallic {
+                writeln!(text, "Pm {metallic}").unwrap();
+            }
+
+            let maps = [
+                ("map_Kd", &material.diffuse_map),
+                ("map_Bump", &material.bump_map),
+                ("norm", &material.normal_map),
+                ("map_d", &material.dissolve_map),
+                ("map_Ke", &material.emissive_map),
+                ("map_Pr", &material.roughness_map),
+                ("map_Pm", &material.metallic_map)
+            ];
+
+            for (keyword, map) in maps {
+                if let Some(map) = map {
+                    writeln!(text, "{keyword} {map}").unwrap();
+                }
+            }
+        }
+
+        text
+    }
+
+    fn vertex(&self, element: &FaceElement) -> Vertex {
+        let tex_coord = match (&self.tex_coords, element.tex_coord) {
+            // OBJ texture coordinates start at the bottom left, however modelo's start at the top left.
+            (Some(tex_coords), Some(index)) => Vec2::new(tex_coords[index].x, 1.0 - tex_coords[index].y),
+            _ => Vec2::new(0.0, 0.0)
+        };
+
+        let normal = match (&self.normals, element.normal) {
+            (Some(normals), Some(index)) => normals[index],
+            _ => Vec3::new(0.0, 0.0, 0.0)
+        };
+
+        let color = match &self.colors {
+            Some(colors) => colors[element.vertex],
+            None => Vec4::new(1.0, 1.0, 1.0, 1.0)
+        };
+
+        Vertex {
+            position: self.vertices[element.vertex],
+            tex_coord,
+            color,
+            normal,
+            tangent: Vec4::new(0.0, 0.0, 0.0, 0.0)
+        }
     }
+}
+
+/// Pushes the mesh into the list if it has any faces, and resets it.
+fn push_mesh(meshes: &mut Vec<Mesh>, mesh: &mut Mesh) {
+    let mesh = std::mem::replace(mesh, Mesh {
+        name: None,
+        face_elements: Vec::new(),
+        material: None
+    });
 
-    fn to_scene(&self, directory: &Path) -> crate::Scene {
-        todo!()
+    if !mesh.face_elements.is_empty() {
+        meshes.push(mesh);
     }
-}
\ No newline at end of file
+}
+
+/// Splits the text into lines, stripping comments and joining lines that end in a `\`.
+/// Each line is returned with its 1-based line number, for error messages.
+fn logical_lines(text: &str) -> Vec<(usize, String)> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut start = 0;
+
+    for (i, line) in text.lines().enumerate() {
+        if current.is_empty() {
+            start = i + 1;
+        }
+
+        let line = match line.find('#') {
+            Some(comment) => &line[..comment],
+            None => line
+        };
+
+        if let Some(line) = line.trim_end().strip_suffix('\\') {
+            current.push_str(line);
+            current.push(' ');
+        } else {
+            current.push_str(line);
+            lines.push((start, std::mem::take(&mut current)));
+        }
+    }
+
+    if !current.is_empty() {
+        lines.push((start, current));
+    }
+
+    lines
+}
+
+/// Gets the file name from a texture map statement, skipping over any options before it.
+fn texture_path(mut words: std::str::SplitWhitespace) -> Option<String> {
+    let mut path = Vec::new();
+
+    while let Some(word) = words.next() {
+        // Only options can appear before the file name, and file names can contain spaces.
+        if !path.is_empty() || !word.starts_with('-') {
+            path.push(word);
+            continue;
+        }
+
+        match word {
+            // These take up to 3 numbers.
+            "-o" | "-s" | "-t" => {
+                let mut peek = words.clone();
+                let mut count = 0;
+
+                while count < 3 && peek.next().is_some_and(|value| value.parse::<f32>().is_ok()) {
+                    count += 1;
+                }
+
+                for _ in 0..count {
+                    words.next();
+                }
+            },
+
+            "-mm" => {
+                words.next();
+                words.next();
+            },
+
+            // Every other option takes a single value.
+            _ => {
+                words.next();
+            }
+        }
+    }
+
+    if path.is_empty() {
+        None
+    } else {
+        Some(path.join(" "))
+    }
+}
+
+fn parse_floats<'a>(words: impl Iterator<Item = &'a str>) -> Result<Vec<f32>, std::num::ParseFloatError> {
+    words.map(|word| word.parse::<f32>()).collect()
+}
+
+/// Parses a face element in any of the forms `v`, `v/vt`, `v//vn` or `v/vt/vn`.
+fn parse_face_element(word: &str, num_vertices: usize, num_tex_coords: usize, num_normals: usize) -> Result<FaceElement, String> {
+    let mut parts = word.split('/');
+
+    let vertex = match parts.next() {
+        Some(vertex) if !vertex.is_empty() => resolve_index(vertex, num_vertices)?,
+        _ => return Err(format!("Face element \"{word}\" has no vertex."))
+    };
+
+    let tex_coord = match parts.next() {
+        Some(tex_coord) if !tex_coord.is_empty() => Some(resolve_index(tex_coord, num_tex_coords)?),
+        _ => None
+    };
+
+    let normal = match parts.next() {
+        Some(normal) if !normal.is_empty() => Some(resolve_index(normal, num_normals)?),
+        _ => None
+    };
+
+    Ok(FaceElement {
+        vertex,
+        tex_coord,
+        normal
+    })
+}
+
+/// Converts a 1-based OBJ index into a 0-based one. Negative indices are relative to the end of the
+/// list, so `-1` refers to the most recently declared element.
+fn resolve_index(index: &str, count: usize) -> Result<usize, String> {
+    let value = index.parse::<i64>().map_err(|_| format!("Invalid index \"{index}\"."))?;
+
+    let resolved = match value {
+        0 => None,
+        value if value > 0 => Some(value as usize - 1),
+        value => (count as i64).checked_add(value).and_then(|index| usize::try_from(index).ok())
+    };
+
+    match resolved {
+        Some(resolved) if resolved < count => Ok(resolved),
+        _ => Err(format!("Index {value} is out of range."))
+    }
+}
@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+
+use crate::{resolver::ResourceResolver, Importer, Exporter, ImportError, ImportErrorType, ExportError, Vec2, Vec3, Vec4, Vertex, BoundingBox};
+
+/// A polygon, with its own color in COFF files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Face {
+    pub vertices: Vec<u32>,
+    pub color:    Option<Vec4>
+}
+
+/// An Object File Format mesh, which is a list of vertices and a list of polygons.
+///
+/// The header keyword says which vertex properties there are: `ST` for texture coordinates, `C`
+/// for colors and `N` for normals, as in `COFF` or `STCNOFF`. Only the text form with 3D
+/// positions is supported.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Off {
+    pub positions:  Vec<Vec3>,
+    pub normals:    Option<Vec<Vec3>>,
+    pub colors:     Option<Vec<Vec4>>,
+    pub tex_coords: Option<Vec<Vec2>>,
+    pub faces:      Vec<Face>
+}
+
+impl Off {
+    pub fn parse(text: &str) -> Result<Self, ImportError> {
+        // Comments can be anywhere, and blank lines are ignored.
+        let mut lines = text.lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.split('#').next().unwrap_or_default()))
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(number, line)| (number, line.split_whitespace().collect::<Vec<_>>()));
+
+        let Some((header_line, header)) = lines.next() else {
+            return Err(parse_error("The file is empty."));
+        };
+
+        let keyword = header[0];
+
+        let Some(prefix) = keyword.strip_suffix("OFF") else {
+            return Err(parse_error("The file is not an OFF file."));
+        };
+
+        let has_tex_coords = prefix.starts_with("ST");
+        let rest = prefix.strip_prefix("ST").unwrap_or(prefix);
+        let has_colors = rest.starts_with('C');
+        let rest = rest.strip_prefix('C').unwrap_or(rest);
+        let has_normals = rest.starts_with('N');
+        let rest = rest.strip_prefix('N').unwrap_or(rest);
+
+        if !rest.is_empty() || header.get(1) == Some(&"BINARY") {
+            return Err(ImportError::new(ImportErrorType::UnsupportedFormat, format!("\"{}\" OFF files aren't supported.", header.join(" "))));
+        }
+
+        // The counts can be on the same line as the keyword.
+        let (line, counts) = if header.len() > 1 {
+            (header_line, header[1..].to_vec())
+        } else {
+            lines.next().ok_or_else(|| parse_error("The file has no vertex and face counts."))?
+        };
+
+        let count = |i: usize| counts.get(i).and_then(|count| count.parse::<usize>().ok()).ok_or_else(|| parse_error(format!("Line {line}: Invalid vertex and face counts.")));
+        let (vertex_count, face_count) = (count(0)?, count(1)?);
+
+        let mut off = Self {
+            positions: Vec::new(),
+            normals: has_normals.then(Vec::new),
+            colors: has_colors.then(Vec::new),
+            tex_coords: has_tex_coords.then(Vec::new),
+            faces: Vec::new()
+        };
+
+        for _ in 0..vertex_count {
+            let (line, words) = lines.next().ok_or_else(|| parse_error("The file has fewer vertices than its header says."))?;
+            let error = |message: &str| parse_error(format!("Line {line}: {message}"));
+
+            let values = words.iter().map(|word| word.parse::<f32>()).collect::<Result<Vec<_>, _>>().map_err(|_| error("Invalid number."))?;
+
+            // Colors can have three or four components, so they're whatever is left over.
+            let color_start = if has_normals { 6 } else { 3 };
+            let tex_coords_length = if has_tex_coords { 2 } else { 0 };
+
+            let color_length = match values.len().checked_sub(color_start + tex_coords_length) {
+                Some(length @ (3 | 4)) if has_colors => length,
+                Some(0) if !has_colors => 0,
+                _ => return Err(error(&format!("Expected the properties of a \"{keyword}\" vertex.")))
+            };
+
+            let color_end = color_start + color_length;
+
+            off.positions.push(Vec3::new(values[0], values[1], values[2]));
+
+            if let Some(normals) = &mut off.normals {
+                normals.push(Vec3::new(values[3], values[4], values[5]));
+            }
+
+            if let Some(colors) = &mut off.colors {
+                colors.push(parse_color(&words[color_start..color_end]).ok_or_else(|| error("Invalid color."))?);
+            }
+
+            if let Some(tex_coords) = &mut off.tex_coords {
+                tex_coords.push(Vec2::new(values[color_end], values[color_end + 1]));
+            }
+        }
+
+        for _ in 0..face_count {
+            let (line, words) = lines.next().ok_or_else(|| parse_error("The file has fewer faces than its header says."))?;
+            let error = |message: &str| parse_error(format!("Line {line}: {message}"));
+
+            let length = words[0].parse::<usize>().map_err(|_| error("Invalid face vertex count."))?;
+
+            if length < 3 {
+                return Err(error("A face has fewer than 3 vertices."));
+            }
+
+            let vertices = words.get(1..=length).ok_or_else(|| error("A face has fewer vertices than its count."))?
+                .iter()
+                .map(|word| word.parse::<u32>().ok().filter(|&index| (index as usize) < vertex_count))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| error("A face vertex index is invalid."))?;
+
+            // A single value after the vertices is an index into a color map, which isn't supported.
+            let color = match &words[length + 1..] {
+                [] | [_] => None,
+                color => Some(parse_color(color).ok_or_else(|| error("Invalid face color."))?)
+            };
+
+            off.faces.push(Face { vertices, color });
+        }
+
+        Ok(off)
+    }
+
+    /// Gets the header keyword for the vertex properties.
+    pub fn keyword(&self) -> String {
+        let mut keyword = String::new();
+
+        if self.tex_coords.is_some() {
+            keyword += "ST";
+        }
+
+        if self.colors.is_some() {
+            keyword += "C";
+        }
+
+        if self.normals.is_some() {
+            keyword += "N";
+        }
+
+        keyword + "OFF"
+    }
+
+    pub fn write(&self) -> String {
+        let mut text = format!("{}\n{} {} 0\n", self.keyword(), self.positions.len(), self.faces.len());
+
+        for (i, position) in self.positions.iter().enumerate() {
+            let mut values = vec![position.x, position.y, position.z];
+
+            if let Some(normal) = self.normals.as_ref().and_then(|normals| normals.get(i)) {
+                values.extend([normal.x, normal.y, normal.z]);
+            }
+
+            if let Some(color) = self.colors.as_ref().and_then(|colors| colors.get(i)) {
+                values.extend([color.x, color.y, color.z, color.w]);
+            }
+
+            if let Some(tex_coord) = self.tex_coords.as_ref().and_then(|tex_coords| tex_coords.get(i)) {
+                values.extend([tex_coord.x, tex_coord.y]);
+            }
+
+            text += &values.iter().map(f32::to_string).collect::<Vec<_>>().join(" ");
+            text += "\n";
+        }
+
+        for face in &self.faces {
+            text += &face.vertices.len().to_string();
+
+            for vertex in &face.vertices {
+                text += &format!(" {vertex}");
+            }
+
+            if let Some(color) = face.color {
+                text += &format!(" {} {} {} {}", color.x, color.y, color.z, color.w);
+            }
+
+            text += "\n";
+        }
+
+        text
+    }
+}
+
+/// Parses a color of three or four components. Colors are integers from 0 to 255 if they're
+/// written without decimal points and any component is above one, otherwise they're from 0 to 1.
+fn parse_color(words: &[&str]) -> Option<Vec4> {
+    let values = words.iter().map(|word| word.parse::<f32>().ok()).collect::<Option<Vec<_>>>()?;
+
+    let integers = words.iter().all(|word| !word.contains(['.', 'e', 'E'])) && values.iter().any(|&value| value > 1.0);
+    let scale = if integers { 1.0 / 255.0 } else { 1.0 };
+
+    match values[..] {
+        [r, g, b] => Some(Vec4::new(r * scale, g * scale, b * scale, 1.0)),
+        [r, g, b, a] => Some(Vec4::new(r * scale, g * scale, b * scale, a * scale)),
+        _ => None
+    }
+}
+
+impl Exporter for Off {
+    fn export(&self, path: &str) -> Result<(), ExportError> {
+        std::fs::write(path, self.write())?;
+
+        Ok(())
+    }
+
+    /// Creates an OFF mesh from the scene, with a triangle for each face.
+    ///
+    /// OFF files contain a single mesh, so every mesh in the scene is merged into one, without
+    /// any node transforms applied.
+    fn from_scene(scene: &crate::Scene) -> Self {
+        let all_vertices = || scene.meshes.iter().flat_map(|mesh| &mesh.vertices);
+
+        let has_normals = scene.meshes.iter().any(|mesh| mesh.has_normals);
+        let has_tex_coords = all_vertices().any(|v| v.tex_coord.x != 0.0 || v.tex_coord.y != 0.0);
+        let has_colors = all_vertices().any(|v| v.color != Vec4::new(1.0, 1.0, 1.0, 1.0));
+
+        let mut faces = Vec::new();
+        let mut offset = 0;
+
+        for mesh in &scene.meshes {
+            let indices = mesh.indices.clone().unwrap_or_else(|| (0..mesh.vertices.len() as u32).collect());
+
+            faces.extend(indices.chunks_exact(3).map(|triangle| Face {
+                vertices: triangle.iter().map(|index| index + offset).collect(),
+                color: None
+            }));
+
+            offset += mesh.vertices.len() as u32;
+        }
+
+        Self {
+            positions: all_vertices().map(|v| v.position).collect(),
+            normals: has_normals.then(|| all_vertices().map(|v| v.normal).collect()),
+            colors: has_colors.then(|| all_vertices().map(|v| v.color).collect()),
+            tex_coords: has_tex_coords.then(|| all_vertices().map(|v| v.tex_coord).collect()),
+            faces
+        }
+    }
+}
+
+impl Importer for Off {
+    fn import_bytes(data: &[u8], _resolver: &dyn ResourceResolver) -> Result<Self, ImportError> where Self : Sized {
+        let text = std::str::from_utf8(data).map_err(|_| parse_error("The file contains invalid UTF-8."))?;
+
+        Self::parse(text)
+    }
+
+    /// Creates a scene with a single mesh. Polygons are triangulated as a fan, and vertices are
+    /// split where faces with their own colors meet.
+    fn to_scene(&self, _resolver: &dyn ResourceResolver) -> Result<crate::Scene, ImportError> {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        let mut cache = HashMap::new();
+
+        for face in &self.faces {
+            let corners = face.vertices.iter().map(|&index| {
+                let key = (index, face.color.map(|color| [color.x, color.y, color.z, color.w].map(f32::to_bits)));
+
+                *cache.entry(key).or_insert_with(|| {
+                    let i = index as usize;
+
+                    vertices.push(Vertex {
+                        position: self.positions[i],
+                        tex_coord: self.tex_coords.as_ref().map_or(Vec2::new(0.0, 0.0), |tex_coords| tex_coords[i]),
+                        color: face.color.or_else(|| self.colors.as_ref().map(|colors| colors[i])).unwrap_or(Vec4::new(1.0, 1.0, 1.0, 1.0)),
+                        normal: self.normals.as_ref().map_or(Vec3::new(0.0, 0.0, 0.0), |normals| normals[i]),
+                        tangent: Vec4::new(0.0, 0.0, 0.0, 0.0)
+                    });
+
+                    vertices.len() as u32 - 1
+                })
+            }).collect::<Vec<_>>();
+
+            for i in 2..corners.len() {
+                indices.extend_from_slice(&[corners[0], corners[i - 1], corners[i]]);
+            }
+        }
+
+        Ok(crate::Scene {
+            meshes: vec![crate::Mesh {
+                bounds: BoundingBox::from_vertices(&vertices),
+                vertices,
+                indices: Some(indices),
+                material: None,
+                has_normals: self.normals.is_some(),
+                name: None,
+                extras: None
+            }],
+            materials: None,
+            images: None,
+            nodes: Vec::new(),
+            root_nodes: Vec::new()
+        })
+    }
+}
+
+fn parse_error<T: ToString>(message: T) -> ImportError {
+    ImportError::new(ImportErrorType::StringParseError, message)
+}
@@ -0,0 +1,621 @@
+use crate::{resolver::ResourceResolver, Importer, Exporter, ImportError, ImportErrorType, ExportError, Vec2, Vec3, Vec4, Vertex, BoundingBox};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian
+}
+
+impl Format {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Format::Ascii => "ascii",
+            Format::BinaryLittleEndian => "binary_little_endian",
+            Format::BinaryBigEndian => "binary_big_endian"
+        }
+    }
+}
+
+/// The type of a property, or of the count or items of a list property.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScalarType {
+    Char,
+    UChar,
+    Short,
+    UShort,
+    Int,
+    UInt,
+    Float,
+    Double
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "char" | "int8" => Some(ScalarType::Char),
+            "uchar" | "uint8" => Some(ScalarType::UChar),
+            "short" | "int16" => Some(ScalarType::Short),
+            "ushort" | "uint16" => Some(ScalarType::UShort),
+            "int" | "int32" => Some(ScalarType::Int),
+            "uint" | "uint32" => Some(ScalarType::UInt),
+            "float" | "float32" => Some(ScalarType::Float),
+            "double" | "float64" => Some(ScalarType::Double),
+            _ => None
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        match self {
+            ScalarType::Char | ScalarType::UChar => 1,
+            ScalarType::Short | ScalarType::UShort => 2,
+            ScalarType::Int | ScalarType::UInt | ScalarType::Float => 4,
+            ScalarType::Double => 8
+        }
+    }
+
+    /// The largest value of an unsigned integer type, used to normalize colors. Float colors are
+    /// already normalized.
+    fn max_value(&self) -> f64 {
+        match self {
+            ScalarType::Char => i8::MAX as f64,
+            ScalarType::UChar => u8::MAX as f64,
+            ScalarType::Short => i16::MAX as f64,
+            ScalarType::UShort => u16::MAX as f64,
+            ScalarType::Int => i32::MAX as f64,
+            ScalarType::UInt => u32::MAX as f64,
+            ScalarType::Float | ScalarType::Double => 1.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyType {
+    Scalar(ScalarType),
+    List { count: ScalarType, item: ScalarType }
+}
+
+#[derive(Debug)]
+pub struct Property {
+    pub name:          String,
+    pub property_type: PropertyType
+}
+
+/// An element declared in the header. Only the `vertex` and `face` elements are read, every other
+/// element is skipped over.
+#[derive(Debug)]
+pub struct Element {
+    pub name:       String,
+    pub count:      usize,
+    pub properties: Vec<Property>
+}
+
+/// A Stanford PLY file.
+///
+/// Files without a `face` element, such as point clouds, have no indices.
+#[derive(Debug)]
+pub struct Ply {
+    pub format:     Format,
+    pub comments:   Vec<String>,
+
+    pub vertices:   Vec<Vec3>,
+    pub normals:    Option<Vec<Vec3>>,
+    pub tex_coords: Option<Vec<Vec2>>,
+    pub colors:     Option<Vec<Vec4>>,
+
+    /// Faces are triangulated on import, so every 3 indices form a triangle.
+    pub indices:    Vec<u32>
+}
+
+impl Ply {
+    /// Parses the contents of a PLY file, in any of the three formats.
+    pub fn parse(data: &[u8]) -> Result<Self, ImportError> {
+        let Header { format, comments, elements, body } = read_header(data)?;
+
+        let mut reader = match format {
+            Format::Ascii => {
+                let text = std::str::from_utf8(body).map_err(|_| parse_error("The file contains invalid UTF-8."))?;
+                ValueReader::Ascii(text.split_ascii_whitespace())
+            },
+            Format::BinaryLittleEndian => ValueReader::Binary { data: body, position: 0, big_endian: false },
+            Format::BinaryBigEndian => ValueReader::Binary { data: body, position: 0, big_endian: true }
+        };
+
+        let mut vertices = Vec::new();
+        let mut normals = Vec::new();
+        let mut tex_coords = Vec::new();
+        let mut colors = Vec::new();
+        let mut indices = Vec::new();
+
+        let mut polygon = Vec::new();
+
+        for element in &elements {
+            match element.name.as_str() {
+                "vertex" => {
+                    // Find where each attribute is stored, so we don't have to match names for every vertex.
+                    let find = |names: &[&str]| {
+                        element.properties.iter().position(|property| names.contains(&property.name.as_str()))
+                    };
+
+                    let position = [find(&["x"]), find(&["y"]), find(&["z"])];
+                    let normal = [find(&["nx"]), find(&["ny"]), find(&["nz"])];
+                    let tex_coord = [find(&["s", "u", "texture_u", "texture_s"]), find(&["t", "v", "texture_v", "texture_t"])];
+                    let color = [find(&["red", "r", "diffuse_red"]), find(&["green", "g", "diffuse_green"]), find(&["blue", "b", "diffuse_blue"])];
+                    let alpha = find(&["alpha", "a"]);
+
+                    let has_normals = normal.iter().all(Option::is_some);
+                    let has_tex_coords = tex_coord.iter().all(Option::is_some);
+                    let has_colors = color.iter().all(Option::is_some);
+
+                    // Colors are normalized based on the type they are stored as.
+                    let scales = element.properties
+                        .iter()
+                        .enumerate()
+                        .map(|(i, property)| match property.property_type {
+                            PropertyType::Scalar(scalar_type) if color.contains(&Some(i)) || alpha == Some(i) => scalar_type.max_value(),
+                            _ => 1.0
+                        })
+                        .collect::<Vec<_>>();
+
+                    let mut values = vec![0.0; element.properties.len()];
+
+                    for _ in 0..element.count {
+                        for (i, property) in element.properties.iter().enumerate() {
+                            match property.property_type {
+                                PropertyType::Scalar(scalar_type) => {
+                                    values[i] = reader.read(scalar_type)? / scales[i];
+                                },
+
+                                // Vertex list properties aren't used for anything.
+                                PropertyType::List { count, item } => {
+                                    let count = reader.read(count)? as usize;
+
+                                    for _ in 0..count {
+                                        reader.read(item)?;
+                                    }
+                                }
+                            }
+                        }
+
+                        let get = |index: Option<usize>| index.map_or(0.0, |index| values[index] as f32);
+
+                        vertices.push(Vec3::new(get(position[0]), get(position[1]), get(position[2])));
+
+                        if has_normals {
+                            normals.push(Vec3::new(get(normal[0]), get(normal[1]), get(normal[2])));
+                        }
+
+                        if has_tex_coords {
+                            // PLY texture coordinates start at the bottom left, however modelo's start at the top left.
+                            tex_coords.push(Vec2::new(get(tex_coord[0]), 1.0 - get(tex_coord[1])));
+                        }
+
+                        if has_colors {
+                            let alpha = if alpha.is_some() { get(alpha) } else { 1.0 };
+                            colors.push(Vec4::new(get(color[0]), get(color[1]), get(color[2]), alpha));
+                        }
+                    }
+                },
+
+                "face" => {
+                    for _ in 0..element.count {
+                        for property in &element.properties {
+                            match property.property_type {
+                                PropertyType::List { count, item } => {
+                                    let count = reader.read(count)? as usize;
+                                    let is_indices = property.name == "vertex_indices" || property.name == "vertex_index";
+
+                                    polygon.clear();
+
+                                    for _ in 0..count {
+                                        let value = reader.read(item)?;
+
+                                        if is_indices {
+                                            if value < 0.0 || value as usize >= vertices.len() {
+                                                return Err(parse_error(format!("Face index {value} is out of range.")));
+                                            }
+
+                                            polygon.push(value as u32);
+                                        }
+                                    }
+
+                                    // Triangulate polygons as a fan.
+                                    for i in 2..polygon.len() {
+                                        indices.extend_from_slice(&[polygon[0], polygon[i - 1], polygon[i]]);
+                                    }
+                                },
+
+                                PropertyType::Scalar(scalar_type) => {
+                                    reader.read(scalar_type)?;
+                                }
+                            }
+                        }
+                    }
+                },
+
+                _ => reader.skip(element)?
+            }
+        }
+
+        Ok(Self {
+            format,
+            comments,
+            vertices,
+            normals: if normals.is_empty() { None } else { Some(normals) },
+            tex_coords: if tex_coords.is_empty() { None } else { Some(tex_coords) },
+            colors: if colors.is_empty() { None } else { Some(colors) },
+            indices
+        })
+    }
+
+    /// Writes the file in its format. Colors are stored as `uchar`, and indices as `uint`.
+    pub fn write(&self) -> Vec<u8> {
+        let mut header = String::from("ply\n");
+        header.push_str(&format!("format {} 1.0\n", self.format.as_str()));
+
+        for comment in &self.comments {
+            header.push_str(&format!("comment {comment}\n"));
+        }
+
+        header.push_str(&format!("element vertex {}\n", self.vertices.len()));
+        header.push_str("property float x\nproperty float y\nproperty float z\n");
+
+        if self.normals.is_some() {
+            header.push_str("property float nx\nproperty float ny\nproperty float nz\n");
+        }
+
+        if self.tex_coords.is_some() {
+            header.push_str("property float s\nproperty float t\n");
+        }
+
+        if self.colors.is_some() {
+            header.push_str("property uchar red\nproperty uchar green\nproperty uchar blue\nproperty uchar alpha\n");
+        }
+
+        if !self.indices.is_empty() {
+            header.push_str(&format!("element face {}\n", self.indices.len() / 3));
+            header.push_str("property list uchar uint vertex_indices\n");
+        }
+
+        header.push_str("end_header\n");
+
+        let mut writer = ValueWriter { data: header.into_bytes(), format: self.format, first: true };
+
+        for (i, vertex) in self.vertices.iter().enumerate() {
+            writer.floats(&[vertex.x, vertex.y, vertex.z]);
+
+            if let Some(normal) = self.normals.as_ref().and_then(|normals| normals.get(i)) {
+                writer.floats(&[normal.x, normal.y, normal.z]);
+            }
+
+            if let Some(tex_coord) = self.tex_coords.as_ref().and_then(|tex_coords| tex_coords.get(i)) {
+                // Flip back to PLY's bottom left origin.
+                writer.floats(&[tex_coord.x, 1.0 - tex_coord.y]);
+            }
+
+            if let Some(color) = self.colors.as_ref().and_then(|colors| colors.get(i)) {
+                for value in [color.x, color.y, color.z, color.w] {
+                    writer.uchar((value.clamp(0.0, 1.0) * 255.0).round() as u8);
+                }
+            }
+
+            writer.end_line();
+        }
+
+        for triangle in self.indices.chunks_exact(3) {
+            writer.uchar(3);
+
+            for &index in triangle {
+                writer.uint(index);
+            }
+
+            writer.end_line();
+        }
+
+        writer.data
+    }
+}
+
+impl Exporter for Ply {
+    fn export(&self, path: &str) -> Result<(), ExportError> {
+        std::fs::write(path, self.write())?;
+
+        Ok(())
+    }
+
+    /// Creates a binary little-endian PLY from the scene.
+    ///
+    /// PLY files contain a single mesh, so every mesh in the scene is merged into one, without
+    /// any node transforms applied.
+    fn from_scene(scene: &crate::Scene) -> Self {
+        let all_vertices = || scene.meshes.iter().flat_map(|mesh| &mesh.vertices);
+
+        let has_normals = scene.meshes.iter().any(|mesh| mesh.has_normals);
+        let has_tex_coords = all_vertices().any(|v| v.tex_coord.x != 0.0 || v.tex_coord.y != 0.0);
+        let has_colors = all_vertices().any(|v| v.color != Vec4::new(1.0, 1.0, 1.0, 1.0));
+
+        let mut indices = Vec::new();
+
+        for (offset, mesh) in scene.meshes.iter().scan(0, |offset, mesh| {
+            let start = *offset;
+            *offset += mesh.vertices.len() as u32;
+            Some((start, mesh))
+        }) {
+            match &mesh.indices {
+                Some(mesh_indices) => indices.extend(mesh_indices.iter().map(|index| index + offset)),
+                None => indices.extend(offset..offset + mesh.vertices.len() as u32)
+            }
+        }
+
+        Self {
+            format: Format::BinaryLittleEndian,
+            comments: vec![String::from("Created by modelo")],
+            vertices: all_vertices().map(|v| v.position).collect(),
+            normals: has_normals.then(|| all_vertices().map(|v| v.normal).collect()),
+            tex_coords: has_tex_coords.then(|| all_vertices().map(|v| v.tex_coord).collect()),
+            colors: has_colors.then(|| all_vertices().map(|v| v.color).collect()),
+            indices
+        }
+    }
+}
+
+impl Importer for Ply {
+    fn import_bytes(data: &[u8], _resolver: &dyn ResourceResolver) -> Result<Self, ImportError> where Self : Sized {
+        Self::parse(data)
+    }
+
+    fn to_scene(&self, _resolver: &dyn ResourceResolver) -> Result<crate::Scene, ImportError> {
+        let vertices = self.vertices
+            .iter()
+            .enumerate()
+            .map(|(i, &position)| Vertex {
+                position,
+                tex_coord: self.tex_coords.as_ref().map_or(Vec2::new(0.0, 0.0), |tex_coords| tex_coords[i]),
+                color: self.colors.as_ref().map_or(Vec4::new(1.0, 1.0, 1.0, 1.0), |colors| colors[i]),
+                normal: self.normals.as_ref().map_or(Vec3::new(0.0, 0.0, 0.0), |normals| normals[i]),
+                tangent: Vec4::new(0.0, 0.0, 0.0, 0.0)
+            })
+            .collect::<Vec<_>>();
+
+        let bounds = BoundingBox::from_vertices(&vertices);
+
+        Ok(crate::Scene {
+            meshes: vec![crate::Mesh {
+                vertices,
+                indices: (!self.indices.is_empty()).then(|| self.indices.clone()),
+                material: None,
+                has_normals: self.normals.is_some(),
+                bounds,
+                name: None,
+                extras: None
+            }],
+            materials: None,
+            images: None,
+            nodes: Vec::new(),
+            root_nodes: Vec::new()
+        })
+    }
+}
+
+fn parse_error<T: ToString>(message: T) -> ImportError {
+    ImportError::new(ImportErrorType::StringParseError, message)
+}
+
+struct Header<'a> {
+    format:   Format,
+    comments: Vec<String>,
+    elements: Vec<Element>,
+
+    /// The element data after the header.
+    body:     &'a [u8]
+}
+
+fn read_header(data: &[u8]) -> Result<Header<'_>, ImportError> {
+    const END_HEADER: &[u8] = b"end_header";
+
+    if !data.starts_with(b"ply") {
+        return Err(parse_error("The file is not a PLY file."));
+    }
+
+    let header_end = data
+        .windows(END_HEADER.len())
+        .position(|window| window == END_HEADER)
+        .ok_or_else(|| parse_error("The header has no end_header."))?;
+
+    // The data starts after the line ending following end_header, which may be \r\n.
+    let mut body_start = header_end + END_HEADER.len();
+
+    while body_start < data.len() && data[body_start] != b'\n' {
+        body_start += 1;
+    }
+
+    let header = std::str::from_utf8(&data[..header_end]).map_err(|_| parse_error("The header contains invalid UTF-8."))?;
+
+    let mut format = None;
+    let mut comments = Vec::new();
+    let mut elements: Vec<Element> = Vec::new();
+
+    for (line_number, line) in header.lines().enumerate().skip(1) {
+        let error = |message: &str| parse_error(format!("Line {}: {message}", line_number + 1));
+
+        let mut words = line.split_whitespace();
+
+        match words.next() {
+            Some("format") => {
+                format = Some(match words.next() {
+                    Some("ascii") => Format::Ascii,
+                    Some("binary_little_endian") => Format::BinaryLittleEndian,
+                    Some("binary_big_endian") => Format::BinaryBigEndian,
+                    _ => return Err(error("Unknown format."))
+                });
+            },
+
+            Some("comment") => comments.push(line.trim_start()["comment".len()..].trim().to_string()),
+
+            Some("element") => {
+                let (Some(name), Some(count)) = (words.next(), words.next()) else {
+                    return Err(error("Elements must have a name and a count."));
+                };
+
+                elements.push(Element {
+                    name: name.to_string(),
+                    count: count.parse().map_err(|_| error("Invalid element count."))?,
+                    properties: Vec::new()
+                });
+            },
+
+            Some("property") => {
+                let scalar = |name: Option<&str>| name.and_then(ScalarType::parse).ok_or_else(|| error("Unknown property type."));
+
+                let property_type = match words.next() {
+                    Some("list") => PropertyType::List { count: scalar(words.next())?, item: scalar(words.next())? },
+                    name => PropertyType::Scalar(scalar(name)?)
+                };
+
+                let Some(name) = words.next() else {
+                    return Err(error("Properties must have a name."));
+                };
+
+                let Some(element) = elements.last_mut() else {
+                    return Err(error("Properties must come after an element."));
+                };
+
+                element.properties.push(Property { name: name.to_string(), property_type });
+            },
+
+            // obj_info is an unofficial extension, used for file-wide information.
+            Some("obj_info") | None => {},
+
+            Some(_) => return Err(error("Unknown header statement."))
+        }
+    }
+
+    let Some(format) = format else {
+        return Err(parse_error("The header has no format."));
+    };
+
+    Ok(Header {
+        format,
+        comments,
+        elements,
+        body: &data[(body_start + 1).min(data.len())..]
+    })
+}
+
+enum ValueReader<'a> {
+    Ascii(std::str::SplitAsciiWhitespace<'a>),
+    Binary { data: &'a [u8], position: usize, big_endian: bool }
+}
+
+impl ValueReader<'_> {
+    fn read(&mut self, scalar_type: ScalarType) -> Result<f64, ImportError> {
+        match self {
+            ValueReader::Ascii(words) => {
+                let word = words.next().ok_or_else(|| parse_error("Unexpected end of file."))?;
+                word.parse::<f64>().map_err(|_| parse_error(format!("Invalid value \"{word}\".")))
+            },
+
+            ValueReader::Binary { data, position, big_endian } => {
+                let size = scalar_type.size();
+
+                let Some(bytes) = data.get(*position..*position + size) else {
+                    return Err(parse_error("Unexpected end of file."));
+                };
+
+                *position += size;
+
+                // Read into a fixed size buffer, so the conversions below can use from_le_bytes.
+                let mut buffer = [0u8; 8];
+                buffer[..size].copy_from_slice(bytes);
+
+                if *big_endian {
+                    buffer[..size].reverse();
+                }
+
+                Ok(match scalar_type {
+                    ScalarType::Char => buffer[0] as i8 as f64,
+                    ScalarType::UChar => buffer[0] as f64,
+                    ScalarType::Short => i16::from_le_bytes([buffer[0], buffer[1]]) as f64,
+                    ScalarType::UShort => u16::from_le_bytes([buffer[0], buffer[1]]) as f64,
+                    ScalarType::Int => i32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as f64,
+                    ScalarType::UInt => u32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as f64,
+                    ScalarType::Float => f32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as f64,
+                    ScalarType::Double => f64::from_le_bytes(buffer)
+                })
+            }
+        }
+    }
+
+    fn skip(&mut self, element: &Element) -> Result<(), ImportError> {
+        for _ in 0..element.count {
+            for property in &element.properties {
+                match property.property_type {
+                    PropertyType::Scalar(scalar_type) => {
+                        self.read(scalar_type)?;
+                    },
+
+                    PropertyType::List { count, item } => {
+                        let count = self.read(count)? as usize;
+
+                        for _ in 0..count {
+                            self.read(item)?;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+struct ValueWriter {
+    data:   Vec<u8>,
+    format: Format,
+
+    /// Whether the next ASCII value is the first on its line, and so shouldn't have a space before it.
+    first:  bool
+}
+
+impl ValueWriter {
+    fn ascii(&mut self, value: impl std::fmt::Display) {
+        if !self.first {
+            self.data.push(b' ');
+        }
+
+        self.data.extend_from_slice(value.to_string().as_bytes());
+        self.first = false;
+    }
+
+    fn floats(&mut self, values: &[f32]) {
+        for &value in values {
+            match self.format {
+                Format::Ascii => self.ascii(value),
+                Format::BinaryLittleEndian => self.data.extend_from_slice(&value.to_le_bytes()),
+                Format::BinaryBigEndian => self.data.extend_from_slice(&value.to_be_bytes())
+            }
+        }
+    }
+
+    fn uchar(&mut self, value: u8) {
+        match self.format {
+            Format::Ascii => self.ascii(value),
+            _ => self.data.push(value)
+        }
+    }
+
+    fn uint(&mut self, value: u32) {
+        match self.format {
+            Format::Ascii => self.ascii(value),
+            Format::BinaryLittleEndian => self.data.extend_from_slice(&value.to_le_bytes()),
+            Format::BinaryBigEndian => self.data.extend_from_slice(&value.to_be_bytes())
+        }
+    }
+
+    fn end_line(&mut self) {
+        if self.format == Format::Ascii {
+            self.data.push(b'\n');
+            self.first = true;
+        }
+    }
+}
@@ -0,0 +1,388 @@
+use std::collections::HashMap;
+
+use crate::{BoundingBox, Mesh, NormalMode, Vec3, Vec4, Vertex, WeldOptions};
+
+/// Generates normals for the mesh, replacing any it has. Vertices are split where the faces sharing
+/// them need different normals.
+pub(crate) fn generate_normals(mesh: &mut Mesh, mode: NormalMode) {
+    smooth_normals(mesh, mode, None);
+}
+
+/// Generates normals like [`generate_normals`], but a corner is only smoothed with the faces that
+/// `smooths` accepts, given the index of its own triangle and the other's. This is for formats
+/// that say which faces are smoothed together, such as with smoothing groups.
+pub(crate) fn generate_normals_where(mesh: &mut Mesh, mode: NormalMode, smooths: impl Fn(usize, usize) -> bool) {
+    smooth_normals(mesh, mode, Some(&smooths));
+}
+
+fn smooth_normals(mesh: &mut Mesh, mode: NormalMode, smooths: Option<&dyn Fn(usize, usize) -> bool>) {
+    let mut indices = match &mesh.indices {
+        Some(indices) => indices.clone(),
+        None => (0..mesh.vertices.len() as u32).collect()
+    };
+
+    // The cross product of two edges has a length of twice the face's area.
+    let faces = indices.chunks_exact(3).map(|triangle| {
+        let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[triangle[i] as usize].position);
+        (b - a).cross(&(c - a))
+    }).collect::<Vec<_>>();
+
+    let corner_angle = |corner: usize| {
+        let triangle = corner / 3 * 3;
+        let position = |i: usize| mesh.vertices[indices[triangle + i % 3] as usize].position;
+        let here = position(corner);
+
+        let to_next = normalized(position(corner + 1) - here);
+        let to_previous = normalized(position(corner + 2) - here);
+        to_next.dot(&to_previous).clamp(-1.0, 1.0).acos()
+    };
+
+    let mut normals = vec![Vec3::new(0.0, 0.0, 0.0); faces.len() * 3];
+
+    if mode == NormalMode::Flat {
+        for (corner, normal) in normals.iter_mut().enumerate() {
+            *normal = normalized(faces[corner / 3]);
+        }
+    } else {
+        // Corners are smoothed with every other corner at the same position, rather than only those
+        // sharing a vertex, so seams in texture coordinates don't show. Adding zero turns -0.0
+        // into 0.0, so both are the same position.
+        let mut positions: HashMap<[u32; 3], Vec<usize>> = HashMap::new();
+
+        for (corner, &index) in indices.iter().enumerate().take(normals.len()) {
+            let position = mesh.vertices[index as usize].position;
+            positions.entry([position.x + 0.0, position.y + 0.0, position.z + 0.0].map(f32::to_bits)).or_default().push(corner);
+        }
+
+        let weighted = |corner: usize| match mode {
+            NormalMode::AreaWeighted => faces[corner / 3],
+            _ => normalized(faces[corner / 3]) * corner_angle(corner)
+        };
+
+        let threshold = match mode {
+            NormalMode::CreaseAngle(angle) => Some(angle.cos()),
+            _ => None
+        };
+
+        for corners in positions.values() {
+            if threshold.is_some() || smooths.is_some() {
+                for &corner in corners {
+                    let face = normalized(faces[corner / 3]);
+                    let mut normal = Vec3::new(0.0, 0.0, 0.0);
+
+                    for &other in corners {
+                        let within_crease = threshold.is_none_or(|threshold| face.dot(&normalized(faces[other / 3])) >= threshold);
+
+                        if other == corner || (within_crease && smooths.is_none_or(|smooths| smooths(corner / 3, other / 3))) {
+                            normal += weighted(other);
+                        }
+                    }
+
+                    normals[corner] = normalized(normal);
+                }
+            } else {
+                let mut normal = Vec3::new(0.0, 0.0, 0.0);
+
+                for &corner in corners {
+                    normal += weighted(corner);
+                }
+
+                let normal = normalized(normal);
+
+                for &corner in corners {
+                    normals[corner] = normal;
+                }
+            }
+        }
+    }
+
+    // The first normal given to a vertex replaces its existing one, and it's copied for any
+    // different normals. As with positions, -0.0 and 0.0 are treated as the same.
+    let mut assigned = vec![false; mesh.vertices.len()];
+    let mut copies = HashMap::new();
+
+    for (corner, normal) in normals.into_iter().enumerate() {
+        let index = indices[corner] as usize;
+
+        if !assigned[index] {
+            assigned[index] = true;
+            mesh.vertices[index].normal = normal;
+        } else if mesh.vertices[index].normal != normal {
+            indices[corner] = *copies.entry((index, [normal.x + 0.0, normal.y + 0.0, normal.z + 0.0].map(f32::to_bits))).or_insert_with(|| {
+                let mut vertex = mesh.vertices[index];
+                vertex.normal = normal;
+                mesh.vertices.push(vertex);
+                (mesh.vertices.len() - 1) as u32
+            });
+        }
+    }
+
+    if mesh.indices.is_some() {
+        mesh.indices = Some(indices);
+    }
+
+    mesh.has_normals = true;
+}
+
+/// Merges vertices within the tolerances of each other into the first of them, returning how many
+/// were merged away.
+///
+/// Without a normal tolerance, merging vertices with different normals, such as across a hard edge,
+/// leaves the kept normal wrong for some faces, so the mesh is marked as not having normals for them
+/// to be generated again. A vertex without a normal takes the normal of a vertex merged into it.
+pub(crate) fn weld_vertices(mesh: &mut Mesh, options: &WeldOptions) -> usize {
+    let epsilon = options.position_epsilon.max(0.0);
+
+    // Positions are hashed into cells at least as large as the epsilon, so any vertex close enough
+    // to merge is in the same cell or a neighbouring one.
+    let cell_size = epsilon.max(f32::EPSILON);
+    let cell = |position: Vec3| [position.x, position.y, position.z].map(|value| (value / cell_size).floor() as i64);
+
+    let mut cells: HashMap<[i64; 3], Vec<u32>> = HashMap::new();
+    let mut vertices = Vec::new();
+    let mut remap = Vec::with_capacity(mesh.vertices.len());
+    let mut normals_differ = false;
+
+    for vertex in &mesh.vertices {
+        let [x, y, z] = cell(vertex.position);
+
+        // Infinite and huge positions are in the outermost cells, so the neighbours saturate.
+        let around = |value: i64| value.saturating_sub(1)..=value.saturating_add(1);
+
+        let existing = around(x)
+            .flat_map(|x| around(y).flat_map(move |y| around(z).map(move |z| [x, y, z])))
+            .filter_map(|neighbour| cells.get(&neighbour))
+            .flatten()
+            .copied()
+            .filter(|&index| can_weld(&vertices[index as usize], vertex, epsilon, options))
+            .min();
+
+        let index = match existing {
+            Some(index) => {
+                let kept = &mut vertices[index as usize];
+
+                if options.normal_tolerance.is_none() && kept.normal != vertex.normal {
+                    if kept.normal.magnitude_squared() == 0.0 {
+                        kept.normal = vertex.normal;
+                    } else if vertex.normal.magnitude_squared() != 0.0 {
+                        normals_differ = true;
+                    }
+                }
+
+                // A tangent for only some of the merged vertices would be wrong for the rest.
+                if options.tangent_tolerance.is_none() && kept.tangent != vertex.tangent {
+                    kept.tangent = Vec4::new(0.0, 0.0, 0.0, 0.0);
+                }
+
+                index
+            },
+
+            None => {
+                let index = vertices.len() as u32;
+                vertices.push(*vertex);
+                cells.entry([x, y, z]).or_default().push(index);
+                index
+            }
+        };
+
+        remap.push(index);
+    }
+
+    let merged = mesh.vertices.len() - vertices.len();
+
+    mesh.indices = Some(match &mesh.indices {
+        Some(indices) => indices.iter().map(|&index| remap[index as usize]).collect(),
+        None => remap
+    });
+
+    mesh.vertices = vertices;
+    mesh.bounds = BoundingBox::from_vertices(&mesh.vertices);
+
+    if normals_differ {
+        mesh.has_normals = false;
+    }
+
+    merged
+}
+
+fn can_weld(a: &Vertex, b: &Vertex, epsilon: f32, options: &WeldOptions) -> bool {
+    let within = |tolerance: Option<f32>, a: &[f32], b: &[f32]| {
+        tolerance.is_none_or(|tolerance| a.iter().zip(b).all(|(a, b)| (a - b).abs() <= tolerance))
+    };
+
+    (a.position - b.position).magnitude() <= epsilon
+        && within(options.tex_coord_tolerance, &[a.tex_coord.x, a.tex_coord.y], &[b.tex_coord.x, b.tex_coord.y])
+        && within(options.color_tolerance, &[a.color.x, a.color.y, a.color.z, a.color.w], &[b.color.x, b.color.y, b.color.z, b.color.w])
+        && within(options.normal_tolerance, &[a.normal.x, a.normal.y, a.normal.z], &[b.normal.x, b.normal.y, b.normal.z])
+        && within(options.tangent_tolerance, &[a.tangent.x, a.tangent.y, a.tangent.z, a.tangent.w], &[b.tangent.x, b.tangent.y, b.tangent.z, b.tangent.w])
+}
+
+/// Generates tangents for the mesh the same way MikkTSpace does, so that normal maps baked with it
+/// look right. Meshes without normals, or without any face with a texture mapping, are left alone.
+///
+/// Texture coordinates are treated as having their origin at the top left, like glTF, so the
+/// bitangent points towards the top of the texture, which is what tangent space normal maps expect.
+pub(crate) fn generate_tangents(mesh: &mut Mesh) {
+    if !mesh.has_normals {
+        return;
+    }
+
+    let mut indices = match &mesh.indices {
+        Some(indices) => indices.clone(),
+        None => (0..mesh.vertices.len() as u32).collect()
+    };
+
+    let triangle_count = indices.len() / 3;
+    let faces = indices.chunks_exact(3).map(|triangle| face_tangent(mesh, triangle)).collect::<Vec<_>>();
+
+    // Faces without a texture mapping have no direction for the tangent to follow.
+    if faces.iter().all(Option::is_none) {
+        return;
+    }
+
+    // Faces with mirrored texture coordinates have bitangents with the opposite sign, so vertices
+    // shared between mirrored and unmirrored faces are split in two. The first copy of each vertex
+    // is the original, and whether its faces are mirrored is kept alongside it.
+    let mut copies = vec![[None; 2]; mesh.vertices.len()];
+    let mut mirrored = vec![false; mesh.vertices.len()];
+
+    for (triangle, face) in faces.iter().enumerate() {
+        let Some(face) = face else {
+            continue;
+        };
+
+        for index in &mut indices[triangle * 3..triangle * 3 + 3] {
+            let original = *index as usize;
+
+            *index = match copies[original] {
+                [Some(copy), _] | [_, Some(copy)] if mirrored[copy as usize] == face.mirrored => copy,
+                [None, None] => {
+                    mirrored[original] = face.mirrored;
+                    copies[original][0] = Some(*index);
+                    *index
+                },
+                _ => {
+                    let copy = mesh.vertices.len() as u32;
+                    mesh.vertices.push(mesh.vertices[original]);
+                    mirrored.push(face.mirrored);
+                    copies[original][1] = Some(copy);
+                    copy
+                }
+            };
+        }
+    }
+
+    // Degenerate faces don't affect the tangents, so use whichever copy already exists.
+    for (triangle, face) in faces.iter().enumerate() {
+        if face.is_none() {
+            for index in &mut indices[triangle * 3..triangle * 3 + 3] {
+                *index = copies[*index as usize][0].unwrap_or(*index);
+            }
+        }
+    }
+
+    // Each face's tangent is projected onto the plane of the vertex normal, then weighted by the
+    // angle of the face's corner. Like MikkTSpace, corners are averaged with every other corner with
+    // the same position, normal and texture coordinates, rather than only those sharing a vertex, so
+    // meshes with duplicated vertices get the same tangents as indexed ones.
+    let group = |index: usize| {
+        let Vertex { position: p, normal: n, tex_coord: t, .. } = mesh.vertices[index];
+        let bits = [p.x, p.y, p.z, n.x, n.y, n.z, t.x, t.y].map(|value| (value + 0.0).to_bits());
+        (bits, mirrored[index])
+    };
+
+    let mut tangents: HashMap<_, Vec3> = HashMap::new();
+
+    for triangle in 0..triangle_count {
+        let Some(face) = &faces[triangle] else {
+            continue;
+        };
+
+        for corner in 0..3 {
+            let index = indices[triangle * 3 + corner] as usize;
+            let next = indices[triangle * 3 + (corner + 1) % 3] as usize;
+            let previous = indices[triangle * 3 + (corner + 2) % 3] as usize;
+
+            let normal = normalized(mesh.vertices[index].normal);
+            let position = mesh.vertices[index].position;
+
+            let tangent = normalized(project(face.tangent, normal));
+            let to_next = normalized(project(mesh.vertices[next].position - position, normal));
+            let to_previous = normalized(project(mesh.vertices[previous].position - position, normal));
+            let angle = to_next.dot(&to_previous).clamp(-1.0, 1.0).acos();
+
+            *tangents.entry(group(index)).or_insert(Vec3::new(0.0, 0.0, 0.0)) += tangent * angle;
+        }
+    }
+
+    let tangents = (0..mesh.vertices.len())
+        .map(|index| tangents.get(&group(index)).copied().unwrap_or(Vec3::new(0.0, 0.0, 0.0)))
+        .collect::<Vec<_>>();
+
+    for (vertex, (tangent, mirrored)) in mesh.vertices.iter_mut().zip(tangents.into_iter().zip(mirrored)) {
+        let normal = normalized(vertex.normal);
+        let mut tangent = normalized(project(tangent, normal));
+
+        // Vertices that aren't used by any face, or only by degenerate ones, are given any
+        // tangent perpendicular to the normal.
+        if tangent.magnitude_squared() == 0.0 {
+            let axis = if normal.x.abs() < 0.9 { Vec3::new(1.0, 0.0, 0.0) } else { Vec3::new(0.0, 1.0, 0.0) };
+            tangent = normalized(project(axis, normal));
+        }
+
+        vertex.tangent = Vec4::new(tangent.x, tangent.y, tangent.z, if mirrored { -1.0 } else { 1.0 });
+    }
+
+    if mesh.indices.is_some() {
+        mesh.indices = Some(indices);
+    }
+}
+
+struct FaceTangent {
+    tangent:  Vec3,
+
+    /// Whether the texture coordinates are wound the opposite way to the positions.
+    mirrored: bool
+}
+
+/// Works out the direction of increasing U across the triangle, or `None` if its texture
+/// coordinates are degenerate.
+fn face_tangent(mesh: &Mesh, triangle: &[u32]) -> Option<FaceTangent> {
+    let [v0, v1, v2] = [0, 1, 2].map(|i| &mesh.vertices[triangle[i] as usize]);
+
+    let e1 = v1.position - v0.position;
+    let e2 = v2.position - v0.position;
+
+    // V is flipped, so the texture's origin is at the bottom left, as MikkTSpace expects.
+    let (du1, dv1) = (v1.tex_coord.x - v0.tex_coord.x, v0.tex_coord.y - v1.tex_coord.y);
+    let (du2, dv2) = (v2.tex_coord.x - v0.tex_coord.x, v0.tex_coord.y - v2.tex_coord.y);
+
+    let area = du1 * dv2 - du2 * dv1;
+    if area.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let tangent = (e1 * dv2 - e2 * dv1) * (1.0 / area);
+    if tangent.magnitude_squared() == 0.0 {
+        return None;
+    }
+
+    Some(FaceTangent {
+        tangent,
+        mirrored: area < 0.0
+    })
+}
+
+/// Removes the part of the vector along the normal, which must be normalized.
+fn project(vector: Vec3, normal: Vec3) -> Vec3 {
+    vector - normal * normal.dot(&vector)
+}
+
+/// Normalizes the vector, leaving it as zero if it has no length.
+fn normalized(mut vector: Vec3) -> Vec3 {
+    if vector.magnitude_squared() > 0.0 {
+        vector.normalize();
+    }
+
+    vector
+}
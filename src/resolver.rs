@@ -0,0 +1,173 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use crate::{ImportError, ImportErrorType};
+
+/// Loads the external files a model refers to, such as glTF buffers and OBJ material libraries.
+///
+/// URIs are given exactly as they appear in the model, so are usually relative to it.
+pub trait ResourceResolver {
+    fn resolve(&self, uri: &str) -> Result<Vec<u8>, ImportError>;
+}
+
+impl<F: Fn(&str) -> Result<Vec<u8>, ImportError>> ResourceResolver for F {
+    fn resolve(&self, uri: &str) -> Result<Vec<u8>, ImportError> {
+        self(uri)
+    }
+}
+
+/// Resolves files relative to the directory. Unlike [`FileSystemResolver`], URIs aren't limited to
+/// the directory, so models loaded from disk can refer to files next to their parent directory.
+impl ResourceResolver for PathBuf {
+    fn resolve(&self, uri: &str) -> Result<Vec<u8>, ImportError> {
+        Ok(std::fs::read(self.join(uri))?)
+    }
+}
+
+/// Resolves files from the local filesystem, relative to a root directory.
+pub struct FileSystemResolver {
+    pub root: PathBuf
+}
+
+impl FileSystemResolver {
+    pub fn new<T: Into<PathBuf>>(root: T) -> Self {
+        Self {
+            root: root.into()
+        }
+    }
+}
+
+impl ResourceResolver for FileSystemResolver {
+    fn resolve(&self, uri: &str) -> Result<Vec<u8>, ImportError> {
+        Ok(std::fs::read(self.root.join(normalize_path(uri)?))?)
+    }
+}
+
+/// Resolves files from a map of paths to their contents.
+#[derive(Debug, Default)]
+pub struct MemoryResolver {
+    pub files: HashMap<String, Vec<u8>>
+}
+
+impl MemoryResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file. Paths outside the root can never be resolved, so they aren't added.
+    pub fn insert<T: Into<Vec<u8>>>(&mut self, path: &str, data: T) {
+        if let Ok(path) = normalize_path(path) {
+            self.files.insert(path, data.into());
+        }
+    }
+}
+
+impl ResourceResolver for MemoryResolver {
+    fn resolve(&self, uri: &str) -> Result<Vec<u8>, ImportError> {
+        match self.files.get(&normalize_path(uri)?) {
+            Some(data) => Ok(data.clone()),
+            None => Err(not_found(uri))
+        }
+    }
+}
+
+/// Resolves files from inside a zip archive.
+#[cfg(feature = "zip")]
+pub struct ZipResolver<R: std::io::Read + std::io::Seek> {
+    // Reading a file from the archive needs mutable access, but resolvers only get a shared reference.
+    archive: std::sync::Mutex<zip::ZipArchive<R>>
+}
+
+#[cfg(feature = "zip")]
+impl<R: std::io::Read + std::io::Seek> ZipResolver<R> {
+    pub fn new(reader: R) -> Result<Self, ImportError> {
+        let archive = zip::ZipArchive::new(reader).map_err(|err| ImportError::new(ImportErrorType::Other, err))?;
+
+        Ok(Self {
+            archive: std::sync::Mutex::new(archive)
+        })
+    }
+}
+
+#[cfg(feature = "zip")]
+impl ZipResolver<std::fs::File> {
+    pub fn open(path: &str) -> Result<Self, ImportError> {
+        Self::new(std::fs::File::open(path)?)
+    }
+}
+
+#[cfg(feature = "zip")]
+impl<R: std::io::Read + std::io::Seek> ResourceResolver for ZipResolver<R> {
+    fn resolve(&self, uri: &str) -> Result<Vec<u8>, ImportError> {
+        use std::io::Read;
+
+        let mut archive = self.archive.lock().unwrap();
+
+        let mut file = match archive.by_name(&normalize_path(uri)?) {
+            Ok(file) => file,
+            Err(zip::result::ZipError::FileNotFound) => return Err(not_found(uri)),
+            Err(err) => return Err(ImportError::new(ImportErrorType::Other, err))
+        };
+
+        let mut data = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut data)?;
+
+        Ok(data)
+    }
+}
+
+/// Resolves files relative to a directory inside another resolver. This is used to load the
+/// files next to a model that isn't at the root of its resolver.
+pub(crate) struct RelativeResolver<'a> {
+    pub resolver:  &'a dyn ResourceResolver,
+    pub directory: String
+}
+
+impl ResourceResolver for RelativeResolver<'_> {
+    fn resolve(&self, uri: &str) -> Result<Vec<u8>, ImportError> {
+        if self.directory.is_empty() {
+            self.resolver.resolve(uri)
+        } else {
+            self.resolver.resolve(&format!("{}/{uri}", self.directory))
+        }
+    }
+}
+
+fn not_found(uri: &str) -> ImportError {
+    ImportError::new(ImportErrorType::FileNotFound, format!("\"{uri}\" was not found."))
+}
+
+/// Normalizes a relative path, so paths that refer to the same file compare equal. Backslashes
+/// become forward slashes, and `.` and `..` components are removed.
+///
+/// Absolute paths, and paths with more `..` components than directories before them, would
+/// escape the root, so they're a `FileNotFound` error.
+pub fn normalize_path(path: &str) -> Result<String, ImportError> {
+    let escapes = || ImportError::new(ImportErrorType::FileNotFound, format!("\"{path}\" is outside the root."));
+
+    if path.starts_with(['/', '\\']) || path.split(['/', '\\']).next().is_some_and(|first| first.ends_with(':')) {
+        return Err(escapes());
+    }
+
+    let mut components = Vec::new();
+
+    for component in path.split(['/', '\\']) {
+        match component {
+            "" | "." => {},
+            ".." => {
+                components.pop().ok_or_else(escapes)?;
+            },
+            component => components.push(component)
+        }
+    }
+
+    Ok(components.join("/"))
+}
+
+/// A resolver for models that don't refer to any external files, which fails for every URI.
+pub struct NoResolver;
+
+impl ResourceResolver for NoResolver {
+    fn resolve(&self, uri: &str) -> Result<Vec<u8>, ImportError> {
+        Err(ImportError::new(ImportErrorType::FileNotFound, format!("No resolver was given to load \"{uri}\".")))
+    }
+}
@@ -0,0 +1,405 @@
+use std::{collections::HashMap, fmt::Write};
+
+use crate::{resolver::ResourceResolver, Importer, Exporter, ImportError, ImportErrorType, ExportError, Vec2, Vec3, Vec4, Vertex, BoundingBox};
+
+/// The size of a binary STL header, which is followed by the number of facets.
+const HEADER_SIZE: usize = 80;
+
+/// The size of a facet in a binary STL: a normal, 3 vertices, and a 2-byte attribute.
+const FACET_SIZE: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Ascii,
+    Binary
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Facet {
+    pub normal:    Vec3,
+    pub vertices:  [Vec3; 3],
+
+    /// The attribute byte count of a binary facet, which some programs use to store a color.
+    pub attribute: u16
+}
+
+/// A solid, which becomes a single mesh. ASCII files can contain several solids, binary files
+/// contain exactly one.
+#[derive(Debug)]
+pub struct Solid {
+    pub name:   Option<String>,
+    pub facets: Vec<Facet>
+}
+
+/// An STL file.
+///
+/// Scenes keep the flat facet normals, so [`crate::PostProcessFlags::GENERATE_NORMALS`] leaves
+/// them as they are. For smooth normals, weld the solids with [`Stl::weld`] and `smooth` first.
+#[derive(Debug)]
+pub struct Stl {
+    pub format: Format,
+    pub solids: Vec<Solid>
+}
+
+impl Stl {
+    /// Parses the contents of an ASCII or binary STL file.
+    pub fn parse(data: &[u8]) -> Result<Self, ImportError> {
+        // Binary files are allowed to start with "solid" too, so check the size matches the
+        // facet count before assuming the file is ASCII.
+        let is_binary = match data.get(HEADER_SIZE..HEADER_SIZE + 4) {
+            Some(count) => {
+                let count = u32::from_le_bytes(count.try_into().unwrap()) as usize;
+                data.len() == HEADER_SIZE + 4 + count * FACET_SIZE
+            },
+            None => false
+        };
+
+        if is_binary || !data.trim_ascii_start().starts_with(b"solid") {
+            Self::parse_binary(data)
+        } else {
+            let text = std::str::from_utf8(data).map_err(|_| parse_error("The file contains invalid UTF-8."))?;
+            Self::parse_ascii(text)
+        }
+    }
+
+    fn parse_binary(data: &[u8]) -> Result<Self, ImportError> {
+        if data.len() < HEADER_SIZE + 4 {
+            return Err(parse_error("The file is too small to be an STL file."));
+        }
+
+        let count = u32::from_le_bytes(data[HEADER_SIZE..HEADER_SIZE + 4].try_into().unwrap()) as usize;
+        let facet_data = &data[HEADER_SIZE + 4..];
+
+        if facet_data.len() < count * FACET_SIZE {
+            return Err(parse_error(format!("The file should contain {count} facets, but is too small.")));
+        }
+
+        let facets = facet_data
+            .chunks_exact(FACET_SIZE)
+            .take(count)
+            .map(|facet| {
+                let float = |i: usize| f32::from_le_bytes(facet[i * 4..i * 4 + 4].try_into().unwrap());
+                let vec3 = |i: usize| Vec3::new(float(i), float(i + 1), float(i + 2));
+
+                Facet {
+                    normal: vec3(0),
+                    vertices: [vec3(3), vec3(6), vec3(9)],
+                    attribute: u16::from_le_bytes([facet[48], facet[49]])
+                }
+            })
+            .collect();
+
+        // The header is free-form, but is often used for a name.
+        let name = String::from_utf8_lossy(&data[..HEADER_SIZE]).trim_matches(|c: char| c == '\0' || c.is_whitespace()).to_string();
+
+        Ok(Self {
+            format: Format::Binary,
+            solids: vec![Solid {
+                name: if name.is_empty() { None } else { Some(name) },
+                facets
+            }]
+        })
+    }
+
+    fn parse_ascii(text: &str) -> Result<Self, ImportError> {
+        let mut solids = Vec::new();
+        let mut solid: Option<Solid> = None;
+
+        let mut normal = Vec3::new(0.0, 0.0, 0.0);
+        let mut polygon = Vec::new();
+
+        for (line_number, line) in text.lines().enumerate() {
+            let error = |message: &str| parse_error(format!("Line {}: {message}", line_number + 1));
+
+            let mut words = line.split_whitespace();
+
+            let Some(keyword) = words.next() else {
+                continue;
+            };
+
+            let vec3 = |words: std::str::SplitWhitespace| {
+                let values = words.map(str::parse::<f32>).collect::<Result<Vec<_>, _>>().map_err(|_| error("Invalid number."))?;
+
+                match values[..] {
+                    [x, y, z] => Ok(Vec3::new(x, y, z)),
+                    _ => Err(error("Expected 3 numbers."))
+                }
+            };
+
+            match keyword {
+                "solid" => {
+                    if solid.is_some() {
+                        return Err(error("Solids can't be nested."));
+                    }
+
+                    let name = line.trim_start()["solid".len()..].trim();
+
+                    solid = Some(Solid {
+                        name: if name.is_empty() { None } else { Some(name.to_string()) },
+                        facets: Vec::new()
+                    });
+                },
+
+                "endsolid" => match solid.take() {
+                    Some(solid) => solids.push(solid),
+                    None => return Err(error("endsolid without a solid."))
+                },
+
+                "facet" => {
+                    if words.next() != Some("normal") {
+                        return Err(error("Expected \"facet normal\"."));
+                    }
+
+                    normal = vec3(words)?;
+                    polygon.clear();
+                },
+
+                "vertex" => polygon.push(vec3(words)?),
+
+                "endfacet" => {
+                    let Some(solid) = &mut solid else {
+                        return Err(error("Facets must be inside a solid."));
+                    };
+
+                    if polygon.len() < 3 {
+                        return Err(error("Facets must have at least 3 vertices."));
+                    }
+
+                    // Facets should be triangles, but some programs write polygons, so triangulate them as a fan.
+                    for i in 2..polygon.len() {
+                        solid.facets.push(Facet {
+                            normal,
+                            vertices: [polygon[0], polygon[i - 1], polygon[i]],
+                            attribute: 0
+                        });
+                    }
+                },
+
+                "outer" | "endloop" => {},
+
+                _ => return Err(error("Unknown statement."))
+            }
+        }
+
+        // Be lenient with files that are missing their final endsolid.
+        if let Some(solid) = solid {
+            solids.push(solid);
+        }
+
+        Ok(Self {
+            format: Format::Ascii,
+            solids
+        })
+    }
+
+    /// Writes the file in its format. Binary files can only contain one solid, so every solid is
+    /// merged into one, using the name of the first.
+    pub fn write(&self) -> Vec<u8> {
+        match self.format {
+            Format::Ascii => {
+                let mut text = String::new();
+
+                for solid in &self.solids {
+                    let name = solid.name.as_deref().unwrap_or("");
+
+                    writeln!(text, "solid {name}").unwrap();
+
+                    for facet in &solid.facets {
+                        let n = facet.normal;
+                        writeln!(text, "  facet normal {:e} {:e} {:e}", n.x, n.y, n.z).unwrap();
+                        text.push_str("    outer loop\n");
+
+                        for v in facet.vertices {
+                            writeln!(text, "      vertex {:e} {:e} {:e}", v.x, v.y, v.z).unwrap();
+                        }
+
+                        text.push_str("    endloop\n  endfacet\n");
+                    }
+
+                    writeln!(text, "endsolid {name}").unwrap();
+                }
+
+                text.into_bytes()
+            },
+
+            Format::Binary => {
+                let facets = self.solids.iter().flat_map(|solid| &solid.facets);
+                let count = facets.clone().count();
+
+                let mut data = Vec::with_capacity(HEADER_SIZE + 4 + count * FACET_SIZE);
+
+                // Don't start the header with "solid", as that would make it look like an ASCII file.
+                let name = self.solids.first().and_then(|solid| solid.name.as_deref()).unwrap_or("");
+                let name = if name.starts_with("solid") { "" } else { name };
+
+                data.extend(name.bytes().take(HEADER_SIZE));
+                data.resize(HEADER_SIZE, 0);
+                data.extend_from_slice(&(count as u32).to_le_bytes());
+
+                for facet in facets {
+                    for v in [facet.normal, facet.vertices[0], facet.vertices[1], facet.vertices[2]] {
+                        data.extend_from_slice(&v.x.to_le_bytes());
+                        data.extend_from_slice(&v.y.to_le_bytes());
+                        data.extend_from_slice(&v.z.to_le_bytes());
+                    }
+
+                    data.extend_from_slice(&facet.attribute.to_le_bytes());
+                }
+
+                data
+            }
+        }
+    }
+
+    /// Welds the facets of a solid into an indexed mesh.
+    ///
+    /// Without `smooth`, vertices are only shared between facets with the same normal, so the
+    /// mesh keeps its flat facet normals. With `smooth`, vertices are shared by position alone,
+    /// and have no normals, so they can be generated with [`crate::PostProcessFlags::GENERATE_NORMALS`].
+    pub fn weld(solid: &Solid, smooth: bool) -> crate::Mesh {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::with_capacity(solid.facets.len() * 3);
+
+        let mut vertex_cache = HashMap::new();
+
+        for facet in &solid.facets {
+            let normal = if smooth {
+                Vec3::new(0.0, 0.0, 0.0)
+            } else {
+                facet_normal(facet)
+            };
+
+            for position in facet.vertices {
+                let key = [position.x, position.y, position.z, normal.x, normal.y, normal.z].map(f32::to_bits);
+
+                let index = *vertex_cache.entry(key).or_insert_with(|| {
+                    vertices.push(Vertex {
+                        position,
+                        tex_coord: Vec2::new(0.0, 0.0),
+                        color: Vec4::new(1.0, 1.0, 1.0, 1.0),
+                        normal,
+                        tangent: Vec4::new(0.0, 0.0, 0.0, 0.0)
+                    });
+
+                    (vertices.len() - 1) as u32
+                });
+
+                indices.push(index);
+            }
+        }
+
+        let bounds = BoundingBox::from_vertices(&vertices);
+
+        crate::Mesh {
+            vertices,
+            indices: Some(indices),
+            material: None,
+            has_normals: !smooth,
+            bounds,
+            name: solid.name.clone(),
+            extras: None
+        }
+    }
+}
+
+impl Exporter for Stl {
+    fn export(&self, path: &str) -> Result<(), ExportError> {
+        std::fs::write(path, self.write())?;
+
+        Ok(())
+    }
+
+    /// Creates a binary STL from the scene, with one solid for each mesh instance.
+    ///
+    /// STL files are usually sent to be printed or machined, so node transforms are applied to
+    /// the vertices. Scenes without nodes have their meshes written as they are.
+    fn from_scene(scene: &crate::Scene) -> Self {
+        let mut instances = Vec::new();
+
+        if scene.nodes.is_empty() {
+            instances.extend((0..scene.meshes.len()).map(|mesh| (mesh, crate::Mat4::identity(), None)));
+        } else {
+            for (i, node) in scene.nodes.iter().enumerate() {
+                let transform = scene.world_transform(i);
+
+                for &mesh in &node.meshes {
+                    instances.push((mesh, transform, node.name.as_deref()));
+                }
+            }
+        }
+
+        let solids = instances
+            .into_iter()
+            .map(|(mesh, transform, node_name)| {
+                let mesh = &scene.meshes[mesh];
+
+                let position = |index: usize| transform.transform_point(mesh.vertices[index].position);
+
+                let facets = match &mesh.indices {
+                    Some(indices) => indices
+                        .chunks_exact(3)
+                        .map(|triangle| [position(triangle[0] as usize), position(triangle[1] as usize), position(triangle[2] as usize)])
+                        .collect::<Vec<_>>(),
+                    None => (0..mesh.vertices.len() / 3)
+                        .map(|i| [position(i * 3), position(i * 3 + 1), position(i * 3 + 2)])
+                        .collect()
+                };
+
+                Solid {
+                    name: mesh.name.as_deref().or(node_name).map(String::from),
+                    facets: facets
+                        .into_iter()
+                        .map(|vertices| {
+                            let mut facet = Facet { normal: Vec3::new(0.0, 0.0, 0.0), vertices, attribute: 0 };
+                            facet.normal = facet_normal(&facet);
+                            facet
+                        })
+                        .collect()
+                }
+            })
+            .collect();
+
+        Self {
+            format: Format::Binary,
+            solids
+        }
+    }
+}
+
+impl Importer for Stl {
+    fn import_bytes(data: &[u8], _resolver: &dyn ResourceResolver) -> Result<Self, ImportError> where Self : Sized {
+        Self::parse(data)
+    }
+
+    fn to_scene(&self, _resolver: &dyn ResourceResolver) -> Result<crate::Scene, ImportError> {
+        Ok(crate::Scene {
+            meshes: self.solids.iter().map(|solid| Self::weld(solid, false)).collect(),
+            materials: None,
+            images: None,
+            nodes: Vec::new(),
+            root_nodes: Vec::new()
+        })
+    }
+}
+
+fn parse_error<T: ToString>(message: T) -> ImportError {
+    ImportError::new(ImportErrorType::StringParseError, message)
+}
+
+/// Returns the normal of the facet. Many programs write zero normals, so these are calculated
+/// from the winding of the vertices instead.
+fn facet_normal(facet: &Facet) -> Vec3 {
+    if facet.normal.magnitude_squared() > 0.0 {
+        return facet.normal;
+    }
+
+    let [a, b, c] = facet.vertices;
+    let mut normal = (b - a).cross(&(c - a));
+
+    // Degenerate facets have no normal.
+    if normal.magnitude_squared() > 0.0 {
+        normal.normalize();
+    }
+
+    normal
+}
@@ -0,0 +1,841 @@
+use std::{collections::HashMap, io::{Cursor, Seek, Write}};
+
+use roxmltree::{Document, Node as XmlNode};
+
+use crate::{resolver::{ResourceResolver, ZipResolver}, Importer, Exporter, ImportError, ImportErrorType, ExportError, ExportErrorType, Mat4, Vec2, Vec3, Vec4, Vertex, BoundingBox};
+
+const MODEL_PATH: &str = "3D/3dmodel.model";
+const MODEL_RELATIONSHIP: &str = "http://schemas.microsoft.com/3dmanufacturing/2013/01/3dmodel";
+const CORE_NAMESPACE: &str = "http://schemas.microsoft.com/3dmanufacturing/core/2015/02";
+const MATERIAL_NAMESPACE: &str = "http://schemas.microsoft.com/3dmanufacturing/material/2015/02";
+
+/// The unit of the coordinates in a model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Micron,
+    Millimeter,
+    Centimeter,
+    Inch,
+    Foot,
+    Meter
+}
+
+impl Unit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Micron => "micron",
+            Self::Millimeter => "millimeter",
+            Self::Centimeter => "centimeter",
+            Self::Inch => "inch",
+            Self::Foot => "foot",
+            Self::Meter => "meter"
+        }
+    }
+
+    /// The size of the unit in meters.
+    pub fn meters(&self) -> f32 {
+        match self {
+            Self::Micron => 0.000001,
+            Self::Millimeter => 0.001,
+            Self::Centimeter => 0.01,
+            Self::Inch => 0.0254,
+            Self::Foot => 0.3048,
+            Self::Meter => 1.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BaseMaterial {
+    pub name:  String,
+    pub color: Vec4
+}
+
+/// A `<basematerials>` resource.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BaseMaterials {
+    pub id:        u32,
+    pub materials: Vec<BaseMaterial>
+}
+
+/// A `<colorgroup>` resource from the materials extension.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorGroup {
+    pub id:     u32,
+    pub colors: Vec<Vec4>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Triangle {
+    pub vertices:   [u32; 3],
+
+    /// The property group of the triangle, which overrides the object's.
+    pub property:   Option<u32>,
+
+    /// The index into the property group for each vertex. The second and third default to the first.
+    pub properties: [Option<u32>; 3]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Component {
+    pub object:    u32,
+    pub transform: Mat4
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjectContent {
+    Mesh {
+        vertices:  Vec<Vec3>,
+        triangles: Vec<Triangle>
+    },
+    Components(Vec<Component>)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Object {
+    pub id:             u32,
+    pub name:           Option<String>,
+
+    /// The default property group and index for the object's triangles.
+    pub property:       Option<u32>,
+    pub property_index: Option<u32>,
+
+    pub content:        ObjectContent
+}
+
+/// An object to be built, from the `<build>` element.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Item {
+    pub object:    u32,
+    pub transform: Mat4
+}
+
+/// A 3D Manufacturing Format package.
+///
+/// Only the core spec and the color groups and base materials of the materials extension are
+/// supported. 3MF is +Z up, so scenes are converted to +Y up and meters when they're imported,
+/// and back again when they're exported.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThreeMf {
+    pub unit:           Unit,
+    pub metadata:       Vec<(String, String)>,
+    pub base_materials: Vec<BaseMaterials>,
+    pub color_groups:   Vec<ColorGroup>,
+    pub objects:        Vec<Object>,
+    pub items:          Vec<Item>
+}
+
+impl ThreeMf {
+    /// Parses the contents of a model part, such as `3D/3dmodel.model`.
+    pub fn parse_model(text: &str) -> Result<Self, ImportError> {
+        let document = Document::parse(text).map_err(|err| parse_error(format!("Invalid XML: {err}")))?;
+        let root = document.root_element();
+
+        if root.tag_name().name() != "model" {
+            return Err(parse_error("The model part has no model element."));
+        }
+
+        let unit = match root.attribute("unit").unwrap_or("millimeter") {
+            "micron" => Unit::Micron,
+            "millimeter" => Unit::Millimeter,
+            "centimeter" => Unit::Centimeter,
+            "inch" => Unit::Inch,
+            "foot" => Unit::Foot,
+            "meter" => Unit::Meter,
+            unit => return Err(parse_error(format!("Unknown unit \"{unit}\".")))
+        };
+
+        let metadata = children(root, "metadata")
+            .filter_map(|metadata| Some((metadata.attribute("name")?.to_string(), metadata.text().unwrap_or("").to_string())))
+            .collect();
+
+        let mut three_mf = Self {
+            unit,
+            metadata,
+            base_materials: Vec::new(),
+            color_groups: Vec::new(),
+            objects: Vec::new(),
+            items: Vec::new()
+        };
+
+        if let Some(resources) = child(root, "resources") {
+            for resource in resources.children().filter(|node| node.is_element()) {
+                match resource.tag_name().name() {
+                    "basematerials" => three_mf.base_materials.push(BaseMaterials {
+                        id: required(resource, "id")?,
+                        materials: children(resource, "base").map(|base| Ok(BaseMaterial {
+                            name: base.attribute("name").unwrap_or("").to_string(),
+                            color: parse_color(base.attribute("displaycolor").unwrap_or("#FFFFFF"))?
+                        })).collect::<Result<_, ImportError>>()?
+                    }),
+                    "colorgroup" => three_mf.color_groups.push(ColorGroup {
+                        id: required(resource, "id")?,
+                        colors: children(resource, "color")
+                            .map(|color| parse_color(color.attribute("color").unwrap_or("#FFFFFF")))
+                            .collect::<Result<_, ImportError>>()?
+                    }),
+                    "object" => three_mf.objects.push(parse_object(resource)?),
+                    _ => {}
+                }
+            }
+        }
+
+        if let Some(build) = child(root, "build") {
+            for item in children(build, "item") {
+                three_mf.items.push(Item {
+                    object: required(item, "objectid")?,
+                    transform: parse_transform(item.attribute("transform"))?
+                });
+            }
+        }
+
+        Ok(three_mf)
+    }
+
+    /// Writes the model part.
+    pub fn write_model(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+
+        xml += &format!("<model unit=\"{}\" xml:lang=\"en-US\" xmlns=\"{CORE_NAMESPACE}\"", self.unit.as_str());
+
+        if !self.color_groups.is_empty() {
+            xml += &format!(" xmlns:m=\"{MATERIAL_NAMESPACE}\"");
+        }
+
+        xml += ">\n";
+
+        for (name, value) in &self.metadata {
+            xml += &format!("  <metadata name=\"{}\">{}</metadata>\n", escape(name), escape(value));
+        }
+
+        xml += "  <resources>\n";
+
+        for group in &self.base_materials {
+            xml += &format!("    <basematerials id=\"{}\">\n", group.id);
+
+            for material in &group.materials {
+                xml += &format!("      <base name=\"{}\" displaycolor=\"{}\"/>\n", escape(&material.name), format_color(material.color));
+            }
+
+            xml += "    </basematerials>\n";
+        }
+
+        for group in &self.color_groups {
+            xml += &format!("    <m:colorgroup id=\"{}\">\n", group.id);
+
+            for &color in &group.colors {
+                xml += &format!("      <m:color color=\"{}\"/>\n", format_color(color));
+            }
+
+            xml += "    </m:colorgroup>\n";
+        }
+
+        for object in &self.objects {
+            xml += &format!("    <object id=\"{}\" type=\"model\"", object.id);
+
+            if let Some(name) = &object.name {
+                xml += &format!(" name=\"{}\"", escape(name));
+            }
+
+            if let Some(property) = object.property {
+                xml += &format!(" pid=\"{property}\"");
+            }
+
+            if let Some(index) = object.property_index {
+                xml += &format!(" pindex=\"{index}\"");
+            }
+
+            xml += ">\n";
+
+            match &object.content {
+                ObjectContent::Mesh { vertices, triangles } => {
+                    xml += "      <mesh>\n        <vertices>\n";
+
+                    for vertex in vertices {
+                        xml += &format!("          <vertex x=\"{}\" y=\"{}\" z=\"{}\"/>\n", vertex.x, vertex.y, vertex.z);
+                    }
+
+                    xml += "        </vertices>\n        <triangles>\n";
+
+                    for triangle in triangles {
+                        let [v1, v2, v3] = triangle.vertices;
+                        xml += &format!("          <triangle v1=\"{v1}\" v2=\"{v2}\" v3=\"{v3}\"");
+
+                        if let Some(property) = triangle.property {
+                            xml += &format!(" pid=\"{property}\"");
+                        }
+
+                        for (i, index) in triangle.properties.iter().enumerate() {
+                            if let Some(index) = index {
+                                xml += &format!(" p{}=\"{index}\"", i + 1);
+                            }
+                        }
+
+                        xml += "/>\n";
+                    }
+
+                    xml += "        </triangles>\n      </mesh>\n";
+                },
+                ObjectContent::Components(components) => {
+                    xml += "      <components>\n";
+
+                    for component in components {
+                        xml += &format!("        <component objectid=\"{}\" transform=\"{}\"/>\n", component.object, format_transform(&component.transform));
+                    }
+
+                    xml += "      </components>\n";
+                }
+            }
+
+            xml += "    </object>\n";
+        }
+
+        xml += "  </resources>\n  <build>\n";
+
+        for item in &self.items {
+            xml += &format!("    <item objectid=\"{}\" transform=\"{}\"/>\n", item.object, format_transform(&item.transform));
+        }
+
+        xml += "  </build>\n</model>\n";
+
+        xml
+    }
+
+    /// Writes the package, which is a zip archive.
+    pub fn write<W: Write + Seek>(&self, writer: W) -> Result<(), ExportError> {
+        let zip_error = |err: zip::result::ZipError| ExportError::new(ExportErrorType::Other, err);
+
+        let mut zip = zip::ZipWriter::new(writer);
+        let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+        let content_types = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+            <Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\n  \
+            <Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\n  \
+            <Default Extension=\"model\" ContentType=\"application/vnd.ms-package.3dmanufacturing-3dmodel+xml\"/>\n\
+            </Types>\n";
+
+        let relationships = format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+            <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\n  \
+            <Relationship Target=\"/{MODEL_PATH}\" Id=\"rel0\" Type=\"{MODEL_RELATIONSHIP}\"/>\n\
+            </Relationships>\n");
+
+        for (path, contents) in [("[Content_Types].xml", content_types.to_string()), ("_rels/.rels", relationships), (MODEL_PATH, self.write_model())] {
+            zip.start_file(path, options).map_err(zip_error)?;
+            zip.write_all(contents.as_bytes())?;
+        }
+
+        zip.finish().map_err(zip_error)?;
+
+        Ok(())
+    }
+
+    /// The transform that converts from the model's units and +Z up to meters and +Y up.
+    pub fn axis_transform(&self) -> Mat4 {
+        let s = self.unit.meters();
+
+        Mat4::new(
+            Vec4::new(s, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, s, 0.0),
+            Vec4::new(0.0, -s, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, 0.0, 1.0)
+        )
+    }
+
+    fn object(&self, id: u32) -> Option<&Object> {
+        self.objects.iter().find(|object| object.id == id)
+    }
+}
+
+impl Exporter for ThreeMf {
+    fn export(&self, path: &str) -> Result<(), ExportError> {
+        self.write(std::fs::File::create(path)?)
+    }
+
+    /// Creates a 3MF from the scene.
+    ///
+    /// Triangles can only have one property group, so meshes with vertex colors use a color group,
+    /// and other meshes use their material. Node hierarchies become objects with components.
+    fn from_scene(scene: &crate::Scene) -> Self {
+        let materials = scene.materials.as_deref().unwrap_or_default();
+
+        let base_materials = if materials.is_empty() {
+            Vec::new()
+        } else {
+            vec![BaseMaterials {
+                id: 1,
+                materials: materials.iter().enumerate().map(|(i, material)| BaseMaterial {
+                    name: material.name.clone().unwrap_or_else(|| format!("Material{i}")),
+                    color: material.albedo_color
+                }).collect()
+            }]
+        };
+
+        let mut colors = ColorGroup {
+            id: 2,
+            colors: Vec::new()
+        };
+
+        let mut color_indices = HashMap::new();
+        let mut objects = Vec::new();
+
+        // Resources have to be defined before they are used, so meshes come first.
+        let mut next_id = 3;
+
+        for (i, mesh) in scene.meshes.iter().enumerate() {
+            let has_colors = mesh.vertices.iter().any(|v| v.color != Vec4::new(1.0, 1.0, 1.0, 1.0));
+
+            let mut vertex_colors = Vec::new();
+
+            if has_colors {
+                for vertex in &mesh.vertices {
+                    let key = [vertex.color.x, vertex.color.y, vertex.color.z, vertex.color.w].map(f32::to_bits);
+
+                    let index = *color_indices.entry(key).or_insert_with(|| {
+                        colors.colors.push(vertex.color);
+                        colors.colors.len() as u32 - 1
+                    });
+
+                    vertex_colors.push(index);
+                }
+            }
+
+            let indices = mesh.indices.clone().unwrap_or_else(|| (0..mesh.vertices.len() as u32).collect());
+
+            let triangles = indices.chunks_exact(3).map(|triangle| {
+                let vertices = [triangle[0], triangle[1], triangle[2]];
+
+                Triangle {
+                    vertices,
+                    property: has_colors.then_some(colors.id),
+                    properties: vertices.map(|vertex| vertex_colors.get(vertex as usize).copied())
+                }
+            }).collect();
+
+            let material = mesh.material.filter(|_| !has_colors && !base_materials.is_empty());
+
+            objects.push(Object {
+                id: next_id + i as u32,
+                name: mesh.name.clone(),
+                property: material.map(|_| 1),
+                property_index: material.map(|material| material as u32),
+                content: ObjectContent::Mesh {
+                    vertices: mesh.vertices.iter().map(|v| v.position).collect(),
+                    triangles
+                }
+            });
+        }
+
+        next_id += scene.meshes.len() as u32;
+
+        // 3MF is +Z up and in millimeters.
+        let unit = Unit::Millimeter;
+        let s = 1.0 / unit.meters();
+
+        let to_z_up = Mat4::new(
+            Vec4::new(s, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, -s, 0.0),
+            Vec4::new(0.0, s, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, 0.0, 1.0)
+        );
+
+        let mut items = Vec::new();
+
+        if scene.nodes.is_empty() {
+            items.extend(objects.iter().map(|object| Item { object: object.id, transform: to_z_up }));
+        } else {
+            let mut node_objects = vec![None; scene.nodes.len()];
+
+            for &root in &scene.root_nodes {
+                if let Some(object) = node_object(scene, root, &mut node_objects, &mut objects, &mut next_id, 0) {
+                    items.push(Item { object, transform: to_z_up * scene.nodes[root].transform });
+                }
+            }
+        }
+
+        Self {
+            unit,
+            metadata: vec![(String::from("Application"), String::from("modelo"))],
+            base_materials,
+            color_groups: if colors.colors.is_empty() { Vec::new() } else { vec![colors] },
+            objects,
+            items
+        }
+    }
+}
+
+impl Importer for ThreeMf {
+    fn import_bytes(data: &[u8], _resolver: &dyn ResourceResolver) -> Result<Self, ImportError> where Self : Sized {
+        // Everything is inside the package, so the resolver isn't needed.
+        let package = ZipResolver::new(Cursor::new(data))?;
+
+        // The root relationships say where the model is, although it's almost always in the same place.
+        let model_path = match package.resolve("_rels/.rels") {
+            Ok(relationships) => {
+                let text = String::from_utf8_lossy(&relationships).into_owned();
+                let document = Document::parse(&text).map_err(|err| parse_error(format!("Invalid relationships: {err}")))?;
+
+                document.root_element()
+                    .children()
+                    .find(|node| node.has_tag_name("Relationship") && node.attribute("Type") == Some(MODEL_RELATIONSHIP))
+                    .and_then(|relationship| relationship.attribute("Target"))
+                    .map_or_else(|| MODEL_PATH.to_string(), |target| target.trim_start_matches('/').to_string())
+            },
+            Err(_) => MODEL_PATH.to_string()
+        };
+
+        let model = package.resolve(&model_path)?;
+        let text = std::str::from_utf8(&model).map_err(|_| parse_error("The model contains invalid UTF-8."))?;
+
+        Self::parse_model(text)
+    }
+
+    fn to_scene(&self, _resolver: &dyn ResourceResolver) -> Result<crate::Scene, ImportError> {
+        let mut materials = Vec::new();
+        let mut material_offsets = HashMap::new();
+
+        for group in &self.base_materials {
+            material_offsets.insert(group.id, materials.len());
+
+            materials.extend(group.materials.iter().map(|material| crate::Material {
+                albedo_color: material.color,
+                albedo_texture: None,
+                normal_texture: None,
+                metallic: 0.0,
+                metallic_texture: None,
+                roughness: 1.0,
+                roughness_texture: None,
+                occlusion_texture: None,
+                emissive_texture: None,
+                alpha_mode: if material.color.w < 1.0 { crate::AlphaMode::Blend } else { crate::AlphaMode::Opaque },
+                alpha_cutoff: 0.5,
+                double_sided: false,
+                name: Some(material.name.clone()),
+                extras: None
+            }));
+        }
+
+        let mut builder = SceneBuilder {
+            three_mf: self,
+            material_offsets,
+            meshes: Vec::new(),
+            object_meshes: HashMap::new(),
+            nodes: Vec::new()
+        };
+
+        let mut root_nodes = Vec::new();
+        let axis_transform = self.axis_transform();
+
+        for item in &self.items {
+            let node = builder.node(item.object, axis_transform * item.transform, 0)?;
+            root_nodes.push(node);
+        }
+
+        Ok(crate::Scene {
+            meshes: builder.meshes,
+            materials: if materials.is_empty() { None } else { Some(materials) },
+            images: None,
+            nodes: builder.nodes,
+            root_nodes
+        })
+    }
+}
+
+/// Creates the object for a node, returning `None` if the node and its children have no meshes.
+fn node_object(scene: &crate::Scene, node: usize, node_objects: &mut Vec<Option<u32>>, objects: &mut Vec<Object>, next_id: &mut u32, depth: usize) -> Option<u32> {
+    if depth > 256 {
+        return None;
+    }
+
+    if let Some(object) = node_objects[node] {
+        return Some(object);
+    }
+
+    // Mesh objects have the first IDs, in the same order as the scene's meshes.
+    let mut components = scene.nodes[node].meshes.iter().map(|&mesh| Component {
+        object: 3 + mesh as u32,
+        transform: Mat4::identity()
+    }).collect::<Vec<_>>();
+
+    for &child in &scene.nodes[node].children {
+        if let Some(object) = node_object(scene, child, node_objects, objects, next_id, depth + 1) {
+            components.push(Component { object, transform: scene.nodes[child].transform });
+        }
+    }
+
+    if components.is_empty() {
+        return None;
+    }
+
+    let id = *next_id;
+    *next_id += 1;
+
+    objects.push(Object {
+        id,
+        name: scene.nodes[node].name.clone(),
+        property: None,
+        property_index: None,
+        content: ObjectContent::Components(components)
+    });
+
+    node_objects[node] = Some(id);
+
+    Some(id)
+}
+
+struct SceneBuilder<'a> {
+    three_mf:         &'a ThreeMf,
+    material_offsets: HashMap<u32, usize>,
+    meshes:           Vec<crate::Mesh>,
+    object_meshes:    HashMap<u32, Vec<usize>>,
+    nodes:            Vec<crate::Node>
+}
+
+impl SceneBuilder<'_> {
+    /// Adds a node for an object, with a child node for each of its components.
+    fn node(&mut self, id: u32, transform: Mat4, depth: usize) -> Result<usize, ImportError> {
+        if depth > 256 {
+            return Err(parse_error("The components are nested too deeply."));
+        }
+
+        let object = self.three_mf.object(id).ok_or_else(|| parse_error(format!("Object {id} does not exist.")))?;
+
+        let index = self.nodes.len();
+
+        self.nodes.push(crate::Node {
+            transform,
+            meshes: Vec::new(),
+            children: Vec::new(),
+            name: object.name.clone(),
+            extras: None
+        });
+
+        match &object.content {
+            ObjectContent::Mesh { .. } => {
+                self.nodes[index].meshes = self.object_meshes(object)?;
+            },
+            ObjectContent::Components(components) => {
+                let mut children = Vec::with_capacity(components.len());
+
+                for component in components {
+                    children.push(self.node(component.object, component.transform, depth + 1)?);
+                }
+
+                self.nodes[index].children = children;
+            }
+        }
+
+        Ok(index)
+    }
+
+    /// Gets the meshes of a mesh object, creating them the first time. Triangles are split into a
+    /// mesh for each material.
+    fn object_meshes(&mut self, object: &Object) -> Result<Vec<usize>, ImportError> {
+        if let Some(meshes) = self.object_meshes.get(&object.id) {
+            return Ok(meshes.clone());
+        }
+
+        let ObjectContent::Mesh { vertices, triangles } = &object.content else {
+            return Ok(Vec::new());
+        };
+
+        let error = || parse_error(format!("Object {}: A triangle refers to a vertex or property that does not exist.", object.id));
+
+        struct Builder {
+            vertices: Vec<Vertex>,
+            indices:  Vec<u32>,
+            cache:    HashMap<(u32, Option<u32>), u32>
+        }
+
+        let mut builders: Vec<(Option<usize>, Builder)> = Vec::new();
+
+        for triangle in triangles {
+            let property = triangle.property.or(object.property);
+            let first = triangle.properties[0].or(object.property_index);
+            let properties = [first, triangle.properties[1].or(first), triangle.properties[2].or(first)];
+
+            let mut material = None;
+            let mut colors = None;
+
+            if let Some(property) = property {
+                if let Some(&offset) = self.material_offsets.get(&property) {
+                    material = first.map(|index| offset + index as usize);
+                } else if let Some(group) = self.three_mf.color_groups.iter().find(|group| group.id == property) {
+                    colors = Some((group, properties));
+                }
+            }
+
+            let builder = match builders.iter().position(|(m, _)| *m == material) {
+                Some(builder) => &mut builders[builder].1,
+                None => {
+                    builders.push((material, Builder { vertices: Vec::new(), indices: Vec::new(), cache: HashMap::new() }));
+                    &mut builders.last_mut().unwrap().1
+                }
+            };
+
+            for (corner, &vertex) in triangle.vertices.iter().enumerate() {
+                let color_index = colors.and_then(|(_, properties)| properties[corner]);
+
+                let index = match builder.cache.get(&(vertex, color_index)) {
+                    Some(&index) => index,
+                    None => {
+                        let position = *vertices.get(vertex as usize).ok_or_else(error)?;
+
+                        let color = match (colors, color_index) {
+                            (Some((group, _)), Some(color)) => *group.colors.get(color as usize).ok_or_else(error)?,
+                            _ => Vec4::new(1.0, 1.0, 1.0, 1.0)
+                        };
+
+                        builder.vertices.push(Vertex {
+                            position,
+                            tex_coord: Vec2::new(0.0, 0.0),
+                            color,
+                            normal: Vec3::new(0.0, 0.0, 0.0),
+                            tangent: Vec4::new(0.0, 0.0, 0.0, 0.0)
+                        });
+
+                        let index = builder.vertices.len() as u32 - 1;
+                        builder.cache.insert((vertex, color_index), index);
+                        index
+                    }
+                };
+
+                builder.indices.push(index);
+            }
+        }
+
+        let mut meshes = Vec::new();
+
+        for (material, builder) in builders {
+            self.meshes.push(crate::Mesh {
+                bounds: BoundingBox::from_vertices(&builder.vertices),
+                vertices: builder.vertices,
+                indices: Some(builder.indices),
+                material,
+                has_normals: false,
+                name: object.name.clone(),
+                extras: None
+            });
+
+            meshes.push(self.meshes.len() - 1);
+        }
+
+        self.object_meshes.insert(object.id, meshes.clone());
+
+        Ok(meshes)
+    }
+}
+
+fn parse_object(object: XmlNode) -> Result<Object, ImportError> {
+    let id = required(object, "id")?;
+
+    let content = if let Some(mesh) = child(object, "mesh") {
+        let vertices = child(mesh, "vertices")
+            .into_iter()
+            .flat_map(|vertices| children(vertices, "vertex"))
+            .map(|vertex| Ok(Vec3::new(required(vertex, "x")?, required(vertex, "y")?, required(vertex, "z")?)))
+            .collect::<Result<_, ImportError>>()?;
+
+        let triangles = child(mesh, "triangles")
+            .into_iter()
+            .flat_map(|triangles| children(triangles, "triangle"))
+            .map(|triangle| Ok(Triangle {
+                vertices: [required(triangle, "v1")?, required(triangle, "v2")?, required(triangle, "v3")?],
+                property: optional(triangle, "pid")?,
+                properties: [optional(triangle, "p1")?, optional(triangle, "p2")?, optional(triangle, "p3")?]
+            }))
+            .collect::<Result<_, ImportError>>()?;
+
+        ObjectContent::Mesh { vertices, triangles }
+    } else if let Some(components) = child(object, "components") {
+        ObjectContent::Components(children(components, "component").map(|component| Ok(Component {
+            object: required(component, "objectid")?,
+            transform: parse_transform(component.attribute("transform"))?
+        })).collect::<Result<_, ImportError>>()?)
+    } else {
+        return Err(parse_error(format!("Object {id} has neither a mesh nor components.")));
+    };
+
+    Ok(Object {
+        id,
+        name: object.attribute("name").map(String::from),
+        property: optional(object, "pid")?,
+        property_index: optional(object, "pindex")?,
+        content
+    })
+}
+
+/// Parses a transform, which is the first three columns of a matrix that transforms row vectors.
+fn parse_transform(transform: Option<&str>) -> Result<Mat4, ImportError> {
+    let Some(transform) = transform else {
+        return Ok(Mat4::identity());
+    };
+
+    let values = transform.split_whitespace()
+        .map(|value| value.parse::<f32>().map_err(|_| parse_error(format!("Invalid transform \"{transform}\"."))))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let [m00, m01, m02, m10, m11, m12, m20, m21, m22, m30, m31, m32] = values[..] else {
+        return Err(parse_error(format!("Transforms must have 12 values, not {}.", values.len())));
+    };
+
+    Ok(Mat4::new(
+        Vec4::new(m00, m10, m20, m30),
+        Vec4::new(m01, m11, m21, m31),
+        Vec4::new(m02, m12, m22, m32),
+        Vec4::new(0.0, 0.0, 0.0, 1.0)
+    ))
+}
+
+fn format_transform(transform: &Mat4) -> String {
+    let (c0, c1, c2, c3) = (transform.column0(), transform.column1(), transform.column2(), transform.column3());
+
+    [c0.x, c0.y, c0.z, c1.x, c1.y, c1.z, c2.x, c2.y, c2.z, c3.x, c3.y, c3.z]
+        .map(|value| value.to_string())
+        .join(" ")
+}
+
+/// Parses an sRGB color in the form `#RRGGBB` or `#RRGGBBAA`.
+fn parse_color(color: &str) -> Result<Vec4, ImportError> {
+    let error = || parse_error(format!("Invalid color \"{color}\"."));
+
+    let hex = color.strip_prefix('#').filter(|hex| hex.len() == 6 || hex.len() == 8).ok_or_else(error)?;
+    let channel = |i: usize| u8::from_str_radix(hex.get(i * 2..i * 2 + 2).ok_or_else(error)?, 16).map(|value| value as f32 / 255.0).map_err(|_| error());
+
+    Ok(Vec4::new(channel(0)?, channel(1)?, channel(2)?, if hex.len() == 8 { channel(3)? } else { 1.0 }))
+}
+
+fn format_color(color: Vec4) -> String {
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    format!("#{:02X}{:02X}{:02X}{:02X}", channel(color.x), channel(color.y), channel(color.z), channel(color.w))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn required<T: std::str::FromStr>(node: XmlNode, name: &str) -> Result<T, ImportError> {
+    match optional(node, name)? {
+        Some(value) => Ok(value),
+        None => Err(parse_error(format!("<{}> is missing the \"{name}\" attribute.", node.tag_name().name())))
+    }
+}
+
+fn optional<T: std::str::FromStr>(node: XmlNode, name: &str) -> Result<Option<T>, ImportError> {
+    match node.attribute(name) {
+        Some(value) => value.trim().parse().map(Some).map_err(|_| parse_error(format!("<{}> has an invalid \"{name}\" attribute.", node.tag_name().name()))),
+        None => Ok(None)
+    }
+}
+
+fn child<'a, 'input>(node: XmlNode<'a, 'input>, name: &str) -> Option<XmlNode<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(name))
+}
+
+fn children<'a, 'input: 'a>(node: XmlNode<'a, 'input>, name: &'a str) -> impl Iterator<Item = XmlNode<'a, 'input>> + 'a {
+    node.children().filter(move |child| child.has_tag_name(name))
+}
+
+fn parse_error<T: ToString>(message: T) -> ImportError {
+    ImportError::new(ImportErrorType::StringParseError, message)
+}
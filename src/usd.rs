@@ -0,0 +1,1683 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::{resolver::ResourceResolver, utils::affine_inverse, Importer, Exporter, ImportError, ImportErrorType, ExportError, Mat4, Quat, Vec2, Vec3, Vec4, Vertex, BoundingBox};
+
+/// A value of an attribute or metadata field. Tokens are stored as strings.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    None,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Asset(String),
+    Path(String),
+    Tuple(Vec<Value>),
+    Array(Vec<Value>),
+    Dictionary(Vec<(String, Value)>)
+}
+
+impl Value {
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Self::Float(value) => Some(value),
+            Self::Int(value) => Some(value as f64),
+            Self::Bool(value) => Some(value as i64 as f64),
+            _ => None
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Self::Int(value) => Some(value),
+            Self::Bool(value) => Some(value as i64),
+            _ => None
+        }
+    }
+
+    /// Gets the text of a string, token, asset path or prim path.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) | Self::Asset(value) | Self::Path(value) => Some(value),
+            _ => None
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Self::Array(values) => Some(values),
+            _ => None
+        }
+    }
+
+    /// Gets a tuple of numbers, or a single number as a tuple of one.
+    pub fn to_floats(&self) -> Option<Vec<f32>> {
+        match self {
+            Self::Tuple(values) => values.iter().map(|value| value.as_f64().map(|value| value as f32)).collect(),
+            value => value.as_f64().map(|value| vec![value as f32])
+        }
+    }
+}
+
+fn float(value: f32) -> Value {
+    // Go through the shortest decimal representation, so values don't gain extra digits.
+    Value::Float(value.to_string().parse().unwrap_or(value as f64))
+}
+
+fn floats(values: &[f32]) -> Value {
+    Value::Tuple(values.iter().map(|&value| float(value)).collect())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute {
+    pub name:        String,
+
+    /// The type, such as `point3f[]` or `token`.
+    pub type_name:   String,
+    pub custom:      bool,
+    pub uniform:     bool,
+    pub value:       Option<Value>,
+
+    /// The paths of the attributes this attribute is connected to.
+    pub connections: Vec<String>,
+    pub metadata:    Vec<(String, Value)>
+}
+
+impl Attribute {
+    pub fn new(type_name: &str, name: &str, value: Option<Value>) -> Self {
+        Self {
+            name: name.to_string(),
+            type_name: type_name.to_string(),
+            custom: false,
+            uniform: false,
+            value,
+            connections: Vec::new(),
+            metadata: Vec::new()
+        }
+    }
+
+    pub fn metadata(&self, name: &str) -> Option<&Value> {
+        self.metadata.iter().find(|(key, _)| key == name).map(|(_, value)| value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Relationship {
+    pub name:    String,
+    pub custom:  bool,
+    pub targets: Vec<String>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Specifier {
+    Def,
+    Over,
+    Class
+}
+
+impl Specifier {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Def => "def",
+            Self::Over => "over",
+            Self::Class => "class"
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Prim {
+    pub specifier:     Specifier,
+    pub type_name:     Option<String>,
+    pub name:          String,
+
+    /// Metadata fields. List-edited fields keep their operation, as in `prepend apiSchemas`.
+    pub metadata:      Vec<(String, Value)>,
+    pub attributes:    Vec<Attribute>,
+    pub relationships: Vec<Relationship>,
+    pub children:      Vec<Prim>
+}
+
+impl Prim {
+    pub fn new(type_name: Option<&str>, name: &str) -> Self {
+        Self {
+            specifier: Specifier::Def,
+            type_name: type_name.map(String::from),
+            name: name.to_string(),
+            metadata: Vec::new(),
+            attributes: Vec::new(),
+            relationships: Vec::new(),
+            children: Vec::new()
+        }
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&Attribute> {
+        self.attributes.iter().find(|attribute| attribute.name == name)
+    }
+
+    pub fn value(&self, name: &str) -> Option<&Value> {
+        self.attribute(name).and_then(|attribute| attribute.value.as_ref())
+    }
+
+    pub fn relationship(&self, name: &str) -> Option<&Relationship> {
+        self.relationships.iter().find(|relationship| relationship.name == name)
+    }
+
+    fn is_type(&self, type_name: &str) -> bool {
+        self.type_name.as_deref() == Some(type_name)
+    }
+
+    fn attribute_mut(&mut self, type_name: &str, name: &str) -> &mut Attribute {
+        match self.attributes.iter().position(|attribute| attribute.name == name) {
+            Some(index) => &mut self.attributes[index],
+            None => {
+                self.attributes.push(Attribute::new(type_name, name, None));
+                self.attributes.last_mut().unwrap()
+            }
+        }
+    }
+}
+
+/// The +Y or +Z up axis of a stage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpAxis {
+    Y,
+    Z
+}
+
+/// A USD layer in the `.usda` text format.
+///
+/// Only the layer itself is read, so references, payloads, variants and sublayers aren't composed.
+/// The scene is built from the `UsdGeom` Xform and Mesh prims, and materials from
+/// `UsdPreviewSurface` shaders.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Usd {
+    pub metadata: Vec<(String, Value)>,
+    pub prims:    Vec<Prim>
+}
+
+impl Usd {
+    pub fn metadata(&self, name: &str) -> Option<&Value> {
+        self.metadata.iter().find(|(key, _)| key == name).map(|(_, value)| value)
+    }
+
+    pub fn up_axis(&self) -> UpAxis {
+        match self.metadata("upAxis").and_then(Value::as_str) {
+            Some("Z") => UpAxis::Z,
+            _ => UpAxis::Y
+        }
+    }
+
+    /// The size of a unit in meters, which is centimeters if it isn't given.
+    pub fn meters_per_unit(&self) -> f32 {
+        self.metadata("metersPerUnit").and_then(Value::as_f64).map_or(0.01, |value| value as f32)
+    }
+
+    /// The transform that converts from the stage's units and up axis to meters and +Y up.
+    pub fn axis_transform(&self) -> Mat4 {
+        let s = self.meters_per_unit();
+
+        match self.up_axis() {
+            UpAxis::Y => Mat4::new(
+                Vec4::new(s, 0.0, 0.0, 0.0),
+                Vec4::new(0.0, s, 0.0, 0.0),
+                Vec4::new(0.0, 0.0, s, 0.0),
+                Vec4::new(0.0, 0.0, 0.0, 1.0)
+            ),
+            UpAxis::Z => Mat4::new(
+                Vec4::new(s, 0.0, 0.0, 0.0),
+                Vec4::new(0.0, 0.0, s, 0.0),
+                Vec4::new(0.0, -s, 0.0, 0.0),
+                Vec4::new(0.0, 0.0, 0.0, 1.0)
+            )
+        }
+    }
+
+    /// Finds a prim by its absolute path, such as `/World/Cube`.
+    pub fn prim(&self, path: &str) -> Option<&Prim> {
+        let mut names = path.strip_prefix('/')?.split('/');
+        let root = names.next()?;
+        let mut prim = self.prims.iter().find(|prim| prim.name == root)?;
+
+        for name in names {
+            prim = prim.children.iter().find(|child| child.name == name)?;
+        }
+
+        Some(prim)
+    }
+
+    /// Parses the text of a `.usda` layer.
+    pub fn parse(text: &str) -> Result<Self, ImportError> {
+        if !text.starts_with("#usda ") {
+            return Err(parse_error("The file is not a USD text file."));
+        }
+
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            position: 0
+        };
+
+        let metadata = if parser.peek() == &Token::Punct('(') {
+            parser.metadata()?
+        } else {
+            Vec::new()
+        };
+
+        let mut prims = Vec::new();
+
+        while parser.peek() != &Token::End {
+            prims.push(parser.prim()?);
+        }
+
+        Ok(Self {
+            metadata,
+            prims
+        })
+    }
+
+    /// Writes the layer as text.
+    pub fn write(&self) -> String {
+        let mut text = String::from("#usda 1.0\n");
+
+        if !self.metadata.is_empty() {
+            text += "(\n";
+
+            for (key, value) in &self.metadata {
+                text += &format!("    {key} = {}\n", write_value(value));
+            }
+
+            text += ")\n";
+        }
+
+        for prim in &self.prims {
+            text += "\n";
+            write_prim(&mut text, prim, 0);
+        }
+
+        text
+    }
+}
+
+impl Exporter for Usd {
+    fn export(&self, path: &str) -> Result<(), ExportError> {
+        std::fs::write(path, self.write())?;
+
+        Ok(())
+    }
+
+    /// Creates a layer from the scene, with everything under a `/Root` prim.
+    ///
+    /// USD prims can only have one parent, so meshes used by several nodes are written once for
+    /// each node.
+    fn from_scene(scene: &crate::Scene) -> Self {
+        let mut root = Prim::new(Some("Xform"), "Root");
+
+        let materials = scene.materials.as_deref().unwrap_or_default();
+        let images = scene.images.as_deref().unwrap_or_default();
+
+        let mut material_paths = Vec::new();
+
+        if !materials.is_empty() {
+            let mut scope = Prim::new(Some("Scope"), "Materials");
+            let mut names = HashSet::new();
+
+            for (i, material) in materials.iter().enumerate() {
+                let name = unique_name(&mut names, material.name.as_deref(), &format!("Material{i}"));
+                let path = format!("/Root/Materials/{name}");
+
+                scope.children.push(write_material(material, &name, &path, images));
+                material_paths.push(path);
+            }
+
+            root.children.push(scope);
+        }
+
+        let mesh_prim = |mesh: usize, name: &str| write_mesh(&scene.meshes[mesh], name, mesh_material(scene, mesh, &material_paths));
+
+        let mut names = HashSet::from([String::from("Materials")]);
+
+        if scene.nodes.is_empty() {
+            for (i, mesh) in scene.meshes.iter().enumerate() {
+                let name = unique_name(&mut names, mesh.name.as_deref(), &format!("Mesh{i}"));
+                root.children.push(mesh_prim(i, &name));
+            }
+        } else {
+            fn node_prim(scene: &crate::Scene, node: usize, name: &str, mesh_prim: &dyn Fn(usize, &str) -> Prim, depth: usize) -> Prim {
+                let mut prim = Prim::new(Some("Xform"), name);
+                let scene_node = &scene.nodes[node];
+
+                let t = scene_node.transform;
+                let rows = [t.column0(), t.column1(), t.column2(), t.column3()];
+
+                // USD matrices transform row vectors, so are the transpose of modelo's.
+                prim.attributes.push(Attribute::new("matrix4d", "xformOp:transform", Some(Value::Tuple(
+                    rows.iter().map(|row| floats(&[row.x, row.y, row.z, row.w])).collect()
+                ))));
+
+                let mut order = Attribute::new("token[]", "xformOpOrder", Some(Value::Array(vec![Value::String(String::from("xformOp:transform"))])));
+                order.uniform = true;
+                prim.attributes.push(order);
+
+                let mut names = HashSet::new();
+
+                for &mesh in &scene_node.meshes {
+                    let name = unique_name(&mut names, scene.meshes[mesh].name.as_deref(), &format!("Mesh{mesh}"));
+                    prim.children.push(mesh_prim(mesh, &name));
+                }
+
+                if depth < 256 {
+                    for &child in &scene_node.children {
+                        let name = unique_name(&mut names, scene.nodes[child].name.as_deref(), &format!("Node{child}"));
+                        prim.children.push(node_prim(scene, child, &name, mesh_prim, depth + 1));
+                    }
+                }
+
+                prim
+            }
+
+            for &node in &scene.root_nodes {
+                let name = unique_name(&mut names, scene.nodes[node].name.as_deref(), &format!("Node{node}"));
+                root.children.push(node_prim(scene, node, &name, &mesh_prim, 0));
+            }
+        }
+
+        Self {
+            metadata: vec![
+                (String::from("defaultPrim"), Value::String(String::from("Root"))),
+                (String::from("metersPerUnit"), Value::Int(1)),
+                (String::from("upAxis"), Value::String(String::from("Y")))
+            ],
+            prims: vec![root]
+        }
+    }
+}
+
+impl Importer for Usd {
+    fn import_bytes(data: &[u8], _resolver: &dyn ResourceResolver) -> Result<Self, ImportError> where Self : Sized {
+        let text = std::str::from_utf8(data).map_err(|_| parse_error("The file contains invalid UTF-8."))?;
+
+        Self::parse(text)
+    }
+
+    fn to_scene(&self, _resolver: &dyn ResourceResolver) -> Result<crate::Scene, ImportError> {
+        let mut builder = SceneBuilder {
+            usd: self,
+            meshes: Vec::new(),
+            materials: Vec::new(),
+            material_indices: HashMap::new(),
+            images: Vec::new(),
+            image_indices: HashMap::new(),
+            nodes: Vec::new()
+        };
+
+        let mut root_nodes = Vec::new();
+        let axis_transform = self.axis_transform();
+
+        for prim in &self.prims {
+            if let Some(node) = builder.node(prim, &format!("/{}", prim.name), 0)? {
+                builder.nodes[node].transform = axis_transform * builder.nodes[node].transform;
+                root_nodes.push(node);
+            }
+        }
+
+        Ok(crate::Scene {
+            meshes: builder.meshes,
+            materials: if builder.materials.is_empty() { None } else { Some(builder.materials) },
+            images: if builder.images.is_empty() { None } else { Some(builder.images) },
+            nodes: builder.nodes,
+            root_nodes
+        })
+    }
+}
+
+fn mesh_material<'a>(scene: &crate::Scene, mesh: usize, material_paths: &'a [String]) -> Option<&'a str> {
+    scene.meshes[mesh].material.and_then(|material| material_paths.get(material)).map(String::as_str)
+}
+
+/// Makes a valid prim name that is unique among its siblings.
+fn unique_name(names: &mut HashSet<String>, name: Option<&str>, default: &str) -> String {
+    let mut base = name.unwrap_or(default).chars().map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' }).collect::<String>();
+
+    if base.is_empty() {
+        base = default.to_string();
+    }
+
+    if base.starts_with(|c: char| c.is_ascii_digit()) {
+        base.insert(0, '_');
+    }
+
+    let mut name = base.clone();
+    let mut suffix = 1;
+
+    while !names.insert(name.clone()) {
+        name = format!("{base}_{suffix}");
+        suffix += 1;
+    }
+
+    name
+}
+
+fn write_mesh(mesh: &crate::Mesh, name: &str, material: Option<&str>) -> Prim {
+    let mut prim = Prim::new(Some("Mesh"), name);
+
+    let indices = mesh.indices.clone().unwrap_or_else(|| (0..mesh.vertices.len() as u32).collect());
+
+    let vec3s = |values: &mut dyn Iterator<Item = Vec3>| Value::Array(values.map(|v| floats(&[v.x, v.y, v.z])).collect());
+    let vertex_interpolation = || vec![(String::from("interpolation"), Value::String(String::from("vertex")))];
+
+    prim.attributes.push(Attribute::new("int[]", "faceVertexCounts", Some(Value::Array(vec![Value::Int(3); indices.len() / 3]))));
+    prim.attributes.push(Attribute::new("int[]", "faceVertexIndices", Some(Value::Array(indices.iter().map(|&index| Value::Int(index as i64)).collect()))));
+    prim.attributes.push(Attribute::new("point3f[]", "points", Some(vec3s(&mut mesh.vertices.iter().map(|v| v.position)))));
+
+    if mesh.has_normals {
+        let mut normals = Attribute::new("normal3f[]", "normals", Some(vec3s(&mut mesh.vertices.iter().map(|v| v.normal))));
+        normals.metadata = vertex_interpolation();
+        prim.attributes.push(normals);
+    }
+
+    if mesh.vertices.iter().any(|v| v.tex_coord.x != 0.0 || v.tex_coord.y != 0.0) {
+        // USD texture coordinates start at the bottom left, however modelo's start at the top left.
+        let tex_coords = mesh.vertices.iter().map(|v| floats(&[v.tex_coord.x, 1.0 - v.tex_coord.y])).collect();
+
+        let mut st = Attribute::new("texCoord2f[]", "primvars:st", Some(Value::Array(tex_coords)));
+        st.metadata = vertex_interpolation();
+        prim.attributes.push(st);
+    }
+
+    if mesh.vertices.iter().any(|v| v.color != Vec4::new(1.0, 1.0, 1.0, 1.0)) {
+        let mut colors = Attribute::new("color3f[]", "primvars:displayColor", Some(vec3s(&mut mesh.vertices.iter().map(|v| Vec3::new(v.color.x, v.color.y, v.color.z)))));
+        colors.metadata = vertex_interpolation();
+        prim.attributes.push(colors);
+
+        if mesh.vertices.iter().any(|v| v.color.w != 1.0) {
+            let mut opacities = Attribute::new("float[]", "primvars:displayOpacity", Some(Value::Array(mesh.vertices.iter().map(|v| float(v.color.w)).collect())));
+            opacities.metadata = vertex_interpolation();
+            prim.attributes.push(opacities);
+        }
+    }
+
+    let mut subdivision = Attribute::new("token", "subdivisionScheme", Some(Value::String(String::from("none"))));
+    subdivision.uniform = true;
+    prim.attributes.push(subdivision);
+
+    if let Some(material) = material {
+        prim.metadata.push((String::from("prepend apiSchemas"), Value::Array(vec![Value::String(String::from("MaterialBindingAPI"))])));
+        prim.relationships.push(Relationship {
+            name: String::from("material:binding"),
+            custom: false,
+            targets: vec![material.to_string()]
+        });
+    }
+
+    prim
+}
+
+fn write_material(material: &crate::Material, name: &str, path: &str, images: &[crate::Image]) -> Prim {
+    let mut prim = Prim::new(Some("Material"), name);
+    let mut shader = Prim::new(Some("Shader"), "PreviewSurface");
+
+    let info_id = |id: &str| {
+        let mut attribute = Attribute::new("token", "info:id", Some(Value::String(id.to_string())));
+        attribute.uniform = true;
+        attribute
+    };
+
+    let connected = |type_name: &str, name: &str, value: Option<Value>, connection: String| {
+        let mut attribute = Attribute::new(type_name, name, value);
+        attribute.connections.push(connection);
+        attribute
+    };
+
+    shader.attributes.push(info_id("UsdPreviewSurface"));
+
+    let color = material.albedo_color;
+
+    let mut attributes = vec![
+        Attribute::new("color3f", "inputs:diffuseColor", Some(floats(&[color.x, color.y, color.z]))),
+        Attribute::new("float", "inputs:opacity", Some(float(color.w))),
+        Attribute::new("float", "inputs:roughness", Some(float(material.roughness))),
+        Attribute::new("float", "inputs:metallic", Some(float(material.metallic)))
+    ];
+
+    if material.alpha_mode == crate::AlphaMode::Cutoff {
+        attributes.push(Attribute::new("float", "inputs:opacityThreshold", Some(float(material.alpha_cutoff))));
+    }
+
+    let textures = [
+        (material.albedo_texture, "inputs:diffuseColor", "DiffuseTexture", "rgb"),
+        (material.normal_texture, "inputs:normal", "NormalTexture", "rgb"),
+        (material.emissive_texture, "inputs:emissiveColor", "EmissiveTexture", "rgb"),
+        (material.occlusion_texture, "inputs:occlusion", "OcclusionTexture", "r"),
+        (material.roughness_texture, "inputs:roughness", "RoughnessTexture", "g"),
+        (material.metallic_texture, "inputs:metallic", "MetallicTexture", "b")
+    ];
+
+    let mut has_textures = false;
+
+    for (texture, input, texture_name, channel) in textures {
+        let Some(file) = texture.and_then(|texture| images.get(texture)).and_then(|image| image.path.as_deref()) else {
+            continue;
+        };
+
+        let output = format!("{path}/{texture_name}.outputs:{channel}");
+
+        match attributes.iter_mut().find(|attribute| attribute.name == input) {
+            Some(attribute) => attribute.connections.push(output),
+            None => attributes.push(connected(if channel == "rgb" { "color3f" } else { "float" }, input, None, output))
+        }
+
+        let mut texture_prim = Prim::new(Some("Shader"), texture_name);
+        texture_prim.attributes.push(info_id("UsdUVTexture"));
+        texture_prim.attributes.push(Attribute::new("asset", "inputs:file", Some(Value::Asset(file.to_string()))));
+        texture_prim.attributes.push(connected("float2", "inputs:st", None, format!("{path}/TexCoordReader.outputs:result")));
+        texture_prim.attributes.push(Attribute::new(if channel == "rgb" { "float3" } else { "float" }, &format!("outputs:{channel}"), None));
+
+        prim.children.push(texture_prim);
+        has_textures = true;
+    }
+
+    if has_textures {
+        let mut reader = Prim::new(Some("Shader"), "TexCoordReader");
+        reader.attributes.push(info_id("UsdPrimvarReader_float2"));
+        reader.attributes.push(Attribute::new("string", "inputs:varname", Some(Value::String(String::from("st")))));
+        reader.attributes.push(Attribute::new("float2", "outputs:result", None));
+        prim.children.push(reader);
+    }
+
+    shader.attributes.extend(attributes);
+    shader.attributes.push(Attribute::new("token", "outputs:surface", None));
+
+    prim.attributes.push(connected("token", "outputs:surface", None, format!("{path}/PreviewSurface.outputs:surface")));
+    prim.children.insert(0, shader);
+
+    prim
+}
+
+fn write_prim(text: &mut String, prim: &Prim, depth: usize) {
+    let indent = "    ".repeat(depth);
+
+    *text += &format!("{indent}{} ", prim.specifier.as_str());
+
+    if let Some(type_name) = &prim.type_name {
+        *text += &format!("{type_name} ");
+    }
+
+    *text += &write_string(&prim.name);
+
+    if !prim.metadata.is_empty() {
+        *text += " (\n";
+
+        for (key, value) in &prim.metadata {
+            *text += &format!("{indent}    {key} = {}\n", write_value(value));
+        }
+
+        *text += &format!("{indent})");
+    }
+
+    *text += &format!("\n{indent}{{\n");
+
+    for attribute in &prim.attributes {
+        let mut declaration = String::new();
+
+        if attribute.custom {
+            declaration += "custom ";
+        }
+
+        if attribute.uniform {
+            declaration += "uniform ";
+        }
+
+        declaration += &format!("{} {}", attribute.type_name, attribute.name);
+
+        if attribute.value.is_some() || attribute.connections.is_empty() {
+            *text += &format!("{indent}    {declaration}");
+
+            if let Some(value) = &attribute.value {
+                *text += &format!(" = {}", write_value(value));
+            }
+
+            if !attribute.metadata.is_empty() {
+                *text += " (\n";
+
+                for (key, value) in &attribute.metadata {
+                    *text += &format!("{indent}        {key} = {}\n", write_value(value));
+                }
+
+                *text += &format!("{indent}    )");
+            }
+
+            *text += "\n";
+        }
+
+        if !attribute.connections.is_empty() {
+            *text += &format!("{indent}    {declaration}.connect = {}\n", write_targets(&attribute.connections));
+        }
+    }
+
+    for relationship in &prim.relationships {
+        let custom = if relationship.custom { "custom " } else { "" };
+        *text += &format!("{indent}    {custom}rel {}", relationship.name);
+
+        if !relationship.targets.is_empty() {
+            *text += &format!(" = {}", write_targets(&relationship.targets));
+        }
+
+        *text += "\n";
+    }
+
+    for child in &prim.children {
+        *text += "\n";
+        write_prim(text, child, depth + 1);
+    }
+
+    *text += &format!("{indent}}}\n");
+}
+
+fn write_targets(targets: &[String]) -> String {
+    match targets {
+        [target] => format!("<{target}>"),
+        targets => format!("[{}]", targets.iter().map(|target| format!("<{target}>")).collect::<Vec<_>>().join(", "))
+    }
+}
+
+fn write_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))
+}
+
+fn write_value(value: &Value) -> String {
+    let join = |values: &[Value]| values.iter().map(write_value).collect::<Vec<_>>().join(", ");
+
+    match value {
+        Value::None => String::from("None"),
+        Value::Bool(value) => value.to_string(),
+        Value::Int(value) => value.to_string(),
+        Value::Float(value) if value.is_finite() => format!("{value:?}"),
+        Value::Float(value) if value.is_nan() => String::from("nan"),
+        Value::Float(value) => String::from(if *value > 0.0 { "inf" } else { "-inf" }),
+        Value::String(value) => write_string(value),
+        Value::Asset(value) => format!("@{value}@"),
+        Value::Path(value) => format!("<{value}>"),
+        Value::Tuple(values) => format!("({})", join(values)),
+        Value::Array(values) => format!("[{}]", join(values)),
+        Value::Dictionary(entries) => {
+            let entries = entries.iter().map(|(key, value)| {
+                let type_name = match value {
+                    Value::Bool(_) => "bool",
+                    Value::Int(_) => "int",
+                    Value::Float(_) => "double",
+                    Value::Asset(_) => "asset",
+                    Value::Dictionary(_) => "dictionary",
+                    _ => "string"
+                };
+
+                format!("{type_name} {key} = {}", write_value(value))
+            }).collect::<Vec<_>>();
+
+            format!("{{ {} }}", entries.join("; "))
+        }
+    }
+}
+
+struct SceneBuilder<'a> {
+    usd:              &'a Usd,
+    meshes:           Vec<crate::Mesh>,
+    materials:        Vec<crate::Material>,
+    material_indices: HashMap<String, Option<usize>>,
+    images:           Vec<crate::Image>,
+    image_indices:    HashMap<String, usize>,
+    nodes:            Vec<crate::Node>
+}
+
+impl SceneBuilder<'_> {
+    /// Adds a node for an Xform, Mesh or untyped prim and its children. Materials, shaders and
+    /// scopes that only hold materials aren't part of the hierarchy.
+    fn node(&mut self, prim: &Prim, path: &str, depth: usize) -> Result<Option<usize>, ImportError> {
+        if depth > 256 {
+            return Err(parse_error("The prim hierarchy is too deep."));
+        }
+
+        if prim.specifier != Specifier::Def || is_shading_prim(prim) {
+            return Ok(None);
+        }
+
+        let index = self.nodes.len();
+
+        self.nodes.push(crate::Node {
+            transform: local_transform(prim)?,
+            meshes: Vec::new(),
+            children: Vec::new(),
+            name: Some(prim.name.clone()),
+            extras: None
+        });
+
+        if prim.is_type("Mesh") {
+            self.nodes[index].meshes = self.mesh(prim, path)?;
+        }
+
+        let mut children = Vec::new();
+
+        for child in &prim.children {
+            if let Some(node) = self.node(child, &format!("{path}/{}", child.name), depth + 1)? {
+                children.push(node);
+            }
+        }
+
+        self.nodes[index].children = children;
+
+        Ok(Some(index))
+    }
+
+    /// Gets the index of the material a prim is bound to.
+    fn binding(&mut self, prim: &Prim) -> Result<Option<usize>, ImportError> {
+        match prim.relationship("material:binding").and_then(|binding| binding.targets.first()) {
+            Some(target) => self.material(target),
+            None => Ok(None)
+        }
+    }
+
+    fn material(&mut self, path: &str) -> Result<Option<usize>, ImportError> {
+        if let Some(&material) = self.material_indices.get(path) {
+            return Ok(material);
+        }
+
+        let Some(prim) = self.usd.prim(path) else {
+            self.material_indices.insert(path.to_string(), None);
+            return Ok(None);
+        };
+
+        // Find the surface shader through the material's output, or look for one.
+        let shader = prim.attribute("outputs:surface")
+            .and_then(|surface| surface.connections.first())
+            .and_then(|connection| self.usd.prim(connection_prim(connection)))
+            .or_else(|| prim.children.iter().find(|child| child.value("info:id").and_then(Value::as_str) == Some("UsdPreviewSurface")));
+
+        let mut material = crate::Material {
+            albedo_color: Vec4::new(1.0, 1.0, 1.0, 1.0),
+            albedo_texture: None,
+            normal_texture: None,
+            metallic: 0.0,
+            metallic_texture: None,
+            roughness: 0.5,
+            roughness_texture: None,
+            occlusion_texture: None,
+            emissive_texture: None,
+            alpha_mode: crate::AlphaMode::Opaque,
+            alpha_cutoff: 0.5,
+            double_sided: false,
+            name: Some(prim.name.clone()),
+            extras: None
+        };
+
+        if let Some(shader) = shader {
+            let input = |name: &str| shader.value(name).and_then(Value::to_floats);
+
+            if let Some(&[r, g, b]) = input("inputs:diffuseColor").as_deref() {
+                material.albedo_color = Vec4::new(r, g, b, 1.0);
+            }
+
+            if let Some(&[opacity]) = input("inputs:opacity").as_deref() {
+                material.albedo_color.w = opacity;
+            }
+
+            if let Some(&[roughness]) = input("inputs:roughness").as_deref() {
+                material.roughness = roughness;
+            }
+
+            if let Some(&[metallic]) = input("inputs:metallic").as_deref() {
+                material.metallic = metallic;
+            }
+
+            material.albedo_texture = self.texture(shader, "inputs:diffuseColor")?;
+            material.normal_texture = self.texture(shader, "inputs:normal")?;
+            material.emissive_texture = self.texture(shader, "inputs:emissiveColor")?;
+            material.occlusion_texture = self.texture(shader, "inputs:occlusion")?;
+            material.roughness_texture = self.texture(shader, "inputs:roughness")?;
+            material.metallic_texture = self.texture(shader, "inputs:metallic")?;
+
+            let opacity_texture = self.texture(shader, "inputs:opacity")?;
+
+            match input("inputs:opacityThreshold").as_deref() {
+                Some(&[threshold]) if threshold > 0.0 => {
+                    material.alpha_mode = crate::AlphaMode::Cutoff;
+                    material.alpha_cutoff = threshold;
+                },
+                _ if material.albedo_color.w < 1.0 || opacity_texture.is_some() => material.alpha_mode = crate::AlphaMode::Blend,
+                _ => {}
+            }
+        }
+
+        self.materials.push(material);
+
+        let index = Some(self.materials.len() - 1);
+        self.material_indices.insert(path.to_string(), index);
+
+        Ok(index)
+    }
+
+    /// Gets the image of the texture an input is connected to.
+    fn texture(&mut self, shader: &Prim, input: &str) -> Result<Option<usize>, ImportError> {
+        let texture = shader.attribute(input)
+            .and_then(|input| input.connections.first())
+            .and_then(|connection| self.usd.prim(connection_prim(connection)));
+
+        let Some(file) = texture.and_then(|texture| texture.value("inputs:file")).and_then(Value::as_str) else {
+            return Ok(None);
+        };
+
+        if let Some(&image) = self.image_indices.get(file) {
+            return Ok(Some(image));
+        }
+
+        self.images.push(crate::Image {
+            path: Some(file.to_string()),
+            data_type: None,
+            data: None,
+            name: texture.map(|texture| texture.name.clone()),
+            extras: None
+        });
+
+        self.image_indices.insert(file.to_string(), self.images.len() - 1);
+
+        Ok(Some(self.images.len() - 1))
+    }
+
+    /// Creates the meshes of a Mesh prim. Faces are split into a mesh for each material, using
+    /// any face subsets with their own material.
+    fn mesh(&mut self, prim: &Prim, path: &str) -> Result<Vec<usize>, ImportError> {
+        let error = |message: &str| parse_error(format!("Mesh \"{path}\": {message}"));
+
+        let points = vec_array::<3>(prim.value("points")).unwrap_or_default();
+        let counts = int_array(prim.value("faceVertexCounts")).unwrap_or_default();
+        let indices = int_array(prim.value("faceVertexIndices")).unwrap_or_default();
+
+        let normals = Primvar::<3>::read(prim, "primvars:normals", "vertex").or_else(|| Primvar::read(prim, "normals", "vertex"));
+        let colors = Primvar::<3>::read(prim, "primvars:displayColor", "constant");
+        let opacities = Primvar::<1>::read(prim, "primvars:displayOpacity", "constant");
+
+        // Use the "st" primvar, or any other texture coordinates.
+        let tex_coords = Primvar::<2>::read(prim, "primvars:st", "constant").or_else(|| {
+            let name = &prim.attributes.iter().find(|attribute| attribute.name.starts_with("primvars:") && attribute.type_name == "texCoord2f[]")?.name;
+            Primvar::read(prim, name, "constant")
+        });
+
+        let left_handed = prim.value("orientation").and_then(Value::as_str) == Some("leftHanded");
+
+        let mut face_materials = vec![self.binding(prim)?; counts.len()];
+
+        for subset in prim.children.iter().filter(|child| child.is_type("GeomSubset")) {
+            if subset.value("elementName").and_then(Value::as_str).is_some_and(|element| element != "face") {
+                continue;
+            }
+
+            let material = self.binding(subset)?;
+
+            for face in int_array(subset.value("indices")).unwrap_or_default() {
+                if let Some(face_material) = usize::try_from(face).ok().and_then(|face| face_materials.get_mut(face)) {
+                    *face_material = material;
+                }
+            }
+        }
+
+        struct Builder {
+            vertices: Vec<Vertex>,
+            indices:  Vec<u32>,
+            cache:    HashMap<[Option<usize>; 5], u32>
+        }
+
+        let mut builders: BTreeMap<Option<usize>, Builder> = BTreeMap::new();
+        let mut start = 0;
+
+        for (face, &count) in counts.iter().enumerate() {
+            let end = start + count.max(0) as usize;
+
+            let Some(face_indices) = indices.get(start..end) else {
+                return Err(error("faceVertexIndices is shorter than the face vertex counts."));
+            };
+
+            let builder = builders.entry(face_materials[face]).or_insert_with(|| Builder { vertices: Vec::new(), indices: Vec::new(), cache: HashMap::new() });
+
+            let mut corners = Vec::with_capacity(face_indices.len());
+
+            for (corner, &point) in face_indices.iter().enumerate() {
+                let point = usize::try_from(point).map_err(|_| error("A face vertex index is negative."))?;
+                let face_vertex = start + corner;
+
+                let key = [
+                    Some(point),
+                    Primvar::index_of(&normals, face, face_vertex, point),
+                    Primvar::index_of(&tex_coords, face, face_vertex, point),
+                    Primvar::index_of(&colors, face, face_vertex, point),
+                    Primvar::index_of(&opacities, face, face_vertex, point)
+                ];
+
+                if let Some(&vertex) = builder.cache.get(&key) {
+                    corners.push(vertex);
+                    continue;
+                }
+
+                let position = *points.get(point).ok_or_else(|| error("A face vertex index is out of range."))?;
+
+                let normal = Primvar::value_of(&normals, key[1]).map_err(|_| error("A normal index is out of range."))?.map_or(Vec3::new(0.0, 0.0, 0.0), |[x, y, z]| Vec3::new(x, y, z));
+
+                // USD texture coordinates start at the bottom left, however modelo's start at the top left.
+                let tex_coord = Primvar::value_of(&tex_coords, key[2]).map_err(|_| error("A texture coordinate index is out of range."))?.map_or(Vec2::new(0.0, 0.0), |[u, v]| Vec2::new(u, 1.0 - v));
+
+                let [r, g, b] = Primvar::value_of(&colors, key[3]).map_err(|_| error("A color index is out of range."))?.unwrap_or([1.0; 3]);
+                let [a] = Primvar::value_of(&opacities, key[4]).map_err(|_| error("A opacity index is out of range."))?.unwrap_or([1.0]);
+
+                builder.vertices.push(Vertex {
+                    position: Vec3::new(position[0], position[1], position[2]),
+                    tex_coord,
+                    color: Vec4::new(r, g, b, a),
+                    normal,
+                    tangent: Vec4::new(0.0, 0.0, 0.0, 0.0)
+                });
+
+                let vertex = builder.vertices.len() as u32 - 1;
+                builder.cache.insert(key, vertex);
+                corners.push(vertex);
+            }
+
+            if left_handed {
+                corners.reverse();
+            }
+
+            // Triangulate polygons as a fan.
+            for i in 2..corners.len() {
+                builder.indices.extend_from_slice(&[corners[0], corners[i - 1], corners[i]]);
+            }
+
+            start = end;
+        }
+
+        let mut meshes = Vec::new();
+
+        for (material, builder) in builders {
+            self.meshes.push(crate::Mesh {
+                bounds: BoundingBox::from_vertices(&builder.vertices),
+                vertices: builder.vertices,
+                indices: Some(builder.indices),
+                material,
+                has_normals: normals.is_some(),
+                name: Some(prim.name.clone()),
+                extras: None
+            });
+
+            meshes.push(self.meshes.len() - 1);
+        }
+
+        Ok(meshes)
+    }
+}
+
+fn is_shading_prim(prim: &Prim) -> bool {
+    match prim.type_name.as_deref() {
+        Some("Material" | "Shader" | "NodeGraph" | "GeomSubset") => true,
+        Some("Scope") => !prim.children.is_empty() && prim.children.iter().all(is_shading_prim),
+        _ => false
+    }
+}
+
+/// Gets the path of the prim from a property path, such as `/Looks/Red/Shader.outputs:surface`.
+fn connection_prim(connection: &str) -> &str {
+    connection.rsplit_once('.').map_or(connection, |(prim, _)| prim)
+}
+
+fn int_array(value: Option<&Value>) -> Option<Vec<i64>> {
+    value?.as_array()?.iter().map(Value::as_i64).collect()
+}
+
+fn vec_array<const N: usize>(value: Option<&Value>) -> Option<Vec<[f32; N]>> {
+    value?.as_array()?.iter().map(|value| value.to_floats()?.try_into().ok()).collect()
+}
+
+/// A primvar, which has an interpolation that says how its values map to the mesh.
+struct Primvar<const N: usize> {
+    values:        Vec<[f32; N]>,
+    indices:       Option<Vec<i64>>,
+    interpolation: Interpolation
+}
+
+#[derive(Clone, Copy)]
+enum Interpolation {
+    Constant,
+    Uniform,
+    Vertex,
+    FaceVarying
+}
+
+impl<const N: usize> Primvar<N> {
+    fn read(prim: &Prim, name: &str, default_interpolation: &str) -> Option<Self> {
+        let attribute = prim.attribute(name)?;
+        let values = vec_array::<N>(attribute.value.as_ref())?;
+
+        let interpolation = match attribute.metadata("interpolation").and_then(Value::as_str).unwrap_or(default_interpolation) {
+            "uniform" => Interpolation::Uniform,
+            "vertex" | "varying" => Interpolation::Vertex,
+            "faceVarying" => Interpolation::FaceVarying,
+            _ => Interpolation::Constant
+        };
+
+        Some(Self {
+            values,
+            indices: int_array(prim.value(&format!("{name}:indices"))),
+            interpolation
+        })
+    }
+
+    fn index_of(primvar: &Option<Self>, face: usize, face_vertex: usize, point: usize) -> Option<usize> {
+        primvar.as_ref().and_then(|primvar| primvar.index(face, face_vertex, point))
+    }
+
+    /// Gets the value at an index from [`Primvar::index_of`], or an error if it's out of range.
+    fn value_of(primvar: &Option<Self>, index: Option<usize>) -> Result<Option<[f32; N]>, ()> {
+        match (primvar, index) {
+            (Some(primvar), Some(index)) => primvar.values.get(index).copied().map(Some).ok_or(()),
+            _ => Ok(None)
+        }
+    }
+
+    fn index(&self, face: usize, face_vertex: usize, point: usize) -> Option<usize> {
+        let index = match self.interpolation {
+            Interpolation::Constant => 0,
+            Interpolation::Uniform => face,
+            Interpolation::Vertex => point,
+            Interpolation::FaceVarying => face_vertex
+        };
+
+        match &self.indices {
+            Some(indices) => indices.get(index).and_then(|&index| usize::try_from(index).ok()),
+            None => Some(index)
+        }
+    }
+}
+
+/// Works out the local transform of a prim from its transform operations.
+fn local_transform(prim: &Prim) -> Result<Mat4, ImportError> {
+    let mut transform = Mat4::identity();
+
+    let Some(order) = prim.value("xformOpOrder").and_then(Value::as_array) else {
+        return Ok(transform);
+    };
+
+    for op in order {
+        let Some(op) = op.as_str() else {
+            continue;
+        };
+
+        let (invert, name) = match op.strip_prefix("!invert!") {
+            Some(name) => (true, name),
+            None => (false, op)
+        };
+
+        let Some(kind) = name.strip_prefix("xformOp:").and_then(|kind| kind.split(':').next()) else {
+            continue;
+        };
+
+        let error = || parse_error(format!("Prim \"{}\" has an invalid {name}.", prim.name));
+        let value = prim.value(name);
+
+        let vec3 = || match value.and_then(Value::to_floats).as_deref() {
+            Some(&[x, y, z]) => Ok(Vec3::new(x, y, z)),
+            _ => Err(error())
+        };
+
+        let identity = Quat::new(0.0, 0.0, 0.0, 1.0);
+        let one = Vec3::new(1.0, 1.0, 1.0);
+        let zero = Vec3::new(0.0, 0.0, 0.0);
+
+        let op_transform = match kind {
+            "translate" => Mat4::from_trs(vec3()?, identity, one),
+            "scale" => Mat4::from_trs(zero, identity, vec3()?),
+            "rotateX" | "rotateY" | "rotateZ" => {
+                let angle = value.and_then(Value::as_f64).ok_or_else(error)? as f32;
+                rotation(&[(kind.chars().last().unwrap_or('Z'), angle)])
+            },
+            "rotateXYZ" | "rotateXZY" | "rotateYXZ" | "rotateYZX" | "rotateZXY" | "rotateZYX" => {
+                let angles = vec3()?;
+                let angle = |axis: char| match axis {
+                    'X' => angles.x,
+                    'Y' => angles.y,
+                    _ => angles.z
+                };
+
+                rotation(&kind[6..].chars().map(|axis| (axis, angle(axis))).collect::<Vec<_>>())
+            },
+            "orient" => match value.and_then(Value::to_floats).as_deref() {
+                // Quaternions are written with the real part first.
+                Some(&[w, x, y, z]) => Mat4::from_trs(zero, Quat::new(x, y, z, w), one),
+                _ => return Err(error())
+            },
+            "transform" => match value {
+                Some(Value::Tuple(rows)) if rows.len() == 4 => {
+                    let rows = rows.iter().map(|row| match row.to_floats().as_deref() {
+                        Some(&[x, y, z, w]) => Ok(Vec4::new(x, y, z, w)),
+                        _ => Err(error())
+                    }).collect::<Result<Vec<_>, _>>()?;
+
+                    // USD matrices transform row vectors, so are the transpose of modelo's.
+                    let matrix = Mat4::new(rows[0], rows[1], rows[2], rows[3]);
+                    Mat4::new(matrix.column0(), matrix.column1(), matrix.column2(), matrix.column3())
+                },
+                _ => return Err(error())
+            },
+            _ => continue
+        };
+
+        transform = transform * if invert { affine_inverse(&op_transform) } else { op_transform };
+    }
+
+    Ok(transform)
+}
+
+/// Creates a rotation from angles in degrees around each axis, applied in the given order.
+fn rotation(order: &[(char, f32)]) -> Mat4 {
+    order.iter().fold(Mat4::identity(), |transform, &(axis, angle)| {
+        let (sin, cos) = (angle.to_radians() / 2.0).sin_cos();
+
+        let quat = match axis {
+            'X' => Quat::new(sin, 0.0, 0.0, cos),
+            'Y' => Quat::new(0.0, sin, 0.0, cos),
+            _ => Quat::new(0.0, 0.0, sin, cos)
+        };
+
+        // Later rotations are applied after earlier ones, so go on the left.
+        Mat4::from_trs(Vec3::new(0.0, 0.0, 0.0), quat, Vec3::new(1.0, 1.0, 1.0)) * transform
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Identifier(String),
+    String(String),
+    Asset(String),
+    Path(String),
+    Number(String),
+    Punct(char),
+    End
+}
+
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, ImportError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    let mut line = 1;
+
+    // Skip the "#usda 1.0" header.
+    for (_, c) in chars.by_ref() {
+        if c == '\n' {
+            line += 1;
+            break;
+        }
+    }
+
+    while let Some((start, c)) = chars.next() {
+        let token = match c {
+            '\n' => {
+                line += 1;
+                continue;
+            },
+            c if c.is_whitespace() => continue,
+            '#' => {
+                while chars.next_if(|&(_, c)| c != '\n').is_some() {}
+                continue;
+            },
+            '"' | '\'' => {
+                let triple = text[start..].starts_with(&c.to_string().repeat(3));
+                let quote = if triple { c.to_string().repeat(3) } else { c.to_string() };
+                let mut value = String::new();
+
+                if triple {
+                    chars.next();
+                    chars.next();
+                }
+
+                let start_line = line;
+
+                loop {
+                    let Some((i, c)) = chars.next() else {
+                        return Err(parse_error(format!("Line {start_line}: Unterminated string.")));
+                    };
+
+                    if c == '\n' {
+                        line += 1;
+                    }
+
+                    if c == '\\' {
+                        match chars.next() {
+                            Some((_, 'n')) => value.push('\n'),
+                            Some((_, 't')) => value.push('\t'),
+                            Some((_, c)) => value.push(c),
+                            None => {}
+                        }
+                    } else if text[i..].starts_with(&quote) {
+                        for _ in 1..quote.len() {
+                            chars.next();
+                        }
+
+                        break;
+                    } else {
+                        value.push(c);
+                    }
+                }
+
+                Token::String(value)
+            },
+            '@' => {
+                let delimiter = if text[start..].starts_with("@@@") { "@@@" } else { "@" };
+                let contents = start + delimiter.len();
+
+                let Some(length) = text[contents..].find(delimiter) else {
+                    return Err(parse_error(format!("Line {line}: Unterminated asset path.")));
+                };
+
+                while chars.next_if(|&(i, _)| i < contents + length + delimiter.len()).is_some() {}
+
+                Token::Asset(text[contents..contents + length].to_string())
+            },
+            '<' => {
+                let Some(length) = text[start + 1..].find('>') else {
+                    return Err(parse_error(format!("Line {line}: Unterminated path.")));
+                };
+
+                while chars.next_if(|&(i, _)| i <= start + 1 + length).is_some() {}
+
+                Token::Path(text[start + 1..start + 1 + length].to_string())
+            },
+            c if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => {
+                let mut end = start + c.len_utf8();
+
+                while let Some((i, c)) = chars.next_if(|&(_, c)| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+')) {
+                    end = i + c.len_utf8();
+                }
+
+                Token::Number(text[start..end].to_string())
+            },
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = start + c.len_utf8();
+
+                while let Some((i, c)) = chars.next_if(|&(_, c)| c.is_alphanumeric() || matches!(c, '_' | ':' | '.')) {
+                    end = i + c.len_utf8();
+                }
+
+                Token::Identifier(text[start..end].to_string())
+            },
+            '(' | ')' | '[' | ']' | '{' | '}' | '=' | ',' | ';' | ':' => Token::Punct(c),
+            c => return Err(parse_error(format!("Line {line}: Unexpected character '{c}'.")))
+        };
+
+        tokens.push((token, line));
+    }
+
+    tokens.push((Token::End, line));
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens:   Vec<(Token, usize)>,
+    position: usize
+}
+
+const LIST_OPS: [&str; 5] = ["prepend", "append", "add", "delete", "reorder"];
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position].0
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.position].0.clone();
+
+        if token != Token::End {
+            self.position += 1;
+        }
+
+        token
+    }
+
+    fn error(&self, message: &str) -> ImportError {
+        parse_error(format!("Line {}: {message}", self.tokens[self.position].1))
+    }
+
+    fn expect(&mut self, punct: char) -> Result<(), ImportError> {
+        if self.peek() == &Token::Punct(punct) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{punct}'.")))
+        }
+    }
+
+    fn eat(&mut self, punct: char) -> bool {
+        let found = self.peek() == &Token::Punct(punct);
+
+        if found {
+            self.position += 1;
+        }
+
+        found
+    }
+
+    fn identifier(&mut self) -> Result<String, ImportError> {
+        match self.next() {
+            Token::Identifier(identifier) => Ok(identifier),
+            _ => {
+                self.position -= 1;
+                Err(self.error("Expected a name."))
+            }
+        }
+    }
+
+    /// Parses a metadata block, such as `( upAxis = "Z" )`.
+    fn metadata(&mut self) -> Result<Vec<(String, Value)>, ImportError> {
+        self.expect('(')?;
+
+        let mut metadata = Vec::new();
+
+        loop {
+            match self.next() {
+                Token::Punct(')') => return Ok(metadata),
+                Token::Punct(';') => {},
+
+                // A string on its own is the documentation.
+                Token::String(doc) => metadata.push((String::from("doc"), Value::String(doc))),
+                Token::Identifier(mut key) => {
+                    if LIST_OPS.contains(&key.as_str()) {
+                        key = format!("{key} {}", self.identifier()?);
+                    }
+
+                    self.expect('=')?;
+                    metadata.push((key, self.value()?));
+                },
+                _ => {
+                    self.position -= 1;
+                    return Err(self.error("Expected a metadata field."));
+                }
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, ImportError> {
+        let value = match self.next() {
+            Token::Number(number) => parse_number(&number).ok_or_else(|| {
+                self.position -= 1;
+                self.error(&format!("Invalid number \"{number}\"."))
+            })?,
+            Token::String(value) => Value::String(value),
+            Token::Path(path) => Value::Path(path),
+            Token::Asset(asset) => {
+                // References can be followed by the path of a prim in the asset.
+                if let Token::Path(_) = self.peek() {
+                    self.position += 1;
+                }
+
+                Value::Asset(asset)
+            },
+            Token::Identifier(identifier) => match identifier.as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                "None" => Value::None,
+                "inf" => Value::Float(f64::INFINITY),
+                "nan" => Value::Float(f64::NAN),
+                _ => Value::String(identifier)
+            },
+            Token::Punct('(') => Value::Tuple(self.values(')')?),
+            Token::Punct('[') => Value::Array(self.values(']')?),
+            Token::Punct('{') => Value::Dictionary(self.dictionary()?),
+            _ => {
+                self.position -= 1;
+                return Err(self.error("Expected a value."));
+            }
+        };
+
+        Ok(value)
+    }
+
+    fn values(&mut self, close: char) -> Result<Vec<Value>, ImportError> {
+        let mut values = Vec::new();
+
+        while !self.eat(close) {
+            values.push(self.value()?);
+
+            if !self.eat(',') && self.peek() != &Token::Punct(close) {
+                return Err(self.error(&format!("Expected ',' or '{close}'.")));
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// Parses the entries of a dictionary, after the opening brace.
+    fn dictionary(&mut self) -> Result<Vec<(String, Value)>, ImportError> {
+        let mut entries = Vec::new();
+
+        loop {
+            match self.next() {
+                Token::Punct('}') => return Ok(entries),
+                Token::Punct(';' | ',') => {},
+                Token::Identifier(_) => {
+                    if self.eat('[') {
+                        self.expect(']')?;
+                    }
+
+                    let key = match self.next() {
+                        Token::Identifier(key) | Token::String(key) => key,
+                        _ => {
+                            self.position -= 1;
+                            return Err(self.error("Expected a dictionary key."));
+                        }
+                    };
+
+                    self.expect('=')?;
+                    entries.push((key, self.value()?));
+                },
+
+                // Time samples are a dictionary of times to values.
+                Token::Number(time) => {
+                    self.expect(':')?;
+                    entries.push((time, self.value()?));
+                },
+                _ => {
+                    self.position -= 1;
+                    return Err(self.error("Expected a dictionary entry."));
+                }
+            }
+        }
+    }
+
+    fn prim(&mut self) -> Result<Prim, ImportError> {
+        let specifier = match self.identifier()?.as_str() {
+            "def" => Specifier::Def,
+            "over" => Specifier::Over,
+            "class" => Specifier::Class,
+            _ => {
+                self.position -= 1;
+                return Err(self.error("Expected \"def\", \"over\" or \"class\"."));
+            }
+        };
+
+        let type_name = match self.peek() {
+            Token::Identifier(_) => Some(self.identifier()?),
+            _ => None
+        };
+
+        let name = match self.next() {
+            Token::String(name) => name,
+            _ => {
+                self.position -= 1;
+                return Err(self.error("Expected the name of the prim."));
+            }
+        };
+
+        let mut prim = Prim {
+            specifier,
+            type_name,
+            name,
+            metadata: Vec::new(),
+            attributes: Vec::new(),
+            relationships: Vec::new(),
+            children: Vec::new()
+        };
+
+        if self.peek() == &Token::Punct('(') {
+            prim.metadata = self.metadata()?;
+        }
+
+        self.expect('{')?;
+
+        loop {
+            match self.peek().clone() {
+                Token::Punct('}') => {
+                    self.position += 1;
+                    return Ok(prim);
+                },
+                Token::Punct(';') => self.position += 1,
+                Token::Identifier(identifier) => match identifier.as_str() {
+                    "def" | "over" | "class" => prim.children.push(self.prim()?),
+                    "variantSet" => self.skip_variant_set()?,
+                    _ => self.property(&mut prim)?
+                },
+                _ => return Err(self.error("Expected a prim or property."))
+            }
+        }
+    }
+
+    fn skip_variant_set(&mut self) -> Result<(), ImportError> {
+        self.position += 1;
+        self.next();
+        self.expect('=')?;
+        self.expect('{')?;
+
+        let mut depth = 1;
+
+        while depth > 0 {
+            match self.next() {
+                Token::Punct('{') => depth += 1,
+                Token::Punct('}') => depth -= 1,
+                Token::End => return Err(self.error("Unexpected end of file.")),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn property(&mut self, prim: &mut Prim) -> Result<(), ImportError> {
+        let mut custom = false;
+        let mut uniform = false;
+
+        let mut identifier = self.identifier()?;
+
+        if LIST_OPS.contains(&identifier.as_str()) {
+            // Reordering properties or children doesn't affect anything that's read.
+            if identifier == "reorder" && matches!(self.peek(), Token::Identifier(name) if name == "properties" || name == "nameChildren") {
+                self.position += 1;
+                self.expect('=')?;
+                self.value()?;
+                return Ok(());
+            }
+
+            identifier = self.identifier()?;
+        }
+
+        loop {
+            match identifier.as_str() {
+                "custom" => custom = true,
+                "uniform" | "varying" | "config" => uniform = identifier == "uniform",
+                _ => break
+            }
+
+            identifier = self.identifier()?;
+        }
+
+        if identifier == "rel" {
+            let name = self.identifier()?;
+
+            let targets = if self.eat('=') {
+                match self.value()? {
+                    Value::Path(path) => vec![path],
+                    Value::Array(paths) => paths.iter().filter_map(|path| path.as_str().map(String::from)).collect(),
+                    _ => Vec::new()
+                }
+            } else {
+                Vec::new()
+            };
+
+            if self.peek() == &Token::Punct('(') {
+                self.metadata()?;
+            }
+
+            prim.relationships.push(Relationship { name, custom, targets });
+
+            return Ok(());
+        }
+
+        let mut type_name = identifier;
+
+        if self.eat('[') {
+            self.expect(']')?;
+            type_name += "[]";
+        }
+
+        let name = self.identifier()?;
+
+        if let Some(name) = name.strip_suffix(".connect") {
+            self.expect('=')?;
+
+            let connections = match self.value()? {
+                Value::Path(path) => vec![path],
+                Value::Array(paths) => paths.iter().filter_map(|path| path.as_str().map(String::from)).collect(),
+                _ => Vec::new()
+            };
+
+            prim.attribute_mut(&type_name, name).connections = connections;
+
+            return Ok(());
+        }
+
+        // Only the first time sample is used.
+        let (name, time_samples) = match name.strip_suffix(".timeSamples") {
+            Some(name) => (name.to_string(), true),
+            None => (name, false)
+        };
+
+        let mut value = None;
+
+        if self.eat('=') {
+            value = Some(self.value()?);
+
+            if time_samples {
+                value = match value {
+                    Some(Value::Dictionary(samples)) => samples.into_iter().next().map(|(_, value)| value),
+                    _ => None
+                };
+            }
+        }
+
+        let metadata = if self.peek() == &Token::Punct('(') {
+            self.metadata()?
+        } else {
+            Vec::new()
+        };
+
+        let attribute = prim.attribute_mut(&type_name, &name);
+        attribute.custom = custom;
+        attribute.uniform = uniform;
+        attribute.value = value.or(attribute.value.take());
+        attribute.metadata.extend(metadata);
+
+        Ok(())
+    }
+}
+
+fn parse_number(number: &str) -> Option<Value> {
+    if number.contains(['.', 'e', 'E']) || number.ends_with("inf") || number.ends_with("nan") {
+        number.parse().ok().map(Value::Float)
+    } else {
+        number.parse().ok().map(Value::Int)
+    }
+}
+
+fn parse_error<T: ToString>(message: T) -> ImportError {
+    ImportError::new(ImportErrorType::StringParseError, message)
+}
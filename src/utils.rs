@@ -1,3 +1,8 @@
+/// Reinterprets a byte slice as a slice of `TTo`.
+///
+/// # Safety
+///
+/// The slice must be suitably aligned for `TTo`, and every bit pattern must be a valid `TTo`.
 pub unsafe fn reinterpret_cast_slice<TTo>(slice: &[u8]) -> &[TTo] {
     std::slice::from_raw_parts::<TTo>(slice.as_ptr() as *const _, slice.len() / std::mem::size_of::<TTo>())
 }
@@ -14,4 +19,163 @@ pub fn cast_slice_to_type<TFrom, TTo>(slice: &[TFrom]) -> Vec<TTo> where TFrom :
         .collect();
 
     vec
-}
\ No newline at end of file
+}
+
+/// Reads little-endian values from a byte slice, for the binary formats that are laid out as a
+/// header of offsets to lumps.
+pub(crate) struct LeReader<'a> {
+    data:     &'a [u8],
+    position: usize
+}
+
+impl<'a> LeReader<'a> {
+    pub(crate) fn new(data: &'a [u8], position: usize) -> Self {
+        Self {
+            data,
+            position
+        }
+    }
+
+    /// Reads a header's unsigned 32-bit counts and offsets.
+    pub(crate) fn counts<const N: usize>(&mut self) -> Result<[usize; N], crate::ImportError> {
+        let mut counts = [0; N];
+
+        for count in &mut counts {
+            *count = self.u32()? as usize;
+        }
+
+        Ok(counts)
+    }
+
+    pub(crate) fn position(&self) -> usize {
+        self.position
+    }
+
+    pub(crate) fn bytes(&mut self, length: usize) -> Result<&'a [u8], crate::ImportError> {
+        let bytes = self.position.checked_add(length)
+            .and_then(|end| self.data.get(self.position..end))
+            .ok_or_else(|| crate::ImportError::new(crate::ImportErrorType::StringParseError, format!("Unexpected end of file at offset {}.", self.position)))?;
+
+        self.position += length;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], crate::ImportError> {
+        Ok(self.bytes(N)?.try_into().unwrap())
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, crate::ImportError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    pub(crate) fn i16(&mut self) -> Result<i16, crate::ImportError> {
+        Ok(i16::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, crate::ImportError> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn i32(&mut self) -> Result<i32, crate::ImportError> {
+        Ok(i32::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, crate::ImportError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn f32(&mut self) -> Result<f32, crate::ImportError> {
+        Ok(f32::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn vec3(&mut self) -> Result<crate::Vec3, crate::ImportError> {
+        Ok(crate::Vec3::new(self.f32()?, self.f32()?, self.f32()?))
+    }
+
+    /// Reads a fixed size string, which ends at the first null.
+    pub(crate) fn string(&mut self, length: usize) -> Result<String, crate::ImportError> {
+        let bytes = self.bytes(length)?;
+        let end = bytes.iter().position(|&byte| byte == 0).unwrap_or(length);
+
+        Ok(String::from_utf8_lossy(&bytes[..end]).into_owned())
+    }
+
+    /// Reads a string that ends with a null, with no fixed size.
+    pub(crate) fn null_terminated_string(&mut self) -> Result<String, crate::ImportError> {
+        let rest = self.data.get(self.position..).unwrap_or_default();
+
+        let Some(length) = rest.iter().position(|&byte| byte == 0) else {
+            return Err(crate::ImportError::new(crate::ImportErrorType::StringParseError, format!("Unterminated string at offset {}.", self.position)));
+        };
+
+        let string = String::from_utf8_lossy(&rest[..length]).into_owned();
+        self.position += length + 1;
+
+        Ok(string)
+    }
+}
+
+/// The transform from +Z up coordinates, as used by id Tech and 3D Studio models, to +Y up.
+pub(crate) fn z_up_axis_transform() -> crate::Mat4 {
+    crate::Mat4::new(
+        crate::Vec4::new(1.0, 0.0, 0.0, 0.0),
+        crate::Vec4::new(0.0, 0.0, 1.0, 0.0),
+        crate::Vec4::new(0.0, -1.0, 0.0, 0.0),
+        crate::Vec4::new(0.0, 0.0, 0.0, 1.0)
+    )
+}
+
+/// Inverts a matrix with no projection.
+pub(crate) fn affine_inverse(matrix: &crate::Mat4) -> crate::Mat4 {
+    let a = crate::Vec3::new(matrix.row0.x, matrix.row0.y, matrix.row0.z);
+    let b = crate::Vec3::new(matrix.row1.x, matrix.row1.y, matrix.row1.z);
+    let c = crate::Vec3::new(matrix.row2.x, matrix.row2.y, matrix.row2.z);
+
+    let (bc, ca, ab) = (b.cross(&c), c.cross(&a), a.cross(&b));
+    let determinant = a.dot(&bc);
+
+    if determinant == 0.0 {
+        return crate::Mat4::identity();
+    }
+
+    let rows = [
+        crate::Vec3::new(bc.x, ca.x, ab.x),
+        crate::Vec3::new(bc.y, ca.y, ab.y),
+        crate::Vec3::new(bc.z, ca.z, ab.z)
+    ].map(|row| crate::Vec3::new(row.x / determinant, row.y / determinant, row.z / determinant));
+
+    let translation = crate::Vec3::new(matrix.row0.w, matrix.row1.w, matrix.row2.w);
+    let row = |row: crate::Vec3| crate::Vec4::new(row.x, row.y, row.z, -row.dot(&translation));
+
+    crate::Mat4::new(row(rows[0]), row(rows[1]), row(rows[2]), crate::Vec4::new(0.0, 0.0, 0.0, 1.0))
+}
+
+/// Creates a plain material for a texture, and the image it uses.
+pub(crate) fn texture_material(skin: &str, image: usize) -> (crate::Material, crate::Image) {
+    let material = crate::Material {
+        albedo_color: crate::Vec4::new(1.0, 1.0, 1.0, 1.0),
+        albedo_texture: Some(image),
+        normal_texture: None,
+        metallic: 0.0,
+        metallic_texture: None,
+        roughness: 1.0,
+        roughness_texture: None,
+        occlusion_texture: None,
+        emissive_texture: None,
+        alpha_mode: crate::AlphaMode::Opaque,
+        alpha_cutoff: 0.5,
+        double_sided: false,
+        name: Some(skin.to_string()),
+        extras: None
+    };
+
+    let image = crate::Image {
+        path: Some(skin.to_string()),
+        data_type: None,
+        data: None,
+        name: None,
+        extras: None
+    };
+
+    (material, image)
+}
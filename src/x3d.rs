@@ -0,0 +1,990 @@
+use std::collections::HashMap;
+
+use roxmltree::{Document, Node as XmlNode, ParsingOptions};
+
+use crate::{resolver::ResourceResolver, Importer, ImportError, ImportErrorType, Mat4, NormalMode, Quat, Vec2, Vec3, Vec4, Vertex, BoundingBox};
+
+/// A field value. VRML files don't say what type a field is without the node's definition, so
+/// values are stored by what they look like, and every number is a double.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Numbers(Vec<f64>),
+    Strings(Vec<String>),
+
+    /// Both single nodes and lists of nodes. A `NULL` node is an empty list.
+    Nodes(Vec<Node>)
+}
+
+/// A node, such as a `Transform` or an `IndexedFaceSet`. Nodes reused with `USE` are copied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub type_name: String,
+
+    /// The name given to the node with `DEF`.
+    pub name:      Option<String>,
+    pub fields:    HashMap<String, Value>
+}
+
+impl Node {
+    pub fn new(type_name: &str, fields: Vec<(&str, Value)>) -> Self {
+        Self {
+            type_name: type_name.to_string(),
+            name: None,
+            fields: fields.into_iter().map(|(name, value)| (name.to_string(), value)).collect()
+        }
+    }
+
+    /// Gets a field's numbers, or nothing if the field isn't set or isn't numbers.
+    pub fn numbers(&self, field: &str) -> &[f64] {
+        match self.fields.get(field) {
+            Some(Value::Numbers(numbers)) => numbers,
+            _ => &[]
+        }
+    }
+
+    pub fn number(&self, field: &str, default: f64) -> f64 {
+        self.numbers(field).first().copied().unwrap_or(default)
+    }
+
+    pub fn bool(&self, field: &str, default: bool) -> bool {
+        match self.fields.get(field) {
+            Some(Value::Bool(value)) => *value,
+            _ => default
+        }
+    }
+
+    pub fn strings(&self, field: &str) -> &[String] {
+        match self.fields.get(field) {
+            Some(Value::Strings(strings)) => strings,
+            _ => &[]
+        }
+    }
+
+    pub fn nodes(&self, field: &str) -> &[Node] {
+        match self.fields.get(field) {
+            Some(Value::Nodes(nodes)) => nodes,
+            _ => &[]
+        }
+    }
+
+    /// Gets the node in a single node field.
+    pub fn node(&self, field: &str) -> Option<&Node> {
+        self.nodes(field).first()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// The VRML97 text encoding, which X3D's classic encoding is based on.
+    Vrml,
+    Xml
+}
+
+/// A VRML97 or X3D scene graph. Both describe the same nodes, so are read into the same structure.
+///
+/// Only the nodes that describe static geometry are converted to a scene: `Transform` and the
+/// other grouping nodes, and `Shape`s with an `IndexedFaceSet`, a `Material` and an `ImageTexture`.
+/// Prototypes and routes are skipped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct X3d {
+    pub encoding: Encoding,
+
+    /// The X3D version, or "2.0" for VRML97 files.
+    pub version:  String,
+    pub nodes:    Vec<Node>
+}
+
+impl X3d {
+    pub fn parse_vrml(text: &str) -> Result<Self, ImportError> {
+        let header = text.lines().next().unwrap_or_default().trim();
+
+        if !header.starts_with("#VRML") {
+            return Err(parse_error("The file is not a VRML file."));
+        }
+
+        if !header.starts_with("#VRML V2.0") {
+            return Err(ImportError::new(ImportErrorType::UnsupportedFormat, format!("\"{header}\" files aren't supported.")));
+        }
+
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            position: 0,
+            definitions: HashMap::new()
+        };
+
+        let mut nodes = Vec::new();
+
+        while parser.peek() != &Token::End {
+            nodes.extend(parser.statement()?);
+        }
+
+        Ok(Self {
+            encoding: Encoding::Vrml,
+            version: String::from("2.0"),
+            nodes
+        })
+    }
+
+    pub fn parse_xml(text: &str) -> Result<Self, ImportError> {
+        // X3D files usually have a document type declaration.
+        let options = ParsingOptions {
+            allow_dtd: true,
+            ..ParsingOptions::default()
+        };
+
+        let document = Document::parse_with_options(text, options).map_err(|err| parse_error(format!("Invalid XML: {err}")))?;
+        let root = document.root_element();
+
+        if root.tag_name().name() != "X3D" {
+            return Err(parse_error("The document is not an X3D document."));
+        }
+
+        let mut definitions = HashMap::new();
+        let mut nodes = Vec::new();
+
+        if let Some(scene) = root.children().find(|child| child.has_tag_name("Scene")) {
+            for element in scene.children().filter(XmlNode::is_element) {
+                nodes.extend(xml_node(element, &mut definitions)?);
+            }
+        }
+
+        Ok(Self {
+            encoding: Encoding::Xml,
+            version: root.attribute("version").unwrap_or("3.0").to_string(),
+            nodes
+        })
+    }
+}
+
+impl Importer for X3d {
+    fn import_bytes(data: &[u8], _resolver: &dyn ResourceResolver) -> Result<Self, ImportError> where Self : Sized {
+        let text = std::str::from_utf8(data).map_err(|_| parse_error("The file contains invalid UTF-8."))?;
+        let text = text.trim_start_matches('\u{feff}');
+
+        if text.starts_with("#VRML") {
+            Self::parse_vrml(text)
+        } else {
+            Self::parse_xml(text)
+        }
+    }
+
+    fn to_scene(&self, _resolver: &dyn ResourceResolver) -> Result<crate::Scene, ImportError> {
+        let mut builder = SceneBuilder {
+            meshes: Vec::new(),
+            materials: Vec::new(),
+            images: Vec::new(),
+            nodes: Vec::new(),
+            mesh_indices: HashMap::new(),
+            material_indices: HashMap::new(),
+            image_indices: HashMap::new()
+        };
+
+        let mut root_nodes = Vec::new();
+
+        for node in &self.nodes {
+            root_nodes.extend(builder.node(node)?);
+        }
+
+        Ok(crate::Scene {
+            meshes: builder.meshes,
+            materials: if builder.materials.is_empty() { None } else { Some(builder.materials) },
+            images: if builder.images.is_empty() { None } else { Some(builder.images) },
+            nodes: builder.nodes,
+            root_nodes
+        })
+    }
+}
+
+struct SceneBuilder {
+    meshes:           Vec<crate::Mesh>,
+    materials:        Vec<crate::Material>,
+    images:           Vec<crate::Image>,
+    nodes:            Vec<crate::Node>,
+
+    /// Shapes and appearances reused with `USE` keep their `DEF` name, so are only converted once.
+    mesh_indices:     HashMap<String, usize>,
+    material_indices: HashMap<(String, bool), usize>,
+    image_indices:    HashMap<String, usize>
+}
+
+impl SceneBuilder {
+    /// Converts a grouping node or a shape to a scene node. Other nodes, such as lights and
+    /// viewpoints, are skipped.
+    fn node(&mut self, node: &Node) -> Result<Option<usize>, ImportError> {
+        let (transform, children) = match node.type_name.as_str() {
+            "Transform" => (transform(node), node.nodes("children")),
+            "Group" | "StaticGroup" | "Anchor" | "Billboard" | "Collision" => (Mat4::identity(), node.nodes("children")),
+
+            // Only the most detailed level is kept. VRML97 calls the children "level".
+            "LOD" => {
+                let levels = if node.fields.contains_key("level") { node.nodes("level") } else { node.nodes("children") };
+                (Mat4::identity(), levels.get(..1).unwrap_or_default())
+            },
+            "Switch" => {
+                let choices = if node.fields.contains_key("choice") { node.nodes("choice") } else { node.nodes("children") };
+                let choice = usize::try_from(node.number("whichChoice", -1.0) as i64).ok();
+
+                (Mat4::identity(), choice.and_then(|choice| choices.get(choice..=choice)).unwrap_or_default())
+            },
+            "Shape" => (Mat4::identity(), std::slice::from_ref(node)),
+            _ => return Ok(None)
+        };
+
+        let index = self.nodes.len();
+
+        self.nodes.push(crate::Node {
+            transform,
+            meshes: Vec::new(),
+            children: Vec::new(),
+            name: node.name.clone(),
+            extras: None
+        });
+
+        for child in children {
+            if child.type_name == "Shape" {
+                if let Some(mesh) = self.shape(child)? {
+                    self.nodes[index].meshes.push(mesh);
+                }
+            } else if let Some(child) = self.node(child)? {
+                self.nodes[index].children.push(child);
+            }
+        }
+
+        Ok(Some(index))
+    }
+
+    fn shape(&mut self, shape: &Node) -> Result<Option<usize>, ImportError> {
+        if let Some(&mesh) = shape.name.as_ref().and_then(|name| self.mesh_indices.get(name)) {
+            return Ok(Some(mesh));
+        }
+
+        let Some(geometry) = shape.node("geometry").filter(|geometry| geometry.type_name == "IndexedFaceSet") else {
+            return Ok(None);
+        };
+
+        let appearance = shape.node("appearance");
+        let textured = appearance.and_then(|appearance| appearance.node("texture")).is_some();
+        let (vertices, indices, has_normals) = indexed_face_set(geometry, textured)?;
+
+        let material = appearance.map(|appearance| self.material(appearance, !geometry.bool("solid", true)));
+
+        let mut mesh = crate::Mesh {
+            bounds: BoundingBox::from_vertices(&vertices),
+            vertices,
+            indices: Some(indices),
+            material,
+            has_normals,
+            name: geometry.name.clone().or_else(|| shape.name.clone()),
+            extras: None
+        };
+
+        // Missing normals are smoothed across edges sharper than the crease angle.
+        if !has_normals {
+            crate::post_process::generate_normals(&mut mesh, NormalMode::CreaseAngle(geometry.number("creaseAngle", 0.0) as f32));
+        }
+
+        self.meshes.push(mesh);
+
+        let mesh = self.meshes.len() - 1;
+
+        if let Some(name) = &shape.name {
+            self.mesh_indices.insert(name.clone(), mesh);
+        }
+
+        Ok(Some(mesh))
+    }
+
+    /// Converts an `Appearance`. Single sided materials are culled, so shapes that aren't solid
+    /// need their own double sided material.
+    fn material(&mut self, appearance: &Node, double_sided: bool) -> usize {
+        let key = appearance.name.clone().map(|name| (name, double_sided));
+
+        if let Some(&material) = key.as_ref().and_then(|key| self.material_indices.get(key)) {
+            return material;
+        }
+
+        let material = appearance.node("material");
+
+        let texture = appearance.node("texture")
+            .filter(|texture| texture.type_name == "ImageTexture")
+            .and_then(|texture| texture.strings("url").first().map(|url| self.image(url, texture)));
+
+        // Without a material, shapes are unlit and white. Textures replace the diffuse color.
+        let diffuse = match material.map(|material| material.numbers("diffuseColor")) {
+            _ if texture.is_some() => Vec3::new(1.0, 1.0, 1.0),
+            Some(&[r, g, b, ..]) => Vec3::new(r as f32, g as f32, b as f32),
+            Some(_) => Vec3::new(0.8, 0.8, 0.8),
+            None => Vec3::new(1.0, 1.0, 1.0)
+        };
+
+        let transparency = material.map_or(0.0, |material| material.number("transparency", 0.0)) as f32;
+
+        // Shininess is a fraction of the largest Phong exponent, 128.
+        let roughness = material.map_or(1.0, |material| {
+            let exponent = material.number("shininess", 0.2) as f32 * 128.0;
+            (2.0 / (exponent + 2.0)).sqrt()
+        });
+
+        self.materials.push(crate::Material {
+            albedo_color: Vec4::new(diffuse.x, diffuse.y, diffuse.z, 1.0 - transparency),
+            albedo_texture: texture,
+            normal_texture: None,
+            metallic: 0.0,
+            metallic_texture: None,
+            roughness,
+            roughness_texture: None,
+            occlusion_texture: None,
+            emissive_texture: None,
+            alpha_mode: if transparency > 0.0 { crate::AlphaMode::Blend } else { crate::AlphaMode::Opaque },
+            alpha_cutoff: 0.5,
+            double_sided,
+            name: material.and_then(|material| material.name.clone()).or_else(|| appearance.name.clone()),
+            extras: None
+        });
+
+        let index = self.materials.len() - 1;
+
+        if let Some(key) = key {
+            self.material_indices.insert(key, index);
+        }
+
+        index
+    }
+
+    fn image(&mut self, url: &str, texture: &Node) -> usize {
+        *self.image_indices.entry(url.to_string()).or_insert_with(|| {
+            self.images.push(crate::Image {
+                path: Some(url.to_string()),
+                data_type: None,
+                data: None,
+                name: texture.name.clone(),
+                extras: None
+            });
+
+            self.images.len() - 1
+        })
+    }
+}
+
+/// Gets a node's `Transform` matrix, which scales about the center in the scale orientation, then
+/// rotates about the center, then translates.
+fn transform(node: &Node) -> Mat4 {
+    let vector = |field: &str, default: f32| match node.numbers(field) {
+        &[x, y, z, ..] => Vec3::new(x as f32, y as f32, z as f32),
+        _ => Vec3::new(default, default, default)
+    };
+
+    let translation = vector("translation", 0.0);
+    let center = vector("center", 0.0);
+    let scale = vector("scale", 1.0);
+    let rotation = axis_angle(node.numbers("rotation"));
+    let orientation = axis_angle(node.numbers("scaleOrientation"));
+
+    let zero = Vec3::new(0.0, 0.0, 0.0);
+    let one = Vec3::new(1.0, 1.0, 1.0);
+    let identity = Quat::new(0.0, 0.0, 0.0, 1.0);
+    let inverse_orientation = Quat::new(-orientation.x, -orientation.y, -orientation.z, orientation.w);
+
+    Mat4::from_trs(Vec3::new(translation.x + center.x, translation.y + center.y, translation.z + center.z), rotation, one)
+        * Mat4::from_trs(zero, orientation, scale)
+        * Mat4::from_trs(zero, inverse_orientation, one)
+        * Mat4::from_trs(Vec3::new(-center.x, -center.y, -center.z), identity, one)
+}
+
+/// Converts an axis and an angle in radians to a quaternion.
+fn axis_angle(values: &[f64]) -> Quat {
+    let &[x, y, z, angle, ..] = values else {
+        return Quat::new(0.0, 0.0, 0.0, 1.0);
+    };
+
+    let mut axis = Vec3::new(x as f32, y as f32, z as f32);
+
+    if axis.magnitude_squared() == 0.0 {
+        return Quat::new(0.0, 0.0, 0.0, 1.0);
+    }
+
+    axis.normalize();
+
+    let (sin, cos) = (angle as f32 / 2.0).sin_cos();
+
+    Quat::new(axis.x * sin, axis.y * sin, axis.z * sin, cos)
+}
+
+/// Gets the vectors of a property node, such as the points of a `Coordinate`.
+fn vectors<const N: usize>(node: Option<&Node>, field: &str) -> Vec<[f32; N]> {
+    node.map_or_else(Vec::new, |node| {
+        node.numbers(field).chunks_exact(N).map(|values| std::array::from_fn(|i| values[i] as f32)).collect()
+    })
+}
+
+/// Looks up the value for a corner or a face, through the index list if there is one.
+fn lookup<T: Copy>(values: &[T], indices: &[f64], i: usize, fallback: usize, field: &str) -> Result<T, ImportError> {
+    let index = if indices.is_empty() { Some(fallback) } else { indices.get(i).and_then(|&index| usize::try_from(index as i64).ok()) };
+
+    index.and_then(|index| values.get(index).copied())
+        .ok_or_else(|| parse_error(format!("An IndexedFaceSet's {field} refers to a value that doesn't exist.")))
+}
+
+/// Converts an `IndexedFaceSet` to vertices and triangle indices, and whether it has normals. Faces
+/// are triangulated as a fan.
+fn indexed_face_set(set: &Node, textured: bool) -> Result<(Vec<Vertex>, Vec<u32>, bool), ImportError> {
+    let points = vectors::<3>(set.node("coord"), "point");
+    let normals = vectors::<3>(set.node("normal"), "vector");
+    let tex_coords = vectors::<2>(set.node("texCoord"), "point");
+
+    let colors = match set.node("color") {
+        Some(color) if color.type_name == "ColorRGBA" => vectors::<4>(Some(color), "color"),
+        color => vectors::<3>(color, "color").into_iter().map(|[r, g, b]| [r, g, b, 1.0]).collect()
+    };
+
+    let coord_index = set.numbers("coordIndex");
+    let normal_index = set.numbers("normalIndex");
+    let color_index = set.numbers("colorIndex");
+    let tex_coord_index = set.numbers("texCoordIndex");
+
+    let ccw = set.bool("ccw", true);
+    let normal_per_vertex = set.bool("normalPerVertex", true);
+    let color_per_vertex = set.bool("colorPerVertex", true);
+
+    // Faces end with -1. Corners are kept as positions in coordIndex, as the other index lists
+    // are parallel to it.
+    let mut faces = Vec::new();
+    let mut face = Vec::new();
+
+    for (corner, &index) in coord_index.iter().enumerate() {
+        if index < 0.0 {
+            faces.push(std::mem::take(&mut face));
+        } else {
+            face.push(corner);
+        }
+    }
+
+    if !face.is_empty() {
+        faces.push(face);
+    }
+
+    let point = |corner: usize| lookup(&points, &[], 0, coord_index[corner] as usize, "coordIndex");
+
+    // Without texture coordinates, textures are mapped onto the two longest sides of the bounds.
+    let bounds = points.iter().fold(BoundingBox::empty(), |mut bounds, &[x, y, z]| {
+        bounds.expand(Vec3::new(x, y, z));
+        bounds
+    });
+
+    let size = bounds.size();
+    let size = [size.x, size.y, size.z];
+    let mut axes = [0, 1, 2];
+    axes.sort_by(|&a, &b| size[b].total_cmp(&size[a]));
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    let mut cache = HashMap::new();
+
+    for (i, face) in faces.iter().enumerate() {
+        if face.len() < 3 {
+            continue;
+        }
+
+        let mut corners = Vec::with_capacity(face.len());
+
+        for &corner in face {
+            let point_index = coord_index[corner] as usize;
+            let position = point(corner)?;
+
+            let normal = if normals.is_empty() {
+                Vec3::new(0.0, 0.0, 0.0)
+            } else if normal_per_vertex {
+                let [x, y, z] = lookup(&normals, normal_index, corner, point_index, "normalIndex")?;
+                Vec3::new(x, y, z)
+            } else {
+                let [x, y, z] = lookup(&normals, normal_index, i, i, "normalIndex")?;
+                Vec3::new(x, y, z)
+            };
+
+            let color = match (colors.is_empty(), color_per_vertex) {
+                (true, _) => [1.0; 4],
+                (false, true) => lookup(&colors, color_index, corner, point_index, "colorIndex")?,
+                (false, false) => lookup(&colors, color_index, i, i, "colorIndex")?
+            };
+
+            let [s, t] = if !tex_coords.is_empty() {
+                lookup(&tex_coords, tex_coord_index, corner, point_index, "texCoordIndex")?
+            } else if textured && size[axes[0]] > 0.0 {
+                let min = [bounds.min.x, bounds.min.y, bounds.min.z];
+                [(position[axes[0]] - min[axes[0]]) / size[axes[0]], (position[axes[1]] - min[axes[1]]) / size[axes[0]]]
+            } else {
+                [0.0, 0.0]
+            };
+
+            let vertex = Vertex {
+                position: Vec3::new(position[0], position[1], position[2]),
+                tex_coord: Vec2::new(s, 1.0 - t),
+                color: Vec4::new(color[0], color[1], color[2], color[3]),
+                normal,
+                tangent: Vec4::new(0.0, 0.0, 0.0, 0.0)
+            };
+
+            let key = (point_index, [normal.x, normal.y, normal.z, color[0], color[1], color[2], color[3], s, t].map(f32::to_bits));
+
+            corners.push(*cache.entry(key).or_insert_with(|| {
+                vertices.push(vertex);
+                vertices.len() as u32 - 1
+            }));
+        }
+
+        for i in 2..corners.len() {
+            if ccw {
+                indices.extend_from_slice(&[corners[0], corners[i - 1], corners[i]]);
+            } else {
+                indices.extend_from_slice(&[corners[0], corners[i], corners[i - 1]]);
+            }
+        }
+    }
+
+    Ok((vertices, indices, !normals.is_empty()))
+}
+
+/// The field that a child element is put in, if it doesn't have a `containerField` attribute.
+fn container_field(type_name: &str) -> &'static str {
+    match type_name {
+        "Coordinate" | "CoordinateDouble" => "coord",
+        "Normal" => "normal",
+        "Color" | "ColorRGBA" => "color",
+        "TextureCoordinate" => "texCoord",
+        "Appearance" => "appearance",
+        "Material" => "material",
+        "ImageTexture" | "PixelTexture" | "MovieTexture" => "texture",
+        "TextureTransform" => "textureTransform",
+        "IndexedFaceSet" | "IndexedTriangleSet" | "IndexedLineSet" | "PointSet" | "Box" | "Cone" | "Cylinder" | "Sphere" | "ElevationGrid" | "Extrusion" | "Text" => "geometry",
+        _ => "children"
+    }
+}
+
+/// Reads an element as a node. Elements that aren't nodes, such as `ROUTE`, are skipped.
+fn xml_node(element: XmlNode, definitions: &mut HashMap<String, Node>) -> Result<Option<Node>, ImportError> {
+    let type_name = element.tag_name().name();
+
+    if matches!(type_name, "ROUTE" | "ProtoDeclare" | "ExternProtoDeclare" | "IS" | "IMPORT" | "EXPORT") {
+        return Ok(None);
+    }
+
+    if let Some(name) = element.attribute("USE") {
+        return definitions.get(name).cloned().map(Some).ok_or_else(|| parse_error(format!("\"{name}\" is used before it's defined.")));
+    }
+
+    let mut fields = element.attributes()
+        .filter(|attribute| !matches!(attribute.name(), "DEF" | "USE" | "containerField" | "class" | "id" | "style"))
+        .map(|attribute| (attribute.name().to_string(), parse_attribute(attribute.value())))
+        .collect::<HashMap<_, _>>();
+
+    for child in element.children().filter(XmlNode::is_element) {
+        let Some(node) = xml_node(child, definitions)? else {
+            continue;
+        };
+
+        let field = child.attribute("containerField").unwrap_or_else(|| container_field(&node.type_name));
+
+        if let Value::Nodes(nodes) = fields.entry(field.to_string()).or_insert_with(|| Value::Nodes(Vec::new())) {
+            nodes.push(node);
+        }
+    }
+
+    let node = Node {
+        type_name: type_name.to_string(),
+        name: element.attribute("DEF").map(String::from),
+        fields
+    };
+
+    if let Some(name) = &node.name {
+        definitions.insert(name.clone(), node.clone());
+    }
+
+    Ok(Some(node))
+}
+
+/// Parses an attribute by what it looks like. Lists of strings are quoted, as in
+/// `url='"a.png" "b.png"'`, and numbers in lists can be separated by commas.
+fn parse_attribute(text: &str) -> Value {
+    let text = text.trim();
+
+    match text {
+        "true" | "TRUE" => return Value::Bool(true),
+        "false" | "FALSE" => return Value::Bool(false),
+        _ => {}
+    }
+
+    if text.starts_with('"') {
+        return Value::Strings(parse_quoted(text));
+    }
+
+    let numbers = text.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|word| !word.is_empty())
+        .map(parse_number)
+        .collect::<Option<Vec<_>>>();
+
+    match numbers {
+        Some(numbers) => Value::Numbers(numbers),
+        None => Value::Strings(vec![text.to_string()])
+    }
+}
+
+fn parse_quoted(text: &str) -> Vec<String> {
+    let mut strings = Vec::new();
+    let mut chars = text.chars();
+
+    while chars.any(|c| c == '"') {
+        let mut value = String::new();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => value.extend(chars.next()),
+                c => value.push(c)
+            }
+        }
+
+        strings.push(value);
+    }
+
+    strings
+}
+
+/// Parses a number, which can be in hexadecimal for integers, as in `SFImage` pixels.
+fn parse_number(word: &str) -> Option<f64> {
+    if !word.starts_with(|c: char| c.is_ascii_digit() || matches!(c, '+' | '-' | '.')) {
+        return None;
+    }
+
+    match word.strip_prefix("0x").or_else(|| word.strip_prefix("0X")) {
+        Some(hex) => i64::from_str_radix(hex, 16).ok().map(|value| value as f64),
+        None => word.parse().ok()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    String(String),
+    Punct(char),
+    End
+}
+
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, ImportError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    let mut line = 1;
+
+    while let Some((start, c)) = chars.next() {
+        let token = match c {
+            '\n' => {
+                line += 1;
+                continue;
+            },
+
+            // Commas are whitespace in VRML.
+            c if c.is_whitespace() || c == ',' => continue,
+            '#' => {
+                while chars.next_if(|&(_, c)| c != '\n').is_some() {}
+                continue;
+            },
+            '"' => {
+                let start_line = line;
+                let mut value = String::new();
+
+                loop {
+                    let Some((_, c)) = chars.next() else {
+                        return Err(parse_error(format!("Line {start_line}: Unterminated string.")));
+                    };
+
+                    let c = match c {
+                        '"' => break,
+                        '\\' => match chars.next() {
+                            Some((_, c)) => c,
+                            None => continue
+                        },
+                        c => c
+                    };
+
+                    if c == '\n' {
+                        line += 1;
+                    }
+
+                    value.push(c);
+                }
+
+                Token::String(value)
+            },
+            '[' | ']' | '{' | '}' => Token::Punct(c),
+            c => {
+                let mut end = start + c.len_utf8();
+
+                while let Some((i, c)) = chars.next_if(|&(_, c)| !c.is_whitespace() && !matches!(c, ',' | '"' | '#' | '[' | ']' | '{' | '}')) {
+                    end = i + c.len_utf8();
+                }
+
+                Token::Word(text[start..end].to_string())
+            }
+        };
+
+        tokens.push((token, line));
+    }
+
+    tokens.push((Token::End, line));
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens:      Vec<(Token, usize)>,
+    position:    usize,
+    definitions: HashMap<String, Node>
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position].0
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.position].0.clone();
+
+        if token != Token::End {
+            self.position += 1;
+        }
+
+        token
+    }
+
+    fn error(&self, message: &str) -> ImportError {
+        parse_error(format!("Line {}: {message}", self.tokens[self.position].1))
+    }
+
+    fn expect(&mut self, punct: char) -> Result<(), ImportError> {
+        if self.peek() == &Token::Punct(punct) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{punct}'.")))
+        }
+    }
+
+    fn eat(&mut self, punct: char) -> bool {
+        let found = self.peek() == &Token::Punct(punct);
+
+        if found {
+            self.position += 1;
+        }
+
+        found
+    }
+
+    fn word(&mut self) -> Result<String, ImportError> {
+        match self.peek().clone() {
+            Token::Word(word) => {
+                self.position += 1;
+                Ok(word)
+            },
+            _ => Err(self.error("Expected a name."))
+        }
+    }
+
+    /// Skips a bracketed block, such as a prototype's interface or body.
+    fn skip_block(&mut self, open: char, close: char) -> Result<(), ImportError> {
+        self.expect(open)?;
+
+        let mut depth = 1;
+
+        while depth > 0 {
+            match self.next() {
+                Token::Punct(c) if c == open => depth += 1,
+                Token::Punct(c) if c == close => depth -= 1,
+                Token::End => return Err(self.error(&format!("Expected '{close}'."))),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Parses a node, or skips a route or a prototype.
+    fn statement(&mut self) -> Result<Option<Node>, ImportError> {
+        match self.peek() {
+            Token::Word(word) if word == "ROUTE" => {
+                // ROUTE node.field TO node.field
+                for _ in 0..4 {
+                    self.word()?;
+                }
+
+                Ok(None)
+            },
+            Token::Word(word) if word == "PROTO" => {
+                self.next();
+                self.word()?;
+                self.skip_block('[', ']')?;
+                self.skip_block('{', '}')?;
+
+                Ok(None)
+            },
+            Token::Word(word) if word == "EXTERNPROTO" => {
+                self.next();
+                self.word()?;
+                self.skip_block('[', ']')?;
+
+                if self.peek() == &Token::Punct('[') {
+                    self.skip_block('[', ']')?;
+                } else {
+                    self.next();
+                }
+
+                Ok(None)
+            },
+            _ => self.node().map(Some)
+        }
+    }
+
+    fn node(&mut self) -> Result<Node, ImportError> {
+        let type_name = self.word()?;
+
+        match type_name.as_str() {
+            "DEF" => {
+                let name = self.word()?;
+                let mut node = self.node()?;
+
+                node.name = Some(name.clone());
+                self.definitions.insert(name, node.clone());
+
+                Ok(node)
+            },
+            "USE" => {
+                let name = self.word()?;
+
+                match self.definitions.get(&name) {
+                    Some(node) => Ok(node.clone()),
+                    None => {
+                        self.position -= 1;
+                        Err(self.error(&format!("\"{name}\" is used before it's defined.")))
+                    }
+                }
+            },
+            _ => {
+                self.expect('{')?;
+
+                let mut fields = HashMap::new();
+
+                while !self.eat('}') {
+                    let name = self.word()?;
+
+                    match name.as_str() {
+                        "ROUTE" => {
+                            for _ in 0..3 {
+                                self.word()?;
+                            }
+                        },
+
+                        // Script nodes declare their own fields and events.
+                        "eventIn" | "eventOut" => {
+                            self.word()?;
+                            self.word()?;
+                        },
+                        "field" | "exposedField" => {
+                            self.word()?;
+                            let name = self.word()?;
+                            fields.insert(name, self.value()?);
+                        },
+                        _ => {
+                            fields.insert(name, self.value()?);
+                        }
+                    }
+                }
+
+                Ok(Node {
+                    type_name,
+                    name: None,
+                    fields
+                })
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, ImportError> {
+        match self.peek().clone() {
+            Token::String(value) => {
+                self.position += 1;
+                Ok(Value::Strings(vec![value]))
+            },
+            Token::Punct('[') => {
+                self.position += 1;
+                self.list()
+            },
+            Token::Word(word) => match word.as_str() {
+                "TRUE" | "FALSE" => {
+                    self.position += 1;
+                    Ok(Value::Bool(word == "TRUE"))
+                },
+                "NULL" => {
+                    self.position += 1;
+                    Ok(Value::Nodes(Vec::new()))
+                },
+
+                // Vectors and colors are several numbers without brackets.
+                _ if parse_number(&word).is_some() => {
+                    let mut numbers = Vec::new();
+
+                    while let Token::Word(word) = self.peek() {
+                        let Some(number) = parse_number(word) else {
+                            break;
+                        };
+
+                        numbers.push(number);
+                        self.position += 1;
+                    }
+
+                    Ok(Value::Numbers(numbers))
+                },
+                _ => Ok(Value::Nodes(vec![self.node()?]))
+            },
+            _ => Err(self.error("Expected a value."))
+        }
+    }
+
+    /// Parses the values of a list, after the opening bracket.
+    fn list(&mut self) -> Result<Value, ImportError> {
+        let mut numbers = Vec::new();
+        let mut strings = Vec::new();
+        let mut nodes = Vec::new();
+
+        loop {
+            match self.peek().clone() {
+                Token::Punct(']') => {
+                    self.position += 1;
+                    break;
+                },
+                Token::String(value) => {
+                    self.position += 1;
+                    strings.push(value);
+                },
+                Token::Word(word) => match parse_number(&word) {
+                    Some(number) => {
+                        self.position += 1;
+                        numbers.push(number);
+                    },
+                    None => nodes.extend(self.statement()?)
+                },
+                _ => return Err(self.error("Expected ']'."))
+            }
+        }
+
+        Ok(if !nodes.is_empty() {
+            Value::Nodes(nodes)
+        } else if !strings.is_empty() {
+            Value::Strings(strings)
+        } else {
+            Value::Numbers(numbers)
+        })
+    }
+}
+
+fn parse_error<T: ToString>(message: T) -> ImportError {
+    ImportError::new(ImportErrorType::StringParseError, message)
+}
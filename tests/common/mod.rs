@@ -0,0 +1,395 @@
+//! Shared helpers for the integration tests.
+//!
+//! The glTF fixtures in `tests/fixtures` are generated by the functions in this module, so that they
+//! stay small and it's obvious what each one contains. Run the tests with `MODELO_UPDATE_FIXTURES=1`
+//! set to regenerate them after changing a generator.
+
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+use modelo::{Scene, Vec2, Vec3, Vec4, Mat4};
+use serde_json::{json, Value};
+
+pub fn fixtures_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures")
+}
+
+pub fn fixture_path(name: &str) -> String {
+    fixtures_dir().join(name).to_str().unwrap().to_string()
+}
+
+/// A temporary directory unique to the given test.
+pub fn temp_dir(test: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("modelo_{test}"));
+    std::fs::create_dir_all(&directory).unwrap();
+
+    directory
+}
+
+/// Appends a string padded with zeros to the given length, as used by binary formats.
+pub fn fixed_string(data: &mut Vec<u8>, value: &str, length: usize) {
+    data.extend_from_slice(value.as_bytes());
+    data.resize(data.len() + length - value.len(), 0);
+}
+
+/// A fixture file, with its name and contents.
+pub struct Fixture {
+    pub name: String,
+    pub data: Vec<u8>
+}
+
+/// Generates every fixture file.
+pub fn generate_fixtures() -> Vec<Fixture> {
+    let mut fixtures = Vec::new();
+
+    fixtures.extend(gltf_fixture("interleaved", interleaved()));
+    fixtures.extend(gltf_fixture("quantized", quantized()));
+    fixtures.extend(gltf_fixture("sparse", sparse()));
+    fixtures.push(glb_fixture("hierarchy", hierarchy()));
+
+    fixtures
+}
+
+/// Builds a binary buffer, keeping each view aligned to 4 bytes.
+#[derive(Default)]
+struct Bin {
+    data:  Vec<u8>,
+    views: Vec<Value>
+}
+
+impl Bin {
+    fn view(&mut self, bytes: &[u8], stride: Option<usize>) -> usize {
+        while !self.data.len().is_multiple_of(4) {
+            self.data.push(0);
+        }
+
+        let mut view = json!({ "buffer": 0, "byteOffset": self.data.len(), "byteLength": bytes.len() });
+
+        if let Some(stride) = stride {
+            view["byteStride"] = json!(stride);
+        }
+
+        self.data.extend_from_slice(bytes);
+        self.views.push(view);
+
+        self.views.len() - 1
+    }
+}
+
+fn f32_bytes(values: &[f32]) -> Vec<u8> {
+    values.iter().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+fn gltf_fixture(name: &str, (mut json, bin): (Value, Bin)) -> Vec<Fixture> {
+    let bin_name = format!("{name}.bin");
+
+    json["buffers"] = json!([{ "uri": bin_name, "byteLength": bin.data.len() }]);
+    json["bufferViews"] = Value::from(bin.views);
+
+    vec![
+        Fixture { name: format!("{name}.gltf"), data: serde_json::to_string_pretty(&json).unwrap().into_bytes() },
+        Fixture { name: bin_name, data: bin.data }
+    ]
+}
+
+fn glb_fixture(name: &str, (mut json, bin): (Value, Bin)) -> Fixture {
+    json["buffers"] = json!([{ "byteLength": bin.data.len() }]);
+    json["bufferViews"] = Value::from(bin.views);
+
+    let mut json = serde_json::to_vec(&json).unwrap();
+    while !json.len().is_multiple_of(4) {
+        json.push(b' ');
+    }
+
+    let mut data = bin.data;
+    while !data.len().is_multiple_of(4) {
+        data.push(0);
+    }
+
+    let mut glb = Vec::new();
+    glb.extend_from_slice(b"glTF");
+    glb.extend_from_slice(&2u32.to_le_bytes());
+    glb.extend_from_slice(&((12 + 8 + json.len() + 8 + data.len()) as u32).to_le_bytes());
+    glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    glb.extend_from_slice(b"JSON");
+    glb.extend_from_slice(&json);
+    glb.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    glb.extend_from_slice(b"BIN\0");
+    glb.extend_from_slice(&data);
+
+    Fixture { name: format!("{name}.glb"), data: glb }
+}
+
+/// A quad with positions, normals and texture coordinates interleaved in a single buffer view.
+fn interleaved() -> (Value, Bin) {
+    let mut bin = Bin::default();
+
+    let vertices: [[f32; 8]; 4] = [
+        [0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0],
+        [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0],
+        [1.0, 1.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0]
+    ];
+
+    let vertex_view = bin.view(&f32_bytes(vertices.as_flattened()), Some(32));
+
+    let indices: [u16; 6] = [0, 1, 2, 2, 3, 0];
+    let index_view = bin.view(&indices.iter().flat_map(|index| index.to_le_bytes()).collect::<Vec<_>>(), None);
+
+    let json = json!({
+        "asset": { "version": "2.0", "generator": "modelo fixtures" },
+        "accessors": [
+            { "bufferView": vertex_view, "byteOffset": 0, "componentType": 5126, "count": 4, "type": "VEC3", "min": [0.0, 0.0, 0.0], "max": [1.0, 1.0, 0.0] },
+            { "bufferView": vertex_view, "byteOffset": 12, "componentType": 5126, "count": 4, "type": "VEC3" },
+            { "bufferView": vertex_view, "byteOffset": 24, "componentType": 5126, "count": 4, "type": "VEC2" },
+            { "bufferView": index_view, "componentType": 5123, "count": 6, "type": "SCALAR" }
+        ],
+        "materials": [ { "name": "Grey", "pbrMetallicRoughness": { "baseColorFactor": [0.5, 0.5, 0.5, 1.0], "metallicFactor": 0.0 } } ],
+        "meshes": [ { "name": "Quad", "primitives": [ { "attributes": { "POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2 }, "indices": 3, "material": 0 } ] } ],
+        "nodes": [ { "name": "Quad", "mesh": 0, "translation": [0.0, 0.0, -2.0] } ],
+        "scene": 0,
+        "scenes": [ { "nodes": [0] } ]
+    });
+
+    (json, bin)
+}
+
+/// A triangle using KHR_mesh_quantization, with integer positions, normalized integer normals,
+/// texture coordinates and colors, and byte indices.
+fn quantized() -> (Value, Bin) {
+    let mut bin = Bin::default();
+
+    // Vertex attributes must be aligned to 4 bytes, so the positions are padded with a stride of 8.
+    let positions: [[u16; 4]; 3] = [[0, 0, 0, 0], [100, 0, 0, 0], [0, 200, 0, 0]];
+    let position_view = bin.view(&positions.as_flattened().iter().flat_map(|value| value.to_le_bytes()).collect::<Vec<_>>(), Some(8));
+
+    let normals: [[i8; 4]; 3] = [[0, 0, 127, 0], [0, 0, 127, 0], [0, 0, 127, 0]];
+    let normal_view = bin.view(&normals.as_flattened().iter().map(|value| *value as u8).collect::<Vec<_>>(), Some(4));
+
+    let tex_coords: [u16; 6] = [0, 0, 65535, 0, 0, 65535];
+    let tex_coord_view = bin.view(&tex_coords.iter().flat_map(|value| value.to_le_bytes()).collect::<Vec<_>>(), None);
+
+    let colors: [u8; 12] = [255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 51];
+    let color_view = bin.view(&colors, None);
+
+    let index_view = bin.view(&[0, 1, 2], None);
+
+    let json = json!({
+        "asset": { "version": "2.0", "generator": "modelo fixtures" },
+        "extensionsUsed": [ "KHR_mesh_quantization" ],
+        "extensionsRequired": [ "KHR_mesh_quantization" ],
+        "accessors": [
+            { "bufferView": position_view, "componentType": 5123, "count": 3, "type": "VEC3", "min": [0, 0, 0], "max": [100, 200, 0] },
+            { "bufferView": normal_view, "componentType": 5120, "normalized": true, "count": 3, "type": "VEC3" },
+            { "bufferView": tex_coord_view, "componentType": 5123, "normalized": true, "count": 3, "type": "VEC2" },
+            { "bufferView": color_view, "componentType": 5121, "normalized": true, "count": 3, "type": "VEC4" },
+            { "bufferView": index_view, "componentType": 5121, "count": 3, "type": "SCALAR" }
+        ],
+        "meshes": [ { "name": "Triangle", "primitives": [ { "attributes": { "POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2, "COLOR_0": 3 }, "indices": 4 } ] } ],
+        "nodes": [ { "name": "Dequantize", "mesh": 0, "scale": [0.01, 0.01, 0.01] } ],
+        "scene": 0,
+        "scenes": [ { "nodes": [0] } ]
+    });
+
+    (json, bin)
+}
+
+/// A quad where two of the positions are replaced by a sparse accessor, and colors come from a sparse
+/// accessor with no buffer view.
+fn sparse() -> (Value, Bin) {
+    let mut bin = Bin::default();
+
+    let positions: [f32; 12] = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0];
+    let position_view = bin.view(&f32_bytes(&positions), None);
+
+    let sparse_indices = bin.view(&[1, 2], None);
+    let sparse_positions = bin.view(&f32_bytes(&[2.0, 0.0, 0.0, 2.0, 1.0, 0.0]), None);
+
+    let sparse_color_indices = bin.view(&3u16.to_le_bytes(), None);
+    let sparse_colors = bin.view(&f32_bytes(&[1.0, 0.0, 0.0, 1.0]), None);
+
+    let indices: [u32; 6] = [0, 1, 2, 0, 2, 3];
+    let index_view = bin.view(&indices.iter().flat_map(|index| index.to_le_bytes()).collect::<Vec<_>>(), None);
+
+    let json = json!({
+        "asset": { "version": "2.0", "generator": "modelo fixtures" },
+        "accessors": [
+            {
+                "bufferView": position_view, "componentType": 5126, "count": 4, "type": "VEC3", "min": [0.0, 0.0, 0.0], "max": [2.0, 1.0, 0.0],
+                "sparse": {
+                    "count": 2,
+                    "indices": { "bufferView": sparse_indices, "componentType": 5121 },
+                    "values": { "bufferView": sparse_positions }
+                }
+            },
+            {
+                "componentType": 5126, "count": 4, "type": "VEC4",
+                "sparse": {
+                    "count": 1,
+                    "indices": { "bufferView": sparse_color_indices, "componentType": 5123 },
+                    "values": { "bufferView": sparse_colors }
+                }
+            },
+            { "bufferView": index_view, "componentType": 5125, "count": 6, "type": "SCALAR" }
+        ],
+        "meshes": [ { "name": "Stretched", "primitives": [ { "attributes": { "POSITION": 0, "COLOR_0": 1 }, "indices": 2 } ] } ],
+        "nodes": [ { "mesh": 0 } ],
+        "scene": 0,
+        "scenes": [ { "nodes": [0] } ]
+    });
+
+    (json, bin)
+}
+
+/// A GLB containing a node hierarchy, a mesh with multiple primitives and materials, a mesh shared by
+/// two nodes, names, extras and an embedded image.
+fn hierarchy() -> (Value, Bin) {
+    let mut bin = Bin::default();
+
+    let triangle_a = bin.view(&f32_bytes(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]), None);
+    let triangle_b = bin.view(&f32_bytes(&[0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 0.0, 1.0, 1.0]), None);
+    let wheel = bin.view(&f32_bytes(&[-0.5, -0.5, 0.0, 0.5, -0.5, 0.0, 0.0, 0.5, 0.0]), None);
+
+    // Not a real PNG, but the data is only ever copied around.
+    let image = bin.view(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 0, b'I', b'E', b'N', b'D'], None);
+
+    let json = json!({
+        "asset": { "version": "2.0", "generator": "modelo fixtures" },
+        "accessors": [
+            { "bufferView": triangle_a, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0.0, 0.0, 0.0], "max": [1.0, 1.0, 0.0] },
+            { "bufferView": triangle_b, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0.0, 0.0, 1.0], "max": [1.0, 1.0, 1.0] },
+            { "bufferView": wheel, "componentType": 5126, "count": 3, "type": "VEC3", "min": [-0.5, -0.5, 0.0], "max": [0.5, 0.5, 0.0] }
+        ],
+        "images": [ { "name": "Paint", "bufferView": image, "mimeType": "image/png" } ],
+        "samplers": [ { "magFilter": 9729, "minFilter": 9987, "wrapS": 33071, "wrapT": 33648 } ],
+        "textures": [ { "sampler": 0, "source": 0 } ],
+        "materials": [
+            { "name": "Red", "pbrMetallicRoughness": { "baseColorFactor": [1.0, 0.0, 0.0, 1.0], "baseColorTexture": { "index": 0 }, "roughnessFactor": 0.25 } },
+            { "name": "Glass", "alphaMode": "BLEND", "doubleSided": true, "extras": { "ior": 1.5 } }
+        ],
+        "meshes": [
+            {
+                "name": "Body",
+                "primitives": [
+                    { "attributes": { "POSITION": 0 }, "material": 0 },
+                    { "attributes": { "POSITION": 1 }, "material": 1 }
+                ]
+            },
+            { "name": "Wheel", "primitives": [ { "attributes": { "POSITION": 2 } } ] }
+        ],
+        "nodes": [
+            { "name": "Root", "children": [1, 2, 3], "matrix": [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 5.0, 0.0, 0.0, 1.0] },
+            { "name": "Body", "mesh": 0, "rotation": [0.0, 0.0, std::f32::consts::FRAC_1_SQRT_2, std::f32::consts::FRAC_1_SQRT_2], "children": [4] },
+            { "name": "Wheel_L", "mesh": 1, "translation": [-1.0, 0.0, 0.0], "scale": [2.0, 2.0, 2.0] },
+            { "name": "Wheel_R", "mesh": 1, "translation": [1.0, 0.0, 0.0], "scale": [2.0, 2.0, 2.0] },
+            { "name": "Attach_Light", "translation": [0.0, 0.0, 2.0], "extras": { "light": "spot", "intensity": 10 } }
+        ],
+        "scene": 0,
+        "scenes": [ { "nodes": [0] } ]
+    });
+
+    (json, bin)
+}
+
+fn assert_f32_eq(a: f32, b: f32, what: &str) {
+    assert!((a - b).abs() <= 1e-5, "{what}: {a} != {b}");
+}
+
+pub fn assert_vec2_eq(a: Vec2, b: Vec2, what: &str) {
+    assert_f32_eq(a.x, b.x, what);
+    assert_f32_eq(a.y, b.y, what);
+}
+
+pub fn assert_vec3_eq(a: Vec3, b: Vec3, what: &str) {
+    assert_f32_eq(a.x, b.x, what);
+    assert_f32_eq(a.y, b.y, what);
+    assert_f32_eq(a.z, b.z, what);
+}
+
+pub fn assert_vec4_eq(a: Vec4, b: Vec4, what: &str) {
+    assert_f32_eq(a.x, b.x, what);
+    assert_f32_eq(a.y, b.y, what);
+    assert_f32_eq(a.z, b.z, what);
+    assert_f32_eq(a.w, b.w, what);
+}
+
+pub fn assert_mat4_eq(a: &Mat4, b: &Mat4, what: &str) {
+    assert_vec4_eq(a.row0, b.row0, what);
+    assert_vec4_eq(a.row1, b.row1, what);
+    assert_vec4_eq(a.row2, b.row2, what);
+    assert_vec4_eq(a.row3, b.row3, what);
+}
+
+/// Compares two scenes structurally, allowing for small floating point differences.
+pub fn assert_scenes_eq(a: &Scene, b: &Scene) {
+    assert_eq!(a.meshes.len(), b.meshes.len(), "mesh count");
+
+    for (i, (a, b)) in a.meshes.iter().zip(&b.meshes).enumerate() {
+        assert_eq!(a.vertices.len(), b.vertices.len(), "mesh {i} vertex count");
+
+        for (a, b) in a.vertices.iter().zip(&b.vertices) {
+            assert_vec3_eq(a.position, b.position, &format!("mesh {i} position"));
+            assert_vec2_eq(a.tex_coord, b.tex_coord, &format!("mesh {i} tex coord"));
+            assert_vec4_eq(a.color, b.color, &format!("mesh {i} color"));
+            assert_vec3_eq(a.normal, b.normal, &format!("mesh {i} normal"));
+            assert_vec4_eq(a.tangent, b.tangent, &format!("mesh {i} tangent"));
+        }
+
+        assert_eq!(a.indices, b.indices, "mesh {i} indices");
+        assert_eq!(a.material, b.material, "mesh {i} material");
+        assert_eq!(a.name, b.name, "mesh {i} name");
+        assert_eq!(a.extras, b.extras, "mesh {i} extras");
+        assert_vec3_eq(a.bounds.min, b.bounds.min, &format!("mesh {i} bounds"));
+        assert_vec3_eq(a.bounds.max, b.bounds.max, &format!("mesh {i} bounds"));
+    }
+
+    assert_eq!(a.materials.is_some(), b.materials.is_some(), "materials");
+
+    if let (Some(a), Some(b)) = (&a.materials, &b.materials) {
+        assert_eq!(a.len(), b.len(), "material count");
+
+        for (i, (a, b)) in a.iter().zip(b).enumerate() {
+            assert_vec4_eq(a.albedo_color, b.albedo_color, &format!("material {i} albedo"));
+            assert_eq!(a.albedo_texture, b.albedo_texture, "material {i} albedo texture");
+            assert_eq!(a.normal_texture, b.normal_texture, "material {i} normal texture");
+            assert_f32_eq(a.metallic, b.metallic, &format!("material {i} metallic"));
+            assert_eq!(a.metallic_texture, b.metallic_texture, "material {i} metallic texture");
+            assert_f32_eq(a.roughness, b.roughness, &format!("material {i} roughness"));
+            assert_eq!(a.roughness_texture, b.roughness_texture, "material {i} roughness texture");
+            assert_eq!(a.occlusion_texture, b.occlusion_texture, "material {i} occlusion texture");
+            assert_eq!(a.emissive_texture, b.emissive_texture, "material {i} emissive texture");
+            assert_eq!(a.alpha_mode, b.alpha_mode, "material {i} alpha mode");
+            assert_f32_eq(a.alpha_cutoff, b.alpha_cutoff, &format!("material {i} alpha cutoff"));
+            assert_eq!(a.double_sided, b.double_sided, "material {i} double sided");
+            assert_eq!(a.name, b.name, "material {i} name");
+            assert_eq!(a.extras, b.extras, "material {i} extras");
+        }
+    }
+
+    assert_eq!(a.images.is_some(), b.images.is_some(), "images");
+
+    if let (Some(a), Some(b)) = (&a.images, &b.images) {
+        assert_eq!(a.len(), b.len(), "image count");
+
+        for (i, (a, b)) in a.iter().zip(b).enumerate() {
+            assert_eq!(a.path, b.path, "image {i} path");
+            assert_eq!(a.data_type, b.data_type, "image {i} data type");
+            assert_eq!(a.data, b.data, "image {i} data");
+            assert_eq!(a.name, b.name, "image {i} name");
+            assert_eq!(a.extras, b.extras, "image {i} extras");
+        }
+    }
+
+    assert_eq!(a.nodes.len(), b.nodes.len(), "node count");
+
+    for (i, (a, b)) in a.nodes.iter().zip(&b.nodes).enumerate() {
+        assert_mat4_eq(&a.transform, &b.transform, &format!("node {i} transform"));
+        assert_eq!(a.meshes, b.meshes, "node {i} meshes");
+        assert_eq!(a.children, b.children, "node {i} children");
+        assert_eq!(a.name, b.name, "node {i} name");
+        assert_eq!(a.extras, b.extras, "node {i} extras");
+    }
+
+    assert_eq!(a.root_nodes, b.root_nodes, "root nodes");
+}
@@ -0,0 +1,111 @@
+use modelo::{gltf::Gltf, BoundingBox, Importer, Mat4, Mesh, Node, Quat, Scene, Vec2, Vec3, Vec4, Vertex};
+
+fn vertex(x: f32, y: f32, z: f32) -> Vertex {
+    Vertex {
+        position: Vec3::new(x, y, z),
+        tex_coord: Vec2::new(0.0, 0.0),
+        color: Vec4::new(1.0, 1.0, 1.0, 1.0),
+        normal: Vec3::new(0.0, 0.0, 0.0),
+        tangent: Vec4::new(0.0, 0.0, 0.0, 0.0)
+    }
+}
+
+fn assert_vec3_eq(a: Vec3, b: Vec3) {
+    assert!((a - b).magnitude() < 1e-5, "{a:?} != {b:?}");
+}
+
+#[test]
+fn bounds_from_vertices() {
+    let bounds = BoundingBox::from_vertices(&[vertex(-1.0, 2.0, 0.5), vertex(3.0, -4.0, 0.0), vertex(0.0, 0.0, 1.0)]);
+
+    assert_vec3_eq(bounds.min, Vec3::new(-1.0, -4.0, 0.0));
+    assert_vec3_eq(bounds.max, Vec3::new(3.0, 2.0, 1.0));
+
+    assert!(BoundingBox::from_vertices(&[]).is_empty());
+}
+
+#[test]
+fn scene_and_node_bounds() {
+    let vertices = vec![vertex(-1.0, -1.0, -1.0), vertex(1.0, 1.0, 1.0), vertex(0.0, 1.0, 0.0)];
+    let bounds = BoundingBox::from_vertices(&vertices);
+
+    let half_turn_y = Quat::new(0.0, 1.0, 0.0, 0.0);
+
+    let scene = Scene {
+        meshes: vec![Mesh { vertices, indices: None, material: None, has_normals: false, bounds, name: None, extras: None }],
+        materials: None,
+        images: None,
+        nodes: vec![
+            Node {
+                transform: Mat4::from_trs(Vec3::new(10.0, 0.0, 0.0), Quat::new(0.0, 0.0, 0.0, 1.0), Vec3::new(1.0, 1.0, 1.0)),
+                meshes: vec![0],
+                children: vec![1],
+                name: None,
+                extras: None
+            },
+            Node {
+                transform: Mat4::from_trs(Vec3::new(0.0, 5.0, 0.0), half_turn_y, Vec3::new(2.0, 2.0, 2.0)),
+                meshes: vec![0],
+                children: vec![],
+                name: None,
+                extras: None
+            }
+        ],
+        root_nodes: vec![0]
+    };
+
+    let child = scene.node_bounds(1);
+    assert_vec3_eq(child.min, Vec3::new(8.0, 3.0, -2.0));
+    assert_vec3_eq(child.max, Vec3::new(12.0, 7.0, 2.0));
+
+    let root = scene.node_bounds(0);
+    assert_vec3_eq(root.min, Vec3::new(8.0, -1.0, -2.0));
+    assert_vec3_eq(root.max, Vec3::new(12.0, 7.0, 2.0));
+
+    let whole = scene.bounds();
+    assert_vec3_eq(whole.min, root.min);
+    assert_vec3_eq(whole.max, root.max);
+}
+
+#[test]
+fn gltf_accessor_bounds() {
+    let directory = std::env::temp_dir().join("modelo_test_bounds");
+    std::fs::create_dir_all(&directory).unwrap();
+
+    let positions: [f32; 9] = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 2.0, 0.0];
+    let buffer: Vec<u8> = positions.iter().flat_map(|value| value.to_le_bytes()).collect();
+    std::fs::write(directory.join("triangle.bin"), &buffer).unwrap();
+
+    // The first primitive has min and max values that are deliberately larger than the data,
+    // so we can check they are preferred over calculating the bounds.
+    let json = r#"{
+        "asset": { "version": "2.0" },
+        "buffers": [ { "uri": "triangle.bin", "byteLength": 36 } ],
+        "bufferViews": [ { "buffer": 0, "byteLength": 36 } ],
+        "accessors": [
+            { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [-5.0, -5.0, -5.0], "max": [5.0, 5.0, 5.0] },
+            { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" }
+        ],
+        "meshes": [ { "primitives": [ { "attributes": { "POSITION": 0 } }, { "attributes": { "POSITION": 1 } } ] } ],
+        "nodes": [ { "mesh": 0, "translation": [0.0, 0.0, 10.0] } ],
+        "scenes": [ { "nodes": [0] } ]
+    }"#;
+
+    let path = directory.join("triangle.gltf");
+    std::fs::write(&path, json).unwrap();
+
+    let gltf = Gltf::import(path.to_str().unwrap()).unwrap();
+    let scene = gltf.to_scene(&directory).unwrap();
+
+    assert_vec3_eq(scene.meshes[0].bounds.min, Vec3::new(-5.0, -5.0, -5.0));
+    assert_vec3_eq(scene.meshes[0].bounds.max, Vec3::new(5.0, 5.0, 5.0));
+
+    assert_vec3_eq(scene.meshes[1].bounds.min, Vec3::new(0.0, 0.0, 0.0));
+    assert_vec3_eq(scene.meshes[1].bounds.max, Vec3::new(1.0, 2.0, 0.0));
+
+    assert_eq!(scene.nodes[0].meshes, vec![0, 1]);
+
+    let bounds = scene.bounds();
+    assert_vec3_eq(bounds.min, Vec3::new(-5.0, -5.0, 5.0));
+    assert_vec3_eq(bounds.max, Vec3::new(5.0, 5.0, 15.0));
+}
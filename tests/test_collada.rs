@@ -0,0 +1,136 @@
+mod common;
+
+use common::{assert_vec2_eq, assert_vec3_eq, assert_vec4_eq, fixture_path, fixtures_dir};
+use modelo::{collada::{Collada, Shading, UpAxis}, format, AlphaMode, Importer, ImportErrorType, PostProcessFlags, Scene, Vec2, Vec3, Vec4};
+
+#[test]
+fn load_from_file() {
+    let collada = Collada::import(&fixture_path("scene.dae")).unwrap();
+
+    assert_eq!(collada.up_axis, UpAxis::Z);
+    assert_eq!(collada.unit, 0.01);
+
+    // Escaped characters and the file scheme are removed from image paths.
+    assert_eq!(collada.images.len(), 1);
+    assert_eq!(collada.images[0].path, "textures/checker board.png");
+
+    // The diffuse texture is found through its sampler and surface.
+    let painted = &collada.effects[0];
+    assert_eq!(painted.shading, Shading::Phong);
+    assert_eq!(painted.diffuse_texture, Some(0));
+    assert_eq!(painted.shininess, 48.0);
+
+    let glass = &collada.effects[1];
+    assert_eq!(glass.shading, Shading::Lambert);
+    assert_eq!(glass.opacity, 0.5);
+
+    // The quad is triangulated.
+    let plane = &collada.geometries[0].primitives[0];
+    assert_eq!(plane.vertices.len(), 4);
+    assert_eq!(plane.indices, vec![0, 1, 2, 0, 2, 3]);
+    assert_eq!(plane.material.as_deref(), Some("surface"));
+
+    assert_eq!(collada.root_nodes, vec![0]);
+    assert_eq!(collada.nodes[0].children, vec![1]);
+    assert_eq!(collada.nodes[1].geometries[0].materials["surface"], 1);
+}
+
+#[test]
+fn to_scene() {
+    let scene = Collada::import(&fixture_path("scene.dae")).unwrap().to_scene(&fixtures_dir()).unwrap();
+
+    assert_eq!(scene.meshes.len(), 2);
+    assert_eq!(scene.meshes[0].name.as_deref(), Some("Plane"));
+    assert_eq!(scene.meshes[0].material, Some(0));
+    assert_eq!(scene.meshes[1].material, Some(1));
+
+    // COLLADA texture coordinates are flipped vertically.
+    let first = &scene.meshes[0].vertices[0];
+    assert_vec2_eq(first.tex_coord, Vec2::new(0.0, 1.0), "tex coord");
+    assert_vec3_eq(first.normal, Vec3::new(0.0, 0.0, 1.0), "normal");
+
+    // Only the plane has a NORMAL input.
+    assert!(scene.meshes[0].has_normals);
+    assert!(!scene.meshes[1].has_normals);
+
+    let materials = scene.materials.as_ref().unwrap();
+    assert_eq!(materials[0].name.as_deref(), Some("Painted"));
+    assert_eq!(materials[0].albedo_texture, Some(0));
+    assert!((materials[0].roughness - 0.2).abs() < 1e-5, "{}", materials[0].roughness);
+
+    assert_eq!(materials[1].alpha_mode, AlphaMode::Blend);
+    assert_vec4_eq(materials[1].albedo_color, Vec4::new(0.2, 0.4, 0.8, 0.5), "albedo color");
+
+    assert_eq!(scene.images.as_ref().unwrap()[0].name.as_deref(), Some("Checker"));
+
+    // The root is converted from centimeters and +Z up.
+    let root = &scene.nodes[0];
+    assert_eq!(root.name.as_deref(), Some("Root"));
+    assert_vec3_eq(root.transform.transform_point(Vec3::new(1.0, 1.0, 0.0)), Vec3::new(0.01, 1.0, -0.01), "root");
+
+    // The child is scaled, then rotated.
+    let child = root.transform * scene.nodes[1].transform;
+    assert_vec3_eq(child.transform_point(Vec3::new(2.0, 0.0, 0.0)), Vec3::new(0.0, 1.0, -0.04), "child");
+}
+
+#[test]
+fn geometry_without_scene() {
+    let collada = Collada::parse(r##"
+        <COLLADA version="1.5.0">
+            <library_geometries>
+                <geometry id="pentagon">
+                    <mesh>
+                        <source id="positions">
+                            <float_array count="15">0 0 0 1 0 0 1.5 1 0 0.5 2 0 -0.5 1 0</float_array>
+                            <technique_common><accessor count="5" stride="3"/></technique_common>
+                        </source>
+                        <vertices id="vertices"><input semantic="POSITION" source="#positions"/></vertices>
+                        <polygons count="1">
+                            <input semantic="VERTEX" source="#vertices" offset="0"/>
+                            <p>0 1 2 3 4</p>
+                        </polygons>
+                    </mesh>
+                </geometry>
+            </library_geometries>
+        </COLLADA>
+    "##).unwrap();
+
+    let scene = collada.to_scene(&fixtures_dir()).unwrap();
+    assert_eq!(scene.meshes.len(), 1);
+    assert!(scene.nodes.is_empty());
+    assert_eq!(scene.meshes[0].indices.as_ref().unwrap(), &vec![0, 1, 2, 0, 2, 3, 0, 3, 4]);
+}
+
+#[test]
+fn errors() {
+    let err = Collada::parse("<COLLADA><library_geometries>").unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::StringParseError), "{err:?}");
+
+    let err = Collada::parse("<scene/>").unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::StringParseError), "{err:?}");
+
+    let err = Collada::parse(r##"
+        <COLLADA>
+            <library_geometries>
+                <geometry id="broken">
+                    <mesh>
+                        <triangles count="1">
+                            <input semantic="VERTEX" source="#missing" offset="0"/>
+                            <p>0 1 2</p>
+                        </triangles>
+                    </mesh>
+                </geometry>
+            </library_geometries>
+        </COLLADA>
+    "##).unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::StringParseError), "{err:?}");
+}
+
+#[test]
+fn load_scene() {
+    let data = std::fs::read(fixture_path("scene.dae")).unwrap();
+    assert_eq!(format::detect(&data).map(|format| format.name), Some("COLLADA"));
+
+    let scene = Scene::load(&fixture_path("scene.dae"), PostProcessFlags::empty()).unwrap();
+    assert_eq!(scene.meshes.len(), 2);
+}
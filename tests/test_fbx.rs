@@ -0,0 +1,245 @@
+mod common;
+
+use std::io::Write;
+
+use common::{assert_vec2_eq, assert_vec3_eq, fixture_path, fixtures_dir};
+use modelo::{fbx::{Fbx, Format, ModelTransform, Property, RotationOrder}, format, AlphaMode, Importer, ImportErrorType, PostProcessFlags, Scene, Vec2, Vec3};
+
+#[test]
+fn load_ascii() {
+    let fbx = Fbx::import(&fixture_path("pyramid.fbx")).unwrap();
+
+    assert_eq!(fbx.format, Format::Ascii);
+    assert_eq!(fbx.version, 7400);
+
+    let geometry = &fbx.geometries[0];
+    assert_eq!(geometry.name, "Pyramid");
+
+    // The quad uses the first material slot, and the triangles use the second.
+    assert_eq!(geometry.primitives.len(), 2);
+    assert_eq!(geometry.primitives[0].vertices.len(), 4);
+    assert_eq!(geometry.primitives[0].indices, vec![0, 1, 2, 0, 2, 3]);
+    assert_eq!(geometry.primitives[1].material, 1);
+    assert_eq!(geometry.primitives[1].indices.len(), 6);
+
+    let first = &geometry.primitives[0].vertices[2];
+    assert_vec3_eq(first.normal, Vec3::new(0.0, -1.0, 0.0), "normal");
+
+    // FBX texture coordinates are flipped vertically.
+    assert_vec2_eq(first.tex_coord, Vec2::new(1.0, 0.0), "tex coord");
+
+    assert_eq!(fbx.models.len(), 2);
+    assert_eq!(fbx.root_models, vec![0]);
+    assert_eq!(fbx.models[0].children, vec![1]);
+    assert_eq!(fbx.models[0].materials, vec![0, 1]);
+
+    assert_eq!(fbx.materials[0].diffuse_texture, Some(0));
+    assert_eq!(fbx.materials[1].opacity, 0.75);
+    assert_eq!(fbx.textures[0].path, "textures/rock.png");
+
+    // Unknown records are kept.
+    let objects = fbx.records.iter().find(|record| record.name == "Objects").unwrap();
+    let model = objects.child("Model").unwrap();
+    assert_eq!(model.child("Shading").unwrap().properties, vec![Property::String(String::from("T"))]);
+}
+
+#[test]
+fn to_scene() {
+    let scene = Fbx::import(&fixture_path("pyramid.fbx")).unwrap().to_scene(&fixtures_dir()).unwrap();
+
+    assert_eq!(scene.meshes.len(), 2);
+    assert_eq!(scene.meshes[0].material, Some(0));
+    assert_eq!(scene.meshes[1].material, Some(1));
+    assert_eq!(scene.nodes[0].meshes, vec![0, 1]);
+
+    let materials = scene.materials.as_ref().unwrap();
+    assert_eq!(materials[0].name.as_deref(), Some("Stone"));
+    assert_eq!(materials[0].roughness, 0.5);
+    assert_eq!(materials[1].alpha_mode, AlphaMode::Blend);
+
+    // The pre-rotation is applied before the translation.
+    let pyramid = &scene.nodes[0];
+    assert_vec3_eq(pyramid.transform.transform_point(Vec3::new(1.0, 0.0, 0.0)), Vec3::new(0.0, 0.0, 4.0), "pyramid");
+
+    // The tip rotates around its pivot.
+    let tip = &scene.nodes[1];
+    assert_eq!(tip.name.as_deref(), Some("Tip"));
+    assert_vec3_eq(tip.transform.transform_point(Vec3::new(0.0, 1.0, 0.0)), Vec3::new(0.0, 1.0, 0.0), "pivot");
+    assert_vec3_eq(tip.transform.transform_point(Vec3::new(0.0, 2.0, 0.0)), Vec3::new(0.0, 1.0, 1.0), "tip");
+}
+
+#[test]
+fn transform_rules() {
+    let transform = ModelTransform {
+        rotation: Vec3::new(90.0, 90.0, 0.0),
+        ..ModelTransform::default()
+    };
+
+    // XYZ order rotates around X first.
+    assert_vec3_eq(transform.matrix().transform_point(Vec3::new(0.0, 1.0, 0.0)), Vec3::new(1.0, 0.0, 0.0), "xyz");
+
+    let transform = ModelTransform {
+        rotation_order: RotationOrder::Yxz,
+        ..transform
+    };
+
+    assert_vec3_eq(transform.matrix().transform_point(Vec3::new(0.0, 1.0, 0.0)), Vec3::new(0.0, 0.0, 1.0), "yxz");
+
+    // The post-rotation is inverted.
+    let transform = ModelTransform {
+        pre_rotation: Vec3::new(0.0, 0.0, 90.0),
+        post_rotation: Vec3::new(0.0, 0.0, 90.0),
+        scaling: Vec3::new(2.0, 1.0, 1.0),
+        ..ModelTransform::default()
+    };
+
+    assert_vec3_eq(transform.matrix().transform_point(Vec3::new(1.0, 0.0, 0.0)), Vec3::new(2.0, 0.0, 0.0), "post rotation");
+}
+
+/// Writes a binary FBX with a single triangle, using the record layout of the given version.
+fn binary_triangle(version: u32) -> Vec<u8> {
+    struct Writer {
+        data: Vec<u8>,
+        wide: bool
+    }
+
+    impl Writer {
+        fn offset(&mut self, value: usize) {
+            if self.wide {
+                self.data.extend_from_slice(&(value as u64).to_le_bytes());
+            } else {
+                self.data.extend_from_slice(&(value as u32).to_le_bytes());
+            }
+        }
+
+        fn null_record(&mut self) {
+            self.data.extend(std::iter::repeat_n(0, if self.wide { 25 } else { 13 }));
+        }
+
+        fn record(&mut self, name: &str, properties: &[Vec<u8>], children: impl FnOnce(&mut Self)) {
+            let start = self.data.len();
+
+            self.offset(0);
+            self.offset(properties.len());
+            self.offset(properties.iter().map(Vec::len).sum());
+            self.data.push(name.len() as u8);
+            self.data.extend_from_slice(name.as_bytes());
+
+            for property in properties {
+                self.data.extend_from_slice(property);
+            }
+
+            let children_start = self.data.len();
+            children(self);
+
+            if self.data.len() != children_start {
+                self.null_record();
+            }
+
+            let end = self.data.len();
+
+            if self.wide {
+                self.data[start..start + 8].copy_from_slice(&(end as u64).to_le_bytes());
+            } else {
+                self.data[start..start + 4].copy_from_slice(&(end as u32).to_le_bytes());
+            }
+        }
+    }
+
+    let string = |value: &str| [&[b'S'][..], &(value.len() as u32).to_le_bytes(), value.as_bytes()].concat();
+    let long = |value: i64| [&[b'L'][..], &value.to_le_bytes()].concat();
+
+    // The vertices are compressed, and the indices aren't.
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+
+    for value in [0.0f64, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+        encoder.write_all(&value.to_le_bytes()).unwrap();
+    }
+
+    let compressed = encoder.finish().unwrap();
+    let vertices = [&[b'd'][..], &9u32.to_le_bytes(), &1u32.to_le_bytes(), &(compressed.len() as u32).to_le_bytes(), &compressed].concat();
+
+    let indices = [0i32, 1, -3].iter().flat_map(|index| index.to_le_bytes()).collect::<Vec<_>>();
+    let indices = [&[b'i'][..], &3u32.to_le_bytes(), &0u32.to_le_bytes(), &(indices.len() as u32).to_le_bytes(), &indices].concat();
+
+    let mut writer = Writer {
+        data: b"Kaydara FBX Binary  \0\x1a\0".to_vec(),
+        wide: version >= 7500
+    };
+
+    writer.data.extend_from_slice(&version.to_le_bytes());
+
+    writer.record("Objects", &[], |writer| {
+        writer.record("Geometry", &[long(10), string("Triangle\0\x01Geometry"), string("Mesh")], |writer| {
+            writer.record("Vertices", &[vertices], |_| {});
+            writer.record("PolygonVertexIndex", &[indices], |_| {});
+        });
+
+        writer.record("Model", &[long(20), string("Triangle\0\x01Model"), string("Mesh")], |_| {});
+    });
+
+    writer.record("Connections", &[], |writer| {
+        writer.record("C", &[string("OO"), long(20), long(0)], |_| {});
+        writer.record("C", &[string("OO"), long(10), long(20)], |_| {});
+    });
+
+    writer.null_record();
+
+    // The footer isn't read.
+    writer.data.extend_from_slice(&[0xfa; 16]);
+
+    writer.data
+}
+
+#[test]
+fn load_binary() {
+    for version in [7400, 7500] {
+        let data = binary_triangle(version);
+        assert_eq!(format::detect(&data).map(|format| format.name), Some("FBX"));
+
+        let fbx = Fbx::parse(&data).unwrap();
+        assert_eq!(fbx.format, Format::Binary);
+        assert_eq!(fbx.version, version);
+        assert_eq!(fbx.models[0].name, "Triangle");
+
+        let primitive = &fbx.geometries[0].primitives[0];
+        assert_eq!(primitive.indices, vec![0, 1, 2]);
+        assert_vec3_eq(primitive.vertices[2].position, Vec3::new(0.0, 1.0, 0.0), "position");
+
+        // Without global settings, the scene is converted from centimeters.
+        let scene = fbx.to_scene(&fixtures_dir()).unwrap();
+        assert_vec3_eq(scene.nodes[0].transform.transform_point(Vec3::new(100.0, 0.0, 0.0)), Vec3::new(1.0, 0.0, 0.0), "scale");
+    }
+}
+
+#[test]
+fn errors() {
+    let mut data = binary_triangle(7400);
+    data.truncate(100);
+
+    let err = Fbx::parse(&data).unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::StringParseError), "{err:?}");
+
+    // An array claiming far more values than its compressed data could hold is rejected, rather than
+    // allocated for.
+    let mut data = binary_triangle(7400);
+    let header = [&[b'd'][..], &9u32.to_le_bytes(), &1u32.to_le_bytes()].concat();
+    let position = data.windows(header.len()).position(|window| window == header).unwrap();
+    data[position + 1..position + 5].copy_from_slice(&0x7fff_ffffu32.to_le_bytes());
+
+    let err = Fbx::parse(&data).unwrap_err();
+    assert!(err.message.contains("doesn't match its data"), "{}", err.message);
+
+    let err = Fbx::parse(b"Objects:  {\n\tModel: 1, \"Model::Cube\", \"Mesh\" {\n").unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::StringParseError), "{err:?}");
+    assert!(err.message.starts_with("Line 3"), "{}", err.message);
+
+    let err = Fbx::parse(b"FBXHeaderExtension:  {\n\tFBXVersion: 6100\n}\n").unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::UnsupportedFormat), "{err:?}");
+}
+
+#[test]
+fn load_scene() {
+    let scene = Scene::load(&fixture_path("pyramid.fbx"), PostProcessFlags::empty()).unwrap();
+    assert_eq!(scene.meshes.len(), 2);
+}
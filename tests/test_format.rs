@@ -0,0 +1,86 @@
+use modelo::{format::{self, Format}, resolver::ResourceResolver, BoundingBox, ExportError, Exporter, Importer, ImportError, ImportErrorType, Mesh, PostProcessFlags, Scene, Vec2, Vec3, Vec4, Vertex};
+
+/// A made up format, containing the 3 corners of a single triangle as text.
+struct Triangle {
+    corners: Vec<Vec3>
+}
+
+impl Exporter for Triangle {
+    fn export(&self, path: &str) -> Result<(), ExportError> {
+        let values = self.corners.iter().map(|c| format!("{} {} {}", c.x, c.y, c.z)).collect::<Vec<_>>();
+        std::fs::write(path, format!("TRIANGLE {}", values.join(" ")))?;
+
+        Ok(())
+    }
+
+    fn from_scene(scene: &Scene) -> Self {
+        Self {
+            corners: scene.meshes[0].vertices.iter().take(3).map(|vertex| vertex.position).collect()
+        }
+    }
+}
+
+impl Importer for Triangle {
+    fn import_bytes(data: &[u8], _resolver: &dyn ResourceResolver) -> Result<Self, ImportError> {
+        let values = String::from_utf8_lossy(data)
+            .trim_start_matches("TRIANGLE")
+            .split_whitespace()
+            .map(|value| value.parse::<f32>().map_err(|_| ImportError::new(ImportErrorType::StringParseError, "Invalid value.")))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            corners: values.chunks_exact(3).map(|v| Vec3::new(v[0], v[1], v[2])).collect()
+        })
+    }
+
+    fn to_scene(&self, _resolver: &dyn ResourceResolver) -> Result<Scene, ImportError> {
+        let vertices = self.corners
+            .iter()
+            .map(|&position| Vertex {
+                position,
+                tex_coord: Vec2::new(0.0, 0.0),
+                color: Vec4::new(1.0, 1.0, 1.0, 1.0),
+                normal: Vec3::new(0.0, 0.0, 0.0),
+                tangent: Vec4::new(0.0, 0.0, 0.0, 0.0)
+            })
+            .collect::<Vec<_>>();
+
+        let bounds = BoundingBox::from_vertices(&vertices);
+
+        Ok(Scene {
+            meshes: vec![Mesh { vertices, indices: None, material: None, has_normals: false, bounds, name: None, extras: None }],
+            materials: None,
+            images: None,
+            nodes: Vec::new(),
+            root_nodes: Vec::new()
+        })
+    }
+}
+
+#[test]
+fn register_format() {
+    format::register(Format::new::<Triangle>("Triangle", &["tri"], &["model/x-triangle"], Some(|data| data.starts_with(b"TRIANGLE"))));
+
+    assert_eq!(format::find_by_extension("TRI").unwrap().name, "Triangle");
+    assert_eq!(format::find_by_mime_type("model/x-triangle").unwrap().name, "Triangle");
+    assert!(format::formats().iter().any(|format| format.name == "glTF 2.0"));
+
+    let directory = std::env::temp_dir().join("modelo_register_format");
+    std::fs::create_dir_all(&directory).unwrap();
+
+    let path = directory.join("shape.tri");
+    std::fs::write(&path, "TRIANGLE 0 0 0 1 0 0 0 1 0").unwrap();
+
+    let scene = Scene::load(path.to_str().unwrap(), PostProcessFlags::empty()).unwrap();
+    assert_eq!(scene.meshes[0].vertices[2].position, Vec3::new(0.0, 1.0, 0.0));
+
+    // Saving goes through the registered exporter too, and the sniffer finds files without an extension.
+    let saved = directory.join("saved_shape");
+    format::find_by_name("Triangle").unwrap().save.unwrap()(&scene, saved.to_str().unwrap()).unwrap();
+
+    let scene = Scene::load(saved.to_str().unwrap(), PostProcessFlags::empty()).unwrap();
+    assert_eq!(scene.meshes[0].vertices.len(), 3);
+
+    scene.save(directory.join("copy.tri").to_str().unwrap()).unwrap();
+    assert!(directory.join("copy.tri").exists());
+}
@@ -1,9 +1,411 @@
-use modelo::{gltf::Gltf, Importer};
+mod common;
+
+use common::{assert_scenes_eq, assert_vec2_eq, assert_vec3_eq, assert_vec4_eq, fixture_path, fixtures_dir, temp_dir};
+use modelo::{gltf::Gltf, Importer, Exporter, Scene, Mesh, Node, Material, Image, Vertex, BoundingBox, AlphaMode, Mat4, Quat, Vec2, Vec3, Vec4};
+
+fn load_fixture(name: &str) -> Scene {
+    Gltf::import(&fixture_path(name)).unwrap().to_scene(&fixtures_dir()).unwrap()
+}
+
+/// Exports the scene in the given format, then imports it again.
+fn round_trip(scene: &Scene, test: &str, extension: &str) -> Scene {
+    let directory = temp_dir(test);
+    let path = directory.join(format!("round_trip.{extension}"));
+
+    Gltf::from_scene(scene).export(path.to_str().unwrap()).unwrap();
+
+    Gltf::import(path.to_str().unwrap()).unwrap().to_scene(&directory).unwrap()
+}
+
+fn assert_round_trips(name: &str) {
+    let scene = load_fixture(name);
+
+    let test = name.replace('.', "_");
+
+    assert_scenes_eq(&scene, &round_trip(&scene, &format!("{test}_gltf"), "gltf"));
+    assert_scenes_eq(&scene, &round_trip(&scene, &format!("{test}_glb"), "glb"));
+
+    // Converting directly without touching the disk should also give the same result.
+    assert_scenes_eq(&scene, &Gltf::from_scene(&scene).to_scene(&fixtures_dir()).unwrap());
+}
+
+#[test]
+fn fixtures_are_up_to_date() {
+    let update = std::env::var_os("MODELO_UPDATE_FIXTURES").is_some();
+
+    for fixture in common::generate_fixtures() {
+        let path = fixtures_dir().join(&fixture.name);
+
+        if update {
+            std::fs::create_dir_all(fixtures_dir()).unwrap();
+            std::fs::write(&path, &fixture.data).unwrap();
+        } else {
+            let existing = std::fs::read(&path).unwrap();
+            assert!(existing == fixture.data, "{} is out of date, run the tests with MODELO_UPDATE_FIXTURES=1 to regenerate it", fixture.name);
+        }
+    }
+}
+
+#[test]
+fn load_interleaved() {
+    let scene = load_fixture("interleaved.gltf");
+
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.vertices.len(), 4);
+    assert_eq!(mesh.indices, Some(vec![0, 1, 2, 2, 3, 0]));
+
+    assert_vec3_eq(mesh.vertices[2].position, Vec3::new(1.0, 1.0, 0.0), "position");
+    assert_vec3_eq(mesh.vertices[2].normal, Vec3::new(0.0, 0.0, 1.0), "normal");
+    assert_vec2_eq(mesh.vertices[2].tex_coord, Vec2::new(1.0, 0.0), "tex coord");
+
+    assert_eq!(scene.materials.as_ref().unwrap()[0].name.as_deref(), Some("Grey"));
+}
+
+#[test]
+fn load_quantized() {
+    let scene = load_fixture("quantized.gltf");
+
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.indices, Some(vec![0, 1, 2]));
+
+    assert_vec3_eq(mesh.vertices[1].position, Vec3::new(100.0, 0.0, 0.0), "position");
+    assert_vec3_eq(mesh.vertices[2].position, Vec3::new(0.0, 200.0, 0.0), "position");
+    assert_vec3_eq(mesh.vertices[0].normal, Vec3::new(0.0, 0.0, 1.0), "normal");
+    assert_vec2_eq(mesh.vertices[1].tex_coord, Vec2::new(1.0, 0.0), "tex coord");
+    assert_vec4_eq(mesh.vertices[2].color, Vec4::new(0.0, 0.0, 1.0, 0.2), "color");
+
+    // The node scale brings the positions back to their original size.
+    let bounds = scene.bounds();
+    assert_vec3_eq(bounds.max, Vec3::new(1.0, 2.0, 0.0), "bounds");
+}
+
+#[test]
+fn load_sparse() {
+    let scene = load_fixture("sparse.gltf");
+
+    let vertices = &scene.meshes[0].vertices;
+    assert_vec3_eq(vertices[0].position, Vec3::new(0.0, 0.0, 0.0), "position");
+    assert_vec3_eq(vertices[1].position, Vec3::new(2.0, 0.0, 0.0), "position");
+    assert_vec3_eq(vertices[2].position, Vec3::new(2.0, 1.0, 0.0), "position");
+    assert_vec3_eq(vertices[3].position, Vec3::new(0.0, 1.0, 0.0), "position");
+
+    assert_vec4_eq(vertices[0].color, Vec4::new(0.0, 0.0, 0.0, 0.0), "color");
+    assert_vec4_eq(vertices[3].color, Vec4::new(1.0, 0.0, 0.0, 1.0), "color");
+}
+
+#[test]
+fn load_hierarchy() {
+    let scene = load_fixture("hierarchy.glb");
+
+    // Each primitive becomes its own mesh.
+    assert_eq!(scene.meshes.len(), 3);
+    assert_eq!(scene.meshes[0].material, Some(0));
+    assert_eq!(scene.meshes[1].material, Some(1));
+    assert_eq!(scene.meshes[1].name.as_deref(), Some("Body"));
+
+    assert_eq!(scene.root_nodes, vec![0]);
+    assert_eq!(scene.nodes[0].children, vec![1, 2, 3]);
+    assert_eq!(scene.nodes[1].meshes, vec![0, 1]);
+    assert_eq!(scene.nodes[2].meshes, vec![2]);
+    assert_eq!(scene.nodes[3].meshes, vec![2]);
+
+    let light = scene.find_node("Attach_Light").unwrap();
+    assert_eq!(scene.nodes[light].extras.as_ref().unwrap()["light"], "spot");
+
+    // Root translates by 5 on X, and Body rotates by 90 degrees around Z.
+    let light_position = scene.world_transform(light).transform_point(Vec3::new(0.0, 0.0, 0.0));
+    assert_vec3_eq(light_position, Vec3::new(5.0, 0.0, 2.0), "light position");
+
+    let wheel = scene.node_bounds(scene.find_node("Wheel_R").unwrap());
+    assert_vec3_eq(wheel.min, Vec3::new(5.0, -1.0, 0.0), "wheel bounds");
+    assert_vec3_eq(wheel.max, Vec3::new(7.0, 1.0, 0.0), "wheel bounds");
+
+    let image = &scene.images.as_ref().unwrap()[0];
+    assert_eq!(image.name.as_deref(), Some("Paint"));
+    assert_eq!(image.data_type, Some(modelo::ImageDataType::Png));
+    assert_eq!(image.data.as_ref().unwrap().len(), 16);
+}
+
+#[test]
+fn round_trip_interleaved() {
+    assert_round_trips("interleaved.gltf");
+}
+
+#[test]
+fn round_trip_quantized() {
+    assert_round_trips("quantized.gltf");
+}
+
+#[test]
+fn round_trip_sparse() {
+    assert_round_trips("sparse.gltf");
+}
+
+#[test]
+fn round_trip_hierarchy() {
+    assert_round_trips("hierarchy.glb");
+}
+
+fn quad_scene() -> Scene {
+    let vertex = |x: f32, y: f32, u: f32, v: f32| Vertex {
+        position: Vec3::new(x, y, 0.0),
+        tex_coord: Vec2::new(u, v),
+        color: Vec4::new(1.0, 1.0, 1.0, 1.0),
+        normal: Vec3::new(0.0, 0.0, 1.0),
+        tangent: Vec4::new(0.0, 0.0, 0.0, 0.0)
+    };
+
+    let vertices = vec![vertex(0.0, 0.0, 0.0, 1.0), vertex(1.0, 0.0, 1.0, 1.0), vertex(1.0, 1.0, 1.0, 0.0), vertex(0.0, 1.0, 0.0, 0.0)];
+    let bounds = BoundingBox::from_vertices(&vertices);
+
+    Scene {
+        meshes: vec![Mesh {
+            vertices,
+            indices: Some(vec![0, 1, 2, 2, 3, 0]),
+            material: Some(0),
+            has_normals: true,
+            bounds,
+            name: Some("Quad".to_string()),
+            extras: None
+        }],
+        materials: Some(vec![Material {
+            albedo_color: Vec4::new(1.0, 0.5, 0.25, 1.0),
+            albedo_texture: Some(0),
+            normal_texture: None,
+            metallic: 0.0,
+            metallic_texture: None,
+            roughness: 0.5,
+            roughness_texture: None,
+            occlusion_texture: None,
+            emissive_texture: None,
+            alpha_mode: AlphaMode::Cutoff,
+            alpha_cutoff: 0.25,
+            double_sided: true,
+            name: Some("Paint".to_string()),
+            extras: Some(serde_json::json!({ "layer": 3 }))
+        }]),
+        images: Some(vec![Image {
+            path: Some("textures/quad albedo.png".to_string()),
+            data_type: None,
+            data: None,
+            name: None,
+            extras: None
+        }]),
+        nodes: vec![
+            Node {
+                transform: Mat4::identity(),
+                meshes: vec![],
+                children: vec![1],
+                name: Some("Root".to_string()),
+                extras: None
+            },
+            Node {
+                transform: Mat4::from_trs(Vec3::new(1.0, 2.0, 3.0), Quat::new(0.0, 0.0, 0.0, 1.0), Vec3::new(2.0, 2.0, 2.0)),
+                meshes: vec![0],
+                children: vec![],
+                name: Some("Quad".to_string()),
+                extras: None
+            }
+        ],
+        root_nodes: vec![0]
+    }
+}
+
+#[test]
+fn export_gltf() {
+    let directory = std::env::temp_dir().join("modelo_test_export_gltf");
+    std::fs::create_dir_all(&directory).unwrap();
+
+    let path = directory.join("quad.gltf");
+
+    let scene = quad_scene();
+    Gltf::from_scene(&scene).export(path.to_str().unwrap()).unwrap();
+
+    assert!(directory.join("quad.bin").exists());
+
+    let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(json["buffers"][0]["uri"], "quad.bin");
+    assert_eq!(json["images"][0]["uri"], "textures/quad%20albedo.png");
+    assert_eq!(json["accessors"][0]["min"], serde_json::json!([0.0, 0.0, 0.0]));
+    assert_eq!(json["accessors"][0]["max"], serde_json::json!([1.0, 1.0, 0.0]));
+
+    let imported = Gltf::import(path.to_str().unwrap()).unwrap().to_scene(&directory).unwrap();
+
+    assert_eq!(imported.meshes.len(), 1);
+    assert_eq!(imported.meshes[0].vertices, scene.meshes[0].vertices);
+    assert_eq!(imported.meshes[0].indices, scene.meshes[0].indices);
+    assert_eq!(imported.meshes[0].material, Some(0));
+    assert_eq!(imported.meshes[0].name.as_deref(), Some("Quad"));
+
+    let material = &imported.materials.as_ref().unwrap()[0];
+    assert_eq!(material.albedo_color, Vec4::new(1.0, 0.5, 0.25, 1.0));
+    assert_eq!(material.albedo_texture, Some(0));
+    assert_eq!(material.alpha_mode, AlphaMode::Cutoff);
+    assert_eq!(material.extras, Some(serde_json::json!({ "layer": 3 })));
+
+    assert_eq!(imported.images.as_ref().unwrap()[0].path.as_deref(), Some("textures/quad albedo.png"));
+
+    assert_eq!(imported.root_nodes, vec![0]);
+    assert_eq!(imported.nodes[0].children, vec![1]);
+    assert_eq!(imported.nodes[1].meshes, vec![0]);
+    assert_eq!(imported.nodes[1].transform, scene.nodes[1].transform);
+    assert_eq!(imported.bounds(), scene.bounds());
+}
+
+#[test]
+fn export_glb() {
+    let directory = std::env::temp_dir().join("modelo_test_export_glb");
+    std::fs::create_dir_all(&directory).unwrap();
+
+    let path = directory.join("quad.glb");
+
+    // A fake PNG with an odd length, so the binary chunk needs padding.
+    let png = vec![0x89, b'P', b'N', b'G', 1, 2, 3];
+
+    let mut scene = quad_scene();
+    scene.images.as_mut().unwrap()[0].data = Some(png.clone());
+
+    Gltf::from_scene(&scene).export(path.to_str().unwrap()).unwrap();
+
+    let glb = std::fs::read(&path).unwrap();
+    let read_u32 = |offset: usize| u32::from_le_bytes(glb[offset..offset + 4].try_into().unwrap());
+
+    assert_eq!(&glb[0..4], b"glTF");
+    assert_eq!(read_u32(4), 2);
+    assert_eq!(read_u32(8) as usize, glb.len());
+    assert_eq!(glb.len() % 4, 0);
+
+    let json_length = read_u32(12) as usize;
+    assert_eq!(json_length % 4, 0);
+    assert_eq!(&glb[16..20], b"JSON");
+
+    let bin_header = 20 + json_length;
+    assert_eq!(read_u32(bin_header) % 4, 0);
+    assert_eq!(&glb[bin_header + 4..bin_header + 8], b"BIN\0");
+
+    let json: serde_json::Value = serde_json::from_slice(&glb[20..bin_header]).unwrap();
+    assert!(json["buffers"][0].get("uri").is_none());
+    assert_eq!(json["images"][0]["mimeType"], "image/png");
+
+    let imported = Gltf::import(path.to_str().unwrap()).unwrap().to_scene(&directory).unwrap();
+
+    assert_eq!(imported.meshes[0].vertices, scene.meshes[0].vertices);
+    assert_eq!(imported.meshes[0].indices, scene.meshes[0].indices);
+
+    let image = &imported.images.as_ref().unwrap()[0];
+    assert_eq!(image.path, None);
+    assert_eq!(image.data_type, Some(modelo::ImageDataType::Png));
+    assert_eq!(image.data.as_ref(), Some(&png));
+}
+
+#[test]
+fn export_glb_image_files() {
+    let directory = temp_dir("export_glb_image_files");
+    let path = directory.join("quad.glb");
+    let _ = std::fs::remove_dir_all(directory.join("textures"));
+
+    // Images referred to by path are missing from a GLB unless the file is next to it.
+    let err = Gltf::from_scene(&quad_scene()).export(path.to_str().unwrap()).unwrap_err();
+    assert!(err.message.contains("quad%20albedo.png"), "{}", err.message);
+
+    let png = vec![0x89, b'P', b'N', b'G', 1, 2, 3];
+    std::fs::create_dir_all(directory.join("textures")).unwrap();
+    std::fs::write(directory.join("textures").join("quad albedo.png"), &png).unwrap();
+
+    Gltf::from_scene(&quad_scene()).export(path.to_str().unwrap()).unwrap();
+
+    // The image is embedded, so the GLB loads without its directory.
+    let imported = Gltf::import_bytes(&std::fs::read(&path).unwrap(), &fixtures_dir()).unwrap().to_scene(&fixtures_dir()).unwrap();
+    let image = &imported.images.as_ref().unwrap()[0];
+
+    assert_eq!(image.path, None);
+    assert_eq!(image.data_type, Some(modelo::ImageDataType::Png));
+    assert_eq!(image.data.as_ref(), Some(&png));
+    assert_eq!(imported.meshes[0].vertices, quad_scene().meshes[0].vertices);
+}
+
+#[test]
+fn image_uris() {
+    let mut scene = quad_scene();
+    scene.images.as_mut().unwrap()[0].path = Some("textures/50% #1 é.png".to_string());
+
+    let directory = temp_dir("image_uris");
+    let path = directory.join("quad.gltf");
+    Gltf::from_scene(&scene).export(path.to_str().unwrap()).unwrap();
+
+    let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(json["images"][0]["uri"], "textures/50%25%20%231%20%C3%A9.png");
+
+    let imported = Gltf::import(path.to_str().unwrap()).unwrap().to_scene(&directory).unwrap();
+    assert_eq!(imported.images.as_ref().unwrap()[0].path.as_deref(), Some("textures/50% #1 é.png"));
+
+    // Buffer URIs are decoded too.
+    assert!(load_modified("interleaved.gltf", |json| json["buffers"][0]["uri"] = "%69nterleaved.bin".into()).is_ok());
+}
+
+/// Loads a glTF fixture after changing its JSON.
+fn load_modified(name: &str, modify: impl FnOnce(&mut serde_json::Value)) -> Result<Scene, modelo::ImportError> {
+    let mut json: serde_json::Value = serde_json::from_slice(&std::fs::read(fixture_path(name)).unwrap()).unwrap();
+    modify(&mut json);
+
+    Gltf::import_bytes(&serde_json::to_vec(&json).unwrap(), &fixtures_dir())?.to_scene(&fixtures_dir())
+}
+
+#[test]
+fn malformed_accessors() {
+    assert!(load_modified("interleaved.gltf", |_| {}).is_ok());
+
+    let errors = [
+        load_modified("interleaved.gltf", |json| json["accessors"][0]["byteOffset"] = 999999999998u64.into()),
+        load_modified("interleaved.gltf", |json| json["accessors"][0]["count"] = 50000000000u64.into()),
+        load_modified("interleaved.gltf", |json| json["accessors"][0]["bufferView"] = 7.into()),
+        load_modified("interleaved.gltf", |json| json["bufferViews"][0]["buffer"] = 3.into()),
+        load_modified("interleaved.gltf", |json| json["bufferViews"][0]["byteLength"] = 4096.into()),
+        load_modified("interleaved.gltf", |json| json["meshes"][0]["primitives"][0]["indices"] = 9.into()),
+        load_modified("sparse.gltf", |json| json["accessors"][1]["count"] = 100000000000u64.into()),
+        load_modified("sparse.gltf", |json| json["accessors"][0]["sparse"]["count"] = 40.into())
+    ];
+
+    for (i, result) in errors.into_iter().enumerate() {
+        assert!(result.is_err(), "case {i} loaded");
+    }
+}
+
+#[test]
+fn out_of_range_indices() {
+    // The last index refers to a vertex past the end of the mesh.
+    let err = load_modified("interleaved.gltf", |json| json["accessors"][0]["count"] = 3.into()).unwrap_err();
+    assert!(err.message.contains("out of range"), "{}", err.message);
+}
+
+#[test]
+fn malformed_nodes() {
+    let errors = [
+        load_modified("interleaved.gltf", |json| json["scenes"][0]["nodes"] = serde_json::json!([5])),
+        load_modified("interleaved.gltf", |json| json["nodes"][0]["mesh"] = 3.into()),
+        load_modified("interleaved.gltf", |json| json["nodes"][0]["children"] = serde_json::json!([4])),
+        load_modified("interleaved.gltf", |json| json["nodes"][0]["children"] = serde_json::json!([0])),
+        load_modified("interleaved.gltf", |json| {
+            json["nodes"][0]["children"] = serde_json::json!([1]);
+            json["nodes"].as_array_mut().unwrap().push(serde_json::json!({ "children": [0] }));
+        })
+    ];
+
+    for (i, result) in errors.into_iter().enumerate() {
+        assert!(result.is_err(), "case {i} loaded");
+    }
+}
 
 #[test]
-fn load_from_file() {
-    let gltf = Gltf::import("/home/skye/Documents/SpaceBox/Models/OldSB/Functional/IonThruster.gltf").unwrap();
-    //let gltf = Gltf::from_path("/home/skye/Downloads/Fox.gltf").unwrap();
+fn round_trip_empty() {
+    let scene = Scene {
+        meshes: Vec::new(),
+        materials: None,
+        images: None,
+        nodes: Vec::new(),
+        root_nodes: Vec::new()
+    };
 
-    println!("{:#?}", gltf);
-}
\ No newline at end of file
+    assert_scenes_eq(&scene, &round_trip(&scene, "empty_gltf", "gltf"));
+    assert_scenes_eq(&scene, &round_trip(&scene, "empty_glb", "glb"));
+}
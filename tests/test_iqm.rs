@@ -0,0 +1,153 @@
+mod common;
+
+use common::{assert_vec2_eq, assert_vec3_eq};
+use modelo::{format, iqm::Iqm, resolver::NoResolver, Importer, ImportErrorType, Vec2, Vec3};
+
+const POSITION: u32 = 0;
+const TEX_COORD: u32 = 1;
+const NORMAL: u32 = 2;
+const BLEND_INDICES: u32 = 4;
+const BLEND_WEIGHTS: u32 = 5;
+
+const UBYTE: u32 = 1;
+const FLOAT: u32 = 7;
+
+fn bytes_u32(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+fn bytes_f32(values: &[f32]) -> Vec<u8> {
+    values.iter().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+/// Writes a triangle skinned to a root joint and an arm joint, with an animation that moves the arm.
+fn write_iqm(version: u32, triangle: [u32; 3]) -> Vec<u8> {
+    let text = b"\0body\0skin.png\0root\0arm\0wave\0";
+    let half = 0.5f32.sqrt();
+
+    let vertex_data = [
+        (POSITION, FLOAT, 3, bytes_f32(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0])),
+        (TEX_COORD, FLOAT, 2, bytes_f32(&[0.0, 0.0, 1.0, 0.0, 0.0, 1.0])),
+        (NORMAL, FLOAT, 3, bytes_f32(&[0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0])),
+        (BLEND_INDICES, UBYTE, 4, vec![0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0]),
+        (BLEND_WEIGHTS, UBYTE, 4, vec![255, 0, 0, 0, 255, 0, 0, 0, 51, 204, 0, 0])
+    ];
+
+    let meshes = bytes_u32(&[1, 6, 0, 3, 0, 1]);
+    let triangles = bytes_u32(&triangle);
+
+    let mut joints = bytes_u32(&[15, u32::MAX]);
+    joints.extend(bytes_f32(&[0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0]));
+    joints.extend(bytes_u32(&[20, 0]));
+    joints.extend(bytes_f32(&[1.0, 0.0, 0.0, 0.0, 0.0, half, half, 1.0, 1.0, 1.0]));
+
+    // Only the arm's height is animated, in steps of a half.
+    let mut poses = bytes_u32(&[u32::MAX, 0]);
+    poses.extend(bytes_f32(&[0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0]));
+    poses.extend(bytes_f32(&[0.0; 10]));
+    poses.extend(bytes_u32(&[0, 0b100]));
+    poses.extend(bytes_f32(&[1.0, 0.0, 0.0, 0.0, 0.0, half, half, 1.0, 1.0, 1.0]));
+    poses.extend(bytes_f32(&[0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]));
+
+    let mut animations = bytes_u32(&[24, 0, 2]);
+    animations.extend(bytes_f32(&[10.0]));
+    animations.extend(bytes_u32(&[1]));
+
+    let frames = [0u16, 4].iter().flat_map(|value| value.to_le_bytes()).collect::<Vec<_>>();
+
+    // Lay out the lumps after the header and the vertex array descriptions.
+    let mut offset = 124 + vertex_data.len() * 20;
+    let mut body = Vec::new();
+    let mut place = |lump: &[u8]| {
+        let start = offset;
+        body.extend_from_slice(lump);
+        offset += lump.len();
+        start as u32
+    };
+
+    let text_offset = place(text);
+    let meshes_offset = place(&meshes);
+    let vertex_offsets = vertex_data.iter().map(|(_, _, _, data)| place(data)).collect::<Vec<_>>();
+    let triangles_offset = place(&triangles);
+    let joints_offset = place(&joints);
+    let poses_offset = place(&poses);
+    let animations_offset = place(&animations);
+    let frames_offset = place(&frames);
+
+    let mut data = b"INTERQUAKEMODEL\0".to_vec();
+    data.extend(bytes_u32(&[version, offset as u32, 0, text.len() as u32, text_offset, 1, meshes_offset, vertex_data.len() as u32, 3, 124, 1, triangles_offset, 0,
+        2, joints_offset, 2, poses_offset, 1, animations_offset, 2, 1, frames_offset, 0, 0, 0, 0, 0]));
+
+    for ((array_type, format, size, _), offset) in vertex_data.iter().zip(vertex_offsets) {
+        data.extend(bytes_u32(&[*array_type, 0, *format, *size, offset]));
+    }
+
+    data.extend(body);
+    data
+}
+
+#[test]
+fn load_model() {
+    let data = write_iqm(2, [0, 2, 1]);
+    assert_eq!(format::detect(&data).map(|format| format.name), Some("Inter-Quake Model"));
+
+    let iqm = Iqm::import_bytes(&data, &NoResolver).unwrap();
+
+    assert_eq!(iqm.meshes[0].name, "body");
+    assert_eq!(iqm.meshes[0].material, "skin.png");
+    assert_eq!(iqm.positions.len(), 3);
+
+    // Blend indices are kept, however weights are normalized.
+    assert_eq!(iqm.blend_indices[2], [0, 1, 0, 0]);
+    assert_eq!(iqm.blend_weights[2], [0.2, 0.8, 0.0, 0.0]);
+
+    assert_eq!(iqm.joints[0].parent, None);
+    assert_eq!(iqm.joints[1].parent, Some(0));
+    assert_eq!(iqm.joints[1].name, "arm");
+
+    let animation = &iqm.animations[0];
+    assert_eq!(animation.name, "wave");
+    assert_eq!(animation.frame_count, 2);
+    assert!(animation.looping);
+
+    // The animated channel is added to the offset.
+    let transforms = iqm.frame_transforms(1).unwrap();
+    assert_vec3_eq(transforms[1].transform_point(Vec3::new(1.0, 0.0, 0.0)), Vec3::new(1.0, 1.0, 2.0), "arm");
+    assert!(iqm.frame_transforms(2).is_none());
+}
+
+#[test]
+fn to_scene() {
+    let scene = Iqm::parse(&write_iqm(2, [0, 2, 1])).unwrap().to_scene(&NoResolver).unwrap();
+
+    // Triangles are wound clockwise, so are reversed.
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.name.as_deref(), Some("body"));
+    assert_eq!(mesh.indices.as_ref().unwrap(), &vec![0, 1, 2]);
+    assert_eq!(mesh.material, Some(0));
+    assert_vec2_eq(mesh.vertices[1].tex_coord, Vec2::new(1.0, 0.0), "tex coord");
+    assert_eq!(scene.images.as_ref().unwrap()[0].path.as_deref(), Some("skin.png"));
+
+    // Joints are nodes under the node that converts from +Z up.
+    let root = &scene.nodes[0];
+    assert_eq!(root.children, vec![1]);
+    assert_eq!(root.extras.as_ref().unwrap()["animations"][0]["name"], "wave");
+
+    assert_eq!(scene.nodes[1].children, vec![2]);
+    assert_eq!(scene.find_node("arm"), Some(2));
+
+    let arm = scene.world_transform(2);
+    assert_vec3_eq(arm.transform_point(Vec3::new(1.0, 0.0, 0.0)), Vec3::new(1.0, 1.0, -1.0), "arm");
+}
+
+#[test]
+fn errors() {
+    let err = Iqm::parse(b"INTERQUAKE").unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::StringParseError), "{err:?}");
+
+    let err = Iqm::parse(&write_iqm(1, [0, 2, 1])).unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::UnsupportedFormat), "{err:?}");
+
+    let err = Iqm::parse(&write_iqm(2, [0, 2, 3])).unwrap_err();
+    assert!(err.message.contains("doesn't exist"), "{}", err.message);
+}
@@ -0,0 +1,231 @@
+mod common;
+
+use common::{assert_vec2_eq, assert_vec3_eq, assert_vec4_eq};
+use modelo::{format, max3ds::Max3ds, resolver::NoResolver, AlphaMode, Importer, ImportErrorType, Vec2, Vec3, Vec4};
+
+fn chunk(id: u16, contents: &[u8]) -> Vec<u8> {
+    let mut data = id.to_le_bytes().to_vec();
+    data.extend_from_slice(&(contents.len() as u32 + 6).to_le_bytes());
+    data.extend_from_slice(contents);
+    data
+}
+
+fn chunks(id: u16, children: &[Vec<u8>]) -> Vec<u8> {
+    chunk(id, &children.concat())
+}
+
+fn string(value: &str) -> Vec<u8> {
+    let mut data = value.as_bytes().to_vec();
+    data.push(0);
+    data
+}
+
+fn floats(values: &[f32]) -> Vec<u8> {
+    values.iter().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+fn shorts(values: &[u16]) -> Vec<u8> {
+    values.iter().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+/// Writes a track with a single key at frame zero.
+fn track(id: u16, value: &[f32]) -> Vec<u8> {
+    let mut data = shorts(&[0, 0, 0, 0, 0]);
+    data.extend_from_slice(&1u32.to_le_bytes());
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.extend_from_slice(&shorts(&[0]));
+    data.extend(floats(value));
+    chunk(id, &data)
+}
+
+/// Writes a roof with two slopes, which meet along a ridge, offset along X.
+fn object(name: &str, offset: f32, smoothing_groups: [u32; 4], material: Option<(&str, &[u16])>, face: u16) -> Vec<u8> {
+    let points = [[0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 0.0, 1.0], [1.0, 1.0, 1.0], [2.0, 0.0, 0.0], [2.0, 1.0, 0.0]];
+
+    let mut point_data = shorts(&[points.len() as u16]);
+    point_data.extend(points.iter().flat_map(|[x, y, z]| floats(&[x + offset, *y, *z])));
+
+    let mut tex_coord_data = shorts(&[points.len() as u16]);
+    tex_coord_data.extend(points.iter().flat_map(|[x, y, _]| floats(&[x / 2.0, *y])));
+
+    let mut face_data = shorts(&[4]);
+    face_data.extend(shorts(&[0, 2, 1, 0, 1, 2, 3, 0, 2, 4, 5, 0, 2, 5, face, 0]));
+    face_data.extend(chunk(0x4150, &smoothing_groups.iter().flat_map(|group| group.to_le_bytes()).collect::<Vec<_>>()));
+
+    if let Some((material, faces)) = material {
+        let mut group = string(material);
+        group.extend(shorts(&[faces.len() as u16]));
+        group.extend(shorts(faces));
+        face_data.extend(chunk(0x4130, &group));
+    }
+
+    let mut contents = string(name);
+    contents.extend(chunks(0x4100, &[
+        chunk(0x4110, &point_data),
+        chunk(0x4140, &tex_coord_data),
+        chunk(0x4120, &face_data),
+        chunk(0x4160, &floats(&[1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, offset, 0.0, 0.0]))
+    ]));
+
+    chunk(0x4000, &contents)
+}
+
+fn node(id: u16, name: &str, parent: u16, pivot: [f32; 3], position: [f32; 3], rotation: [f32; 4]) -> Vec<u8> {
+    let mut header = string(name);
+    header.extend(shorts(&[0, 0, parent]));
+
+    chunks(0xB002, &[
+        chunk(0xB030, &shorts(&[id])),
+        chunk(0xB010, &header),
+        chunk(0xB013, &floats(&pivot)),
+        track(0xB020, &position),
+        track(0xB021, &rotation),
+        track(0xB022, &[1.0, 1.0, 1.0])
+    ])
+}
+
+/// Writes a smooth roof, and a copy with sharp slopes that's a child of the first in the keyframer.
+fn write_3ds(face: u16) -> Vec<u8> {
+    let wood = chunks(0xAFFF, &[
+        chunk(0xA000, &string("Wood")),
+        chunks(0xA020, &[chunk(0x0011, &[255, 0, 0]), chunk(0x0013, &floats(&[0.0, 1.0, 0.0]))]),
+        chunks(0xA040, &[chunk(0x0030, &shorts(&[50]))]),
+        chunk(0xA081, &[]),
+        chunks(0xA200, &[chunk(0x0030, &shorts(&[100])), chunk(0xA300, &string("wood.jpg"))])
+    ]);
+
+    let paint = chunks(0xAFFF, &[
+        chunk(0xA000, &string("Paint")),
+        chunks(0xA020, &[chunk(0x0010, &floats(&[0.0, 0.0, 1.0]))]),
+        chunks(0xA050, &[chunk(0x0030, &shorts(&[25]))])
+    ]);
+
+    let editor = chunks(0x3D3D, &[
+        chunk(0x0100, &floats(&[1.0])),
+        wood,
+        paint,
+        object("Smooth", 0.0, [1, 1, 1, 1], Some(("Wood", &[0, 1, 2, 3])), 3),
+        object("Sharp", 3.0, [1, 1, 2, 2], Some(("Paint", &[0, 1])), face)
+    ]);
+
+    let keyframer = chunks(0xB000, &[
+        node(0, "Smooth", u16::MAX, [0.0; 3], [0.0; 3], [0.0, 0.0, 0.0, 1.0]),
+        node(1, "Sharp", 0, [1.0, 0.0, 0.0], [0.0, 0.0, 5.0], [std::f32::consts::FRAC_PI_2, 0.0, 0.0, 1.0])
+    ]);
+
+    chunks(0x4D4D, &[chunk(0x0002, &3u32.to_le_bytes()), editor, keyframer])
+}
+
+#[test]
+fn load_model() {
+    let data = write_3ds(3);
+    assert_eq!(format::detect(&data).map(|format| format.name), Some("3D Studio"));
+
+    let max3ds = Max3ds::import_bytes(&data, &NoResolver).unwrap();
+    assert_eq!(max3ds.version, 3);
+
+    // Gamma corrected colors are preferred.
+    let wood = &max3ds.materials[0];
+    assert_eq!(wood.name, "Wood");
+    assert_vec3_eq(wood.diffuse, Vec3::new(0.0, 1.0, 0.0), "diffuse");
+    assert_eq!(wood.shininess, 0.5);
+    assert!(wood.two_sided);
+    assert_eq!(wood.texture_map.as_ref().unwrap().path, "wood.jpg");
+    assert_eq!(max3ds.materials[1].transparency, 0.25);
+
+    let sharp = &max3ds.meshes[1];
+    assert_eq!(sharp.name, "Sharp");
+    assert_eq!(sharp.faces.len(), 4);
+    assert_eq!(sharp.smoothing_groups, vec![1, 1, 2, 2]);
+    assert_eq!(sharp.materials[0].faces, vec![0, 1]);
+    assert_vec3_eq(sharp.points[0], Vec3::new(3.0, 0.0, 0.0), "point");
+
+    assert_eq!(max3ds.nodes[1].parent, Some(0));
+    assert_vec3_eq(max3ds.nodes[1].pivot, Vec3::new(1.0, 0.0, 0.0), "pivot");
+}
+
+#[test]
+fn smoothing_groups() {
+    let scene = Max3ds::parse(&write_3ds(3)).unwrap().to_scene(&NoResolver).unwrap();
+
+    // Every face of the smooth roof is in the same group, so the ridge's normals point up.
+    let smooth = &scene.meshes[0];
+    assert_eq!(smooth.vertices.len(), 6);
+    assert_vec3_eq(smooth.vertices[0].normal, Vec3::new(-0.5f32.sqrt(), 0.0, 0.5f32.sqrt()), "slope");
+    assert_vec3_eq(smooth.vertices[1].normal, Vec3::new(0.0, 0.0, 1.0), "ridge");
+
+    // The sharp roof's slopes are in different groups, and in different meshes, as only one has a
+    // material.
+    let left = &scene.meshes[1];
+    let right = &scene.meshes[2];
+    assert_eq!(left.vertices.len(), 4);
+    assert_eq!(right.vertices.len(), 4);
+
+    for vertex in &left.vertices {
+        assert_vec3_eq(vertex.normal, Vec3::new(-0.5f32.sqrt(), 0.0, 0.5f32.sqrt()), "left");
+    }
+
+    for vertex in &right.vertices {
+        assert_vec3_eq(vertex.normal, Vec3::new(0.5f32.sqrt(), 0.0, 0.5f32.sqrt()), "right");
+    }
+}
+
+#[test]
+fn to_scene() {
+    let scene = Max3ds::parse(&write_3ds(3)).unwrap().to_scene(&NoResolver).unwrap();
+
+    assert_eq!(scene.root_nodes, vec![0]);
+    assert_eq!(scene.nodes[0].children, vec![1]);
+    assert_eq!(scene.nodes[0].meshes, vec![0]);
+    assert_eq!(scene.nodes[1].meshes, vec![1, 2]);
+    assert_eq!(scene.find_node("Sharp"), Some(1));
+
+    // Points are moved to be relative to the pivot, then placed by the node, and the scene is
+    // converted from +Z up.
+    let sharp = &scene.meshes[1];
+    assert_vec3_eq(sharp.vertices[0].position, Vec3::new(-1.0, 0.0, 0.0), "position");
+    assert_vec3_eq(scene.world_transform(1).transform_point(sharp.vertices[0].position), Vec3::new(0.0, 5.0, 1.0), "world");
+
+    let materials = scene.materials.as_ref().unwrap();
+
+    // The texture replaces the diffuse color.
+    let wood = &materials[scene.meshes[0].material.unwrap()];
+    assert_eq!(wood.name.as_deref(), Some("Wood"));
+    assert_vec4_eq(wood.albedo_color, Vec4::new(1.0, 1.0, 1.0, 1.0), "albedo");
+    assert_eq!(wood.albedo_texture, Some(0));
+    assert!(wood.double_sided);
+    assert_eq!(scene.images.as_ref().unwrap()[0].path.as_deref(), Some("wood.jpg"));
+
+    let paint = &materials[sharp.material.unwrap()];
+    assert_vec4_eq(paint.albedo_color, Vec4::new(0.0, 0.0, 1.0, 0.75), "albedo");
+    assert_eq!(paint.alpha_mode, AlphaMode::Blend);
+    assert_eq!(scene.meshes[2].material, None);
+
+    // Texture coordinates are flipped, since the origin is at the bottom.
+    assert_vec2_eq(scene.meshes[0].vertices[2].tex_coord, Vec2::new(0.0, 0.0), "tex coord");
+}
+
+#[test]
+fn without_keyframer() {
+    let mut max3ds = Max3ds::parse(&write_3ds(3)).unwrap();
+    max3ds.nodes.clear();
+
+    // Objects are left where they were created.
+    let scene = max3ds.to_scene(&NoResolver).unwrap();
+    assert_eq!(scene.root_nodes, vec![0, 1]);
+    assert_vec3_eq(scene.meshes[1].vertices[0].position, Vec3::new(3.0, 0.0, 0.0), "position");
+}
+
+#[test]
+fn errors() {
+    let err = Max3ds::parse(b"IDP2").unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::StringParseError), "{err:?}");
+
+    let mut data = write_3ds(3);
+    data.truncate(data.len() - 10);
+    let err = Max3ds::parse(&data).unwrap_err();
+    assert!(err.message.contains("longer than its parent"), "{}", err.message);
+
+    let err = Max3ds::parse(&write_3ds(9)).unwrap().to_scene(&NoResolver).unwrap_err();
+    assert!(err.message.contains("doesn't exist"), "{}", err.message);
+}
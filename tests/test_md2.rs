@@ -0,0 +1,119 @@
+mod common;
+
+use common::{assert_vec2_eq, assert_vec3_eq, fixed_string};
+use modelo::{format, md2::Md2, resolver::NoResolver, Importer, ImportErrorType, Vec2, Vec3};
+
+/// Writes a square in the XY plane, with the second frame moved up by one unit.
+fn write_md2(version: i32, triangle_vertex: u16) -> Vec<u8> {
+    let skins = ["models/crate/skin.pcx"];
+    let tex_coords: [[i16; 2]; 4] = [[0, 0], [64, 0], [64, 32], [0, 32]];
+    let triangles: [[u16; 6]; 2] = [[0, 2, 1, 0, 2, 1], [0, 3, 2, 0, 3, triangle_vertex]];
+    let frames = [("stand01", 0u8), ("stand02", 1), ("run1", 0)];
+    let positions: [[u8; 3]; 4] = [[0, 0, 0], [2, 0, 0], [2, 2, 0], [0, 2, 0]];
+
+    let frame_size = 40 + positions.len() * 4;
+    let skins_offset = 68;
+    let tex_coords_offset = skins_offset + skins.len() * 64;
+    let triangles_offset = tex_coords_offset + tex_coords.len() * 4;
+    let frames_offset = triangles_offset + triangles.len() * 12;
+    let end = frames_offset + frames.len() * frame_size;
+
+    let mut data = b"IDP2".to_vec();
+
+    for value in [version, 64, 32, frame_size as i32, skins.len() as i32, positions.len() as i32, tex_coords.len() as i32, triangles.len() as i32, 0, frames.len() as i32,
+        skins_offset as i32, tex_coords_offset as i32, triangles_offset as i32, frames_offset as i32, end as i32, end as i32] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    for skin in skins {
+        fixed_string(&mut data, skin, 64);
+    }
+
+    for value in tex_coords.iter().flatten() {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    for value in triangles.iter().flatten() {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    // Positions are scaled by a half, and translated by the frame's offset.
+    for (name, offset) in frames {
+        for value in [0.5f32, 0.5, 0.5, 0.0, 0.0, offset as f32] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+
+        fixed_string(&mut data, name, 16);
+
+        for position in positions {
+            data.extend_from_slice(&position);
+            data.push(0);
+        }
+    }
+
+    data
+}
+
+#[test]
+fn load_model() {
+    let data = write_md2(8, 2);
+    assert_eq!(format::detect(&data).map(|format| format.name), Some("Quake II MD2"));
+
+    let md2 = Md2::import_bytes(&data, &NoResolver).unwrap();
+
+    assert_eq!(md2.skins, vec![String::from("models/crate/skin.pcx")]);
+    assert_eq!(md2.frames.len(), 3);
+    assert_eq!(md2.frames[1].name, "stand02");
+    assert_vec3_eq(md2.frames[1].positions[2], Vec3::new(1.0, 1.0, 1.0), "position");
+
+    // Frames are grouped into animations by name.
+    assert_eq!(md2.animations(), vec![(String::from("stand"), 0..2), (String::from("run"), 2..3)]);
+
+    // Every frame's mesh has the same vertices.
+    let second = md2.mesh(1).unwrap();
+    assert_eq!(second.vertices.len(), 4);
+    assert_vec3_eq(second.vertices[1].position, Vec3::new(1.0, 0.0, 1.0), "position");
+    assert!(md2.mesh(3).is_none());
+}
+
+#[test]
+fn to_scene() {
+    let scene = Md2::parse(&write_md2(8, 2)).unwrap().to_scene(&NoResolver).unwrap();
+
+    assert_eq!(scene.meshes.len(), 1);
+
+    // Triangles are wound clockwise, so are reversed.
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.indices.as_ref().unwrap(), &vec![0, 1, 2, 0, 2, 3]);
+    assert_eq!(mesh.material, Some(0));
+
+    // Texture coordinates are in pixels of the skin.
+    assert_vec2_eq(mesh.vertices[2].tex_coord, Vec2::new(1.0, 1.0), "tex coord");
+    assert_vec3_eq(mesh.vertices[0].normal, Vec3::new(0.0, 0.0, 1.0), "normal");
+
+    let images = scene.images.as_ref().unwrap();
+    assert_eq!(images[0].path.as_deref(), Some("models/crate/skin.pcx"));
+
+    // The node converts from +Z up, and lists the animations.
+    let node = &scene.nodes[0];
+    assert_vec3_eq(node.transform.transform_point(Vec3::new(0.0, 0.0, 1.0)), Vec3::new(0.0, 1.0, 0.0), "up");
+    assert_eq!(node.extras.as_ref().unwrap()["animations"][1]["name"], "run");
+    assert_eq!(node.extras.as_ref().unwrap()["animations"][0]["frames"], 2);
+}
+
+#[test]
+fn errors() {
+    let err = Md2::parse(b"IDP3").unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::StringParseError), "{err:?}");
+
+    let err = Md2::parse(&write_md2(7, 2)).unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::UnsupportedFormat), "{err:?}");
+
+    let err = Md2::parse(&write_md2(8, 9)).unwrap_err();
+    assert!(err.message.contains("doesn't exist"), "{}", err.message);
+
+    let mut data = write_md2(8, 2);
+    data.truncate(data.len() - 8);
+    let err = Md2::parse(&data).unwrap_err();
+    assert!(err.message.contains("Unexpected end of file"), "{}", err.message);
+}
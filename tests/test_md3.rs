@@ -0,0 +1,135 @@
+mod common;
+
+use common::{assert_vec2_eq, assert_vec3_eq, fixed_string};
+use modelo::{format, md3::Md3, resolver::NoResolver, Importer, ImportErrorType, Vec2, Vec3};
+
+fn extend_i32s(data: &mut Vec<u8>, values: &[i32]) {
+    for value in values {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+fn extend_f32s(data: &mut Vec<u8>, values: &[f32]) {
+    for value in values {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+/// Writes a surface with one triangle in the XY plane.
+fn write_surface(name: &str, shader: &str, triangle: [i32; 3]) -> Vec<u8> {
+    let positions: [[i16; 3]; 3] = [[0, 0, 0], [64, 0, 0], [0, 128, 0]];
+
+    let shaders_offset = 108;
+    let triangles_offset = shaders_offset + 68;
+    let tex_coords_offset = triangles_offset + 12;
+    let vertices_offset = tex_coords_offset + 3 * 8;
+    let end = vertices_offset + 3 * 8;
+
+    let mut data = b"IDP3".to_vec();
+    fixed_string(&mut data, name, 64);
+    extend_i32s(&mut data, &[0, 1, 1, 3, 1, triangles_offset, shaders_offset, tex_coords_offset, vertices_offset, end]);
+
+    fixed_string(&mut data, shader, 64);
+    extend_i32s(&mut data, &[0]);
+    extend_i32s(&mut data, &triangle);
+    extend_f32s(&mut data, &[0.0, 0.0, 1.0, 0.0, 0.0, 0.5]);
+
+    // The normals point up, which is a longitude of zero.
+    for position in positions {
+        for value in position {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+
+        data.extend_from_slice(&[0, 0]);
+    }
+
+    data
+}
+
+fn write_md3(version: i32, triangle: [i32; 3]) -> Vec<u8> {
+    let surfaces = [write_surface("body", "models/players/body.tga", triangle), write_surface("head", "models/players/body.tga", [0, 1, 2])];
+
+    let frames_offset = 108;
+    let tags_offset = frames_offset + 56;
+    let surfaces_offset = tags_offset + 112;
+    let end = surfaces_offset + surfaces.iter().map(Vec::len).sum::<usize>() as i32;
+
+    let mut data = b"IDP3".to_vec();
+    extend_i32s(&mut data, &[version]);
+    fixed_string(&mut data, "models/players/sarge/upper.md3", 64);
+    extend_i32s(&mut data, &[0, 1, 1, surfaces.len() as i32, 0, frames_offset, tags_offset, surfaces_offset, end]);
+
+    extend_f32s(&mut data, &[0.0, 0.0, 0.0, 1.0, 2.0, 0.0, 0.0, 0.0, 0.0, 2.0]);
+    fixed_string(&mut data, "idle", 16);
+
+    // The tag is turned to the left.
+    fixed_string(&mut data, "tag_weapon", 64);
+    extend_f32s(&mut data, &[1.0, 2.0, 3.0, 0.0, 1.0, 0.0, -1.0, 0.0, 0.0, 0.0, 0.0, 1.0]);
+
+    for surface in surfaces {
+        data.extend_from_slice(&surface);
+    }
+
+    data
+}
+
+#[test]
+fn load_model() {
+    let data = write_md3(15, [0, 2, 1]);
+    assert_eq!(format::detect(&data).map(|format| format.name), Some("Quake III MD3"));
+
+    let md3 = Md3::import_bytes(&data, &NoResolver).unwrap();
+
+    assert_eq!(md3.name, "models/players/sarge/upper.md3");
+    assert_eq!(md3.frames[0].name, "idle");
+    assert_eq!(md3.frames[0].radius, 2.0);
+    assert_eq!(md3.tags[0][0].name, "tag_weapon");
+    assert_eq!(md3.surfaces.len(), 2);
+
+    // Positions are fixed point, with 64 steps per unit.
+    let body = &md3.surfaces[0];
+    assert_eq!(body.shaders, vec![String::from("models/players/body.tga")]);
+    assert_vec3_eq(body.frames[0].positions[2], Vec3::new(0.0, 2.0, 0.0), "position");
+    assert_vec3_eq(body.frames[0].normals[0], Vec3::new(0.0, 0.0, 1.0), "normal");
+}
+
+#[test]
+fn to_scene() {
+    let scene = Md3::parse(&write_md3(15, [0, 2, 1])).unwrap().to_scene(&NoResolver).unwrap();
+
+    // Both surfaces use the same shader, so share a material.
+    assert_eq!(scene.meshes.len(), 2);
+    assert_eq!(scene.meshes[0].material, Some(0));
+    assert_eq!(scene.meshes[1].material, Some(0));
+    assert_eq!(scene.materials.as_ref().unwrap().len(), 1);
+
+    // Triangles are wound clockwise, so are reversed.
+    let body = &scene.meshes[0];
+    assert_eq!(body.name.as_deref(), Some("body"));
+    assert_eq!(body.indices.as_ref().unwrap(), &vec![0, 1, 2]);
+    assert_vec2_eq(body.vertices[2].tex_coord, Vec2::new(0.0, 0.5), "tex coord");
+
+    // The tag is a child of the node that converts from +Z up.
+    let root = &scene.nodes[0];
+    assert_eq!(root.children, vec![1]);
+    assert_eq!(root.extras.as_ref().unwrap()["frames"], 1);
+
+    let tag = &scene.nodes[1];
+    assert_eq!(tag.name.as_deref(), Some("tag_weapon"));
+    assert_vec3_eq(tag.transform.transform_point(Vec3::new(1.0, 0.0, 0.0)), Vec3::new(1.0, 3.0, 3.0), "tag");
+
+    let world = root.transform * tag.transform;
+    assert_vec3_eq(world.transform_point(Vec3::new(0.0, 0.0, 0.0)), Vec3::new(1.0, 3.0, -2.0), "world");
+}
+
+#[test]
+fn errors() {
+    let err = Md3::parse(b"IDP2").unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::StringParseError), "{err:?}");
+
+    let err = Md3::parse(&write_md3(16, [0, 2, 1])).unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::UnsupportedFormat), "{err:?}");
+
+    let err = Md3::parse(&write_md3(15, [0, 2, 3])).unwrap_err();
+    assert!(err.message.contains("\"body\""), "{}", err.message);
+}
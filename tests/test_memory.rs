@@ -0,0 +1,76 @@
+mod common;
+
+use std::{cell::RefCell, io::{Cursor, Seek, SeekFrom}};
+
+use common::{assert_scenes_eq, fixtures_dir};
+use modelo::{gltf::Gltf, obj::Obj, resolver::NoResolver, Importer, ImportError, ImportErrorType, PostProcessFlags, Scene};
+
+fn read_fixture(name: &str) -> Vec<u8> {
+    std::fs::read(fixtures_dir().join(name)).unwrap()
+}
+
+#[test]
+fn load_bytes() {
+    let scene = Scene::load_bytes(&read_fixture("hierarchy.glb"), &NoResolver, PostProcessFlags::empty()).unwrap();
+    let expected = Scene::load(fixtures_dir().join("hierarchy.glb").to_str().unwrap(), PostProcessFlags::empty()).unwrap();
+
+    assert_scenes_eq(&expected, &scene);
+}
+
+#[test]
+fn resolve_from_memory() {
+    let requested = RefCell::new(Vec::new());
+
+    let resolver = |uri: &str| {
+        requested.borrow_mut().push(uri.to_string());
+        Ok(std::fs::read(fixtures_dir().join(uri))?)
+    };
+
+    let gltf = Gltf::import_bytes(&read_fixture("interleaved.gltf"), &resolver).unwrap();
+    let scene = gltf.to_scene(&resolver).unwrap();
+
+    assert_eq!(*requested.borrow(), vec!["interleaved.bin"]);
+    assert_scenes_eq(&Gltf::import(fixtures_dir().join("interleaved.gltf").to_str().unwrap()).unwrap().to_scene(&fixtures_dir()).unwrap(), &scene);
+
+    // Material libraries are resolved too.
+    let obj = Obj::import_bytes(&read_fixture("cube.obj"), &resolver).unwrap();
+    assert!(requested.borrow().contains(&"cube.mtl".to_string()));
+    assert_eq!(obj.materials.as_ref().unwrap()[0].diffuse_map.as_deref(), Some("textures/wood diffuse.png"));
+}
+
+#[test]
+fn load_reader() {
+    // Models are often stored inside a larger file, so read from the current position.
+    let mut data = b"PAK HEADER".to_vec();
+    data.extend(read_fixture("quad.ply"));
+
+    let mut cursor = Cursor::new(data);
+    cursor.seek(SeekFrom::Start(10)).unwrap();
+
+    let scene = Scene::load_reader(cursor, &NoResolver, PostProcessFlags::empty()).unwrap();
+    assert_eq!(scene.meshes[0].vertices.len(), 5);
+
+    let mut cursor = Cursor::new(read_fixture("cube.obj"));
+    let obj = Obj::import_reader(&mut cursor, &fixtures_dir()).unwrap();
+    assert_eq!(obj.meshes.len(), 3);
+}
+
+#[test]
+fn resolver_errors() {
+    let gltf = Gltf::import_bytes(&read_fixture("interleaved.gltf"), &NoResolver).unwrap();
+
+    let err = gltf.to_scene(&NoResolver).unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::FileNotFound), "{err:?}");
+
+    let failing = |_: &str| -> Result<Vec<u8>, ImportError> { Err(ImportError::new(ImportErrorType::Other, "Offline.")) };
+    assert!(Scene::load_bytes(&read_fixture("interleaved.gltf"), &failing, PostProcessFlags::empty()).is_err());
+
+    // A missing material library is skipped, but other errors loading it aren't.
+    assert!(Obj::import_bytes(&read_fixture("cube.obj"), &NoResolver).is_ok());
+
+    let err = Obj::import_bytes(&read_fixture("cube.obj"), &failing).unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::Other), "{err:?}");
+
+    let err = Scene::load_bytes(b"\x00\x01\x02", &NoResolver, PostProcessFlags::empty()).unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::UnsupportedFormat), "{err:?}");
+}
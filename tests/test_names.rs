@@ -0,0 +1,49 @@
+use modelo::{gltf::Gltf, Importer};
+use serde_json::json;
+
+#[test]
+fn gltf_names_and_extras() {
+    let directory = std::env::temp_dir().join("modelo_test_names");
+    std::fs::create_dir_all(&directory).unwrap();
+
+    let positions: [f32; 9] = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+    let buffer: Vec<u8> = positions.iter().flat_map(|value| value.to_le_bytes()).collect();
+    std::fs::write(directory.join("named.bin"), &buffer).unwrap();
+
+    let json = r#"{
+        "asset": { "version": "2.0" },
+        "buffers": [ { "uri": "named.bin", "byteLength": 36 } ],
+        "bufferViews": [ { "buffer": 0, "byteLength": 36 } ],
+        "accessors": [ { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" } ],
+        "images": [ { "uri": "hull.png", "name": "HullTexture" } ],
+        "materials": [ { "name": "Hull", "extras": { "surface": "metal" } } ],
+        "meshes": [ { "name": "HullMesh", "extras": { "collision": true }, "primitives": [ { "attributes": { "POSITION": 0 }, "material": 0 } ] } ],
+        "nodes": [
+            { "name": "Ship", "children": [1], "mesh": 0 },
+            { "name": "Attach_Gun", "extras": { "slot": 2, "tags": ["weapon", "front"] } }
+        ],
+        "scenes": [ { "nodes": [0] } ]
+    }"#;
+
+    let path = directory.join("named.gltf");
+    std::fs::write(&path, json).unwrap();
+
+    let scene = Gltf::import(path.to_str().unwrap()).unwrap().to_scene(&directory).unwrap();
+
+    assert_eq!(scene.meshes[0].name.as_deref(), Some("HullMesh"));
+    assert_eq!(scene.meshes[0].extras, Some(json!({ "collision": true })));
+
+    let materials = scene.materials.as_ref().unwrap();
+    assert_eq!(materials[0].name.as_deref(), Some("Hull"));
+    assert_eq!(materials[0].extras, Some(json!({ "surface": "metal" })));
+
+    let images = scene.images.as_ref().unwrap();
+    assert_eq!(images[0].name.as_deref(), Some("HullTexture"));
+    assert_eq!(images[0].extras, None);
+
+    let gun = scene.find_node("Attach_Gun").unwrap();
+    assert_eq!(gun, 1);
+    assert_eq!(scene.nodes[gun].extras.as_ref().unwrap()["tags"][0], "weapon");
+    assert_eq!(scene.nodes[0].name.as_deref(), Some("Ship"));
+    assert!(scene.find_node("Missing").is_none());
+}
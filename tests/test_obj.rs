@@ -1,8 +1,276 @@
-use modelo::{obj::Obj, Importer};
+mod common;
+
+use std::path::Path;
+
+use common::{assert_scenes_eq, assert_vec2_eq, assert_vec3_eq, fixture_path, fixtures_dir, temp_dir};
+use modelo::{obj::{Obj, FaceElement}, AlphaMode, BoundingBox, Importer, Exporter, ImportErrorType, Mesh, Scene, Vec2, Vec3, Vec4, Vertex};
 
 #[test]
 fn load_from_file() {
-    let obj = Obj::import("/home/skye/Documents/spaceboxlogo.obj").unwrap();
+    let obj = Obj::import(&fixture_path("cube.obj")).unwrap();
+
+    assert_eq!(obj.vertices.len(), 8);
+    assert_eq!(obj.tex_coords.as_ref().unwrap().len(), 4);
+    assert_eq!(obj.normals.as_ref().unwrap().len(), 6);
+
+    // "Caps" is split in two by the material change.
+    let names = obj.meshes.iter().map(|mesh| mesh.name.as_deref()).collect::<Vec<_>>();
+    assert_eq!(names, vec![Some("Sides"), Some("Caps"), Some("Caps")]);
+
+    let materials = obj.materials.as_ref().unwrap();
+    assert_eq!(materials.len(), 2);
+    assert_eq!(obj.meshes[0].material, Some(0));
+    assert_eq!(obj.meshes[1].material, Some(1));
+    assert_eq!(obj.meshes[2].material, Some(0));
+
+    // 4 quads, each split into 2 triangles.
+    assert_eq!(obj.meshes[0].face_elements.len(), 4 * 6);
+
+    // Relative indices refer back from the end of each list.
+    assert_eq!(obj.meshes[1].face_elements[0], FaceElement { vertex: 0, tex_coord: None, normal: Some(4) });
+    assert_eq!(obj.meshes[2].face_elements[0], FaceElement { vertex: 3, tex_coord: None, normal: None });
+}
+
+#[test]
+fn to_scene() {
+    let scene = Obj::import(&fixture_path("cube.obj")).unwrap().to_scene(&fixtures_dir()).unwrap();
+
+    assert_eq!(scene.meshes.len(), 3);
+
+    let sides = &scene.meshes[0];
+
+    // Each side has its own normal, so no vertices are shared between sides.
+    assert_eq!(sides.vertices.len(), 16);
+    assert_eq!(sides.indices.as_ref().unwrap().len(), 24);
+
+    let first = &sides.vertices[0];
+    assert_vec3_eq(first.position, Vec3::new(0.0, 0.0, 0.0), "position");
+    assert_vec3_eq(first.normal, Vec3::new(0.0, 0.0, -1.0), "normal");
+
+    // OBJ texture coordinates are flipped vertically.
+    assert_vec2_eq(first.tex_coord, Vec2::new(0.0, 1.0), "tex coord");
+
+    assert_vec3_eq(scene.bounds().max, Vec3::new(1.0, 1.0, 1.0), "bounds");
+
+    let materials = scene.materials.as_ref().unwrap();
+    assert_eq!(materials[1].name.as_deref(), Some("Metal"));
+}
+
+#[test]
+fn triangulate_and_colors() {
+    let obj = Obj::parse("
+        v 0 0 0 1 0 0
+        v 1 0 0 0 1 0
+        v 1 1 0 0 0 1
+        v 0.5 1.5 0 1 1 1
+        v 0 1 0 0 0 0
+        f 1 2 3 4 5
+    ").unwrap();
+
+    let elements = obj.meshes[0].face_elements.iter().map(|element| element.vertex).collect::<Vec<_>>();
+    assert_eq!(elements, vec![0, 1, 2, 0, 2, 3, 0, 3, 4]);
+
+    let scene = obj.to_scene(&fixtures_dir()).unwrap();
+    assert_eq!(scene.meshes[0].vertices[1].color.y, 1.0);
+    assert_eq!(scene.meshes[0].vertices.len(), 5);
+}
+
+#[test]
+fn mixed_colors() {
+    // Colors after a position don't replace the colors of later vertices given by `vc`.
+    let obj = Obj::parse("
+        vc 1 0 0
+        vc 0 1 0
+        v 0 0 0 0 0 1
+        v 1 0 0
+        v 0 1 0
+        f 1 2 3
+    ").unwrap();
+
+    let colors = obj.colors.as_ref().unwrap();
+    assert_eq!(colors, &vec![Vec4::new(0.0, 0.0, 1.0, 1.0), Vec4::new(0.0, 1.0, 0.0, 1.0), Vec4::new(1.0, 1.0, 1.0, 1.0)]);
+}
+
+#[test]
+fn parse_errors() {
+    let err = Obj::parse("v 0 0 0\nv 1 0 0\nf 1 2 3").unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::StringParseError));
+    assert!(err.message.starts_with("Line 3"), "{}", err.message);
+
+    assert!(Obj::parse("v 0 0 0\nf 1 1").is_err());
+    assert!(Obj::parse("v 0 zero 0").is_err());
+    assert!(Obj::parse("v 0 0 0\nf 0 1 1").is_err());
+}
+
+#[test]
+fn material_library() {
+    let obj = Obj::import(&fixture_path("cube.obj")).unwrap();
+
+    assert_eq!(obj.material_libraries, vec!["cube.mtl", "missing.mtl"]);
+
+    let materials = obj.materials.as_ref().unwrap();
+    assert_eq!(materials.len(), 2);
+
+    let wood = &materials[0];
+    assert_vec3_eq(wood.diffuse, Vec3::new(0.8, 0.6, 0.4), "diffuse");
+    assert_vec3_eq(wood.specular, Vec3::new(0.5, 0.5, 0.5), "specular");
+    assert_eq!(wood.illumination, Some(2));
+
+    // Options before the file name are skipped, and the file name can contain spaces.
+    assert_eq!(wood.diffuse_map.as_deref(), Some("textures/wood diffuse.png"));
+    assert_eq!(wood.bump_map.as_deref(), Some("textures/wood_bump.png"));
+
+    let scene = obj.to_scene(&fixtures_dir()).unwrap();
+    let materials = scene.materials.as_ref().unwrap();
+    let images = scene.images.as_ref().unwrap();
+
+    let wood = &materials[0];
+    assert_eq!(wood.alpha_mode, AlphaMode::Opaque);
+    assert!((wood.roughness - 0.1414).abs() < 1e-3, "{}", wood.roughness);
+    assert_eq!(images[wood.albedo_texture.unwrap()].path.as_deref(), Some("textures/wood diffuse.png"));
+    assert_eq!(images[wood.normal_texture.unwrap()].path.as_deref(), Some("textures/wood_bump.png"));
+
+    let metal = &materials[1];
+    assert_eq!(metal.alpha_mode, AlphaMode::Blend);
+    assert_eq!(metal.albedo_color.w, 0.75);
+    assert_eq!(metal.metallic, 1.0);
+    assert_eq!(metal.roughness, 0.2);
+
+    // "norm" is preferred over the bump map, and images used by several slots are only added once.
+    assert_eq!(images[metal.normal_texture.unwrap()].path.as_deref(), Some("textures/metal_normal.png"));
+    assert_eq!(metal.roughness_texture, metal.emissive_texture);
+    assert_eq!(images.len(), 5);
+}
+
+#[test]
+fn material_library_errors() {
+    let mut obj = Obj::parse("usemtl Glass").unwrap();
+
+    obj.load_material_library("newmtl Glass\nd 0.5\nmap_d alpha.png\n\nnewmtl Unused", Path::new("materials")).unwrap();
+
+    let materials = obj.materials.as_ref().unwrap();
+    assert_eq!(materials.len(), 2);
+    assert_eq!(materials[0].dissolve_map.as_deref(), Some("materials/alpha.png"));
+
+    let scene = obj.to_scene(&fixtures_dir()).unwrap();
+    assert_eq!(scene.materials.as_ref().unwrap()[0].alpha_mode, AlphaMode::Cutoff);
+
+    let err = obj.load_material_library("Kd 1 1 1", Path::new("")).unwrap_err();
+    assert!(err.message.starts_with("Line 1"), "{}", err.message);
+
+    assert!(obj.load_material_library("newmtl A\nKd 1 1", Path::new("")).is_err());
+    assert!(obj.load_material_library("newmtl A\nmap_Kd -bm 1", Path::new("")).is_err());
+}
+
+#[test]
+fn round_trip() {
+    let scene = Obj::import(&fixture_path("cube.obj")).unwrap().to_scene(&fixtures_dir()).unwrap();
+
+    let directory = temp_dir("obj_round_trip");
+    let path = directory.join("cube.obj");
+
+    Obj::from_scene(&scene).export(path.to_str().unwrap()).unwrap();
+    assert!(directory.join("cube.mtl").exists());
+
+    let obj = Obj::import(path.to_str().unwrap()).unwrap();
+
+    // Positions, texture coordinates and normals are shared between every object.
+    assert_eq!(obj.vertices.len(), 8);
+    assert_eq!(obj.tex_coords.as_ref().unwrap().len(), 4);
+    assert!(obj.colors.is_none());
+
+    // The last normal in the file isn't used by any face.
+    assert_eq!(obj.normals.as_ref().unwrap().len(), 5);
+
+    assert_scenes_eq(&scene, &obj.to_scene(&directory).unwrap());
+}
+
+#[test]
+fn round_trip_default_material() {
+    let mut scene = Obj::import(&fixture_path("cube.obj")).unwrap().to_scene(&fixtures_dir()).unwrap();
+    let material_count = scene.materials.as_ref().unwrap().len();
+
+    // A mesh without a material doesn't take the material of the mesh before it.
+    let first = &scene.meshes[0];
+
+    scene.meshes.push(Mesh {
+        vertices: first.vertices.clone(),
+        indices: first.indices.clone(),
+        material: None,
+        has_normals: first.has_normals,
+        bounds: first.bounds,
+        name: Some(String::from("Plain")),
+        extras: None
+    });
+
+    let directory = temp_dir("obj_round_trip_default_material");
+    let path = directory.join("cube.obj");
+
+    Obj::from_scene(&scene).export(path.to_str().unwrap()).unwrap();
+
+    let loaded = Obj::import(path.to_str().unwrap()).unwrap().to_scene(&directory).unwrap();
+    let materials = loaded.materials.as_ref().unwrap();
+
+    assert_eq!(materials.len(), material_count + 1);
+    assert_eq!(loaded.meshes.last().unwrap().material, Some(material_count));
+    assert_eq!(materials[material_count].name.as_deref(), Some("default"));
+    assert_eq!(materials[material_count].albedo_color, Vec4::new(1.0, 1.0, 1.0, 1.0));
+}
+
+#[test]
+fn round_trip_name_with_spaces() {
+    let scene = Obj::import(&fixture_path("cube.obj")).unwrap().to_scene(&fixtures_dir()).unwrap();
+
+    let directory = temp_dir("obj_round_trip_name_with_spaces");
+    let path = directory.join("my cube.obj");
+
+    Obj::from_scene(&scene).export(path.to_str().unwrap()).unwrap();
+    assert!(directory.join("my_cube.mtl").exists());
+
+    let obj = Obj::import(path.to_str().unwrap()).unwrap();
+    assert_eq!(obj.material_libraries, vec![String::from("my_cube.mtl")]);
+
+    assert_scenes_eq(&scene, &obj.to_scene(&directory).unwrap());
+}
+
+#[test]
+fn export_scene() {
+    let vertex = |x: f32, y: f32, color: Vec4| Vertex {
+        position: Vec3::new(x, y, 0.0),
+        tex_coord: Vec2::new(0.0, 0.0),
+        color,
+        normal: Vec3::new(0.0, 0.0, 0.0),
+        tangent: Vec4::new(0.0, 0.0, 0.0, 0.0)
+    };
+
+    let red = Vec4::new(1.0, 0.0, 0.0, 1.0);
+    let white = Vec4::new(1.0, 1.0, 1.0, 1.0);
+
+    // The same position with a different color must stay a separate vertex.
+    let vertices = vec![
+        vertex(0.0, 0.0, red), vertex(1.0, 0.0, white), vertex(0.0, 1.0, white),
+        vertex(0.0, 0.0, white), vertex(1.0, 0.0, white), vertex(0.0, 1.0, white)
+    ];
+    let bounds = BoundingBox::from_vertices(&vertices);
+
+    let scene = Scene {
+        meshes: vec![Mesh { vertices, indices: None, material: None, has_normals: false, bounds, name: None, extras: None }],
+        materials: None,
+        images: None,
+        nodes: Vec::new(),
+        root_nodes: Vec::new()
+    };
+
+    let obj = Obj::from_scene(&scene);
+    assert_eq!(obj.vertices.len(), 4);
+    assert!(obj.materials.is_none());
+
+    let text = obj.write_obj(None);
+    assert!(text.starts_with("v 0 0 0 1 0 0\n"), "{text}");
+    assert!(text.contains("o Mesh0\nf 1 2 3\nf 4 2 3\n"), "{text}");
+    assert!(!text.contains("mtllib") && !text.contains("vt") && !text.contains("vn"), "{text}");
 
-    println!("{obj:#?}");
-}
\ No newline at end of file
+    let scene = Obj::parse(&text).unwrap().to_scene(&fixtures_dir()).unwrap();
+    assert_eq!(scene.meshes[0].vertices[0].color, red);
+    assert_eq!(scene.meshes[0].vertices[3].color, white);
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::{assert_vec2_eq, assert_vec3_eq, assert_vec4_eq, temp_dir};
+use modelo::{format, off::Off, resolver::NoResolver, Importer, ImportErrorType, PostProcessFlags, Scene, Vec2, Vec3, Vec4};
+
+const COFF: &str = "# A coloured square
+COFF
+4 1 0
+
+0 0 0 255 0 0
+1 0 0 0 255 0
+1 1 0 0 0 255 128
+0 1 0 255 255 255
+4 0 1 2 3
+";
+
+#[test]
+fn load_coff() {
+    assert_eq!(format::detect(COFF.as_bytes()).map(|format| format.name), Some("Object File Format"));
+
+    let off = Off::import_bytes(COFF.as_bytes(), &NoResolver).unwrap();
+
+    assert_eq!(off.keyword(), "COFF");
+    assert_eq!(off.positions.len(), 4);
+    assert_eq!(off.faces[0].vertices, vec![0, 1, 2, 3]);
+    assert!(off.normals.is_none());
+
+    // Colors without decimal points are from 0 to 255.
+    let colors = off.colors.as_ref().unwrap();
+    assert_vec4_eq(colors[0], Vec4::new(1.0, 0.0, 0.0, 1.0), "color");
+    assert_vec4_eq(colors[2], Vec4::new(0.0, 0.0, 1.0, 128.0 / 255.0), "color");
+}
+
+#[test]
+fn load_noff() {
+    // The counts can be on the same line as the keyword, and faces can have their own colors.
+    let off = Off::parse("STNOFF 3 1 0\n0 0 0 0 0 1 0 0\n1 0 0 0 0 1 1 0\n0 1 0 0 0 1 0 1\n3 0 1 2 0.5 0.5 0.5\n").unwrap();
+
+    assert_vec3_eq(off.normals.as_ref().unwrap()[1], Vec3::new(0.0, 0.0, 1.0), "normal");
+    assert_vec2_eq(off.tex_coords.as_ref().unwrap()[2], Vec2::new(0.0, 1.0), "tex coord");
+    assert_eq!(off.faces[0].color, Some(Vec4::new(0.5, 0.5, 0.5, 1.0)));
+}
+
+#[test]
+fn to_scene() {
+    let scene = Off::parse(COFF).unwrap().to_scene(&NoResolver).unwrap();
+
+    // The quad is triangulated as a fan.
+    assert_eq!(scene.meshes.len(), 1);
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.indices.as_ref().unwrap(), &vec![0, 1, 2, 0, 2, 3]);
+    assert_vec4_eq(mesh.vertices[1].color, Vec4::new(0.0, 1.0, 0.0, 1.0), "color");
+    assert!(scene.nodes.is_empty());
+
+    // Vertices are split where faces with different colors meet.
+    let off = Off::parse("OFF\n4 2 0\n0 0 0\n1 0 0\n1 1 0\n0 1 0\n3 0 1 2 1 0 0\n3 0 2 3 0 0 1\n").unwrap();
+    let mesh = &off.to_scene(&NoResolver).unwrap().meshes[0];
+    assert_eq!(mesh.vertices.len(), 6);
+    assert_vec4_eq(mesh.vertices[3].color, Vec4::new(0.0, 0.0, 1.0, 1.0), "color");
+}
+
+#[test]
+fn round_trip() {
+    let off = Off::parse(COFF).unwrap();
+    assert_eq!(Off::parse(&off.write()).unwrap(), off);
+
+    let path = temp_dir("off").join("square.off");
+    let scene = off.to_scene(&NoResolver).unwrap();
+    scene.save(path.to_str().unwrap()).unwrap();
+
+    let loaded = Scene::load(path.to_str().unwrap(), PostProcessFlags::empty()).unwrap();
+    assert_eq!(loaded.meshes[0].indices, scene.meshes[0].indices);
+    assert_vec3_eq(loaded.meshes[0].vertices[2].position, Vec3::new(1.0, 1.0, 0.0), "position");
+    assert_vec4_eq(loaded.meshes[0].vertices[2].color, Vec4::new(0.0, 0.0, 1.0, 128.0 / 255.0), "color");
+}
+
+#[test]
+fn errors() {
+    let err = Off::parse("PLY\n0 0 0\n").unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::StringParseError), "{err:?}");
+
+    let err = Off::parse("4OFF\n0 0 0\n").unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::UnsupportedFormat), "{err:?}");
+
+    let err = Off::parse("OFF BINARY\n").unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::UnsupportedFormat), "{err:?}");
+
+    let err = Off::parse("OFF\n2 1 0\n0 0 0\n1 0 0\n2 0 1\n").unwrap_err();
+    assert!(err.message.contains("Line 5"), "{}", err.message);
+
+    let err = Off::parse("OFF\n3 1 0\n0 0 0\n1 0 0\n0 1 0\n3 0 1 3\n").unwrap_err();
+    assert!(err.message.contains("index is invalid"), "{}", err.message);
+}
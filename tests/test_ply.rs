@@ -0,0 +1,82 @@
+mod common;
+
+use common::{assert_scenes_eq, assert_vec2_eq, assert_vec4_eq, fixture_path, fixtures_dir, temp_dir};
+use modelo::{ply::{Format, Ply}, Importer, Exporter, ImportErrorType, Vec2, Vec4};
+
+#[test]
+fn load_ascii() {
+    let ply = Ply::import(&fixture_path("quad.ply")).unwrap();
+
+    assert_eq!(ply.format, Format::Ascii);
+    assert_eq!(ply.comments.len(), 2);
+    assert_eq!(ply.vertices.len(), 5);
+    assert_eq!(ply.indices, vec![0, 1, 2, 0, 2, 3, 3, 2, 4]);
+
+    let scene = ply.to_scene(&fixtures_dir()).unwrap();
+    let vertices = &scene.meshes[0].vertices;
+
+    // Colors stored as uchar are normalized, and texture coordinates are flipped vertically.
+    assert_vec4_eq(vertices[4].color, Vec4::new(0.2, 0.4, 0.6, 1.0), "color");
+    assert_vec2_eq(vertices[0].tex_coord, Vec2::new(0.0, 1.0), "tex coord");
+    assert_eq!(vertices[4].normal.z, 1.0);
+}
+
+#[test]
+fn round_trip() {
+    let scene = Ply::import(&fixture_path("quad.ply")).unwrap().to_scene(&fixtures_dir()).unwrap();
+    let directory = temp_dir("ply_round_trip");
+
+    for format in [Format::Ascii, Format::BinaryLittleEndian, Format::BinaryBigEndian] {
+        let mut ply = Ply::from_scene(&scene);
+        ply.format = format;
+
+        let path = directory.join(format!("{}.ply", format.as_str()));
+        ply.export(path.to_str().unwrap()).unwrap();
+
+        let loaded = Ply::import(path.to_str().unwrap()).unwrap();
+        assert_eq!(loaded.format, format);
+
+        assert_scenes_eq(&scene, &loaded.to_scene(&directory).unwrap());
+    }
+}
+
+#[test]
+fn point_cloud() {
+    let data = b"ply\r\nformat binary_big_endian 1.0\r\nelement vertex 2\r\nproperty double x\r\nproperty double y\r\nproperty double z\r\nproperty ushort red\r\nproperty ushort green\r\nproperty ushort blue\r\nend_header\r\n";
+
+    let mut data = data.to_vec();
+
+    for (position, color) in [([1.0f64, 2.0, 3.0], [65535u16, 0, 0]), ([4.0, 5.0, 6.0], [0, 65535, 0])] {
+        position.iter().for_each(|value| data.extend_from_slice(&value.to_be_bytes()));
+        color.iter().for_each(|value| data.extend_from_slice(&value.to_be_bytes()));
+    }
+
+    let ply = Ply::parse(&data).unwrap();
+
+    assert_eq!(ply.vertices[1].y, 5.0);
+    assert_eq!(ply.colors.as_ref().unwrap()[1], Vec4::new(0.0, 1.0, 0.0, 1.0));
+    assert!(ply.indices.is_empty());
+    assert!(ply.to_scene(&fixtures_dir()).unwrap().meshes[0].indices.is_none());
+
+    // Missing data is an error.
+    assert!(Ply::parse(&data[..data.len() - 1]).is_err());
+}
+
+#[test]
+fn parse_errors() {
+    let err = Ply::parse(b"ply\nformat ascii 1.0\nelement vertex 1\nproperty half x\nend_header\n0\n").unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::StringParseError));
+    assert!(err.message.starts_with("Line 4"), "{}", err.message);
+
+    assert!(Ply::parse(b"solid cube").is_err());
+    assert!(Ply::parse(b"ply\nelement vertex 1\nproperty float x\nend_header\n0\n").is_err());
+    assert!(Ply::parse(b"ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\n").is_err());
+
+    let out_of_range = b"ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\nelement face 1\nproperty list uchar int vertex_indices\nend_header\n0\n3 0 0 1\n";
+    assert!(Ply::parse(out_of_range).is_err());
+
+    // A count far larger than the data is an error, rather than allocated for.
+    let huge_count = b"ply\nformat binary_little_endian 1.0\nelement vertex 4294967295\nproperty float x\nend_header\n\0\0\0\0";
+    let err = Ply::parse(huge_count).unwrap_err();
+    assert!(err.message.contains("Unexpected end of file"), "{}", err.message);
+}
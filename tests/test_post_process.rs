@@ -0,0 +1,352 @@
+mod common;
+
+use common::{assert_vec3_eq, assert_vec4_eq};
+use modelo::{gltf::Gltf, resolver::NoResolver, BoundingBox, Importer, Exporter, Mesh, NormalMode, PostProcessFlags, Scene, Vec2, Vec3, Vec4, Vertex, WeldOptions};
+
+fn vertex(x: f32, y: f32, u: f32, v: f32) -> Vertex {
+    Vertex {
+        position: Vec3::new(x, y, 0.0),
+        tex_coord: Vec2::new(u, v),
+        color: Vec4::new(1.0, 1.0, 1.0, 1.0),
+        normal: Vec3::new(0.0, 0.0, 1.0),
+        tangent: Vec4::new(0.0, 0.0, 0.0, 0.0)
+    }
+}
+
+fn scene(vertices: Vec<Vertex>, indices: Option<Vec<u32>>) -> Scene {
+    let bounds = BoundingBox::from_vertices(&vertices);
+
+    Scene {
+        meshes: vec![Mesh {
+            vertices,
+            indices,
+            material: None,
+            has_normals: true,
+            bounds,
+            name: None,
+            extras: None
+        }],
+        materials: None,
+        images: None,
+        nodes: Vec::new(),
+        root_nodes: Vec::new()
+    }
+}
+
+/// A quad facing +Z, with the texture's origin at its top left.
+fn quad() -> Scene {
+    let vertices = vec![vertex(0.0, 0.0, 0.0, 1.0), vertex(1.0, 0.0, 1.0, 1.0), vertex(1.0, 1.0, 1.0, 0.0), vertex(0.0, 1.0, 0.0, 0.0)];
+    scene(vertices, Some(vec![0, 1, 2, 2, 3, 0]))
+}
+
+/// A roof without normals, with two slopes meeting at a right angle along a ridge.
+fn roof() -> Scene {
+    let vertex = |x: f32, y: f32, z: f32| Vertex { position: Vec3::new(x, y, z), normal: Vec3::new(0.0, 0.0, 0.0), ..vertex(0.0, 0.0, 0.0, 0.0) };
+    let vertices = vec![vertex(0.0, 0.0, 0.0), vertex(0.0, 0.0, 1.0), vertex(1.0, 1.0, 0.0), vertex(1.0, 1.0, 1.0), vertex(2.0, 0.0, 0.0), vertex(2.0, 0.0, 1.0)];
+
+    let mut scene = scene(vertices, Some(vec![0, 1, 3, 0, 3, 2, 2, 3, 4, 3, 5, 4]));
+    scene.meshes[0].has_normals = false;
+    scene
+}
+
+/// Checks the normal used by each triangle of the roof at the ridge.
+fn assert_ridge_normals(scene: &Scene, left: Vec3, right: Vec3) {
+    let mesh = &scene.meshes[0];
+    let indices = mesh.indices.as_ref().unwrap();
+
+    assert_vec3_eq(mesh.vertices[indices[2] as usize].normal, left, "left");
+    assert_vec3_eq(mesh.vertices[indices[6] as usize].normal, right, "right");
+}
+
+#[test]
+fn smooth_normals() {
+    let mut scene = roof();
+    scene.post_process(PostProcessFlags::GENERATE_NORMALS);
+
+    assert!(scene.meshes[0].has_normals);
+    assert_eq!(scene.meshes[0].vertices.len(), 6);
+    assert_ridge_normals(&scene, Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+    assert_vec3_eq(scene.meshes[0].vertices[0].normal, Vec3::new(-0.5f32.sqrt(), 0.5f32.sqrt(), 0.0), "eaves");
+}
+
+#[test]
+fn flat_normals() {
+    let mut scene = roof();
+    scene.post_process(PostProcessFlags::GENERATE_NORMALS | PostProcessFlags::GENERATE_FLAT_NORMALS);
+
+    // The ridge's vertices are split between the slopes.
+    assert_eq!(scene.meshes[0].vertices.len(), 8);
+    assert_ridge_normals(&scene, Vec3::new(-0.5f32.sqrt(), 0.5f32.sqrt(), 0.0), Vec3::new(0.5f32.sqrt(), 0.5f32.sqrt(), 0.0));
+}
+
+#[test]
+fn crease_angle_normals() {
+    let mut scene = roof();
+    scene.generate_normals(NormalMode::CreaseAngle(std::f32::consts::FRAC_PI_4));
+
+    // The ridge is sharper than the crease angle, so it's split.
+    assert_eq!(scene.meshes[0].vertices.len(), 8);
+    assert_ridge_normals(&scene, Vec3::new(-0.5f32.sqrt(), 0.5f32.sqrt(), 0.0), Vec3::new(0.5f32.sqrt(), 0.5f32.sqrt(), 0.0));
+
+    let mut scene = roof();
+    scene.generate_normals(NormalMode::CreaseAngle(2.0));
+
+    assert_eq!(scene.meshes[0].vertices.len(), 6);
+    assert_ridge_normals(&scene, Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+}
+
+#[test]
+fn weighted_normals() {
+    // The corner of a cube, where the face on +Z is split into two triangles at the corner, and
+    // the others have one. The mesh isn't indexed, so the corner is found by its position.
+    let vertex = |x: f32, y: f32, z: f32| Vertex { position: Vec3::new(x, y, z), normal: Vec3::new(0.0, 0.0, 0.0), ..vertex(0.0, 0.0, 0.0, 0.0) };
+    let vertices = vec![
+        vertex(0.0, 0.0, 0.0), vertex(1.0, 0.0, 0.0), vertex(1.0, 1.0, 0.0),
+        vertex(0.0, 0.0, 0.0), vertex(1.0, 1.0, 0.0), vertex(0.0, 1.0, 0.0),
+        vertex(0.0, 0.0, 0.0), vertex(0.0, 1.0, 0.0), vertex(0.0, 0.0, 1.0),
+        vertex(0.0, 0.0, 0.0), vertex(0.0, 0.0, 1.0), vertex(1.0, 0.0, 0.0)
+    ];
+
+    let corner = |mode: NormalMode| {
+        let mut scene = scene(vertices.clone(), None);
+        scene.meshes[0].has_normals = false;
+        scene.generate_normals(mode);
+
+        let mesh = &scene.meshes[0];
+        assert!(mesh.indices.is_none());
+
+        for i in [3, 6, 9] {
+            assert_eq!(mesh.vertices[i].normal, mesh.vertices[0].normal);
+        }
+
+        mesh.vertices[0].normal
+    };
+
+    // Weighting by angle gives each side of the cube the same weight, however it's triangulated.
+    let third = (1.0f32 / 3.0).sqrt();
+    assert_vec3_eq(corner(NormalMode::AngleWeighted), Vec3::new(third, third, third), "angle weighted");
+
+    let sixth = (1.0f32 / 6.0).sqrt();
+    assert_vec3_eq(corner(NormalMode::AreaWeighted), Vec3::new(sixth, sixth, 2.0 * sixth), "area weighted");
+}
+
+#[test]
+fn existing_normals() {
+    // Meshes with normals are left alone, even if some are zero.
+    let mut scene = roof();
+    scene.meshes[0].has_normals = true;
+    scene.meshes[0].vertices[1].normal = Vec3::new(1.0, 0.0, 0.0);
+    scene.post_process(PostProcessFlags::GENERATE_NORMALS);
+
+    assert_eq!(scene.meshes[0].vertices[0].normal, Vec3::new(0.0, 0.0, 0.0));
+
+    // Meshes without them are regenerated, whatever the first vertex has.
+    scene.meshes[0].has_normals = false;
+    scene.meshes[0].vertices[0].normal = Vec3::new(1.0, 0.0, 0.0);
+    scene.post_process(PostProcessFlags::GENERATE_NORMALS);
+
+    assert_vec3_eq(scene.meshes[0].vertices[0].normal, Vec3::new(-0.5f32.sqrt(), 0.5f32.sqrt(), 0.0), "normal");
+}
+
+#[test]
+fn generate_tangents() {
+    let mut scene = quad();
+    scene.post_process(PostProcessFlags::GENERATE_TANGENTS);
+
+    // The tangent follows U, and the bitangent points to the top of the texture.
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.vertices.len(), 4);
+
+    for vertex in &mesh.vertices {
+        assert_vec4_eq(vertex.tangent, Vec4::new(1.0, 0.0, 0.0, 1.0), "tangent");
+    }
+}
+
+#[test]
+fn generate_tangents_non_indexed() {
+    let quad = quad();
+    let vertices = quad.meshes[0].indices.as_ref().unwrap().iter().map(|&i| quad.meshes[0].vertices[i as usize]).collect();
+
+    let mut scene = scene(vertices, None);
+    scene.post_process(PostProcessFlags::GENERATE_TANGENTS);
+
+    assert!(scene.meshes[0].indices.is_none());
+    assert_eq!(scene.meshes[0].vertices.len(), 6);
+
+    for vertex in &scene.meshes[0].vertices {
+        assert_vec4_eq(vertex.tangent, Vec4::new(1.0, 0.0, 0.0, 1.0), "tangent");
+    }
+}
+
+#[test]
+fn generate_tangents_curved() {
+    // A low pyramid with smooth normals and an uneven texture mapping, so each face's tangent is
+    // different at the shared apex.
+    let corner = |x: f32, y: f32, z: f32, u: f32, v: f32| {
+        let mut normal = Vec3::new(x * 0.5, y * 0.5, 1.0);
+        normal.normalize();
+
+        Vertex { position: Vec3::new(x, y, z), normal, ..vertex(0.0, 0.0, u, v) }
+    };
+
+    let vertices = vec![
+        corner(0.0, 0.0, 0.5, 0.5, 0.5),
+        corner(1.0, 0.0, 0.0, 1.0, 0.5), corner(0.0, 1.0, 0.0, 0.5, 0.0), corner(-1.0, 0.0, 0.0, 0.0, 0.4), corner(0.0, -1.0, 0.0, 0.4, 1.0)
+    ];
+    let indices = vec![0, 1, 2, 0, 2, 3, 0, 3, 4, 0, 4, 1];
+
+    let mut indexed = scene(vertices.clone(), Some(indices.clone()));
+    indexed.post_process(PostProcessFlags::GENERATE_TANGENTS);
+
+    let mut duplicated = scene(indices.iter().map(|&i| vertices[i as usize]).collect(), None);
+    duplicated.post_process(PostProcessFlags::GENERATE_TANGENTS);
+
+    // Duplicated vertices are averaged like shared ones, so there are no seams between the faces.
+    let indexed_mesh = &indexed.meshes[0];
+    let indexed_vertices = indexed_mesh.indices.as_ref().unwrap().iter().map(|&i| indexed_mesh.vertices[i as usize]);
+
+    for (a, b) in indexed_vertices.zip(&duplicated.meshes[0].vertices) {
+        assert_vec4_eq(a.tangent, b.tangent, "tangent");
+    }
+
+    let apex = duplicated.meshes[0].vertices[0].tangent;
+    assert!(apex.x.abs() < 0.999, "the apex's tangent should be averaged: {apex:?}");
+}
+
+#[test]
+fn mirrored_tangents() {
+    // Two quads sharing an edge, with the right one's texture mirrored.
+    let vertices = vec![
+        vertex(0.0, 0.0, 0.0, 1.0), vertex(1.0, 0.0, 1.0, 1.0), vertex(1.0, 1.0, 1.0, 0.0), vertex(0.0, 1.0, 0.0, 0.0),
+        vertex(2.0, 0.0, 0.0, 1.0), vertex(2.0, 1.0, 0.0, 0.0)
+    ];
+
+    let mut scene = scene(vertices, Some(vec![0, 1, 2, 2, 3, 0, 1, 4, 5, 5, 2, 1]));
+    scene.post_process(PostProcessFlags::GENERATE_TANGENTS);
+
+    // The shared edge's vertices are split, as the sign of the bitangent differs.
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.vertices.len(), 8);
+
+    let indices = mesh.indices.as_ref().unwrap();
+    assert_eq!(&indices[..6], &[0, 1, 2, 2, 3, 0]);
+
+    for &index in &indices[6..] {
+        assert_vec4_eq(mesh.vertices[index as usize].tangent, Vec4::new(-1.0, 0.0, 0.0, -1.0), "mirrored tangent");
+    }
+
+    assert_vec4_eq(mesh.vertices[1].tangent, Vec4::new(1.0, 0.0, 0.0, 1.0), "tangent");
+    assert_eq!(mesh.vertices[indices[6] as usize].position, mesh.vertices[1].position);
+}
+
+#[test]
+fn tangents_need_tex_coords() {
+    let mut scene = quad();
+
+    for vertex in &mut scene.meshes[0].vertices {
+        vertex.tex_coord = Vec2::new(0.0, 0.0);
+    }
+
+    scene.post_process(PostProcessFlags::GENERATE_TANGENTS);
+
+    for vertex in &scene.meshes[0].vertices {
+        assert_eq!(vertex.tangent, Vec4::new(0.0, 0.0, 0.0, 0.0));
+    }
+}
+
+#[test]
+fn tangents_round_trip_gltf() {
+    let mut scene = quad();
+    scene.post_process(PostProcessFlags::GENERATE_TANGENTS);
+
+    // The bitangent sign is kept.
+    let round_trip = Gltf::from_scene(&scene).to_scene(&NoResolver).unwrap();
+    assert_eq!(round_trip.meshes[0].vertices, scene.meshes[0].vertices);
+}
+
+#[test]
+fn weld_vertices() {
+    // The quad's triangles don't share vertices, and the copies have a little noise and no normals.
+    let quad = quad();
+    let mut vertices = quad.meshes[0].indices.as_ref().unwrap().iter().map(|&i| quad.meshes[0].vertices[i as usize]).collect::<Vec<_>>();
+
+    for vertex in &mut vertices[3..] {
+        vertex.position.x += 1e-7;
+        vertex.normal = Vec3::new(0.0, 0.0, 0.0);
+    }
+
+    // Indexing only merges identical vertices.
+    let mut indexed = scene(vertices.clone(), None);
+    indexed.post_process(PostProcessFlags::GENERATE_INDICES);
+    assert_eq!(indexed.meshes[0].vertices.len(), 6);
+
+    let mut welded = scene(vertices, None);
+    assert_eq!(welded.weld_vertices(WeldOptions::default()), 2);
+
+    let mesh = &welded.meshes[0];
+    assert_eq!(mesh.vertices.len(), 4);
+    assert_eq!(mesh.indices.as_ref().unwrap(), &vec![0, 1, 2, 2, 3, 0]);
+    assert_eq!(mesh.vertices[2].normal, Vec3::new(0.0, 0.0, 1.0));
+}
+
+#[test]
+fn weld_tolerances() {
+    // Vertices either side of a cell boundary are still found.
+    let vertices = vec![vertex(0.999, 0.0, 0.0, 0.0), vertex(1.001, 0.0, 0.5, 0.0), vertex(1.0, 1.0, 0.0, 0.0), vertex(1.0, 1.0, 0.0, 0.001)];
+
+    let mut welded = scene(vertices.clone(), Some(vec![0, 2, 1, 1, 2, 3]));
+    assert_eq!(welded.weld_vertices(WeldOptions { position_epsilon: 0.01, ..WeldOptions::default() }), 2);
+    assert_eq!(welded.meshes[0].indices.as_ref().unwrap(), &vec![0, 1, 0, 0, 1, 1]);
+
+    // Texture coordinates further apart than their tolerance keep the vertices separate.
+    let mut welded = scene(vertices, Some(vec![0, 2, 1, 1, 2, 3]));
+    let options = WeldOptions { position_epsilon: 0.01, tex_coord_tolerance: Some(0.01), ..WeldOptions::default() };
+    assert_eq!(welded.weld_vertices(options), 1);
+    assert_eq!(welded.meshes[0].indices.as_ref().unwrap(), &vec![0, 2, 1, 1, 2, 2]);
+}
+
+#[test]
+fn weld_regenerates_normals() {
+    // The roof's ridge is a hard edge, with each slope's vertices having that slope's normal.
+    let mut hard = roof();
+    hard.post_process(PostProcessFlags::GENERATE_FLAT_NORMALS);
+    assert!(hard.meshes[0].vertices.len() > 6);
+
+    hard.post_process(PostProcessFlags::WELD_VERTICES | PostProcessFlags::GENERATE_NORMALS);
+    assert_eq!(hard.meshes[0].vertices.len(), 6);
+    assert!(hard.meshes[0].has_normals);
+    assert_ridge_normals(&hard, Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+
+    // A vertex without a normal takes the normal of the vertex merged into it.
+    let mut vertices = vec![vertex(0.0, 0.0, 0.0, 0.0), vertex(1.0, 0.0, 0.0, 0.0), vertex(0.0, 1.0, 0.0, 0.0), vertex(0.0, 0.0, 0.0, 0.0)];
+    vertices[0].normal = Vec3::new(0.0, 0.0, 0.0);
+
+    let mut welded = scene(vertices, None);
+    welded.post_process(PostProcessFlags::WELD_VERTICES | PostProcessFlags::GENERATE_NORMALS);
+    assert!(welded.meshes[0].has_normals);
+    assert_eq!(welded.meshes[0].vertices[0].normal, Vec3::new(0.0, 0.0, 1.0));
+}
+
+#[test]
+fn weld_tangents() {
+    let mut vertices = vec![vertex(0.0, 0.0, 0.0, 0.0), vertex(0.0, 0.0, 0.0, 0.0), vertex(1.0, 0.0, 0.0, 0.0), vertex(1.0, 0.0, 0.0, 0.0)];
+    vertices[0].tangent = Vec4::new(1.0, 0.0, 0.0, 1.0);
+    vertices[1].tangent = Vec4::new(0.0, 1.0, 0.0, 1.0);
+    vertices[2].tangent = Vec4::new(1.0, 0.0, 0.0, -1.0);
+    vertices[3].tangent = Vec4::new(1.0, 0.0, 0.0, -1.0);
+
+    // Vertices that disagree lose their tangent, and ones that agree keep it.
+    let mut welded = scene(vertices, None);
+    assert_eq!(welded.weld_vertices(WeldOptions::default()), 2);
+    assert_eq!(welded.meshes[0].vertices[0].tangent, Vec4::new(0.0, 0.0, 0.0, 0.0));
+    assert_eq!(welded.meshes[0].vertices[1].tangent, Vec4::new(1.0, 0.0, 0.0, -1.0));
+}
+
+#[test]
+fn weld_huge_positions() {
+    let vertices = vec![vertex(f32::INFINITY, 0.0, 0.0, 0.0), vertex(f32::MAX, f32::NEG_INFINITY, 0.0, 0.0), vertex(f32::NAN, 1.0, 0.0, 0.0), vertex(f32::INFINITY, 0.0, 0.0, 0.0)];
+
+    let mut welded = scene(vertices, None);
+    assert_eq!(welded.weld_vertices(WeldOptions::default()), 0);
+    assert_eq!(welded.meshes[0].vertices.len(), 4);
+}
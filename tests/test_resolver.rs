@@ -0,0 +1,98 @@
+mod common;
+
+use common::{assert_scenes_eq, fixture_path, fixtures_dir, temp_dir};
+use modelo::{resolver::{normalize_path, FileSystemResolver, MemoryResolver, ResourceResolver}, ImportErrorType, PostProcessFlags, Scene};
+
+fn read_fixture(name: &str) -> Vec<u8> {
+    std::fs::read(fixtures_dir().join(name)).unwrap()
+}
+
+#[test]
+fn normalize() {
+    assert_eq!(normalize_path("./models/../models\\textures//wood.png").unwrap(), "models/textures/wood.png");
+
+    // Paths can't escape the root.
+    for path in ["../cube.obj", "models/../../cube.obj", "/cube.obj", "\\cube.obj", "C:\\cube.obj"] {
+        let err = normalize_path(path).unwrap_err();
+        assert!(matches!(err.e_type, ImportErrorType::FileNotFound), "{path}: {err:?}");
+    }
+}
+
+#[test]
+fn file_system() {
+    let resolver = FileSystemResolver::new(fixtures_dir());
+
+    let scene = Scene::load_from(&resolver, "cube.obj", PostProcessFlags::empty()).unwrap();
+    let expected = Scene::load(&fixture_path("cube.obj"), PostProcessFlags::empty()).unwrap();
+
+    assert_scenes_eq(&expected, &scene);
+
+    let err = resolver.resolve("missing.obj").unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::FileNotFound), "{err:?}");
+
+    // Files outside the root aren't loaded.
+    assert!(matches!(resolver.resolve("../fixtures/cube.obj").unwrap_err().e_type, ImportErrorType::FileNotFound));
+}
+
+#[test]
+fn parent_directory() {
+    // Models loaded from disk can refer to files outside their own directory.
+    let directory = temp_dir("parent_directory");
+    std::fs::create_dir_all(directory.join("models")).unwrap();
+    std::fs::write(directory.join("interleaved.bin"), read_fixture("interleaved.bin")).unwrap();
+
+    let gltf = String::from_utf8(read_fixture("interleaved.gltf")).unwrap().replace("\"interleaved.bin\"", "\"../interleaved.bin\"");
+    let path = directory.join("models").join("interleaved.gltf");
+    std::fs::write(&path, gltf).unwrap();
+
+    let scene = Scene::load(path.to_str().unwrap(), PostProcessFlags::empty()).unwrap();
+    let expected = Scene::load(&fixture_path("interleaved.gltf"), PostProcessFlags::empty()).unwrap();
+
+    assert_scenes_eq(&expected, &scene);
+    assert!(fixtures_dir().resolve("../fixtures/cube.obj").is_ok());
+}
+
+#[test]
+fn memory() {
+    let mut resolver = MemoryResolver::new();
+    resolver.insert("models/interleaved.gltf", read_fixture("interleaved.gltf"));
+    resolver.insert("models/interleaved.bin", read_fixture("interleaved.bin"));
+
+    // The buffer is found next to the model.
+    let scene = Scene::load_from(&resolver, "./models/interleaved.gltf", PostProcessFlags::empty()).unwrap();
+    let expected = Scene::load(&fixture_path("interleaved.gltf"), PostProcessFlags::empty()).unwrap();
+
+    assert_scenes_eq(&expected, &scene);
+
+    assert!(resolver.resolve("models\\interleaved.bin").is_ok());
+    assert!(matches!(resolver.resolve("interleaved.bin").unwrap_err().e_type, ImportErrorType::FileNotFound));
+}
+
+#[cfg(feature = "zip")]
+#[test]
+fn zip_archive() {
+    use std::io::{Cursor, Write};
+
+    use modelo::resolver::ZipResolver;
+
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default();
+
+    for (name, fixture) in [("sparse/sparse.gltf", "sparse.gltf"), ("sparse/sparse.bin", "sparse.bin"), ("cube.obj", "cube.obj"), ("cube.mtl", "cube.mtl")] {
+        writer.start_file(name, options).unwrap();
+        writer.write_all(&read_fixture(fixture)).unwrap();
+    }
+
+    let archive = writer.finish().unwrap();
+
+    let resolver = ZipResolver::new(Cursor::new(archive.into_inner())).unwrap();
+
+    let scene = Scene::load_from(&resolver, "sparse/sparse.gltf", PostProcessFlags::empty()).unwrap();
+    let expected = Scene::load(&fixture_path("sparse.gltf"), PostProcessFlags::empty()).unwrap();
+    assert_scenes_eq(&expected, &scene);
+
+    let scene = Scene::load_from(&resolver, "cube.obj", PostProcessFlags::empty()).unwrap();
+    assert_eq!(scene.materials.as_ref().unwrap()[1].metallic, 1.0);
+
+    assert!(matches!(resolver.resolve("missing.gltf").unwrap_err().e_type, ImportErrorType::FileNotFound));
+}
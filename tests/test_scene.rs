@@ -1,10 +1,96 @@
-use modelo::{Scene, PostProcessFlags};
+mod common;
+
+use common::{fixture_path, fixtures_dir, temp_dir};
+use modelo::{format, Scene, PostProcessFlags, ImportErrorType, ExportErrorType};
 
 #[test]
 fn test_scene() {
-    //let scene = Scene::load("/home/skye/Downloads/Cubebs/IMyDefaultCube2GLTFseparate.gltf");
-    let scene = Scene::load("/home/skye/Downloads/Fox.gltf", PostProcessFlags::GENERATE_NORMALS);
-    println!("{scene:?}");
+    // The sparse fixture has no normals, so they will be generated.
+    let scene = Scene::load(&fixture_path("sparse.gltf"), PostProcessFlags::GENERATE_NORMALS).unwrap();
+
+    for vertex in &scene.meshes[0].vertices {
+        assert!((vertex.normal.z.abs() - 1.0).abs() < 1e-5, "{:?}", vertex.normal);
+    }
+}
+
+#[test]
+fn detect_format() {
+    let fixtures = [
+        ("interleaved.gltf", "glTF 2.0"),
+        ("hierarchy.glb", "glTF 2.0"),
+        ("cube.obj", "Wavefront OBJ"),
+        ("quad.ply", "Stanford PLY"),
+        ("tetrahedron.stl", "STL")
+    ];
+
+    let directory = temp_dir("detect_format");
+
+    // The glTF file needs its buffer alongside it.
+    std::fs::copy(fixtures_dir().join("interleaved.bin"), directory.join("interleaved.bin")).unwrap();
+
+    for (name, format_name) in fixtures {
+        let data = std::fs::read(fixtures_dir().join(name)).unwrap();
+        assert_eq!(format::detect(&data).map(|format| format.name), Some(format_name), "{name}");
+
+        // Files without a recognized extension are loaded based on their contents.
+        let path = directory.join(name.replace('.', "_"));
+        std::fs::write(&path, &data).unwrap();
+
+        let scene = Scene::load(path.to_str().unwrap(), PostProcessFlags::empty()).unwrap();
+        assert!(!scene.meshes.is_empty(), "{name}");
+    }
+
+    // Binary STL files are detected from their size.
+    let path = directory.join("binary.stl");
+    Scene::load(&fixture_path("tetrahedron.stl"), PostProcessFlags::empty()).unwrap().save(path.to_str().unwrap()).unwrap();
+    assert_eq!(format::detect(&std::fs::read(&path).unwrap()).map(|format| format.name), Some("STL"));
+
+    assert_eq!(format::find_by_extension("GLB").map(|format| format.name), Some("glTF 2.0"));
+    assert_eq!(format::find_by_mime_type("model/obj").map(|format| format.name), Some("Wavefront OBJ"));
+    assert!(format::detect(b"\x00\x01 not a model").is_none());
+}
+
+#[test]
+fn save_formats() {
+    let scene = Scene::load(&fixture_path("cube.obj"), PostProcessFlags::empty()).unwrap();
+    let directory = temp_dir("save_formats");
+
+    // GLB files embed the textures, so they need to be next to it.
+    for image in scene.images.iter().flatten() {
+        let path = directory.join(image.path.as_ref().unwrap());
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, [0x89, b'P', b'N', b'G']).unwrap();
+    }
+
+    for extension in ["gltf", "glb", "obj", "ply", "stl"] {
+        let path = directory.join(format!("cube.{extension}"));
+        scene.save(path.to_str().unwrap()).unwrap();
+
+        let loaded = Scene::load(path.to_str().unwrap(), PostProcessFlags::empty()).unwrap();
+        let bounds = loaded.bounds();
+
+        assert_eq!(bounds.min, scene.bounds().min, "{extension}");
+        assert_eq!(bounds.max, scene.bounds().max, "{extension}");
+    }
+}
+
+#[test]
+fn unsupported_formats() {
+    let path = temp_dir("unsupported_formats").join("notes.txt");
+    std::fs::write(&path, "Not a model.").unwrap();
+
+    let err = Scene::load(path.to_str().unwrap(), PostProcessFlags::empty()).unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::UnsupportedFormat), "{err:?}");
+
+    let err = Scene::load(&fixture_path("missing.gltf"), PostProcessFlags::empty()).unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::FileNotFound), "{err:?}");
+
+    let scene = Scene::load(&fixture_path("cube.obj"), PostProcessFlags::empty()).unwrap();
+    let err = scene.save(path.to_str().unwrap()).unwrap_err();
+    assert!(matches!(err.e_type, ExportErrorType::UnsupportedFormat), "{err:?}");
 
-    //println!("{}", scene.meshes[0].indices.as_ref().unwrap().len());
-}
\ No newline at end of file
+    // Formats that can only be loaded can't be saved to.
+    let err = scene.save(path.with_extension("dae").to_str().unwrap()).unwrap_err();
+    assert!(matches!(err.e_type, ExportErrorType::UnsupportedFormat), "{err:?}");
+    assert!(!path.with_extension("dae").exists());
+}
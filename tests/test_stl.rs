@@ -0,0 +1,125 @@
+mod common;
+
+use common::{assert_vec3_eq, fixture_path, fixtures_dir, temp_dir};
+use modelo::{stl::{Format, Stl}, Importer, Exporter, ImportErrorType, Mat4, Node, PostProcessFlags, Quat, Scene, Vec3};
+
+#[test]
+fn load_ascii() {
+    let stl = Stl::import(&fixture_path("tetrahedron.stl")).unwrap();
+
+    assert_eq!(stl.format, Format::Ascii);
+    assert_eq!(stl.solids.len(), 2);
+    assert_eq!(stl.solids[0].name.as_deref(), Some("Tetrahedron"));
+    assert_eq!(stl.solids[0].facets.len(), 4);
+
+    // The square facet is triangulated.
+    assert_eq!(stl.solids[1].facets.len(), 2);
+
+    let scene = stl.to_scene(&fixtures_dir()).unwrap();
+    assert_eq!(scene.meshes.len(), 2);
+
+    // Each facet has a different normal, so no vertices are shared.
+    let tetrahedron = &scene.meshes[0];
+    assert_eq!(tetrahedron.vertices.len(), 12);
+    assert_eq!(tetrahedron.indices.as_ref().unwrap().len(), 12);
+
+    // The zero normal is calculated from the winding.
+    let expected = 1.0 / 3.0f32.sqrt();
+    assert_vec3_eq(tetrahedron.vertices[9].normal, Vec3::new(expected, expected, expected), "normal");
+
+    // Both triangles of the square face the same way, so their vertices are shared.
+    assert_eq!(scene.meshes[1].vertices.len(), 4);
+}
+
+#[test]
+fn weld_smooth() {
+    let stl = Stl::import(&fixture_path("tetrahedron.stl")).unwrap();
+
+    let mesh = Stl::weld(&stl.solids[0], true);
+    assert_eq!(mesh.vertices.len(), 4);
+    assert_eq!(mesh.indices.as_ref().unwrap(), &vec![0, 1, 2, 0, 2, 3, 0, 3, 1, 2, 1, 3]);
+    assert_eq!(mesh.vertices[0].normal, Vec3::new(0.0, 0.0, 0.0));
+}
+
+#[test]
+fn generate_smooth_normals() {
+    // The facet normals are kept, so generating normals leaves them flat.
+    let scene = Scene::load(&fixture_path("tetrahedron.stl"), PostProcessFlags::GENERATE_NORMALS).unwrap();
+    assert!(scene.meshes[0].has_normals);
+    assert_vec3_eq(scene.meshes[0].vertices[0].normal, Vec3::new(0.0, 0.0, -1.0), "facet normal");
+
+    // Welded smoothly, the origin is shared by three facets at right angles, so its normal points between them.
+    let stl = Stl::import(&fixture_path("tetrahedron.stl")).unwrap();
+    let mut scene = stl.to_scene(&fixtures_dir()).unwrap();
+    scene.meshes[0] = Stl::weld(&stl.solids[0], true);
+    scene.post_process(PostProcessFlags::GENERATE_NORMALS);
+
+    let expected = -1.0 / 3.0f32.sqrt();
+
+    assert!(scene.meshes[0].has_normals);
+    assert_vec3_eq(scene.meshes[0].vertices[0].normal, Vec3::new(expected, expected, expected), "smooth normal");
+}
+
+#[test]
+fn binary_round_trip() {
+    let mut scene = Stl::import(&fixture_path("tetrahedron.stl")).unwrap().to_scene(&fixtures_dir()).unwrap();
+
+    // Node transforms are applied on export.
+    scene.nodes.push(Node {
+        transform: Mat4::from_trs(Vec3::new(0.0, 0.0, 10.0), Quat::new(0.0, 0.0, 0.0, 1.0), Vec3::new(1.0, 1.0, 1.0)),
+        meshes: vec![0],
+        children: Vec::new(),
+        name: None,
+        extras: None
+    });
+    scene.root_nodes.push(0);
+
+    let stl = Stl::from_scene(&scene);
+    assert_eq!(stl.format, Format::Binary);
+
+    let path = temp_dir("stl_round_trip").join("tetrahedron.stl");
+    stl.export(path.to_str().unwrap()).unwrap();
+
+    let loaded = Stl::import(path.to_str().unwrap()).unwrap();
+    assert_eq!(loaded.format, Format::Binary);
+    assert_eq!(loaded.solids.len(), 1);
+    assert_eq!(loaded.solids[0].facets.len(), 4);
+
+    let bounds = loaded.to_scene(&fixtures_dir()).unwrap().bounds();
+    assert_vec3_eq(bounds.min, Vec3::new(0.0, 0.0, 10.0), "bounds");
+    assert_vec3_eq(bounds.max, Vec3::new(1.0, 1.0, 11.0), "bounds");
+
+    // Some programs start binary headers with "solid", so these must still be read as binary.
+    let mut data = std::fs::read(&path).unwrap();
+    data[..5].copy_from_slice(b"solid");
+    assert_eq!(Stl::parse(&data).unwrap().format, Format::Binary);
+}
+
+#[test]
+fn ascii_round_trip() {
+    let stl = Stl::import(&fixture_path("tetrahedron.stl")).unwrap();
+
+    let mut exported = Stl::from_scene(&stl.to_scene(&fixtures_dir()).unwrap());
+    exported.format = Format::Ascii;
+
+    let loaded = Stl::parse(&exported.write()).unwrap();
+    assert_eq!(loaded.solids.len(), 2);
+    assert_eq!(loaded.solids[1].name.as_deref(), Some("Square"));
+    assert_eq!(loaded.solids[0].facets, exported.solids[0].facets);
+}
+
+#[test]
+fn parse_errors() {
+    let err = Stl::parse(b"solid a\n  facet normal 0 0\nendsolid a").unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::StringParseError));
+    assert!(err.message.starts_with("Line 2"), "{}", err.message);
+
+    assert!(Stl::parse(b"solid a\n  facet normal 0 0 1\n outer loop\n vertex 0 0 0\n endloop\n endfacet\nendsolid").is_err());
+    assert!(Stl::parse(&[0; 40]).is_err());
+
+    // A binary file with fewer facets than its count.
+    let mut data = vec![0; 84];
+    data[80] = 2;
+    data.extend_from_slice(&[0; 50]);
+    assert!(Stl::parse(&data).is_err());
+}
@@ -0,0 +1,150 @@
+#![cfg(feature = "zip")]
+
+mod common;
+
+use std::io::{Cursor, Write};
+
+use common::{assert_mat4_eq, assert_vec3_eq, assert_vec4_eq, fixture_path, fixtures_dir, temp_dir};
+use modelo::{format, resolver::NoResolver, threemf::{ObjectContent, ThreeMf, Unit}, AlphaMode, Importer, Exporter, ImportErrorType, Mat4, PostProcessFlags, Scene, Vec3, Vec4};
+
+const MODEL: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<model unit="centimeter" xml:lang="en-US" xmlns="http://schemas.microsoft.com/3dmanufacturing/core/2015/02" xmlns:m="http://schemas.microsoft.com/3dmanufacturing/material/2015/02">
+  <metadata name="Title">Two triangles</metadata>
+  <resources>
+    <basematerials id="1">
+      <base name="Red" displaycolor="#FF0000"/>
+      <base name="Glass" displaycolor="#0000FF80"/>
+    </basematerials>
+    <m:colorgroup id="2">
+      <m:color color="#00FF00FF"/>
+      <m:color color="#FFFF00FF"/>
+    </m:colorgroup>
+    <object id="3" type="model" name="Part" pid="1" pindex="0">
+      <mesh>
+        <vertices>
+          <vertex x="0" y="0" z="0"/>
+          <vertex x="1" y="0" z="0"/>
+          <vertex x="0" y="1" z="0"/>
+          <vertex x="1" y="1" z="0"/>
+        </vertices>
+        <triangles>
+          <triangle v1="0" v2="1" v3="2"/>
+          <triangle v1="1" v2="3" v3="2" pid="1" p1="1"/>
+        </triangles>
+      </mesh>
+    </object>
+    <object id="4" type="model" name="Painted">
+      <mesh>
+        <vertices>
+          <vertex x="0" y="0" z="0"/>
+          <vertex x="1" y="0" z="0"/>
+          <vertex x="0" y="0" z="1"/>
+        </vertices>
+        <triangles>
+          <triangle v1="0" v2="1" v3="2" pid="2" p1="0" p2="1"/>
+        </triangles>
+      </mesh>
+    </object>
+    <object id="5" type="model" name="Assembly">
+      <components>
+        <component objectid="3"/>
+        <component objectid="4" transform="1 0 0 0 1 0 0 0 1 5 0 0"/>
+      </components>
+    </object>
+  </resources>
+  <build>
+    <item objectid="5" transform="1 0 0 0 1 0 0 0 1 0 0 10"/>
+  </build>
+</model>
+"##;
+
+fn package(model: &str) -> Vec<u8> {
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    writer.start_file("3D/3dmodel.model", zip::write::SimpleFileOptions::default()).unwrap();
+    writer.write_all(model.as_bytes()).unwrap();
+
+    writer.finish().unwrap().into_inner()
+}
+
+#[test]
+fn load_package() {
+    // Packages without relationships use the default model path.
+    let three_mf = ThreeMf::import_bytes(&package(MODEL), &NoResolver).unwrap();
+
+    assert_eq!(three_mf.unit, Unit::Centimeter);
+    assert_eq!(three_mf.metadata, vec![(String::from("Title"), String::from("Two triangles"))]);
+    assert_eq!(three_mf.base_materials[0].materials[1].name, "Glass");
+    assert_vec4_eq(three_mf.color_groups[0].colors[1], Vec4::new(1.0, 1.0, 0.0, 1.0), "color");
+    assert_eq!(three_mf.objects.len(), 3);
+    assert!(matches!(&three_mf.objects[2].content, ObjectContent::Components(components) if components.len() == 2));
+
+    let scene = three_mf.to_scene(&NoResolver).unwrap();
+
+    // The part is split by material.
+    assert_eq!(scene.meshes.len(), 3);
+    assert_eq!(scene.meshes[0].material, Some(0));
+    assert_eq!(scene.meshes[1].material, Some(1));
+
+    let materials = scene.materials.as_ref().unwrap();
+    assert_eq!(materials[1].alpha_mode, AlphaMode::Blend);
+
+    // The third vertex of the painted triangle defaults to the first color.
+    let painted = &scene.meshes[2];
+    assert_eq!(painted.material, None);
+    assert_vec4_eq(painted.vertices[1].color, Vec4::new(1.0, 1.0, 0.0, 1.0), "color");
+    assert_vec4_eq(painted.vertices[2].color, Vec4::new(0.0, 1.0, 0.0, 1.0), "color");
+
+    assert_eq!(scene.root_nodes, vec![0]);
+    assert_eq!(scene.nodes[0].children, vec![1, 2]);
+
+    // The build item is converted from centimeters and +Z up.
+    let world = scene.nodes[0].transform * scene.nodes[2].transform;
+    assert_vec3_eq(world.transform_point(Vec3::new(0.0, 0.0, 1.0)), Vec3::new(0.05, 0.11, 0.0), "world");
+}
+
+#[test]
+fn round_trip() {
+    let scene = Scene::load(&fixture_path("cube.obj"), PostProcessFlags::empty()).unwrap();
+
+    let three_mf = ThreeMf::from_scene(&scene);
+    assert_eq!(three_mf.unit, Unit::Millimeter);
+
+    let mut data = Cursor::new(Vec::new());
+    three_mf.write(&mut data).unwrap();
+
+    let data = data.into_inner();
+    assert_eq!(format::detect(&data).map(|format| format.name), Some("3MF"));
+
+    let loaded = ThreeMf::import_bytes(&data, &NoResolver).unwrap();
+    assert_eq!(loaded.objects, three_mf.objects);
+    assert_eq!(loaded.items, three_mf.items);
+
+    // Node transforms are converted back to +Y up and meters.
+    let loaded_scene = loaded.to_scene(&NoResolver).unwrap();
+    assert_eq!(loaded_scene.meshes.len(), scene.meshes.len());
+    assert_mat4_eq(&loaded_scene.nodes[0].transform, &Mat4::identity(), "transform");
+    assert_eq!(loaded_scene.bounds().min, scene.bounds().min);
+    assert_eq!(loaded_scene.bounds().max, scene.bounds().max);
+
+    let materials = loaded_scene.materials.as_ref().unwrap();
+    assert_eq!(materials[1].name.as_deref(), Some("Metal"));
+
+    let path = temp_dir("threemf_round_trip").join("cube.3mf");
+    scene.save(path.to_str().unwrap()).unwrap();
+    assert_eq!(Scene::load(path.to_str().unwrap(), PostProcessFlags::empty()).unwrap().meshes.len(), scene.meshes.len());
+}
+
+#[test]
+fn errors() {
+    let err = ThreeMf::import_bytes(b"PK not a zip", &NoResolver).unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::Other), "{err:?}");
+
+    let err = ThreeMf::import_bytes(&package(&MODEL.replace("v3=\"2\" pid=\"1\"", "v3=\"9\" pid=\"1\"")), &NoResolver).unwrap().to_scene(&fixtures_dir()).unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::StringParseError), "{err:?}");
+
+    let err = ThreeMf::parse_model(&MODEL.replace("1 0 0 0 1 0 0 0 1 5 0 0", "1 0 0")).unwrap_err();
+    assert!(err.message.contains("12 values"), "{}", err.message);
+
+    let err = ThreeMf::parse_model(&MODEL.replace("centimeter", "parsec")).unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::StringParseError), "{err:?}");
+}
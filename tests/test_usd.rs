@@ -0,0 +1,135 @@
+mod common;
+
+use common::{assert_vec2_eq, assert_vec3_eq, assert_vec4_eq, fixture_path, fixtures_dir, temp_dir};
+use modelo::{format, resolver::NoResolver, usd::{Specifier, UpAxis, Usd, Value}, AlphaMode, Importer, Exporter, ImportErrorType, PostProcessFlags, Scene, Vec2, Vec3, Vec4};
+
+#[test]
+fn load_from_file() {
+    let usd = Usd::import(&fixture_path("scene.usda")).unwrap();
+
+    assert_eq!(usd.up_axis(), UpAxis::Z);
+    assert_eq!(usd.meters_per_unit(), 0.01);
+    assert_eq!(usd.metadata("doc").and_then(Value::as_str), Some("A plane with two materials,\nunder a transformed root."));
+
+    let world = &usd.prims[0];
+    assert_eq!(world.specifier, Specifier::Def);
+    assert_eq!(world.type_name.as_deref(), Some("Xform"));
+    assert_eq!(world.metadata, vec![(String::from("kind"), Value::String(String::from("component")))]);
+
+    // List-edited metadata keeps its operation.
+    let plane = usd.prim("/World/Plane").unwrap();
+    assert_eq!(plane.metadata[0].0, "prepend apiSchemas");
+    assert_eq!(plane.attribute("normals").unwrap().metadata("interpolation").and_then(Value::as_str), Some("constant"));
+    assert_eq!(plane.relationship("material:binding").unwrap().targets, vec![String::from("/World/Looks/Painted")]);
+
+    // Connections are stored with the attribute they're made on.
+    let surface = usd.prim("/World/Looks/Painted/Surface").unwrap();
+    let diffuse = surface.attribute("inputs:diffuseColor").unwrap();
+    assert_eq!(diffuse.value, None);
+    assert_eq!(diffuse.connections, vec![String::from("/World/Looks/Painted/Checker.outputs:rgb")]);
+
+    assert_eq!(usd.prim("/World/Looks/Painted/Checker").unwrap().value("inputs:file"), Some(&Value::Asset(String::from("textures/checker.png"))));
+    assert!(usd.prim("/World/Missing").is_none());
+}
+
+#[test]
+fn to_scene() {
+    let scene = Usd::import(&fixture_path("scene.usda")).unwrap().to_scene(&fixtures_dir()).unwrap();
+
+    // The material scope isn't a node, and the plane is split by its face subset.
+    assert_eq!(scene.root_nodes, vec![0]);
+    assert_eq!(scene.nodes.len(), 2);
+    assert_eq!(scene.nodes[1].name.as_deref(), Some("Plane"));
+    assert_eq!(scene.nodes[1].meshes, vec![0, 1]);
+
+    let quad = &scene.meshes[0];
+    assert_eq!(quad.material, Some(0));
+    assert_eq!(quad.indices.as_ref().unwrap(), &vec![0, 1, 2, 0, 2, 3]);
+
+    // Texture coordinates are flipped vertically, and uniform colors are per face.
+    let first = &quad.vertices[0];
+    assert_vec2_eq(first.tex_coord, Vec2::new(0.0, 1.0), "tex coord");
+    assert_vec3_eq(first.normal, Vec3::new(0.0, 0.0, 1.0), "normal");
+    assert_vec4_eq(first.color, Vec4::new(1.0, 0.0, 0.0, 1.0), "color");
+
+    let triangle = &scene.meshes[1];
+    assert_eq!(triangle.material, Some(1));
+    assert_eq!(triangle.vertices.len(), 3);
+    assert_vec4_eq(triangle.vertices[2].color, Vec4::new(0.0, 1.0, 0.0, 1.0), "color");
+
+    let materials = scene.materials.as_ref().unwrap();
+    assert_eq!(materials[0].name.as_deref(), Some("Painted"));
+    assert_eq!(materials[0].albedo_texture, Some(0));
+    assert_eq!(materials[0].roughness, 0.25);
+    assert_eq!(materials[0].metallic, 1.0);
+
+    assert_eq!(materials[1].alpha_mode, AlphaMode::Blend);
+    assert_vec4_eq(materials[1].albedo_color, Vec4::new(0.2, 0.4, 0.8, 0.5), "albedo color");
+
+    let images = scene.images.as_ref().unwrap();
+    assert_eq!(images[0].path.as_deref(), Some("textures/checker.png"));
+
+    // The root is translated, rotated, then scaled, and converted from centimeters and +Z up.
+    assert_vec3_eq(scene.nodes[0].transform.transform_point(Vec3::new(1.0, 0.0, 0.0)), Vec3::new(0.0, 1.0, -0.02), "root");
+}
+
+#[test]
+fn transform_ops() {
+    let usd = Usd::parse(r#"#usda 1.0
+        def Xform "Pivot"
+        {
+            float3 xformOp:translate:pivot = (1, 0, 0)
+            float3 xformOp:rotateXYZ = (0, 0, 90)
+            quatf xformOp:orient = (1, 0, 0, 0)
+            matrix4d xformOp:transform = ((1, 0, 0, 0), (0, 1, 0, 0), (0, 0, 1, 0), (0, 0, 5, 1))
+            uniform token[] xformOpOrder = ["xformOp:translate:pivot", "xformOp:rotateXYZ", "xformOp:orient", "xformOp:transform", "!invert!xformOp:translate:pivot"]
+        }
+    "#).unwrap();
+
+    // The pivot moves the rotation center, and USD matrices keep their translation in the last row.
+    let scene = usd.to_scene(&NoResolver).unwrap();
+    let transform = scene.nodes[0].transform;
+    assert_vec3_eq(transform.transform_point(Vec3::new(100.0, 0.0, 0.0)), Vec3::new(0.01, 0.99, 0.05), "transform");
+}
+
+#[test]
+fn round_trip() {
+    let scene = Scene::load(&fixture_path("cube.obj"), PostProcessFlags::empty()).unwrap();
+
+    let usd = Usd::from_scene(&scene);
+    assert_eq!(usd.meters_per_unit(), 1.0);
+
+    let text = usd.write();
+    assert_eq!(format::detect(text.as_bytes()).map(|format| format.name), Some("USD"));
+    assert_eq!(Usd::parse(&text).unwrap(), usd);
+
+    // Everything is written under a root prim.
+    let loaded = usd.to_scene(&NoResolver).unwrap();
+    assert_eq!(loaded.meshes.len(), scene.meshes.len());
+    assert_eq!(loaded.nodes[0].name.as_deref(), Some("Root"));
+    assert_eq!(loaded.bounds().min, scene.bounds().min);
+    assert_eq!(loaded.bounds().max, scene.bounds().max);
+
+    let materials = loaded.materials.as_ref().unwrap();
+    assert_eq!(materials[1].name.as_deref(), Some("Metal"));
+
+    let path = temp_dir("usd_round_trip").join("cube.usda");
+    scene.save(path.to_str().unwrap()).unwrap();
+    assert_eq!(Scene::load(path.to_str().unwrap(), PostProcessFlags::empty()).unwrap().meshes.len(), scene.meshes.len());
+}
+
+#[test]
+fn errors() {
+    let err = Usd::parse("def Xform \"World\" {}").unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::StringParseError), "{err:?}");
+
+    let err = Usd::parse("#usda 1.0\ndef Xform \"World\n{}").unwrap_err();
+    assert!(err.message.contains("Line 2"), "{}", err.message);
+
+    let err = Usd::parse("#usda 1.0\ndef Xform \"World\"\n{\n    float3 xformOp:scale = (1, 2\n}").unwrap_err();
+    assert!(err.message.contains("Line 5"), "{}", err.message);
+
+    let text = std::fs::read_to_string(fixture_path("scene.usda")).unwrap().replace("[0, 1, 2, 3, 1, 4, 2]", "[0, 1, 2, 3, 1, 9, 2]");
+    let err = Usd::parse(&text).unwrap().to_scene(&NoResolver).unwrap_err();
+    assert!(err.message.contains("/World/Plane"), "{}", err.message);
+}
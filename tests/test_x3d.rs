@@ -0,0 +1,151 @@
+mod common;
+
+use common::{assert_vec2_eq, assert_vec3_eq, assert_vec4_eq, fixture_path};
+use modelo::{format, resolver::NoResolver, x3d::{Encoding, Value, X3d}, AlphaMode, Importer, ImportErrorType, PostProcessFlags, Scene, Vec2, Vec3, Vec4};
+
+#[test]
+fn load_vrml() {
+    let data = std::fs::read(fixture_path("scene.wrl")).unwrap();
+    assert_eq!(format::detect(&data).map(|format| format.name), Some("VRML97"));
+
+    let x3d = X3d::import_bytes(&data, &NoResolver).unwrap();
+    assert_eq!(x3d.encoding, Encoding::Vrml);
+
+    // The prototype and the route are skipped.
+    let types = x3d.nodes.iter().map(|node| node.type_name.as_str()).collect::<Vec<_>>();
+    assert_eq!(types, vec!["Viewpoint", "Transform", "Transform", "Transform"]);
+
+    let viewpoint = &x3d.nodes[0];
+    assert_eq!(viewpoint.numbers("position"), &[0.0, 0.0, 10.0]);
+    assert_eq!(viewpoint.strings("description"), &[String::from("Front")]);
+
+    // Shapes reused with USE are copies, with the same name.
+    let cube = x3d.nodes[1].node("children").unwrap();
+    assert_eq!(cube.name.as_deref(), Some("Cube"));
+    assert_eq!(x3d.nodes[2].node("children"), Some(cube));
+
+    let roof = x3d.nodes[3].node("children").unwrap().node("geometry").unwrap();
+    assert_eq!(roof.fields.get("solid"), Some(&Value::Bool(false)));
+    assert_eq!(roof.numbers("coordIndex").len(), 9);
+}
+
+#[test]
+fn load_x3d() {
+    let data = std::fs::read(fixture_path("scene.x3d")).unwrap();
+    assert_eq!(format::detect(&data).map(|format| format.name), Some("X3D"));
+
+    let x3d = X3d::import_bytes(&data, &NoResolver).unwrap();
+    assert_eq!(x3d.encoding, Encoding::Xml);
+    assert_eq!(x3d.version, "3.3");
+
+    // Both encodings describe the same nodes.
+    let vrml = X3d::import(&fixture_path("scene.wrl")).unwrap();
+    assert_eq!(x3d.nodes, vrml.nodes);
+
+    let texture = x3d.nodes[3].node("children").unwrap().node("appearance").unwrap().node("texture").unwrap();
+    assert_eq!(texture.strings("url"), &[String::from("checker.png"), String::from("fallback.png")]);
+}
+
+#[test]
+fn to_scene() {
+    let scene = Scene::load(&fixture_path("scene.wrl"), PostProcessFlags::empty()).unwrap();
+
+    // The viewpoint isn't a node, and the copy of the cube shares its mesh.
+    assert_eq!(scene.root_nodes, vec![0, 1, 2]);
+    assert_eq!(scene.meshes.len(), 2);
+    assert_eq!(scene.nodes[0].meshes, vec![0]);
+    assert_eq!(scene.nodes[1].meshes, vec![0]);
+    assert_eq!(scene.find_node("Roof"), Some(2));
+
+    let cube = &scene.meshes[0];
+    assert_eq!(cube.name.as_deref(), Some("Cube"));
+    assert_eq!(cube.indices.as_ref().unwrap().len(), 36);
+
+    let box_transform = scene.world_transform(0);
+    assert_vec3_eq(box_transform.transform_point(Vec3::new(1.0, 0.0, 0.0)), Vec3::new(1.0, 0.0, -1.0), "box");
+
+    let copy = scene.world_transform(1);
+    assert_vec3_eq(copy.transform_point(Vec3::new(1.0, 1.0, 1.0)), Vec3::new(2.0, 4.0, 2.0), "copy");
+
+    let materials = scene.materials.as_ref().unwrap();
+    let red = &materials[cube.material.unwrap()];
+    assert_eq!(red.name.as_deref(), Some("Red"));
+    assert_vec4_eq(red.albedo_color, Vec4::new(1.0, 0.0, 0.0, 0.75), "albedo");
+    assert_eq!(red.alpha_mode, AlphaMode::Blend);
+    assert!(!red.double_sided);
+
+    // Textures replace the diffuse color, and the roof isn't solid, so is double sided.
+    let roof = &scene.meshes[1];
+    let checker = &materials[roof.material.unwrap()];
+    assert_eq!(checker.albedo_texture, Some(0));
+    assert_vec4_eq(checker.albedo_color, Vec4::new(1.0, 1.0, 1.0, 1.0), "albedo");
+    assert!(checker.double_sided);
+
+    let images = scene.images.as_ref().unwrap();
+    assert_eq!(images[0].path.as_deref(), Some("checker.png"));
+    assert_eq!(images[0].name.as_deref(), Some("Checker"));
+
+    // Texture coordinates are flipped, since VRML's origin is at the bottom.
+    assert_vec2_eq(roof.vertices[3].tex_coord, Vec2::new(0.0, 0.0), "tex coord");
+}
+
+#[test]
+fn crease_angle() {
+    let scene = X3d::import(&fixture_path("scene.wrl")).unwrap().to_scene(&NoResolver).unwrap();
+
+    // The cube's edges are sharper than its crease angle, so every face is flat.
+    let cube = &scene.meshes[0];
+    assert_eq!(cube.vertices.len(), 24);
+    assert_vec3_eq(cube.vertices[0].normal, Vec3::new(0.0, 0.0, -1.0), "normal");
+
+    // The roof's ridge isn't, so the normals along it point straight up.
+    let roof = &scene.meshes[1];
+    assert_vec3_eq(roof.vertices[0].normal, Vec3::new(-0.5f32.sqrt(), 0.5f32.sqrt(), 0.0), "normal");
+    assert_vec3_eq(roof.vertices[2].normal, Vec3::new(0.0, 1.0, 0.0), "ridge");
+    assert_vec3_eq(roof.vertices[4].normal, Vec3::new(0.0, 1.0, 0.0), "ridge");
+}
+
+#[test]
+fn face_properties() {
+    // Colors and normals can be given per face, and faces can be wound clockwise.
+    let text = "#VRML V2.0 utf8
+        Shape {
+          geometry IndexedFaceSet {
+            ccw FALSE
+            coord Coordinate { point [ 0 0 0, 1 0 0, 1 1 0, 0 1 0 ] }
+            coordIndex [ 0 3 2 1 ]
+            color Color { color [ 1 0 0, 0 0 1 ] }
+            colorPerVertex FALSE
+            colorIndex [ 1 ]
+            normal Normal { vector [ 0 0 1 ] }
+            normalPerVertex FALSE
+          }
+        }";
+
+    let scene = X3d::parse_vrml(text).unwrap().to_scene(&NoResolver).unwrap();
+
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.material, None);
+    assert_eq!(mesh.indices.as_ref().unwrap(), &vec![0, 2, 1, 0, 3, 2]);
+    assert_vec4_eq(mesh.vertices[1].color, Vec4::new(0.0, 0.0, 1.0, 1.0), "color");
+    assert_vec3_eq(mesh.vertices[1].normal, Vec3::new(0.0, 0.0, 1.0), "normal");
+}
+
+#[test]
+fn errors() {
+    let err = X3d::parse_vrml("#VRML V1.0 ascii\nSeparator { }").unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::UnsupportedFormat), "{err:?}");
+
+    let err = X3d::parse_vrml("#VRML V2.0 utf8\nTransform {\n  children USE Missing\n}").unwrap_err();
+    assert!(err.message.contains("Line 3") && err.message.contains("Missing"), "{}", err.message);
+
+    let err = X3d::parse_vrml("#VRML V2.0 utf8\nTransform {\n  children [").unwrap_err();
+    assert!(matches!(err.e_type, ImportErrorType::StringParseError), "{err:?}");
+
+    let err = X3d::parse_xml("<COLLADA/>").unwrap_err();
+    assert!(err.message.contains("not an X3D document"), "{}", err.message);
+
+    let text = "#VRML V2.0 utf8\nShape { geometry IndexedFaceSet { coord Coordinate { point [ 0 0 0 ] } coordIndex [ 0 1 2 ] } }";
+    let err = X3d::parse_vrml(text).unwrap().to_scene(&NoResolver).unwrap_err();
+    assert!(err.message.contains("doesn't exist"), "{}", err.message);
+}